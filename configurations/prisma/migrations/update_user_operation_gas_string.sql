@@ -0,0 +1,29 @@
+-- Run before pushing the schema w/ the `String` gas and fee columns of `UserOperation`.
+-- MySQL keeps the `BIGINT` values as their decimal strings, which are parsed w/ `U256::from_dec_str`.
+ALTER TABLE `UserOperation`
+MODIFY `callGasLimit` VARCHAR(191) NOT NULL,
+MODIFY `verificationGasLimit` VARCHAR(191) NOT NULL,
+MODIFY `preVerificationGas` VARCHAR(191) NOT NULL,
+MODIFY `maxFeePerGas` VARCHAR(191) NOT NULL,
+MODIFY `maxPriorityFeePerGas` VARCHAR(191) NOT NULL;
+
+-- The values above `i64::MAX` were stored wrapped to negative, restore the low 64 bits of the `uint256`.
+UPDATE `UserOperation`
+SET `callGasLimit` = CAST(CAST(`callGasLimit` AS DECIMAL(65, 0)) + 18446744073709551616 AS CHAR)
+WHERE `callGasLimit` LIKE '-%';
+
+UPDATE `UserOperation`
+SET `verificationGasLimit` = CAST(CAST(`verificationGasLimit` AS DECIMAL(65, 0)) + 18446744073709551616 AS CHAR)
+WHERE `verificationGasLimit` LIKE '-%';
+
+UPDATE `UserOperation`
+SET `preVerificationGas` = CAST(CAST(`preVerificationGas` AS DECIMAL(65, 0)) + 18446744073709551616 AS CHAR)
+WHERE `preVerificationGas` LIKE '-%';
+
+UPDATE `UserOperation`
+SET `maxFeePerGas` = CAST(CAST(`maxFeePerGas` AS DECIMAL(65, 0)) + 18446744073709551616 AS CHAR)
+WHERE `maxFeePerGas` LIKE '-%';
+
+UPDATE `UserOperation`
+SET `maxPriorityFeePerGas` = CAST(CAST(`maxPriorityFeePerGas` AS DECIMAL(65, 0)) + 18446744073709551616 AS CHAR)
+WHERE `maxPriorityFeePerGas` LIKE '-%';
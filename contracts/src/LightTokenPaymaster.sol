@@ -0,0 +1,194 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// SPDX-License-Identifier: Apache-2.0
+
+pragma solidity ^0.8.18;
+
+
+import {IEntryPoint} from "@eth-infinitism/account-abstraction/contracts/interfaces/IEntryPoint.sol";
+import {UserOperation, UserOperationLib} from
+    "@eth-infinitism/account-abstraction/contracts/interfaces/UserOperation.sol";
+import {BasePaymaster} from "@eth-infinitism/account-abstraction/contracts/core/BasePaymaster.sol";
+import {_packValidationData} from "@eth-infinitism/account-abstraction/contracts/core/Helpers.sol";
+import {ECDSA} from "@openzeppelin/contracts/utils/cryptography/ECDSA.sol";
+import {IERC20} from "@openzeppelin/contracts/token/ERC20/IERC20.sol";
+import {SafeERC20} from "@openzeppelin/contracts/token/ERC20/utils/SafeERC20.sol";
+
+// LightTokenPaymaster -- VerifyingPaymaster for Light that charges the gas in the ERC20 tokens
+// The verifying signer quotes the exchange rate of the token, in the token units per 1 ether of the
+// native gas, and the actual gas cost is charged from the sender in the token after the execution.
+contract LightTokenPaymaster is BasePaymaster {
+    using ECDSA for bytes32;
+    using SafeERC20 for IERC20;
+    using UserOperationLib for UserOperation;
+
+    // -------------------------------------------------------------------------
+    // Constants
+    // -------------------------------------------------------------------------
+
+    uint256 private constant VALID_TIMESTAMP_OFFSET = 20;
+
+    uint256 private constant SIGNATURE_OFFSET = 148;
+
+    // -------------------------------------------------------------------------
+    // Storages
+    // -------------------------------------------------------------------------
+
+    address public immutable verifyingSigner;
+
+    mapping(address => uint256) public senderNonce;
+
+    mapping(address => bool) public supportedTokens;
+
+    // -------------------------------------------------------------------------
+    // Events
+    // -------------------------------------------------------------------------
+
+    event SupportedTokenSet(address indexed token, bool isSupported);
+
+    event TokenCharged(address indexed sender, address indexed token, uint256 tokenCost, uint256 exchangeRate);
+
+    // -------------------------------------------------------------------------
+    // Constructor
+    // -------------------------------------------------------------------------
+
+    constructor(IEntryPoint entryPoint, address _verifyingSigner) BasePaymaster(entryPoint) {
+        verifyingSigner = _verifyingSigner;
+        _transferOwnership(tx.origin);
+    }
+
+    // -------------------------------------------------------------------------
+    // Owner
+    // -------------------------------------------------------------------------
+
+    /// @notice Sets whether the gas can be paid in the token
+    function setSupportedToken(address token, bool isSupported) external onlyOwner {
+        supportedTokens[token] = isSupported;
+        emit SupportedTokenSet(token, isSupported);
+    }
+
+    /// @notice Withdraws the tokens charged for the gas
+    function withdrawToken(IERC20 token, address to, uint256 amount) external onlyOwner {
+        token.safeTransfer(to, amount);
+    }
+
+    // -------------------------------------------------------------------------
+    // Hash
+    // -------------------------------------------------------------------------
+
+    /// @notice Returns the hash the verifying signer signs, w/ the token and the exchange rate charged
+    function getHash(
+        UserOperation calldata userOp,
+        uint48 validUntil,
+        uint48 validAfter,
+        address token,
+        uint256 exchangeRate
+    ) public view returns (bytes32) {
+        // Can't use userOp.hash(), since it contains also the paymasterAndData itself.
+        return keccak256(
+            abi.encode(
+                pack(userOp),
+                block.chainid,
+                address(this),
+                senderNonce[userOp.getSender()],
+                validUntil,
+                validAfter,
+                token,
+                exchangeRate
+            )
+        );
+    }
+
+    /// @notice Returns the token units charged for the gas cost in wei, rounded up
+    function getTokenCost(uint256 gasCost, uint256 exchangeRate) public pure returns (uint256) {
+        return (gasCost * exchangeRate + 1e18 - 1) / 1e18;
+    }
+
+    /// @notice Parses the paymaster and data of the token paymaster
+    /// @dev paymasterAndData[:20] : address(this)
+    /// @dev paymasterAndData[20:148] : abi.encode(validUntil, validAfter, token, exchangeRate)
+    /// @dev paymasterAndData[148:] : signature
+    function parsePaymasterAndData(bytes calldata paymasterAndData)
+        public
+        pure
+        returns (uint48 validUntil, uint48 validAfter, address token, uint256 exchangeRate, bytes calldata signature)
+    {
+        (validUntil, validAfter, token, exchangeRate) = abi.decode(
+            paymasterAndData[VALID_TIMESTAMP_OFFSET:SIGNATURE_OFFSET], (uint48, uint48, address, uint256)
+        );
+        signature = paymasterAndData[SIGNATURE_OFFSET:];
+    }
+
+    // -------------------------------------------------------------------------
+    // Internal
+    // -------------------------------------------------------------------------
+
+    /// @dev Verifies the quote signed by the verifying signer, and passes the token to charge to the postOp
+    function _validatePaymasterUserOp(UserOperation calldata userOp, bytes32, uint256)
+        internal
+        override
+        returns (bytes memory context, uint256 validationData)
+    {
+        (uint48 validUntil, uint48 validAfter, address token, uint256 exchangeRate, bytes calldata signature) =
+            parsePaymasterAndData(userOp.paymasterAndData);
+        require(
+            signature.length == 64 || signature.length == 65,
+            "LightTokenPaymaster: invalid signature length in paymasterAndData"
+        );
+        require(supportedTokens[token], "LightTokenPaymaster: unsupported token");
+
+        bytes32 hash = ECDSA.toEthSignedMessageHash(getHash(userOp, validUntil, validAfter, token, exchangeRate));
+        address sender = userOp.getSender();
+        senderNonce[sender]++;
+
+        // Don't revert on the signature failure: return SIG_VALIDATION_FAILED
+        if (verifyingSigner != ECDSA.recover(hash, signature)) {
+            return ("", _packValidationData(true, validUntil, validAfter));
+        }
+
+        return (abi.encode(sender, token, exchangeRate), _packValidationData(false, validUntil, validAfter));
+    }
+
+    /// @dev Charges the actual gas cost in the token from the sender
+    function _postOp(PostOpMode mode, bytes calldata context, uint256 actualGasCost) internal override {
+        // The charge of the sender already reverted, so the paymaster pays for the gas.
+        if (mode == PostOpMode.postOpReverted) {
+            return;
+        }
+
+        (address sender, address token, uint256 exchangeRate) = abi.decode(context, (address, address, uint256));
+        uint256 tokenCost = getTokenCost(actualGasCost, exchangeRate);
+
+        IERC20(token).safeTransferFrom(sender, address(this), tokenCost);
+        emit TokenCharged(sender, token, tokenCost, exchangeRate);
+    }
+
+    /// @dev Packs the user operation up to the paymaster and data, as `VerifyingPaymaster` does
+    function pack(UserOperation calldata userOp) internal pure returns (bytes memory ret) {
+        // lighter signature scheme. must match UserOp.ts#packUserOp
+        bytes calldata pnd = userOp.paymasterAndData;
+        // copy directly the userOp from calldata up to (but not including) the paymasterAndData.
+        // this encoding depends on the ABI encoding of calldata, but is much lighter to copy
+        // than referencing each field separately.
+        assembly {
+            let ofs := userOp
+            let len := sub(sub(pnd.offset, ofs), 32)
+            ret := mload(0x40)
+            mstore(0x40, add(ret, add(len, 32)))
+            mstore(ret, len)
+            calldatacopy(add(ret, 32), ofs, len)
+        }
+    }
+}
@@ -0,0 +1,228 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// SPDX-License-Identifier: Apache-2.0
+
+pragma solidity ^0.8.18;
+
+import {IPaymaster} from "@eth-infinitism/account-abstraction/contracts/interfaces/IPaymaster.sol";
+import {ECDSA} from "@openzeppelin/contracts/utils/cryptography/ECDSA.sol";
+import {ERC20} from "@openzeppelin/contracts/token/ERC20/ERC20.sol";
+import {BaseTest} from "@/test/base/BaseTest.t.sol";
+import {UserOperation} from "@/contracts/LightWallet.sol";
+import {LightTokenPaymaster} from "@/contracts/LightTokenPaymaster.sol";
+
+/// @notice Token w/ the supply minted to the deployer
+contract TestToken is ERC20 {
+    constructor() ERC20("Test Token", "TEST") {
+        _mint(msg.sender, 1_000_000e6);
+    }
+}
+
+/// @notice Unit tests for `LightTokenPaymaster`
+contract LightTokenPaymasterTest is BaseTest {
+    // -------------------------------------------------------------------------
+    // Constants
+    // -------------------------------------------------------------------------
+
+    // The sender of the user operations
+    address internal constant SENDER = address(0xF46D20dC61A5f43773Ad172602647f194a69a16d);
+
+    // 1 ether of the native gas is 3000 units of the 6 decimals token
+    uint256 internal constant EXCHANGE_RATE = 3_000e6;
+
+    // -------------------------------------------------------------------------
+    // Contracts
+    // -------------------------------------------------------------------------
+
+    // LightTokenPaymaster core contract
+    LightTokenPaymaster internal tokenPaymaster;
+    // Token charged for the gas
+    TestToken internal token;
+
+    // -------------------------------------------------------------------------
+    // Utility Storages
+    // -------------------------------------------------------------------------
+
+    address internal verifyingSigner;
+    uint256 internal verifyingSignerKey;
+
+    uint48 internal validUntil = uint48(0xdeadbeef);
+    uint48 internal validAfter = uint48(0x1234);
+
+    // -------------------------------------------------------------------------
+    // Setup
+    // -------------------------------------------------------------------------
+
+    function setUp() public virtual override {
+        // Setup the base tests
+        BaseTest.setUp();
+
+        // Deploy the LightTokenPaymaster w/ the verifying signer
+        (verifyingSigner, verifyingSignerKey) = makeAddrAndKey("verifyingSigner");
+        tokenPaymaster = new LightTokenPaymaster(entryPoint, verifyingSigner);
+
+        // Support the token to pay the gas in
+        token = new TestToken();
+        vm.prank(tokenPaymaster.owner());
+        tokenPaymaster.setSupportedToken(address(token), true);
+
+        // Fund the sender w/ the token, approved to the paymaster
+        token.transfer(SENDER, 1_000e6);
+        vm.prank(SENDER);
+        token.approve(address(tokenPaymaster), type(uint256).max);
+    }
+
+    // -------------------------------------------------------------------------
+    // Tests
+    // -------------------------------------------------------------------------
+
+    /// Tests that the paymaster and data is parsed
+    function test_parsePaymasterAndData() public {
+        bytes memory signature = new bytes(65);
+        (uint48 _validUntil, uint48 _validAfter, address _token, uint256 exchangeRate, bytes memory _signature) =
+            tokenPaymaster.parsePaymasterAndData(_paymasterAndData(address(token), EXCHANGE_RATE, signature));
+
+        assertEq(_validUntil, validUntil);
+        assertEq(_validAfter, validAfter);
+        assertEq(_token, address(token));
+        assertEq(exchangeRate, EXCHANGE_RATE);
+        assertEq(_signature, signature);
+    }
+
+    /// Tests that the hash binds the token and the exchange rate
+    function test_getHash() public {
+        UserOperation memory op = _userOperation(new bytes(65));
+
+        bytes32 hash = tokenPaymaster.getHash(op, validUntil, validAfter, address(token), EXCHANGE_RATE);
+        assertTrue(hash != tokenPaymaster.getHash(op, validUntil, validAfter, address(token), EXCHANGE_RATE - 1));
+        assertTrue(hash != tokenPaymaster.getHash(op, validUntil, validAfter, address(1), EXCHANGE_RATE));
+    }
+
+    /// Tests that the quote signed by the verifying signer is validated
+    function test_validatePaymasterUserOp() public {
+        UserOperation memory op = _signedUserOperation(verifyingSignerKey, address(token), EXCHANGE_RATE);
+
+        vm.prank(address(entryPoint));
+        (bytes memory context, uint256 validationData) = tokenPaymaster.validatePaymasterUserOp(op, bytes32(0), 1e18);
+
+        // The signature is valid w/ the validity window
+        assertEq(uint160(validationData), 0);
+        assertEq(uint48(validationData >> 160), validUntil);
+        assertEq(uint48(validationData >> 208), validAfter);
+        assertEq(context, abi.encode(SENDER, address(token), EXCHANGE_RATE));
+
+        // The sender nonce is incremented
+        assertEq(tokenPaymaster.senderNonce(SENDER), 1);
+    }
+
+    /// Tests that the quote signed by another signer fails the validation
+    function test_validatePaymasterUserOp_invalidSigner() public {
+        (, uint256 otherKey) = makeAddrAndKey("otherSigner");
+        UserOperation memory op = _signedUserOperation(otherKey, address(token), EXCHANGE_RATE);
+
+        vm.prank(address(entryPoint));
+        (, uint256 validationData) = tokenPaymaster.validatePaymasterUserOp(op, bytes32(0), 1e18);
+
+        // SIG_VALIDATION_FAILED
+        assertEq(uint160(validationData), 1);
+    }
+
+    /// Tests that the unsupported token is rejected
+    function test_validatePaymasterUserOp_unsupportedToken() public {
+        UserOperation memory op = _signedUserOperation(verifyingSignerKey, address(1), EXCHANGE_RATE);
+
+        vm.prank(address(entryPoint));
+        vm.expectRevert("LightTokenPaymaster: unsupported token");
+        tokenPaymaster.validatePaymasterUserOp(op, bytes32(0), 1e18);
+    }
+
+    /// Tests that the actual gas cost is charged in the token
+    function test_postOp() public {
+        bytes memory context = abi.encode(SENDER, address(token), EXCHANGE_RATE);
+
+        // 0.001 ether of the gas is 3 units of the token
+        vm.prank(address(entryPoint));
+        tokenPaymaster.postOp(IPaymaster.PostOpMode.opSucceeded, context, 1e15);
+
+        assertEq(token.balanceOf(address(tokenPaymaster)), 3e6);
+        assertEq(token.balanceOf(SENDER), 997e6);
+    }
+
+    /// Tests that the sender is not charged again once the charge reverted
+    function test_postOp_postOpReverted() public {
+        bytes memory context = abi.encode(SENDER, address(token), EXCHANGE_RATE);
+
+        vm.prank(address(entryPoint));
+        tokenPaymaster.postOp(IPaymaster.PostOpMode.postOpReverted, context, 1e15);
+
+        assertEq(token.balanceOf(address(tokenPaymaster)), 0);
+    }
+
+    /// Tests that the token cost is rounded up
+    function test_getTokenCost() public {
+        assertEq(tokenPaymaster.getTokenCost(1e15, EXCHANGE_RATE), 3e6);
+        assertEq(tokenPaymaster.getTokenCost(1, EXCHANGE_RATE), 1);
+        assertEq(tokenPaymaster.getTokenCost(0, EXCHANGE_RATE), 0);
+    }
+
+    // -------------------------------------------------------------------------
+    // Utility
+    // -------------------------------------------------------------------------
+
+    /// @dev Gets the paymaster and data of the token paymaster
+    function _paymasterAndData(address _token, uint256 exchangeRate, bytes memory signature)
+        internal
+        view
+        returns (bytes memory)
+    {
+        return abi.encodePacked(
+            address(tokenPaymaster), abi.encode(validUntil, validAfter, _token, exchangeRate), signature
+        );
+    }
+
+    /// @dev Gets the user operation w/ the signature of the paymaster and data
+    function _userOperation(bytes memory signature) internal view returns (UserOperation memory) {
+        return UserOperation(
+            SENDER,
+            0,
+            hex"",
+            hex"",
+            4514240,
+            1854272,
+            1854272,
+            56674171701,
+            48087546673,
+            _paymasterAndData(address(token), EXCHANGE_RATE, signature),
+            hex""
+        );
+    }
+
+    /// @dev Gets the user operation w/ the quote signed by the key
+    function _signedUserOperation(uint256 key, address _token, uint256 exchangeRate)
+        internal
+        view
+        returns (UserOperation memory op)
+    {
+        // The hash covers the user operation up to the paymaster and data of the signed length
+        op = _userOperation(new bytes(65));
+        op.paymasterAndData = _paymasterAndData(_token, exchangeRate, new bytes(65));
+
+        bytes32 hash = ECDSA.toEthSignedMessageHash(
+            tokenPaymaster.getHash(op, validUntil, validAfter, _token, exchangeRate)
+        );
+        (uint8 v, bytes32 r, bytes32 s) = vm.sign(key, hash);
+        op.paymasterAndData = _paymasterAndData(_token, exchangeRate, abi.encodePacked(r, s, v));
+    }
+}
@@ -65,6 +65,20 @@ contract StorageUnitTest is BaseTest {
         );
     }
 
+    /// Tests that the merkle proof generated by `lightdotso_sequence::merkle` is valid
+    function test_merkle_verify_odd_leaf() public {
+        bytes32[] memory proofs = new bytes32[](1);
+        proofs[0] = bytes32(0xe90b7bceb6e7df5418fb78d8ee546e97c83a08bbccc01a0644d599ccd2a7c2e0);
+
+        assertTrue(
+            MerkleProof.verify(
+                proofs,
+                bytes32(0x9b0225f2c6f59eeaf8302811ea290e95258763189b82dc033158e99a6ef45a87),
+                bytes32(0x0000000000000000000000000000000000000000000000000000000000000003)
+            )
+        );
+    }
+
     /// Tests that the account can correctly transfer ETH
     function test_merkle_decoding() public {
         // Attempt to decode the signature and proof
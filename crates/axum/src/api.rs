@@ -35,12 +35,13 @@ use crate::{
     routes::{
         activity, asset_change, auth, billing, billing_operation, chain, check, configuration,
         configuration_operation, configuration_operation_owner, configuration_operation_signature,
-        feedback, health, interpretation, interpretation_action, invite_code, notification,
-        notification_settings, owner, paymaster, paymaster_operation, portfolio, protocol,
-        protocol_group, queue, signature, simulation, support_request, token, token_group,
-        token_price, transaction, user, user_notification_settings, user_operation,
-        user_operation_merkle, user_operation_merkle_proof, user_settings, wallet, wallet_billing,
-        wallet_features, wallet_notification_settings, wallet_settings,
+        feedback, guardian, health, interpretation, interpretation_action, invite_code,
+        notification, notification_settings, owner, paymaster, paymaster_operation, portfolio,
+        protocol, protocol_group, queue, recovery_operation, signature, simulation,
+        support_request, token, token_group, token_price, transaction, user,
+        user_notification_settings, user_operation, user_operation_merkle,
+        user_operation_merkle_proof, user_settings, wallet, wallet_billing, wallet_features,
+        wallet_notification_settings, wallet_settings,
     },
     sessions::{authenticated, RedisStore},
     state::AppState,
@@ -108,12 +109,18 @@ use utoipa_swagger_ui::SwaggerUi;
         schemas(configuration::error::ConfigurationError),
         schemas(configuration::types::Configuration),
         schemas(configuration::types::ConfigurationOperationOwner),
+        schemas(configuration::types::ConfigurationReconciliation),
         schemas(configuration_operation::create::ConfigurationOperationCreateOwnerParams),
         schemas(configuration_operation::create::ConfigurationOperationCreateRequestParams),
         schemas(configuration_operation::create::ConfigurationOperationSignatureCreateParams),
         schemas(configuration_operation::error::ConfigurationOperationError),
         schemas(configuration_operation::list::ConfigurationOperationListCount),
+        schemas(configuration_operation::plan::ConfigurationOperationPlanRequestParams),
         schemas(configuration_operation::types::ConfigurationOperation),
+        schemas(configuration_operation::types::ConfigurationOperationPlan),
+        schemas(configuration_operation::types::ConfigurationOperationPlanChain),
+        schemas(configuration_operation::types::ConfigurationOperationPlanOwner),
+        schemas(configuration_operation::types::ConfigurationOperationPlanOwnerChange),
         schemas(configuration_operation_owner::error::ConfigurationOperationOwnerError),
         schemas(configuration_operation_owner::types::ConfigurationOperationOwner),
         schemas(configuration_operation_signature::create::ConfigurationOperationSignatureSignatureCreateParams),
@@ -123,6 +130,9 @@ use utoipa_swagger_ui::SwaggerUi;
         schemas(feedback::create::FeedbackCreateRequestParams),
         schemas(feedback::error::FeedbackError),
         schemas(feedback::types::Feedback),
+        schemas(guardian::create::GuardianCreateRequestParams),
+        schemas(guardian::error::GuardianError),
+        schemas(guardian::types::Guardian),
         schemas(interpretation::error::InterpretationError),
         schemas(interpretation::types::Interpretation),
         schemas(interpretation_action::error::InterpretationActionError),
@@ -155,14 +165,25 @@ use utoipa_swagger_ui::SwaggerUi;
         schemas(protocol_group::types::ProtocolGroup),
         schemas(queue::error::QueueError),
         schemas(queue::types::QueueSuccess),
+        schemas(recovery_operation::create::RecoveryOperationCreateRequestParams),
+        schemas(recovery_operation::create::RecoveryOperationSignatureCreateParams),
+        schemas(recovery_operation::error::RecoveryOperationError),
+        schemas(recovery_operation::sign::RecoveryOperationSignRequestParams),
+        schemas(recovery_operation::sign::RecoveryOperationSignSignatureParams),
+        schemas(recovery_operation::types::RecoveryOperation),
         schemas(signature::create::SignatureCreateParams),
         schemas(signature::create::SignatureCreateRequestParams),
         schemas(signature::error::SignatureError),
+        schemas(signature::inspect::SignatureInspectRequestParams),
         schemas(signature::types::Signature),
+        schemas(signature::types::SignatureInspection),
+        schemas(signature::types::SignatureInspectionLeaf),
+        schemas(simulation::create::SimulationCreateAccountOverrideParams),
         schemas(simulation::create::SimulationCreateRequestParams),
         schemas(simulation::list::SimulationListCount),
         schemas(simulation::error::SimulationError),
         schemas(simulation::types::Simulation),
+        schemas(simulation::types::SimulationWarning),
         schemas(support_request::error::SupportRequestError),
         schemas(support_request::types::SupportRequest),
         schemas(support_request::create::SupportRequestCreateRequestParams),
@@ -206,6 +227,7 @@ use utoipa_swagger_ui::SwaggerUi;
         schemas(wallet::error::WalletError),
         schemas(wallet::list::WalletListCount),
         schemas(wallet::types::Wallet),
+        schemas(wallet::types::WalletVersion),
         schemas(wallet::update::WalletUpdateRequestParams),
         schemas(wallet_billing::error::WalletBillingError),
         schemas(wallet_billing::types::WalletBilling),
@@ -248,10 +270,12 @@ use utoipa_swagger_ui::SwaggerUi;
         chain::v1_chain_update_handler,
         configuration::v1_configuration_get_handler,
         configuration::v1_configuration_list_handler,
+        configuration::v1_configuration_reconcile_handler,
         configuration_operation::v1_configuration_operation_create_handler,
         configuration_operation::v1_configuration_operation_get_handler,
         configuration_operation::v1_configuration_operation_list_handler,
         configuration_operation::v1_configuration_operation_list_count_handler,
+        configuration_operation::v1_configuration_operation_plan_handler,
         configuration_operation::v1_configuration_operation_update_handler,
         configuration_operation_owner::v1_configuration_operation_owner_get_handler,
         configuration_operation_owner::v1_configuration_operation_owner_list_handler,
@@ -259,6 +283,8 @@ use utoipa_swagger_ui::SwaggerUi;
         configuration_operation_signature::v1_configuration_operation_signature_get_handler,
         configuration_operation_signature::v1_configuration_operation_signature_list_handler,
         feedback::v1_feedback_create_handler,
+        guardian::v1_guardian_create_handler,
+        guardian::v1_guardian_list_handler,
         interpretation::v1_interpretation_get_handler,
         interpretation::v1_interpretation_list_handler,
         interpretation_action::v1_interpretation_action_get_handler,
@@ -289,12 +315,21 @@ use utoipa_swagger_ui::SwaggerUi;
         protocol_group::v1_protocol_group_list_handler,
         queue::v1_queue_interpretation_handler,
         queue::v1_queue_portfolio_handler,
+        queue::v1_queue_reconciliation_handler,
         queue::v1_queue_node_handler,
         queue::v1_queue_token_handler,
         queue::v1_queue_transaction_handler,
         queue::v1_queue_user_operation_handler,
+        recovery_operation::v1_recovery_operation_confirm_handler,
+        recovery_operation::v1_recovery_operation_create_handler,
+        recovery_operation::v1_recovery_operation_execute_handler,
+        recovery_operation::v1_recovery_operation_get_handler,
+        recovery_operation::v1_recovery_operation_list_handler,
+        recovery_operation::v1_recovery_operation_sign_handler,
+        recovery_operation::v1_recovery_operation_veto_handler,
         signature::v1_signature_create_handler,
         signature::v1_signature_get_handler,
+        signature::v1_signature_inspect_handler,
         signature::v1_signature_list_handler,
         simulation::v1_simulation_create_handler,
         simulation::v1_simulation_get_handler,
@@ -335,7 +370,9 @@ use utoipa_swagger_ui::SwaggerUi;
         wallet::v1_wallet_list_handler,
         wallet::v1_wallet_list_count_handler,
         wallet::v1_wallet_list_count_handler,
+        wallet::v1_wallet_signature_handler,
         wallet::v1_wallet_update_handler,
+        wallet::v1_wallet_version_handler,
         wallet_billing::v1_wallet_billing_get_handler,
         wallet_billing::v1_wallet_billing_update_handler,
         wallet_features::v1_wallet_features_get_handler,
@@ -358,6 +395,7 @@ use utoipa_swagger_ui::SwaggerUi;
         (name = "configuration_operation_signature", description = "Configuration Operation Signature API"),
         (name = "check", description = "Check API"),
         (name = "feedback", description = "Feedback API"),
+        (name = "guardian", description = "Guardian API"),
         (name = "interpretation", description = "Interpretation API"),
         (name = "interpretation_action", description = "Interpretation Action API"),
         (name = "invite_code", description = "Invite Code API"),
@@ -371,6 +409,7 @@ use utoipa_swagger_ui::SwaggerUi;
         (name = "protocol", description = "Protocol API"),
         (name = "protocol_group", description = "Protocol Group API"),
         (name = "queue", description = "Queue API"),
+        (name = "recovery_operation", description = "Recovery Operation API"),
         (name = "signature", description = "Signature API"),
         (name = "simulation", description = "Simulation API"),
         (name = "support_request", description = "Support Request API"),
@@ -499,6 +538,7 @@ pub async fn start_api_server() -> Result<()> {
         .merge(configuration_operation_signature::router())
         .merge(check::router())
         .merge(feedback::router())
+        .merge(guardian::router())
         .merge(health::router())
         .merge(interpretation::router())
         .merge(interpretation_action::router())
@@ -513,6 +553,7 @@ pub async fn start_api_server() -> Result<()> {
         .merge(protocol::router())
         .merge(protocol_group::router())
         .merge(queue::router())
+        .merge(recovery_operation::router())
         .merge(signature::router())
         .merge(simulation::router())
         .merge(support_request::router())
@@ -19,18 +19,19 @@ use crate::routes::{
     configuration_operation::error::ConfigurationOperationError,
     configuration_operation_owner::error::ConfigurationOperationOwnerError,
     configuration_operation_signature::error::ConfigurationOperationSignatureError,
-    feedback::error::FeedbackError, interpretation::error::InterpretationError,
+    feedback::error::FeedbackError, guardian::error::GuardianError,
+    interpretation::error::InterpretationError,
     interpretation_action::error::InterpretationActionError, invite_code::error::InviteCodeError,
     notification::error::NotificationError,
     notification_settings::error::NotificationSettingsError, owner::error::OwnerError,
     paymaster::error::PaymasterError, paymaster_operation::error::PaymasterOperationError,
     portfolio::error::PortfolioError, protocol::error::ProtocolError,
     protocol_group::error::ProtocolGroupError, queue::error::QueueError,
-    signature::error::SignatureError, simulation::error::SimulationError,
-    support_request::error::SupportRequestError, token::error::TokenError,
-    token_group::error::TokenGroupError, token_price::error::TokenPriceError,
-    transaction::error::TransactionError, user::error::UserError,
-    user_notification_settings::error::UserNotificationSettingsError,
+    recovery_operation::error::RecoveryOperationError, signature::error::SignatureError,
+    simulation::error::SimulationError, support_request::error::SupportRequestError,
+    token::error::TokenError, token_group::error::TokenGroupError,
+    token_price::error::TokenPriceError, transaction::error::TransactionError,
+    user::error::UserError, user_notification_settings::error::UserNotificationSettingsError,
     user_operation::error::UserOperationError,
     user_operation_merkle::error::UserOperationMerkleError,
     user_operation_merkle_proof::error::UserOperationMerkleProofError,
@@ -55,6 +56,7 @@ pub(crate) enum RouteError {
     ConfigurationOperationOwnerError(ConfigurationOperationOwnerError),
     ConfigurationOperationSignatureError(ConfigurationOperationSignatureError),
     FeedbackError(FeedbackError),
+    GuardianError(GuardianError),
     InterpretationError(InterpretationError),
     InterpretationActionError(InterpretationActionError),
     InviteCodeError(InviteCodeError),
@@ -67,6 +69,7 @@ pub(crate) enum RouteError {
     ProtocolError(ProtocolError),
     ProtocolGroupError(ProtocolGroupError),
     QueueError(QueueError),
+    RecoveryOperationError(RecoveryOperationError),
     SignatureError(SignatureError),
     SimulationError(SimulationError),
     SupportRequestError(SupportRequestError),
@@ -204,6 +207,15 @@ impl RouteErrorStatusCodeAndMsg for FeedbackError {
     }
 }
 
+impl RouteErrorStatusCodeAndMsg for GuardianError {
+    fn error_status_code_and_msg(&self) -> (StatusCode, String) {
+        match self {
+            GuardianError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.to_string()),
+            GuardianError::NotFound(msg) => (StatusCode::NOT_FOUND, msg.to_string()),
+        }
+    }
+}
+
 impl RouteErrorStatusCodeAndMsg for InterpretationError {
     fn error_status_code_and_msg(&self) -> (StatusCode, String) {
         match self {
@@ -320,6 +332,15 @@ impl RouteErrorStatusCodeAndMsg for QueueError {
     }
 }
 
+impl RouteErrorStatusCodeAndMsg for RecoveryOperationError {
+    fn error_status_code_and_msg(&self) -> (StatusCode, String) {
+        match self {
+            RecoveryOperationError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg.to_string()),
+            RecoveryOperationError::NotFound(msg) => (StatusCode::NOT_FOUND, msg.to_string()),
+        }
+    }
+}
+
 impl RouteErrorStatusCodeAndMsg for SignatureError {
     fn error_status_code_and_msg(&self) -> (StatusCode, String) {
         match self {
@@ -519,6 +540,7 @@ impl RouteErrorStatusCodeAndMsg for RouteError {
                 err.error_status_code_and_msg()
             }
             RouteError::FeedbackError(err) => err.error_status_code_and_msg(),
+            RouteError::GuardianError(err) => err.error_status_code_and_msg(),
             RouteError::InterpretationError(err) => err.error_status_code_and_msg(),
             RouteError::InterpretationActionError(err) => err.error_status_code_and_msg(),
             RouteError::InviteCodeError(err) => err.error_status_code_and_msg(),
@@ -531,6 +553,7 @@ impl RouteErrorStatusCodeAndMsg for RouteError {
             RouteError::ProtocolError(err) => err.error_status_code_and_msg(),
             RouteError::ProtocolGroupError(err) => err.error_status_code_and_msg(),
             RouteError::QueueError(err) => err.error_status_code_and_msg(),
+            RouteError::RecoveryOperationError(err) => err.error_status_code_and_msg(),
             RouteError::SignatureError(err) => err.error_status_code_and_msg(),
             RouteError::SimulationError(err) => err.error_status_code_and_msg(),
             RouteError::SupportRequestError(err) => err.error_status_code_and_msg(),
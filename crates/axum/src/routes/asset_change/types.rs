@@ -12,7 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::routes::{interpretation_action::types::InterpretationAction, token::types::Token};
+use crate::routes::{
+    interpretation_action::types::InterpretationAction, protocol::types::Protocol,
+    token::types::Token,
+};
 use lightdotso_prisma::asset_change;
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
@@ -39,6 +42,8 @@ pub(crate) struct AssetChange {
     action: Option<InterpretationAction>,
     /// The token id of the asset change.
     token: Option<Token>,
+    /// The protocol of the asset change.
+    protocol: Option<Protocol>,
 }
 
 // -----------------------------------------------------------------------------
@@ -60,6 +65,9 @@ impl From<asset_change::Data> for AssetChange {
             token: asset_change
                 .token
                 .and_then(|maybe_token| maybe_token.map(|token| Token::from(*token))),
+            protocol: asset_change.protocol.and_then(|maybe_protocol| {
+                maybe_protocol.map(|protocol| Protocol::from(*protocol))
+            }),
         }
     }
 }
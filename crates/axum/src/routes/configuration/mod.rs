@@ -15,6 +15,7 @@
 pub(crate) mod error;
 pub(crate) mod get;
 pub(crate) mod list;
+pub(crate) mod reconcile;
 pub(crate) mod types;
 
 use crate::state::AppState;
@@ -23,6 +24,9 @@ use axum::{routing::get, Router};
 
 pub(crate) use get::{__path_v1_configuration_get_handler, v1_configuration_get_handler};
 pub(crate) use list::{__path_v1_configuration_list_handler, v1_configuration_list_handler};
+pub(crate) use reconcile::{
+    __path_v1_configuration_reconcile_handler, v1_configuration_reconcile_handler,
+};
 
 // -----------------------------------------------------------------------------
 // Router
@@ -33,4 +37,5 @@ pub(crate) fn router() -> Router<AppState> {
     Router::new()
         .route("/configuration/get", get(v1_configuration_get_handler))
         .route("/configuration/list", get(v1_configuration_list_handler))
+        .route("/configuration/reconcile", get(v1_configuration_reconcile_handler))
 }
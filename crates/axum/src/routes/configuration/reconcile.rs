@@ -0,0 +1,203 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::types::ConfigurationReconciliation;
+use crate::{
+    authentication::authenticate_wallet_user, result::AppJsonResult,
+    routes::user_operation::signature::upgrade_signature_builder, state::AppState,
+};
+use autometrics::autometrics;
+use axum::{
+    extract::{Query, State},
+    headers::{authorization::Bearer, Authorization},
+    Json, TypedHeader,
+};
+use ethers::types::Address;
+use ethers_main::utils::to_checksum;
+use eyre::{eyre, Result};
+use lightdotso_common::traits::{HexToBytes, VecU8ToHex};
+use lightdotso_db::models::configuration::{
+    reconcile_wallet_configurations, upsert_configuration_reconciliations, ChainReconciliation,
+    ReconciliationStatus,
+};
+use lightdotso_prisma::{configuration, owner};
+use lightdotso_sequence::builder::chain_upgrade_signatures;
+use lightdotso_tracing::tracing::info;
+use prisma_client_rust::Direction;
+use serde::Deserialize;
+use tower_sessions_core::Session;
+use utoipa::IntoParams;
+
+// -----------------------------------------------------------------------------
+// Query
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Deserialize, Default, IntoParams)]
+#[serde(rename_all = "snake_case")]
+#[into_params(parameter_in = Query)]
+pub struct ReconcileQuery {
+    /// The address of the wallet to reconcile.
+    pub address: String,
+    /// The user id to reconcile as. (for admin purposes only)
+    pub user_id: Option<String>,
+}
+
+// -----------------------------------------------------------------------------
+// Handler
+// -----------------------------------------------------------------------------
+
+/// Reconcile the on-chain image hashes of a wallet
+#[utoipa::path(
+        get,
+        path = "/configuration/reconcile",
+        params(
+            ReconcileQuery
+        ),
+        responses(
+            (status = 200, description = "Configuration reconciled successfully", body = [ConfigurationReconciliation]),
+            (status = 401, description = "Unauthorized", body = AuthError),
+            (status = 500, description = "Configuration bad request", body = ConfigurationError),
+        )
+    )]
+#[autometrics]
+pub(crate) async fn v1_configuration_reconcile_handler(
+    reconcile_query: Query<ReconcileQuery>,
+    State(state): State<AppState>,
+    mut session: Session,
+    auth: Option<TypedHeader<Authorization<Bearer>>>,
+) -> AppJsonResult<Vec<ConfigurationReconciliation>> {
+    // -------------------------------------------------------------------------
+    // Parse
+    // -------------------------------------------------------------------------
+
+    // Get the reconcile query.
+    let Query(query) = reconcile_query;
+
+    let parsed_query_address: Address = query.address.parse()?;
+    let checksum_address = to_checksum(&parsed_query_address, None);
+
+    // -------------------------------------------------------------------------
+    // Authentication
+    // -------------------------------------------------------------------------
+
+    // Only the owners of the wallet can reconcile, as the reconciliation calls the rpc of every
+    // chain of the wallet.
+    authenticate_wallet_user(
+        &state,
+        &mut session,
+        &parsed_query_address,
+        auth.map(|auth| auth.token().to_string()),
+        query.user_id.clone(),
+    )
+    .await?;
+
+    // -------------------------------------------------------------------------
+    // DB
+    // -------------------------------------------------------------------------
+
+    // Reconcile the on-chain image hashes against the configuration history.
+    let reconciliations =
+        reconcile_wallet_configurations(state.client.clone(), parsed_query_address).await?;
+    info!(?reconciliations);
+
+    // Persist the reconciliations for the configuration operation plans.
+    upsert_configuration_reconciliations(
+        state.client.clone(),
+        parsed_query_address,
+        &reconciliations,
+    )
+    .await?;
+
+    // Get the configurations w/ the upgrade signatures from the database.
+    let configurations = state
+        .client
+        .configuration()
+        .find_many(vec![configuration::address::equals(checksum_address)])
+        .with(configuration::owners::fetch(vec![]).order_by(owner::index::order(Direction::Asc)))
+        .with(configuration::configuration_operation_signatures::fetch(vec![]))
+        .order_by(configuration::checkpoint::order(Direction::Desc))
+        .exec()
+        .await?;
+
+    // -------------------------------------------------------------------------
+    // Return
+    // -------------------------------------------------------------------------
+
+    let mut res = vec![];
+    for reconciliation in reconciliations {
+        // Get the chained upgrade signature for the lagging chains.
+        let chained_signature = match reconciliation.status {
+            ReconciliationStatus::Lagging => Some(
+                chained_upgrade_signature(parsed_query_address, &reconciliation, &configurations)
+                    .await?,
+            ),
+            _ => None,
+        };
+
+        res.push(ConfigurationReconciliation {
+            chain_id: reconciliation.chain_id,
+            status: format!("{:?}", reconciliation.status).to_lowercase(),
+            onchain_image_hash: reconciliation
+                .onchain_image_hash
+                .map(|image_hash| format!("{:?}", image_hash)),
+            onchain_checkpoint: reconciliation
+                .onchain_configuration
+                .map(|configuration| configuration.checkpoint),
+            latest_checkpoint: reconciliation.latest_configuration.checkpoint,
+            chained_signature,
+        });
+    }
+
+    Ok(Json::from(res))
+}
+
+// -----------------------------------------------------------------------------
+// Utils
+// -----------------------------------------------------------------------------
+
+/// Build the chained signature of the upgrades from the on-chain configuration to the latest
+/// configuration, which proves the latest image hash to the wallet on the lagging chain.
+async fn chained_upgrade_signature(
+    address: Address,
+    reconciliation: &ChainReconciliation,
+    configurations: &[configuration::Data],
+) -> Result<String> {
+    let chain_id = reconciliation.chain_id as u64;
+    let onchain_checkpoint = reconciliation
+        .onchain_configuration
+        .as_ref()
+        .map(|configuration| configuration.checkpoint)
+        .ok_or(eyre!("No on-chain configuration"))?;
+
+    // The configurations from the one before the latest down to the on-chain one, in descending
+    // order, each signing the image hash of the next one.
+    let upgrade_configurations = configurations
+        .iter()
+        .filter(|configuration| {
+            configuration.checkpoint >= onchain_checkpoint &&
+                configuration.checkpoint < reconciliation.latest_configuration.checkpoint
+        })
+        .collect::<Vec<_>>();
+
+    let mut next_image_hash = reconciliation.latest_configuration.image_hash.hex_to_bytes32()?;
+    let mut builders = vec![];
+    for configuration in upgrade_configurations {
+        builders.push(
+            upgrade_signature_builder(chain_id, address, &next_image_hash, configuration).await?,
+        );
+        next_image_hash = configuration.image_hash.hex_to_bytes32()?;
+    }
+
+    Ok(chain_upgrade_signatures(builders)?.to_hex_string())
+}
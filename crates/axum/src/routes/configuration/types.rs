@@ -51,10 +51,36 @@ pub(crate) struct ConfigurationOperationOwner {
     address: String,
     /// The weight of the owner.
     weight: i64,
+    /// The leaf type of the owner, either an address, a nested config, or a subdigest.
+    leaf_type: String,
+    /// The internal threshold of the nested config.
+    threshold: Option<i64>,
+    /// The pre-approved subdigest.
+    subdigest: Option<String>,
+    /// The index of the nested config that the owner belongs to.
+    parent_index: Option<i32>,
     /// The user of the owner.
     user: Option<User>,
 }
 
+/// Configuration reconciliation of a chain.
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub(crate) struct ConfigurationReconciliation {
+    /// The chain id of the wallet.
+    pub chain_id: i64,
+    /// The status of the on-chain image hash, either synced, lagging, unknown, or undeployed.
+    pub status: String,
+    /// The image hash of the wallet on the chain.
+    pub onchain_image_hash: Option<String>,
+    /// The checkpoint of the on-chain configuration.
+    pub onchain_checkpoint: Option<i64>,
+    /// The checkpoint of the latest configuration.
+    pub latest_checkpoint: i64,
+    /// The chained upgrade signature from the on-chain configuration to the latest configuration.
+    pub chained_signature: Option<String>,
+}
+
 // -----------------------------------------------------------------------------
 // From
 // -----------------------------------------------------------------------------
@@ -83,6 +109,10 @@ impl From<owner::Data> for ConfigurationOperationOwner {
             address: owner.address.to_string(),
             index: owner.index,
             weight: owner.weight,
+            leaf_type: owner.leaf_type.to_string(),
+            threshold: owner.threshold,
+            subdigest: owner.subdigest,
+            parent_index: owner.parent_index,
             user: owner.user.and_then(|maybe_user| maybe_user.map(|user| User::from(*user))),
         }
     }
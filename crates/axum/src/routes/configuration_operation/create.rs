@@ -23,6 +23,10 @@ use crate::{
             error::ConfigurationOperationError, types::ConfigurationOperation,
         },
         configuration_operation_signature::error::ConfigurationOperationSignatureError,
+        owner::utils::{
+            flatten_members, owner_params_to_member, verify_contract_owners,
+            verify_wallet_owner_signature, OwnerParams,
+        },
     },
     state::AppState,
 };
@@ -35,21 +39,22 @@ use ethers_main::{
     types::{H160, H256},
     utils::to_checksum,
 };
-use eyre::{eyre, Result};
+use eyre::Result;
 use lightdotso_common::traits::{HexToBytes, VecU8ToHex};
-use lightdotso_db::models::activity::CustomParams;
+use lightdotso_db::models::{
+    activity::CustomParams, configuration_operation::create_configuration_operation_with_owners,
+};
 use lightdotso_kafka::{
     topics::activity::produce_activity_message, types::activity::ActivityMessage,
 };
 use lightdotso_prisma::{
-    configuration, configuration_operation, configuration_operation_owner, owner, user, wallet,
-    ActivityEntity, ActivityOperation,
+    configuration, configuration_operation, owner, user, wallet, ActivityEntity, ActivityOperation,
+    OwnerLeafType,
 };
 use lightdotso_sequence::{
-    builder::rooted_node_builder,
+    builder::{config_tree_builder, ConfigMember},
     config::WalletConfig,
-    signature::recover_ecdsa_signature,
-    types::{AddressSignatureLeaf, SignatureLeaf, Signer, SignerNode},
+    transition::check_config_lockout,
     utils::{hash_image_bytes32, render_subdigest},
 };
 use lightdotso_tracing::tracing::{error, info};
@@ -90,14 +95,48 @@ pub struct ConfigurationOperationCreateRequestParams {
 }
 
 /// Wallet owner.
+/// The owner is either an address, a nested config w/ the `threshold` of its `owners`, or a
+/// pre-approved `subdigest`.
 #[derive(Serialize, Deserialize, ToSchema, Clone)]
 #[serde(rename_all = "snake_case")]
 #[schema(example = json!({"address": "0x4fd9D0eE6D6564E80A9Ee00c0163fC952d0A45Ed", "weight": 1}))]
 pub(crate) struct ConfigurationOperationCreateOwnerParams {
     /// The address of the owner.
+    #[serde(default)]
     pub address: String,
-    /// The weight of the owner.
+    /// The weight of the owner, or the external weight of the nested config.
+    #[serde(default)]
     pub weight: u8,
+    /// Whether the owner is a contract that signs w/ EIP-1271.
+    #[serde(default)]
+    pub is_contract: bool,
+    /// The internal threshold of the nested config.
+    pub threshold: Option<u16>,
+    /// The owners of the nested config.
+    pub owners: Option<Vec<ConfigurationOperationCreateOwnerParams>>,
+    /// The pre-approved subdigest, which is approved w/o any signature.
+    pub subdigest: Option<String>,
+}
+
+impl OwnerParams for ConfigurationOperationCreateOwnerParams {
+    fn address(&self) -> &str {
+        &self.address
+    }
+    fn weight(&self) -> u8 {
+        self.weight
+    }
+    fn is_contract(&self) -> bool {
+        self.is_contract
+    }
+    fn threshold(&self) -> Option<u16> {
+        self.threshold
+    }
+    fn owners(&self) -> Option<&[Self]> {
+        self.owners.as_deref()
+    }
+    fn subdigest(&self) -> Option<&str> {
+        self.subdigest.as_deref()
+    }
 }
 
 /// Signature operation
@@ -184,34 +223,31 @@ pub(crate) async fn v1_configuration_operation_create_handler(
         .find(|owner| owner.id == sig.owner_id)
         .ok_or(AppError::BadRequest)?;
 
-    // Check if all of the owner address can be parsed to H160.
-    let owners_addresses = owners
+    // Check if all of the owners can be parsed to the config members.
+    let mut contracts = vec![];
+    let members = owners
         .iter()
-        .map(|owner| owner.address.parse::<H160>())
-        .collect::<Result<Vec<H160>, _>>()?;
+        .map(|owner| owner_params_to_member(owner, &mut contracts))
+        .collect::<Result<Vec<ConfigMember>>>()
+        .map_err(|_| AppError::BadRequest)?;
+
+    // Flatten the owners to store them in order.
+    let owner_entries = flatten_members(&members, &contracts);
+
+    // Get the addresses of the owners, w/o the nested configs and the subdigests.
+    let owners_addresses: Vec<H160> = owner_entries
+        .iter()
+        .filter(|entry| entry.leaf_type == OwnerLeafType::Address)
+        .map(|entry| entry.address)
+        .collect();
 
     // Check if the threshold is greater than 0
     if params.threshold == 0 {
         return Err(AppError::BadRequest);
     }
 
-    // Conver the owners to SignerNode.
-    let owner_nodes: Vec<SignerNode> = owners
-        .iter()
-        .map(|owner| SignerNode {
-            signer: Some(Signer {
-                weight: Some(owner.weight),
-                leaf: SignatureLeaf::AddressSignature(AddressSignatureLeaf {
-                    address: owner.address.parse().unwrap(),
-                }),
-            }),
-            left: None,
-            right: None,
-        })
-        .collect();
-
     // Build the node tree.
-    let tree = rooted_node_builder(owner_nodes)?;
+    let tree = config_tree_builder(&members)?;
 
     // Create a wallet config
     let mut config = WalletConfig {
@@ -238,14 +274,13 @@ pub(crate) async fn v1_configuration_operation_create_handler(
     // Parse the image hash to bytes.
     let image_hash_bytes: H256 = image_hash.into();
 
-    // Check if the wallet configuration is valid.
-    let valid = config.is_wallet_valid();
-
-    // If the wallet configuration is invalid, return a 500.
-    if !valid {
-        error!("Invalid configuration");
-        return Err(eyre!("Invalid configuration").into());
-    }
+    // Check that the wallet configuration can ever be signed.
+    check_config_lockout(&config).map_err(|err| {
+        error!("Invalid configuration: {}", err);
+        RouteError::ConfigurationOperationError(ConfigurationOperationError::BadRequest(
+            err.to_string(),
+        ))
+    })?;
 
     // -------------------------------------------------------------------------
     // DB
@@ -264,6 +299,11 @@ pub(crate) async fn v1_configuration_operation_create_handler(
         ConfigurationOperationError::NotFound("Wallet not found".to_string()),
     ))?;
 
+    // Check that the contract owners are contracts on-chain.
+    if !verify_contract_owners(state.client.clone(), wallet.address.parse()?, &contracts).await? {
+        return Err(AppError::BadRequest);
+    }
+
     // -------------------------------------------------------------------------
     // Signature
     // -------------------------------------------------------------------------
@@ -313,20 +353,27 @@ pub(crate) async fn v1_configuration_operation_create_handler(
     // Signature
     // -------------------------------------------------------------------------
 
-    // Recover the signature.
-    let recovered_sig = recover_ecdsa_signature(&sig_bytes, &subdigest, 0)?;
-    info!(?recovered_sig);
-
     // If the owner is not found, return a 404.
     let owner = owner.ok_or(AppError::NotFound)?;
 
-    // Check that the recovered signature is the same as the signature sender.
-    if recovered_sig.address != owner.clone().address.parse()? {
-        error!(
-            "recovered_sig.address: {}, owner.address: {}",
-            recovered_sig.address,
-            owner.clone().address
-        );
+    // Only the owners of the address leaves can sign.
+    if owner.leaf_type != OwnerLeafType::Address {
+        return Err(AppError::BadRequest);
+    }
+
+    // Check that the signature is valid for the owner, on the chains the wallet is deployed on for
+    // the contract owners.
+    let is_valid = verify_wallet_owner_signature(
+        state.client.clone(),
+        wallet.address.parse()?,
+        owner.address.parse()?,
+        owner.signer_type,
+        &subdigest,
+        &sig_bytes,
+    )
+    .await?;
+    if !is_valid {
+        error!("Invalid signature of owner.address: {}", owner.address);
         return Err(AppError::BadRequest);
     }
 
@@ -345,65 +392,24 @@ pub(crate) async fn v1_configuration_operation_create_handler(
         .await?;
     info!(?user_data);
 
+    // Get the owners to store, w/ the users of the owners.
+    let owner_params =
+        owner_entries.iter().map(|entry| entry.to_owner_params(&user_data)).collect::<Vec<_>>();
+
     // Create a new configuration_operation w/ the same contents as the configuration operation.
     let configuration_operation: Result<configuration_operation::Data> = state
         .client
         ._transaction()
         .run(|client| async move {
-            // Create the configuration to the database.
-            let configuration_operation = client
-                .configuration_operation()
-                .create(
-                    configuration.checkpoint + 1,
-                    format!("{:?}", image_hash_bytes),
-                    threshold as i64,
-                    wallet::address::equals(query.address.clone()),
-                    vec![],
-                )
-                .exec()
-                .await?;
-            info!(?configuration_operation);
-
-            // Create the owners to the database.
-            let owner_data = client
-                .configuration_operation_owner()
-                .create_many(
-                    owners
-                        .iter()
-                        .enumerate()
-                        .map(|(index, config_owner)| {
-                            configuration_operation_owner::create_unchecked(
-                                to_checksum(&config_owner.address.parse::<H160>().unwrap(), None),
-                                config_owner.weight.into(),
-                                index as i32,
-                                configuration_operation.clone().id,
-                                vec![configuration_operation_owner::user_id::set(Some(
-                                    user_data
-                                        .iter()
-                                        .find(|user| {
-                                            user.address ==
-                                                to_checksum(
-                                                    &config_owner
-                                                        .clone()
-                                                        .address
-                                                        .parse::<H160>()
-                                                        .unwrap(),
-                                                    None,
-                                                )
-                                        })
-                                        .unwrap()
-                                        .id
-                                        .clone(),
-                                ))],
-                            )
-                        })
-                        .collect(),
-                )
-                .exec()
-                .await?;
-            info!(?owner_data);
-
-            Ok(configuration_operation)
+            create_configuration_operation_with_owners(
+                &client,
+                query.address.clone(),
+                configuration.checkpoint + 1,
+                format!("{:?}", image_hash_bytes),
+                threshold as i64,
+                &owner_params,
+            )
+            .await
         })
         .await;
     info!(?configuration_operation);
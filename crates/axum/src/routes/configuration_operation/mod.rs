@@ -16,6 +16,7 @@ pub(crate) mod create;
 pub(crate) mod error;
 pub(crate) mod get;
 pub(crate) mod list;
+pub(crate) mod plan;
 pub(crate) mod types;
 pub(crate) mod update;
 
@@ -37,6 +38,9 @@ pub(crate) use list::{
     __path_v1_configuration_operation_list_handler, v1_configuration_operation_list_count_handler,
     v1_configuration_operation_list_handler,
 };
+pub(crate) use plan::{
+    __path_v1_configuration_operation_plan_handler, v1_configuration_operation_plan_handler,
+};
 pub(crate) use update::{
     __path_v1_configuration_operation_update_handler, v1_configuration_operation_update_handler,
 };
@@ -55,5 +59,6 @@ pub(crate) fn router() -> Router<AppState> {
             "/configuration_operation/list/count",
             get(v1_configuration_operation_list_count_handler),
         )
+        .route("/configuration_operation/plan", post(v1_configuration_operation_plan_handler))
         .route("/configuration_operation/update", put(v1_configuration_operation_update_handler))
 }
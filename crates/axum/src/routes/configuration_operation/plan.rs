@@ -0,0 +1,213 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::{
+    error::RouteError,
+    result::AppJsonResult,
+    routes::{
+        configuration_operation::{
+            create::ConfigurationOperationCreateOwnerParams,
+            error::ConfigurationOperationError,
+            types::{ConfigurationOperationPlan, ConfigurationOperationPlanChain},
+        },
+        owner::utils::owner_params_to_member,
+        user_operation::signature::configuration_to_wallet_config,
+    },
+    state::AppState,
+};
+use autometrics::autometrics;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use ethers_main::{
+    types::H160,
+    utils::{hex, to_checksum},
+};
+use eyre::Result;
+use lightdotso_db::models::configuration::get_configuration_reconciliations;
+use lightdotso_prisma::{configuration, ConfigurationReconciliationStatus};
+use lightdotso_sequence::{
+    builder::{config_tree_builder, ConfigMember},
+    config::WalletConfig,
+    transition::{plan_config_transition, update_image_hash_calldata},
+};
+use lightdotso_tracing::tracing::{error, info};
+use prisma_client_rust::Direction;
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+// -----------------------------------------------------------------------------
+// Query
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Deserialize, Default, IntoParams)]
+#[serde(rename_all = "snake_case")]
+#[into_params(parameter_in = Query)]
+pub struct PlanQuery {
+    /// The address of the wallet.
+    pub address: String,
+}
+
+// -----------------------------------------------------------------------------
+// Params
+// -----------------------------------------------------------------------------
+
+/// Configuration operation plan request params
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct ConfigurationOperationPlanRequestParams {
+    /// The array of owners of the next configuration.
+    #[schema(example = json!([{"address": "0x4fd9D0eE6D6564E80A9Ee00c0163fC952d0A45Ed", "weight": 1}]))]
+    pub owners: Vec<ConfigurationOperationCreateOwnerParams>,
+    /// The threshold of the next configuration.
+    #[schema(example = 3, default = 1)]
+    pub threshold: u16,
+}
+
+// -----------------------------------------------------------------------------
+// Handler
+// -----------------------------------------------------------------------------
+
+/// Plan the transition to a configuration
+#[utoipa::path(
+        post,
+        path = "/configuration_operation/plan",
+        params(
+            PlanQuery
+        ),
+        request_body = ConfigurationOperationPlanRequestParams,
+        responses(
+            (status = 200, description = "Configuration operation planned successfully", body = ConfigurationOperationPlan),
+            (status = 400, description = "Invalid configuration", body = ConfigurationOperationError),
+            (status = 404, description = "Configuration not found", body = ConfigurationOperationError),
+        )
+    )]
+#[autometrics]
+pub(crate) async fn v1_configuration_operation_plan_handler(
+    plan_query: Query<PlanQuery>,
+    State(state): State<AppState>,
+    Json(params): Json<ConfigurationOperationPlanRequestParams>,
+) -> AppJsonResult<ConfigurationOperationPlan> {
+    // -------------------------------------------------------------------------
+    // Parse
+    // -------------------------------------------------------------------------
+
+    // Get the plan query.
+    let Query(query) = plan_query;
+    info!(?query);
+
+    let parsed_query_address: H160 = query.address.parse()?;
+
+    // -------------------------------------------------------------------------
+    // DB
+    // -------------------------------------------------------------------------
+
+    // Get the current configuration for the wallet.
+    let configuration = state
+        .client
+        .configuration()
+        .find_first(vec![configuration::address::equals(to_checksum(&parsed_query_address, None))])
+        .order_by(configuration::checkpoint::order(Direction::Desc))
+        .with(configuration::owners::fetch(vec![]))
+        .exec()
+        .await?;
+    info!(?configuration);
+
+    // If the configuration is not found, return a 404.
+    let configuration = configuration.ok_or(RouteError::ConfigurationOperationError(
+        ConfigurationOperationError::NotFound("Configuration not found".to_string()),
+    ))?;
+
+    // -------------------------------------------------------------------------
+    // Plan
+    // -------------------------------------------------------------------------
+
+    // Get the current wallet config from the configuration.
+    let current = configuration_to_wallet_config(&configuration)?;
+
+    // Check if all of the owners can be parsed to the config members.
+    let mut contracts = vec![];
+    let members = params
+        .owners
+        .iter()
+        .map(|owner| owner_params_to_member(owner, &mut contracts))
+        .collect::<Result<Vec<ConfigMember>>>()
+        .map_err(|err| {
+            RouteError::ConfigurationOperationError(ConfigurationOperationError::BadRequest(
+                err.to_string(),
+            ))
+        })?;
+
+    // Create the next wallet config.
+    let next = WalletConfig {
+        // The signature type is 0 since it is not computed in the encoding.
+        signature_type: 0,
+        checkpoint: current.checkpoint + 1,
+        threshold: params.threshold,
+        // Can be 1 since it is not computed in the encoding.
+        weight: 1,
+        // Can be 0 since it is not computed in the encoding.
+        image_hash: [0; 32].into(),
+        tree: config_tree_builder(&members).map_err(|err| {
+            RouteError::ConfigurationOperationError(ConfigurationOperationError::BadRequest(
+                err.to_string(),
+            ))
+        })?,
+        internal_root: None,
+        internal_recovered_configs: None,
+    };
+
+    // Plan the transition, rejecting the configurations which could never be signed.
+    let transition = plan_config_transition(&current, &next).map_err(|err| {
+        error!("Invalid configuration: {}", err);
+        RouteError::ConfigurationOperationError(ConfigurationOperationError::BadRequest(
+            err.to_string(),
+        ))
+    })?;
+    info!(?transition);
+
+    // -------------------------------------------------------------------------
+    // Chains
+    // -------------------------------------------------------------------------
+
+    // Get the chains the wallet is deployed on from the stored reconciliations, which are
+    // refreshed by the reconciliation queue w/o calling the rpc of every chain on each plan.
+    let reconciliations =
+        get_configuration_reconciliations(state.client.clone(), parsed_query_address).await?;
+
+    // The calldata is the same on every chain, since the upgrade signatures are chain agnostic.
+    let calldata = format!("0x{}", hex::encode(update_image_hash_calldata(transition.image_hash)));
+
+    let chains = reconciliations
+        .iter()
+        .filter(|reconciliation| {
+            reconciliation.status != ConfigurationReconciliationStatus::Undeployed
+        })
+        .map(|reconciliation| ConfigurationOperationPlanChain {
+            chain_id: reconciliation.chain_id,
+            calldata: calldata.clone(),
+            status: format!("{:?}", reconciliation.status).to_lowercase(),
+            // The lagging and the unknown image hashes can't verify the upgrade signed by the
+            // current configuration alone.
+            is_chained_signature_required: reconciliation.status !=
+                ConfigurationReconciliationStatus::Synced,
+        })
+        .collect();
+
+    // -------------------------------------------------------------------------
+    // Return
+    // -------------------------------------------------------------------------
+
+    Ok(Json::from(ConfigurationOperationPlan::from((transition, chains))))
+}
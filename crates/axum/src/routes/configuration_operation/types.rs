@@ -12,7 +12,9 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use ethers_main::utils::to_checksum;
 use lightdotso_prisma::configuration_operation;
+use lightdotso_sequence::transition::{ConfigTransition, OwnerWeight, OwnerWeightChange};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
@@ -36,6 +38,75 @@ pub(crate) struct ConfigurationOperation {
     pub status: String,
 }
 
+/// ConfigurationOperationPlan root type.
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub(crate) struct ConfigurationOperationPlan {
+    /// The owners added in the next configuration.
+    pub added_owners: Vec<ConfigurationOperationPlanOwner>,
+    /// The owners removed from the current configuration.
+    pub removed_owners: Vec<ConfigurationOperationPlanOwner>,
+    /// The owners w/ the changed weights.
+    pub changed_owners: Vec<ConfigurationOperationPlanOwnerChange>,
+    /// The pre-approved subdigests added in the next configuration.
+    pub added_subdigests: Vec<String>,
+    /// The pre-approved subdigests removed from the current configuration.
+    pub removed_subdigests: Vec<String>,
+    /// The threshold of the current configuration.
+    pub previous_threshold: i64,
+    /// The threshold of the next configuration.
+    pub threshold: i64,
+    /// The checkpoint of the current configuration.
+    pub previous_checkpoint: i64,
+    /// The checkpoint of the next configuration.
+    pub checkpoint: i64,
+    /// The image hash of the next configuration.
+    pub image_hash: String,
+    /// The weight of the next configuration reachable by the current owners.
+    pub current_signers_weight: i64,
+    /// Whether the current owners can still reach the threshold of the next configuration.
+    pub is_reachable_by_current_signers: bool,
+    /// The `updateImageHash` calldata on each of the chains the wallet is deployed on.
+    pub chains: Vec<ConfigurationOperationPlanChain>,
+}
+
+/// ConfigurationOperationPlan owner.
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub(crate) struct ConfigurationOperationPlanOwner {
+    /// The address of the owner.
+    pub address: String,
+    /// The weight of the owner.
+    pub weight: i64,
+}
+
+/// ConfigurationOperationPlan owner w/ the changed weight.
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub(crate) struct ConfigurationOperationPlanOwnerChange {
+    /// The address of the owner.
+    pub address: String,
+    /// The weight of the owner in the current configuration.
+    pub previous_weight: i64,
+    /// The weight of the owner in the next configuration.
+    pub weight: i64,
+}
+
+/// ConfigurationOperationPlan chain.
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub(crate) struct ConfigurationOperationPlanChain {
+    /// The chain id the wallet is deployed on.
+    pub chain_id: i64,
+    /// The calldata of `updateImageHash` the wallet calls on itself.
+    pub calldata: String,
+    /// The last reconciled status of the wallet configuration on the chain.
+    pub status: String,
+    /// Whether the wallet is not on the latest configuration on the chain, so that the upgrade
+    /// needs to be chained w/ the signatures of the configurations in between.
+    pub is_chained_signature_required: bool,
+}
+
 // -----------------------------------------------------------------------------
 // From
 // -----------------------------------------------------------------------------
@@ -52,3 +123,53 @@ impl From<configuration_operation::Data> for ConfigurationOperation {
         }
     }
 }
+
+/// Implement From<OwnerWeight> for ConfigurationOperationPlanOwner.
+impl From<OwnerWeight> for ConfigurationOperationPlanOwner {
+    fn from(owner: OwnerWeight) -> Self {
+        Self { address: to_checksum(&owner.address, None), weight: owner.weight.into() }
+    }
+}
+
+/// Implement From<OwnerWeightChange> for ConfigurationOperationPlanOwnerChange.
+impl From<OwnerWeightChange> for ConfigurationOperationPlanOwnerChange {
+    fn from(owner: OwnerWeightChange) -> Self {
+        Self {
+            address: to_checksum(&owner.address, None),
+            previous_weight: owner.previous_weight.into(),
+            weight: owner.weight.into(),
+        }
+    }
+}
+
+/// Implement From<(ConfigTransition, Vec<ConfigurationOperationPlanChain>)> for
+/// ConfigurationOperationPlan.
+impl From<(ConfigTransition, Vec<ConfigurationOperationPlanChain>)> for ConfigurationOperationPlan {
+    fn from(
+        (transition, chains): (ConfigTransition, Vec<ConfigurationOperationPlanChain>),
+    ) -> Self {
+        Self {
+            added_owners: transition.added_owners.into_iter().map(Into::into).collect(),
+            removed_owners: transition.removed_owners.into_iter().map(Into::into).collect(),
+            changed_owners: transition.changed_owners.into_iter().map(Into::into).collect(),
+            added_subdigests: transition
+                .added_subdigests
+                .iter()
+                .map(|subdigest| format!("{:?}", subdigest))
+                .collect(),
+            removed_subdigests: transition
+                .removed_subdigests
+                .iter()
+                .map(|subdigest| format!("{:?}", subdigest))
+                .collect(),
+            previous_threshold: transition.previous_threshold.into(),
+            threshold: transition.threshold.into(),
+            previous_checkpoint: transition.previous_checkpoint.into(),
+            checkpoint: transition.checkpoint.into(),
+            image_hash: format!("{:?}", transition.image_hash),
+            current_signers_weight: transition.current_signers_weight as i64,
+            is_reachable_by_current_signers: transition.is_reachable_by_current_signers,
+            chains,
+        }
+    }
+}
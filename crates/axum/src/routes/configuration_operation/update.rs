@@ -28,7 +28,9 @@ use axum::{
     Json,
 };
 use eyre::Result;
-use lightdotso_db::models::activity::CustomParams;
+use lightdotso_db::models::{
+    activity::CustomParams, configuration_operation::create_configuration_with_owners,
+};
 use lightdotso_kafka::{
     topics::activity::produce_activity_message, types::activity::ActivityMessage,
 };
@@ -205,56 +207,12 @@ pub(crate) async fn v1_configuration_operation_update_handler(
             .client
             ._transaction()
             .run(|client| async move {
-                // Create the configuration to the database.
-                let configuration_data = client
-                    .configuration()
-                    .create(
-                        configuration_operation.clone().address,
-                        configuration_operation.clone().checkpoint,
-                        configuration_operation.clone().image_hash,
-                        configuration_operation.clone().threshold,
-                        vec![configuration::configuration_operation::connect(
-                            configuration_operation::id::equals(configuration_operation.clone().id),
-                        )],
-                    )
-                    .exec()
-                    .await?;
-                info!(?configuration_data);
-
-                // Create the owners to the database.
-                let owner_data = client
-                    .owner()
-                    .create_many(
-                        owners
-                            .iter()
-                            .enumerate()
-                            .map(|(_index, owner)| {
-                                owner::create_unchecked(
-                                    owner.clone().address,
-                                    owner.clone().weight,
-                                    owner.clone().index,
-                                    configuration_data.clone().id,
-                                    vec![owner::user_id::set(Some(
-                                        owner
-                                            .clone()
-                                            .user
-                                            .as_ref()
-                                            .unwrap()
-                                            .as_ref()
-                                            .unwrap()
-                                            .id
-                                            .clone(),
-                                    ))],
-                                )
-                            })
-                            .collect(),
-                    )
-                    .skip_duplicates()
-                    .exec()
-                    .await?;
-                info!(?owner_data);
-
-                Ok(configuration_data)
+                create_configuration_with_owners(
+                    &client,
+                    &configuration_operation,
+                    &owners.iter().map(Into::into).collect::<Vec<_>>(),
+                )
+                .await
             })
             .await;
         info!(?configuration);
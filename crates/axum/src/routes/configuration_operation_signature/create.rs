@@ -18,7 +18,10 @@ use super::types::ConfigurationOperationSignature;
 use crate::{
     error::RouteError,
     result::{AppError, AppJsonResult},
-    routes::configuration_operation_signature::error::ConfigurationOperationSignatureError,
+    routes::{
+        configuration_operation_signature::error::ConfigurationOperationSignatureError,
+        owner::utils::{unflatten_members, verify_wallet_owner_signature, OwnerEntry},
+    },
     state::AppState,
 };
 use autometrics::autometrics;
@@ -26,6 +29,7 @@ use axum::{
     extract::{Query, State},
     Json,
 };
+use eyre::Result;
 use lightdotso_common::traits::HexToBytes;
 use lightdotso_db::models::activity::CustomParams;
 use lightdotso_kafka::{
@@ -33,13 +37,11 @@ use lightdotso_kafka::{
 };
 use lightdotso_prisma::{
     configuration, configuration_operation, configuration_operation_owner,
-    configuration_operation_signature, owner, ActivityEntity, ActivityOperation,
+    configuration_operation_signature, owner, ActivityEntity, ActivityOperation, OwnerLeafType,
 };
 use lightdotso_sequence::{
-    builder::rooted_node_builder,
+    builder::config_tree_builder,
     config::WalletConfig,
-    signature::recover_ecdsa_signature,
-    types::{AddressSignatureLeaf, SignatureLeaf, Signer, SignerNode},
     utils::{hash_image_bytes32, render_subdigest},
 };
 use lightdotso_tracing::tracing::{error, info};
@@ -193,23 +195,15 @@ pub(crate) async fn v1_configuration_operation_signature_create_handler(
     // Check that the signature is valid.
     let sig_bytes = sig.signature.hex_to_bytes()?;
 
-    // Conver the owners to SignerNode.
-    let owner_nodes: Vec<SignerNode> = configuration_operation_owners
+    // Conver the owners to the config members.
+    let owner_entries = configuration_operation_owners
         .iter()
-        .map(|owner| SignerNode {
-            signer: Some(Signer {
-                weight: Some(owner.weight as u8),
-                leaf: SignatureLeaf::AddressSignature(AddressSignatureLeaf {
-                    address: owner.address.parse().unwrap(),
-                }),
-            }),
-            left: None,
-            right: None,
-        })
-        .collect();
+        .map(OwnerEntry::try_from)
+        .collect::<Result<Vec<_>>>()?;
+    let members = unflatten_members(&owner_entries)?;
 
     // Build the node tree.
-    let tree = rooted_node_builder(owner_nodes)?;
+    let tree = config_tree_builder(&members)?;
 
     // Create a wallet config
     let mut config = WalletConfig {
@@ -235,10 +229,6 @@ pub(crate) async fn v1_configuration_operation_signature_create_handler(
     )?;
     info!(?subdigest);
 
-    // Recover the signature.
-    let recovered_sig = recover_ecdsa_signature(&sig_bytes, &subdigest, 0)?;
-    info!(?recovered_sig);
-
     // -------------------------------------------------------------------------
     // DB
     // -------------------------------------------------------------------------
@@ -261,11 +251,26 @@ pub(crate) async fn v1_configuration_operation_signature_create_handler(
     let configuration_operation_signature_owner =
         configuration_operation_signature_owner.ok_or(AppError::NotFound)?;
 
-    // Check that the recovered signature is the same as the signature sender.
-    if recovered_sig.address != configuration_operation_signature_owner.address.parse()? {
+    // Only the owners of the address leaves can sign.
+    if configuration_operation_signature_owner.leaf_type != OwnerLeafType::Address {
+        return Err(AppError::BadRequest);
+    }
+
+    // Check that the signature is valid for the owner, on the chains the wallet is deployed on for
+    // the contract owners.
+    let is_valid = verify_wallet_owner_signature(
+        state.client.clone(),
+        wallet.address.parse()?,
+        configuration_operation_signature_owner.address.parse()?,
+        configuration_operation_signature_owner.signer_type,
+        &subdigest,
+        &sig_bytes,
+    )
+    .await?;
+    if !is_valid {
         error!(
-            "recovered_sig.address: {}, configuration_operation_signature_owner.address: {}",
-            recovered_sig.address, configuration_operation_signature_owner.address
+            "Invalid signature of configuration_operation_signature_owner.address: {}",
+            configuration_operation_signature_owner.address
         );
         return Err(AppError::BadRequest);
     }
@@ -0,0 +1,398 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::types::Guardian;
+use crate::{
+    authentication::authenticate_wallet_user,
+    error::RouteError,
+    result::{AppError, AppJsonResult},
+    routes::{
+        guardian::error::GuardianError,
+        owner::utils::{flatten_members, unflatten_members, OwnerEntry},
+    },
+    state::AppState,
+};
+use autometrics::autometrics;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use ethers_main::{
+    types::{H160, H256},
+    utils::to_checksum,
+};
+use eyre::Result;
+use lightdotso_common::traits::VecU8ToHex;
+use lightdotso_db::models::{
+    activity::CustomParams, configuration_operation::create_configuration_operation_with_owners,
+};
+use lightdotso_kafka::{
+    topics::activity::produce_activity_message, types::activity::ActivityMessage,
+};
+use lightdotso_prisma::{
+    configuration, configuration_operation, guardian, user, wallet, ActivityEntity,
+    ActivityOperation, GuardianType, OwnerLeafType, OwnerSignerType,
+};
+use lightdotso_sequence::{
+    builder::{config_tree_builder, ConfigMember},
+    config::WalletConfig,
+    recovery::guardian_member,
+    transition::check_config_lockout,
+};
+use lightdotso_tracing::tracing::{error, info};
+use prisma_client_rust::Direction;
+use serde::{Deserialize, Serialize};
+use tower_sessions_core::Session;
+use utoipa::{IntoParams, ToSchema};
+
+// -----------------------------------------------------------------------------
+// Query
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Deserialize, Default, IntoParams)]
+#[serde(rename_all = "snake_case")]
+#[into_params(parameter_in = Query)]
+pub struct PostQuery {
+    /// The address of the wallet.
+    pub address: String,
+}
+
+// -----------------------------------------------------------------------------
+// Params
+// -----------------------------------------------------------------------------
+
+/// Guardian post request params
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct GuardianCreateRequestParams {
+    /// The address of the guardian signer.
+    #[schema(example = "0x4fd9D0eE6D6564E80A9Ee00c0163fC952d0A45Ed")]
+    pub address: String,
+    /// The email backing the signer, if the guardian is an email-backed signer.
+    /// The email has to be verified by the user of the signer address.
+    pub email: Option<String>,
+    /// The number of the guardians required to recover the wallet, w/ the new guardian.
+    #[schema(example = 2, default = 1)]
+    pub threshold: u16,
+}
+
+// -----------------------------------------------------------------------------
+// Handler
+// -----------------------------------------------------------------------------
+
+/// Register a guardian of the wallet, w/ the configuration operation adding the guardians to the
+/// wallet config for the owners to sign
+#[utoipa::path(
+        post,
+        path = "/guardian/create",
+        params(
+            PostQuery
+        ),
+        request_body = GuardianCreateRequestParams,
+        responses(
+            (status = 200, description = "Guardian created successfully", body = Guardian),
+            (status = 400, description = "Invalid guardian", body = GuardianError),
+            (status = 404, description = "Configuration not found", body = GuardianError),
+            (status = 500, description = "Guardian internal error", body = GuardianError),
+        )
+    )]
+#[autometrics]
+pub(crate) async fn v1_guardian_create_handler(
+    post_query: Query<PostQuery>,
+    State(state): State<AppState>,
+    mut session: Session,
+    Json(params): Json<GuardianCreateRequestParams>,
+) -> AppJsonResult<Guardian> {
+    // -------------------------------------------------------------------------
+    // Parse
+    // -------------------------------------------------------------------------
+
+    // Get the post query.
+    let Query(query) = post_query;
+
+    // Parse the address from the post query.
+    let parsed_query_address: H160 = query.address.parse()?;
+    let checksum_address = to_checksum(&parsed_query_address, None);
+
+    // Parse the address of the guardian from the post body.
+    let guardian_address: H160 = params.address.parse().map_err(|_| {
+        RouteError::GuardianError(GuardianError::BadRequest("Invalid guardian address".to_string()))
+    })?;
+
+    // The wallet can't be the guardian of itself.
+    if guardian_address == parsed_query_address || guardian_address.is_zero() {
+        return Err(RouteError::GuardianError(GuardianError::BadRequest(
+            "Invalid guardian address".to_string(),
+        ))
+        .into());
+    }
+
+    // -------------------------------------------------------------------------
+    // Authentication
+    // -------------------------------------------------------------------------
+
+    // Check to see if the user is one of the owners of the wallet configurations.
+    let auth_user_id =
+        authenticate_wallet_user(&state, &mut session, &parsed_query_address, None, None).await?;
+
+    // -------------------------------------------------------------------------
+    // DB
+    // -------------------------------------------------------------------------
+
+    // The email-backed guardian signs w/ the address of the user who verified the email.
+    if let Some(email) = &params.email {
+        let user =
+            state.client.user().find_unique(user::email::equals(email.clone())).exec().await?;
+
+        // If the email is not verified by the user of the signer, return a 400.
+        let is_verified = user.is_some_and(|user| {
+            user.email_verified.is_some() && user.address == to_checksum(&guardian_address, None)
+        });
+        if !is_verified {
+            return Err(RouteError::GuardianError(GuardianError::BadRequest(
+                "Email is not verified by the guardian signer".to_string(),
+            ))
+            .into());
+        }
+    }
+
+    // Get the current configuration for the wallet.
+    let configuration = state
+        .client
+        .configuration()
+        .find_first(vec![configuration::address::equals(checksum_address.clone())])
+        .order_by(configuration::checkpoint::order(Direction::Desc))
+        .with(configuration::owners::fetch(vec![]))
+        .exec()
+        .await?;
+
+    // If the configuration is not found, return a 404.
+    let configuration = configuration.ok_or(RouteError::GuardianError(GuardianError::NotFound(
+        "Configuration not found".to_string(),
+    )))?;
+
+    let configuration_owners = configuration.clone().owners.ok_or(RouteError::GuardianError(
+        GuardianError::NotFound("Configuration owners not found".to_string()),
+    ))?;
+
+    // Get the registered guardians of the wallet from the database.
+    let guardians = state
+        .client
+        .guardian()
+        .find_many(vec![guardian::wallet_address::equals(checksum_address.clone())])
+        .exec()
+        .await?;
+    let mut guardian_addresses = guardians
+        .iter()
+        .map(|guardian| guardian.address.parse())
+        .collect::<Result<Vec<H160>, _>>()?;
+
+    // -------------------------------------------------------------------------
+    // Validate
+    // -------------------------------------------------------------------------
+
+    // Check if the guardian is already registered for the wallet.
+    if guardian_addresses.contains(&guardian_address) {
+        return Err(RouteError::GuardianError(GuardianError::BadRequest(
+            "Guardian is already registered".to_string(),
+        ))
+        .into());
+    }
+
+    // Rebuild the config members of the current configuration.
+    let owner_entries = configuration_owners
+        .iter()
+        .map(OwnerEntry::try_from)
+        .collect::<Result<Vec<OwnerEntry>>>()?;
+    let mut members = unflatten_members(&owner_entries)?;
+
+    // Keep the contract owners signing w/ EIP-1271.
+    let contracts: Vec<H160> = owner_entries
+        .iter()
+        .filter(|entry| entry.signer_type == OwnerSignerType::Erc1271)
+        .map(|entry| entry.address)
+        .collect();
+
+    // Replace the nested config of the registered guardians, if any, w/ the one of all the
+    // guardians, weighted w/ the wallet threshold so that the quorum of the guardians can recover
+    // the wallet on its own.
+    let is_guardian = |member: &ConfigMember| match member {
+        ConfigMember::Owner { address, .. } => guardian_addresses.contains(address),
+        _ => false,
+    };
+    members.retain(|member| match member {
+        ConfigMember::Nested { members, .. } => !members.iter().all(is_guardian),
+        _ => true,
+    });
+    guardian_addresses.push(guardian_address);
+    let wallet_threshold = configuration.threshold as u16;
+    let member = guardian_member(&guardian_addresses, params.threshold, wallet_threshold)
+        .map_err(|err| RouteError::GuardianError(GuardianError::BadRequest(err.to_string())))?;
+    members.push(member);
+
+    // Build the node tree.
+    let tree = config_tree_builder(&members)?;
+
+    // Create a wallet config w/ the guardians.
+    let mut config = WalletConfig {
+        // The signature type is 0 since it is not computed in the encoding.
+        signature_type: 0,
+        checkpoint: configuration.checkpoint as u32 + 1,
+        threshold: wallet_threshold,
+        // Can be 1 since it is not computed in the encoding.
+        weight: 1,
+        // Can be 0 since it is not computed in the encoding.
+        image_hash: [0; 32].into(),
+        tree,
+        internal_root: None,
+        internal_recovered_configs: None,
+    };
+
+    // Check that the wallet configuration can ever be signed.
+    check_config_lockout(&config).map_err(|err| {
+        error!("Invalid configuration: {}", err);
+        RouteError::GuardianError(GuardianError::BadRequest(err.to_string()))
+    })?;
+
+    // Get the image hash of the wallet config w/ the guardians.
+    let image_hash = config.regenerate_image_hash([0; 32]).map_err(|_| AppError::NotFound)?;
+    info!("image_hash: {}", image_hash.to_vec().to_hex_string());
+
+    // Parse the image hash to bytes.
+    let image_hash_bytes: H256 = image_hash.into();
+
+    // -------------------------------------------------------------------------
+    // DB
+    // -------------------------------------------------------------------------
+
+    // Flatten the owners to store them in order.
+    let owner_entries = flatten_members(&members, &contracts);
+
+    // Get the users from the database.
+    let user_data = state
+        .client
+        .user()
+        .find_many(vec![user::address::in_vec(
+            owner_entries
+                .iter()
+                .filter(|entry| entry.leaf_type == OwnerLeafType::Address)
+                .map(|entry| entry.checksum_address())
+                .collect(),
+        )])
+        .exec()
+        .await?;
+    info!(?user_data);
+
+    // Get the owners to store, w/ the users of the owners.
+    let owner_params =
+        owner_entries.iter().map(|entry| entry.to_owner_params(&user_data)).collect::<Vec<_>>();
+
+    // The guardian is email-backed if the email is provided.
+    let guardian_type =
+        if params.email.is_some() { GuardianType::Email } else { GuardianType::Address };
+
+    // Create the configuration operation adding the guardians to the wallet config, for the owners
+    // to sign, and the guardian of the configuration operation at once.
+    let wallet_address = checksum_address.clone();
+    let res: Result<(guardian::Data, configuration_operation::Data)> = state
+        .client
+        ._transaction()
+        .run(|client| async move {
+            // Create the configuration operation w/ the guardians to the database.
+            let configuration_operation = create_configuration_operation_with_owners(
+                &client,
+                wallet_address.clone(),
+                configuration.checkpoint + 1,
+                format!("{:?}", image_hash_bytes),
+                configuration.threshold,
+                &owner_params,
+            )
+            .await?;
+
+            // Create the guardian to the database.
+            let guardian = client
+                .guardian()
+                .create(
+                    to_checksum(&guardian_address, None),
+                    wallet::address::equals(wallet_address),
+                    vec![
+                        guardian::r#type::set(guardian_type),
+                        guardian::email::set(params.email),
+                        guardian::configuration_operation::connect(
+                            configuration_operation::id::equals(configuration_operation.id.clone()),
+                        ),
+                    ],
+                )
+                .exec()
+                .await?;
+
+            Ok((guardian, configuration_operation))
+        })
+        .await;
+
+    // If the guardian is not created, return a 500.
+    let (guardian, configuration_operation) = res.map_err(|err| {
+        error!("Failed to create the guardian: {}", err);
+        AppError::InternalError
+    })?;
+    info!(?guardian);
+    info!(?configuration_operation);
+
+    // -------------------------------------------------------------------------
+    // Kafka
+    // -------------------------------------------------------------------------
+
+    // Produce an activity message.
+    let _ = produce_activity_message(
+        state.producer.clone(),
+        ActivityEntity::Guardian,
+        &ActivityMessage {
+            operation: ActivityOperation::Create,
+            log: serde_json::to_value(&guardian)?,
+            params: CustomParams {
+                guardian_id: Some(guardian.id.clone()),
+                user_id: Some(auth_user_id.clone()),
+                wallet_address: Some(checksum_address.clone()),
+                ..Default::default()
+            },
+        },
+    )
+    .await;
+
+    // Produce an activity message.
+    let _ = produce_activity_message(
+        state.producer.clone(),
+        ActivityEntity::ConfigurationOperation,
+        &ActivityMessage {
+            operation: ActivityOperation::Create,
+            log: serde_json::to_value(&configuration_operation)?,
+            params: CustomParams {
+                configuration_operation_id: Some(configuration_operation.id.clone()),
+                user_id: Some(auth_user_id),
+                wallet_address: Some(checksum_address.clone()),
+                ..Default::default()
+            },
+        },
+    )
+    .await;
+
+    // -------------------------------------------------------------------------
+    // Return
+    // -------------------------------------------------------------------------
+
+    // Change the guardian to the format that the API expects.
+    let guardian: Guardian = guardian.into();
+
+    Ok(Json::from(guardian))
+}
@@ -0,0 +1,90 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::types::Guardian;
+use crate::{result::AppJsonResult, state::AppState};
+use autometrics::autometrics;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use ethers_main::{types::H160, utils::to_checksum};
+use lightdotso_prisma::guardian;
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+// -----------------------------------------------------------------------------
+// Query
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Deserialize, Default, IntoParams)]
+#[serde(rename_all = "snake_case")]
+#[into_params(parameter_in = Query)]
+pub struct ListQuery {
+    /// The address of the wallet.
+    pub address: String,
+}
+
+// -----------------------------------------------------------------------------
+// Handler
+// -----------------------------------------------------------------------------
+
+/// Returns a list of guardians of the wallet
+#[utoipa::path(
+        get,
+        path = "/guardian/list",
+        params(
+            ListQuery
+        ),
+        responses(
+            (status = 200, description = "Guardians returned successfully", body = [Guardian]),
+            (status = 500, description = "Guardian bad request", body = GuardianError),
+        )
+    )]
+#[autometrics]
+pub(crate) async fn v1_guardian_list_handler(
+    list_query: Query<ListQuery>,
+    State(state): State<AppState>,
+) -> AppJsonResult<Vec<Guardian>> {
+    // -------------------------------------------------------------------------
+    // Parse
+    // -------------------------------------------------------------------------
+
+    // Get the list query.
+    let Query(query) = list_query;
+
+    // Parse the address from the list query.
+    let parsed_query_address: H160 = query.address.parse()?;
+
+    // -------------------------------------------------------------------------
+    // DB
+    // -------------------------------------------------------------------------
+
+    // Get the guardians from the database.
+    let guardians = state
+        .client
+        .guardian()
+        .find_many(vec![guardian::wallet_address::equals(to_checksum(&parsed_query_address, None))])
+        .exec()
+        .await?;
+
+    // -------------------------------------------------------------------------
+    // Return
+    // -------------------------------------------------------------------------
+
+    // Change the guardians to the format that the API expects.
+    let guardians: Vec<Guardian> = guardians.into_iter().map(Guardian::from).collect();
+
+    Ok(Json::from(guardians))
+}
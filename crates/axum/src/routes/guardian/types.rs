@@ -0,0 +1,57 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use lightdotso_prisma::guardian;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+// -----------------------------------------------------------------------------
+// Types
+// -----------------------------------------------------------------------------
+
+/// Guardian root type.
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub(crate) struct Guardian {
+    /// The id of the guardian.
+    pub id: String,
+    /// The address of the guardian signer.
+    pub address: String,
+    /// The type of the guardian, either `ADDRESS` or `EMAIL`.
+    pub guardian_type: String,
+    /// The email backing the signer of the guardian.
+    pub email: Option<String>,
+    /// The address of the wallet the guardian recovers.
+    pub wallet_address: String,
+    /// The id of the configuration operation adding the guardian to the wallet config.
+    pub configuration_operation_id: Option<String>,
+}
+
+// -----------------------------------------------------------------------------
+// From
+// -----------------------------------------------------------------------------
+
+/// Implement From<guardian::Data> for Guardian.
+impl From<guardian::Data> for Guardian {
+    fn from(guardian: guardian::Data) -> Self {
+        Self {
+            id: guardian.id,
+            address: guardian.address,
+            guardian_type: guardian.r#type.to_string(),
+            email: guardian.email,
+            wallet_address: guardian.wallet_address,
+            configuration_operation_id: guardian.configuration_operation_id,
+        }
+    }
+}
@@ -24,6 +24,7 @@ pub(crate) mod configuration_operation;
 pub(crate) mod configuration_operation_owner;
 pub(crate) mod configuration_operation_signature;
 pub(crate) mod feedback;
+pub(crate) mod guardian;
 pub(crate) mod health;
 pub(crate) mod interpretation;
 pub(crate) mod interpretation_action;
@@ -38,6 +39,7 @@ pub(crate) mod portfolio;
 pub(crate) mod protocol;
 pub(crate) mod protocol_group;
 pub(crate) mod queue;
+pub(crate) mod recovery_operation;
 pub(crate) mod signature;
 pub(crate) mod simulation;
 pub(crate) mod support_request;
@@ -16,6 +16,7 @@ pub(crate) mod error;
 pub(crate) mod get;
 pub(crate) mod list;
 pub(crate) mod types;
+pub(crate) mod utils;
 
 use crate::state::AppState;
 use autometrics::autometrics;
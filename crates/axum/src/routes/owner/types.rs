@@ -30,6 +30,18 @@ pub(crate) struct Owner {
     pub address: String,
     /// The weight of the owner.
     pub weight: i64,
+    /// The signer type of the owner.
+    pub signer_type: String,
+    /// The index of the owner in the configuration.
+    pub index: i32,
+    /// The leaf type of the owner, either an address, a nested config, or a subdigest.
+    pub leaf_type: String,
+    /// The internal threshold of the nested config.
+    pub threshold: Option<i64>,
+    /// The pre-approved subdigest.
+    pub subdigest: Option<String>,
+    /// The index of the nested config that the owner belongs to.
+    pub parent_index: Option<i32>,
 }
 
 // -----------------------------------------------------------------------------
@@ -39,6 +51,16 @@ pub(crate) struct Owner {
 /// Implement From<owner::Data> for Owner.
 impl From<owner::Data> for Owner {
     fn from(owner: owner::Data) -> Self {
-        Self { id: owner.id.to_string(), address: owner.address.to_string(), weight: owner.weight }
+        Self {
+            id: owner.id.to_string(),
+            address: owner.address.to_string(),
+            weight: owner.weight,
+            signer_type: owner.signer_type.to_string(),
+            index: owner.index,
+            leaf_type: owner.leaf_type.to_string(),
+            threshold: owner.threshold,
+            subdigest: owner.subdigest,
+            parent_index: owner.parent_index,
+        }
     }
 }
@@ -0,0 +1,392 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ethers::providers::Middleware;
+use ethers_main::{
+    types::{H160, H256},
+    utils::to_checksum,
+};
+use eyre::{eyre, Result};
+use lightdotso_constants::chains::ALL_CHAIN_IDS;
+use lightdotso_contracts::provider::get_provider;
+use lightdotso_db::{
+    models::{
+        configuration_operation::ConfigurationOwnerParams, wallet::get_wallet_deployed_chain_ids,
+    },
+    types::Database,
+};
+use lightdotso_prisma::{
+    configuration_operation_owner, owner, user, OwnerLeafType, OwnerSignerType,
+};
+use lightdotso_sequence::{builder::ConfigMember, signature::verify_owner_signature};
+use lightdotso_tracing::tracing::{error, warn};
+use std::collections::BTreeSet;
+
+// -----------------------------------------------------------------------------
+// Types
+// -----------------------------------------------------------------------------
+
+/// The owner of the config tree flattened in order, as stored in the database.
+/// The members of a nested config follow it w/ the index of the nested config as the parent.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct OwnerEntry {
+    /// The index of the owner in the config.
+    pub index: i32,
+    /// The index of the nested config the owner belongs to.
+    pub parent_index: Option<i32>,
+    /// The leaf type of the owner.
+    pub leaf_type: OwnerLeafType,
+    /// The address of the owner, zero for the nested configs and the subdigests.
+    pub address: H160,
+    /// The weight of the owner, or the external weight of the nested config.
+    pub weight: u8,
+    /// The signer type of the owner.
+    pub signer_type: OwnerSignerType,
+    /// The internal threshold of the nested config.
+    pub threshold: Option<u16>,
+    /// The pre-approved subdigest.
+    pub subdigest: Option<H256>,
+}
+
+impl OwnerEntry {
+    /// The checksummed address of the owner.
+    pub(crate) fn checksum_address(&self) -> String {
+        to_checksum(&self.address, None)
+    }
+
+    /// The hex of the pre-approved subdigest.
+    pub(crate) fn subdigest_hex(&self) -> Option<String> {
+        self.subdigest.map(|subdigest| format!("{:?}", subdigest))
+    }
+
+    /// The params of the owner to store, w/ the user of the owner address if any.
+    pub(crate) fn to_owner_params(&self, users: &[user::Data]) -> ConfigurationOwnerParams {
+        ConfigurationOwnerParams {
+            address: self.checksum_address(),
+            weight: self.weight.into(),
+            index: self.index,
+            signer_type: self.signer_type,
+            leaf_type: self.leaf_type,
+            threshold: self.threshold.map(Into::into),
+            subdigest: self.subdigest_hex(),
+            parent_index: self.parent_index,
+            // The nested configs and the subdigests have no user.
+            user_id: users
+                .iter()
+                .find(|user| {
+                    self.leaf_type == OwnerLeafType::Address &&
+                        user.address == self.checksum_address()
+                })
+                .map(|user| user.id.clone()),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// From
+// -----------------------------------------------------------------------------
+
+/// Implement TryFrom<&owner::Data> for OwnerEntry.
+impl TryFrom<&owner::Data> for OwnerEntry {
+    type Error = eyre::Report;
+
+    fn try_from(owner: &owner::Data) -> Result<Self> {
+        Ok(Self {
+            index: owner.index,
+            parent_index: owner.parent_index,
+            leaf_type: owner.leaf_type,
+            address: owner.address.parse()?,
+            weight: owner.weight.try_into()?,
+            signer_type: owner.signer_type,
+            threshold: owner.threshold.map(u16::try_from).transpose()?,
+            subdigest: owner.subdigest.as_ref().map(|subdigest| subdigest.parse()).transpose()?,
+        })
+    }
+}
+
+/// Implement TryFrom<&configuration_operation_owner::Data> for OwnerEntry.
+impl TryFrom<&configuration_operation_owner::Data> for OwnerEntry {
+    type Error = eyre::Report;
+
+    fn try_from(owner: &configuration_operation_owner::Data) -> Result<Self> {
+        Ok(Self {
+            index: owner.index,
+            parent_index: owner.parent_index,
+            leaf_type: owner.leaf_type,
+            address: owner.address.parse()?,
+            weight: owner.weight.try_into()?,
+            signer_type: owner.signer_type,
+            threshold: owner.threshold.map(u16::try_from).transpose()?,
+            subdigest: owner.subdigest.as_ref().map(|subdigest| subdigest.parse()).transpose()?,
+        })
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Traits
+// -----------------------------------------------------------------------------
+
+/// The owner params of the requests, which are either an address, a nested config or a
+/// pre-approved subdigest.
+pub(crate) trait OwnerParams: Sized {
+    /// The address of the owner.
+    fn address(&self) -> &str;
+    /// The weight of the owner, or the external weight of the nested config.
+    fn weight(&self) -> u8;
+    /// Whether the owner is a contract that signs w/ EIP-1271.
+    fn is_contract(&self) -> bool;
+    /// The internal threshold of the nested config.
+    fn threshold(&self) -> Option<u16>;
+    /// The owners of the nested config.
+    fn owners(&self) -> Option<&[Self]>;
+    /// The pre-approved subdigest.
+    fn subdigest(&self) -> Option<&str>;
+}
+
+// -----------------------------------------------------------------------------
+// Utils
+// -----------------------------------------------------------------------------
+
+/// Convert the owner params to the config member, collecting the contract owners.
+pub(crate) fn owner_params_to_member<T: OwnerParams>(
+    owner: &T,
+    contracts: &mut Vec<H160>,
+) -> Result<ConfigMember> {
+    // The pre-approved subdigest
+    if let Some(subdigest) = owner.subdigest() {
+        return Ok(ConfigMember::Subdigest { hash: subdigest.parse()? });
+    }
+
+    // The nested config w/ its own owners
+    if let Some(owners) = owner.owners() {
+        return Ok(ConfigMember::Nested {
+            weight: owner.weight(),
+            threshold: owner.threshold().ok_or(eyre!("No threshold for the nested config"))?,
+            members: owners
+                .iter()
+                .map(|owner| owner_params_to_member(owner, contracts))
+                .collect::<Result<Vec<_>>>()?,
+        });
+    }
+
+    let address: H160 = owner.address().parse()?;
+    if owner.is_contract() {
+        contracts.push(address);
+    }
+
+    Ok(ConfigMember::Owner { address, weight: owner.weight() })
+}
+
+/// Flatten the config members in order, w/ the members of the nested configs following them.
+/// The contract owners are flagged to sign w/ EIP-1271.
+pub(crate) fn flatten_members(members: &[ConfigMember], contracts: &[H160]) -> Vec<OwnerEntry> {
+    let mut entries = vec![];
+    flatten_members_into(members, None, contracts, &mut entries);
+    entries
+}
+
+fn flatten_members_into(
+    members: &[ConfigMember],
+    parent_index: Option<i32>,
+    contracts: &[H160],
+    entries: &mut Vec<OwnerEntry>,
+) {
+    for member in members {
+        let index = entries.len() as i32;
+        let entry = OwnerEntry {
+            index,
+            parent_index,
+            leaf_type: OwnerLeafType::Address,
+            address: H160::zero(),
+            weight: 0,
+            signer_type: OwnerSignerType::Eoa,
+            threshold: None,
+            subdigest: None,
+        };
+
+        match member {
+            ConfigMember::Owner { address, weight } => entries.push(OwnerEntry {
+                address: *address,
+                weight: *weight,
+                signer_type: if contracts.contains(address) {
+                    OwnerSignerType::Erc1271
+                } else {
+                    OwnerSignerType::Eoa
+                },
+                ..entry
+            }),
+            ConfigMember::Nested { weight, threshold, members } => {
+                entries.push(OwnerEntry {
+                    leaf_type: OwnerLeafType::Nested,
+                    weight: *weight,
+                    threshold: Some(*threshold),
+                    ..entry
+                });
+                flatten_members_into(members, Some(index), contracts, entries);
+            }
+            ConfigMember::Subdigest { hash } => entries.push(OwnerEntry {
+                leaf_type: OwnerLeafType::Subdigest,
+                subdigest: Some(*hash),
+                ..entry
+            }),
+        }
+    }
+}
+
+/// Rebuild the config members from the flattened owners.
+pub(crate) fn unflatten_members(entries: &[OwnerEntry]) -> Result<Vec<ConfigMember>> {
+    unflatten_members_of(entries, None)
+}
+
+fn unflatten_members_of(
+    entries: &[OwnerEntry],
+    parent_index: Option<i32>,
+) -> Result<Vec<ConfigMember>> {
+    let mut children: Vec<&OwnerEntry> =
+        entries.iter().filter(|entry| entry.parent_index == parent_index).collect();
+    children.sort_by_key(|entry| entry.index);
+
+    children
+        .into_iter()
+        .map(|entry| match entry.leaf_type {
+            OwnerLeafType::Address => {
+                Ok(ConfigMember::Owner { address: entry.address, weight: entry.weight })
+            }
+            OwnerLeafType::Nested => Ok(ConfigMember::Nested {
+                weight: entry.weight,
+                threshold: entry
+                    .threshold
+                    .ok_or(eyre!("No threshold for the nested config {}", entry.index))?,
+                members: unflatten_members_of(entries, Some(entry.index))?,
+            }),
+            OwnerLeafType::Subdigest => Ok(ConfigMember::Subdigest {
+                hash: entry.subdigest.ok_or(eyre!("No subdigest for the owner {}", entry.index))?,
+            }),
+        })
+        .collect()
+}
+
+/// Check that the contract owners have code on any of the chains, as the `is_contract` flag of
+/// the client isn't trusted. The chains the wallet is deployed on are checked, or all the chains
+/// for the wallet w/o any deployment.
+pub(crate) async fn verify_contract_owners(
+    db: Database,
+    wallet: H160,
+    contracts: &[H160],
+) -> Result<bool> {
+    if contracts.is_empty() {
+        return Ok(true);
+    }
+
+    let mut chain_ids: BTreeSet<u64> =
+        get_wallet_deployed_chain_ids(db, wallet).await?.into_iter().collect();
+    if chain_ids.is_empty() {
+        chain_ids = ALL_CHAIN_IDS.keys().copied().collect();
+    }
+
+    for contract in contracts {
+        if !has_code_on_any_chain(*contract, &chain_ids).await {
+            error!("The contract owner {:?} has no code on the chains {:?}", contract, chain_ids);
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// Whether the address has code on any of the chains, skipping the chains w/ the failed lookups.
+async fn has_code_on_any_chain(address: H160, chain_ids: &BTreeSet<u64>) -> bool {
+    for chain_id in chain_ids {
+        let code = match get_provider(*chain_id).await {
+            Ok(provider) => provider.get_code(address, None).await.map_err(eyre::Report::from),
+            Err(err) => Err(err),
+        };
+        match code {
+            Ok(code) if !code.is_empty() => return true,
+            Ok(_) => {}
+            Err(err) => {
+                warn!("Failed to get the code of {:?} on chain {}: {}", address, chain_id, err)
+            }
+        }
+    }
+
+    false
+}
+
+/// Verify the signature of the owner of the wallet over the subdigest. The contract owners sign
+/// w/ EIP-1271, which the wallet checks on-chain, so it is verified on each of the chains the
+/// wallet is deployed on.
+pub(crate) async fn verify_wallet_owner_signature(
+    db: Database,
+    wallet: H160,
+    owner: H160,
+    signer_type: OwnerSignerType,
+    subdigest: &[u8; 32],
+    signature: &[u8],
+) -> Result<bool> {
+    if signer_type != OwnerSignerType::Erc1271 {
+        return verify_owner_signature(0, owner, false, subdigest, signature).await;
+    }
+
+    let chain_ids = get_wallet_deployed_chain_ids(db, wallet).await?;
+    if chain_ids.is_empty() {
+        error!("The wallet {:?} isn't deployed to verify the contract owner {:?}", wallet, owner);
+        return Ok(false);
+    }
+
+    for chain_id in chain_ids {
+        if !verify_owner_signature(chain_id, owner, true, subdigest, signature).await? {
+            error!("Invalid signature of the contract owner {:?} on chain {}", owner, chain_id);
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flatten_members() -> Result<()> {
+        let owners: Vec<H160> = (1..=3).map(H160::from_low_u64_be).collect();
+        let members = vec![
+            ConfigMember::Owner { address: owners[0], weight: 1 },
+            ConfigMember::Nested {
+                weight: 1,
+                threshold: 1,
+                members: vec![
+                    ConfigMember::Owner { address: owners[1], weight: 1 },
+                    ConfigMember::Subdigest { hash: H256::repeat_byte(1) },
+                ],
+            },
+            ConfigMember::Owner { address: owners[2], weight: 2 },
+        ];
+
+        let entries = flatten_members(&members, &[owners[2]]);
+        assert_eq!(entries.len(), 5);
+        assert_eq!(entries[2].parent_index, Some(1));
+        assert_eq!(entries[3].leaf_type, OwnerLeafType::Subdigest);
+        assert_eq!(entries[4].parent_index, None);
+        assert_eq!(entries[4].signer_type, OwnerSignerType::Erc1271);
+
+        // The members are rebuilt regardless of the order of the entries.
+        let mut reversed = entries;
+        reversed.reverse();
+        assert_eq!(unflatten_members(&reversed)?, members);
+
+        Ok(())
+    }
+}
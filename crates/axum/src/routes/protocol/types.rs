@@ -26,6 +26,8 @@ use utoipa::ToSchema;
 pub(crate) struct Protocol {
     /// The address of the protocol.
     address: String,
+    /// The name of the protocol.
+    name: String,
 }
 
 // -----------------------------------------------------------------------------
@@ -35,6 +37,6 @@ pub(crate) struct Protocol {
 /// Implement From<protocol::Data> for Protocol.
 impl From<protocol::Data> for Protocol {
     fn from(protocol: protocol::Data) -> Self {
-        Self { address: protocol.address }
+        Self { address: protocol.address, name: protocol.name }
     }
 }
@@ -16,6 +16,7 @@ pub(crate) mod error;
 pub(crate) mod interpretation;
 pub(crate) mod node;
 pub(crate) mod portfolio;
+pub(crate) mod reconciliation;
 pub(crate) mod token;
 pub(crate) mod transaction;
 pub(crate) mod types;
@@ -30,6 +31,9 @@ pub(crate) use interpretation::{
 };
 pub(crate) use node::{__path_v1_queue_node_handler, v1_queue_node_handler};
 pub(crate) use portfolio::{__path_v1_queue_portfolio_handler, v1_queue_portfolio_handler};
+pub(crate) use reconciliation::{
+    __path_v1_queue_reconciliation_handler, v1_queue_reconciliation_handler,
+};
 pub(crate) use token::{__path_v1_queue_token_handler, v1_queue_token_handler};
 pub(crate) use transaction::{__path_v1_queue_transaction_handler, v1_queue_transaction_handler};
 pub(crate) use user_operation::{
@@ -45,6 +49,7 @@ pub(crate) fn router() -> Router<AppState> {
     Router::new()
         .route("/queue/interpretation", post(v1_queue_interpretation_handler))
         .route("/queue/portfolio", post(v1_queue_portfolio_handler))
+        .route("/queue/reconciliation", post(v1_queue_reconciliation_handler))
         .route("/queue/node", post(v1_queue_node_handler))
         .route("/queue/token", post(v1_queue_token_handler))
         .route("/queue/transaction", post(v1_queue_transaction_handler))
@@ -0,0 +1,116 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{error::RouteError, result::AppJsonResult, state::AppState};
+use autometrics::autometrics;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use ethers_main::{types::H160, utils::to_checksum};
+use lightdotso_kafka::{
+    topics::reconciliation::produce_reconciliation_message,
+    types::reconciliation::ReconciliationMessage,
+};
+use lightdotso_prisma::wallet;
+use lightdotso_redis::query::reconciliation::reconciliation_rate_limit;
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+use super::{error::QueueError, types::QueueSuccess};
+
+// -----------------------------------------------------------------------------
+// Query
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Deserialize, Default, IntoParams)]
+#[serde(rename_all = "snake_case")]
+#[into_params(parameter_in = Query)]
+pub struct PostQuery {
+    /// The address of the target queue.
+    pub address: String,
+}
+
+// -----------------------------------------------------------------------------
+// Handler
+// -----------------------------------------------------------------------------
+
+/// Queue reconciliation handler
+#[utoipa::path(
+        post,
+        path = "/queue/reconciliation",
+        params(
+            PostQuery
+        ),
+        responses(
+            (status = 200, description = "Queue created successfully", body = QueueSuccess),
+            (status = 500, description = "Queue internal error", body = QueueError),
+        )
+    )]
+#[autometrics]
+pub(crate) async fn v1_queue_reconciliation_handler(
+    post_query: Query<PostQuery>,
+    State(state): State<AppState>,
+) -> AppJsonResult<QueueSuccess> {
+    // -------------------------------------------------------------------------
+    // Parse
+    // -------------------------------------------------------------------------
+
+    // Get the post query.
+    let Query(query) = post_query;
+
+    let parsed_query_address: H160 = query.address.parse()?;
+    let checksum_address = to_checksum(&parsed_query_address, None);
+
+    // -------------------------------------------------------------------------
+    // DB
+    // -------------------------------------------------------------------------
+
+    // Get the wallet from the database.
+    let wallet = state
+        .client
+        .wallet()
+        .find_unique(wallet::address::equals(checksum_address.clone()))
+        .with(wallet::wallet_settings::fetch())
+        .exec()
+        .await?;
+
+    // If the wallet is not found, return a 404.
+    wallet.ok_or(RouteError::QueueError(QueueError::NotFound(checksum_address.clone())))?;
+
+    // -------------------------------------------------------------------------
+    // Redis
+    // -------------------------------------------------------------------------
+
+    // Rate limit the queue.
+    reconciliation_rate_limit(state.redis, checksum_address)
+        .map_err(|err| RouteError::QueueError(QueueError::RateLimitExceeded(err.to_string())))?;
+
+    // -------------------------------------------------------------------------
+    // Kafka
+    // -------------------------------------------------------------------------
+
+    // Run the kafka producer to reconcile the wallet on each chain.
+    produce_reconciliation_message(
+        state.producer.clone(),
+        &ReconciliationMessage { address: parsed_query_address },
+    )
+    .await?;
+
+    // -------------------------------------------------------------------------
+    // Return
+    // -------------------------------------------------------------------------
+
+    Ok(Json::from(QueueSuccess::Queued("Queue Success".to_string())))
+}
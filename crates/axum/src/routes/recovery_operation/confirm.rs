@@ -0,0 +1,306 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::types::RecoveryOperation;
+use crate::{
+    error::RouteError,
+    result::{AppError, AppJsonResult},
+    routes::recovery_operation::error::RecoveryOperationError,
+    state::AppState,
+};
+use autometrics::autometrics;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use ethers::types::{H160, H256};
+use eyre::Result;
+use lightdotso_contracts::light_wallet::get_light_wallet;
+use lightdotso_db::models::{
+    activity::CustomParams, configuration_operation::create_configuration_with_owners,
+};
+use lightdotso_kafka::{
+    topics::activity::produce_activity_message, types::activity::ActivityMessage,
+};
+use lightdotso_prisma::{
+    configuration, configuration_operation, recovery_operation, user_operation, ActivityEntity,
+    ActivityOperation, ConfigurationOperationStatus, RecoveryOperationStatus, UserOperationStatus,
+};
+use lightdotso_tracing::tracing::{error, info};
+use prisma_client_rust::Direction;
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+// -----------------------------------------------------------------------------
+// Query
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Deserialize, Default, IntoParams)]
+#[serde(rename_all = "snake_case")]
+#[into_params(parameter_in = Query)]
+pub struct PutQuery {
+    /// The id of the recovery operation to confirm.
+    pub recovery_operation_id: String,
+    /// The hash of the user operation updating the image hash w/ the recovery signature.
+    pub user_operation_hash: String,
+}
+
+// -----------------------------------------------------------------------------
+// Handler
+// -----------------------------------------------------------------------------
+
+/// Confirm the recovery of the wallet once the user operation updating the image hash is executed
+/// on-chain, recording the recovered configuration
+#[utoipa::path(
+        put,
+        path = "/recovery_operation/confirm",
+        params(
+            PutQuery
+        ),
+        responses(
+            (status = 200, description = "Recovery operation confirmed successfully", body = RecoveryOperation),
+            (status = 400, description = "Invalid recovery operation", body = RecoveryOperationError),
+            (status = 404, description = "Recovery operation not found", body = RecoveryOperationError),
+            (status = 500, description = "Recovery operation internal error", body = RecoveryOperationError),
+        )
+    )]
+#[autometrics]
+pub(crate) async fn v1_recovery_operation_confirm_handler(
+    put_query: Query<PutQuery>,
+    State(state): State<AppState>,
+) -> AppJsonResult<RecoveryOperation> {
+    // -------------------------------------------------------------------------
+    // Parse
+    // -------------------------------------------------------------------------
+
+    // Get the put query.
+    let Query(query) = put_query;
+    info!(?query);
+
+    // Parse the user operation hash from the put query.
+    let user_operation_hash: H256 = query.user_operation_hash.parse()?;
+
+    // -------------------------------------------------------------------------
+    // DB
+    // -------------------------------------------------------------------------
+
+    // Get the recovery operation from the database.
+    let recovery_operation = state
+        .client
+        .recovery_operation()
+        .find_unique(recovery_operation::id::equals(query.recovery_operation_id))
+        .with(
+            recovery_operation::configuration_operation::fetch()
+                .with(configuration_operation::configuration_operation_owners::fetch(vec![])),
+        )
+        .exec()
+        .await?;
+
+    // If the recovery operation is not found, return a 404.
+    let recovery_operation = recovery_operation.ok_or(RouteError::RecoveryOperationError(
+        RecoveryOperationError::NotFound("Recovery operation not found".to_string()),
+    ))?;
+
+    // Get the user operation from the database.
+    let user_operation = state
+        .client
+        .user_operation()
+        .find_unique(user_operation::hash::equals(format!("{:?}", user_operation_hash)))
+        .exec()
+        .await?;
+
+    // If the user operation is not found, return a 404.
+    let user_operation = user_operation.ok_or(RouteError::RecoveryOperationError(
+        RecoveryOperationError::NotFound("User operation not found".to_string()),
+    ))?;
+
+    // -------------------------------------------------------------------------
+    // Validate
+    // -------------------------------------------------------------------------
+
+    // Only the pending recovery w/ the recovery signature can be confirmed.
+    if recovery_operation.status != RecoveryOperationStatus::Pending ||
+        recovery_operation.signature.is_none()
+    {
+        return Err(RouteError::RecoveryOperationError(RecoveryOperationError::BadRequest(
+            "Recovery operation is not executed".to_string(),
+        ))
+        .into());
+    }
+
+    // The user operation has to be of the recovered wallet, and executed on-chain.
+    if user_operation.sender != recovery_operation.address ||
+        user_operation.status != UserOperationStatus::Executed
+    {
+        return Err(RouteError::RecoveryOperationError(RecoveryOperationError::BadRequest(
+            "User operation is not executed by the wallet".to_string(),
+        ))
+        .into());
+    }
+
+    // -------------------------------------------------------------------------
+    // Contracts
+    // -------------------------------------------------------------------------
+
+    // Get the image hash of the wallet on the chain of the user operation.
+    let wallet_address: H160 = recovery_operation.address.parse()?;
+    let wallet = get_light_wallet(user_operation.chain_id as u64, wallet_address).await?;
+    let onchain_image_hash: H256 = wallet.image_hash().await.map_err(eyre::Report::from)?.into();
+
+    // The wallet has to be updated to the recovered image hash on-chain.
+    if format!("{:?}", onchain_image_hash) != recovery_operation.image_hash {
+        return Err(RouteError::RecoveryOperationError(RecoveryOperationError::BadRequest(
+            "Image hash of the wallet is not updated on-chain".to_string(),
+        ))
+        .into());
+    }
+
+    // -------------------------------------------------------------------------
+    // DB
+    // -------------------------------------------------------------------------
+
+    // Get the current configuration for the wallet.
+    let configuration = state
+        .client
+        .configuration()
+        .find_first(vec![configuration::address::equals(recovery_operation.address.clone())])
+        .order_by(configuration::checkpoint::order(Direction::Desc))
+        .exec()
+        .await?;
+
+    // The recovered configuration has to follow the current configuration.
+    if configuration.map(|configuration| configuration.checkpoint + 1) !=
+        Some(recovery_operation.checkpoint)
+    {
+        return Err(RouteError::RecoveryOperationError(RecoveryOperationError::BadRequest(
+            "Configuration has changed since the recovery was requested".to_string(),
+        ))
+        .into());
+    }
+
+    // Get the configuration operation of the recovered configuration.
+    let configuration_operation =
+        recovery_operation.configuration_operation.clone().flatten().ok_or(
+            RouteError::RecoveryOperationError(RecoveryOperationError::NotFound(
+                "Configuration operation not found".to_string(),
+            )),
+        )?;
+
+    // Get the owners of the recovered configuration to store.
+    let owner_params = configuration_operation
+        .configuration_operation_owners
+        .clone()
+        .ok_or(RouteError::RecoveryOperationError(RecoveryOperationError::NotFound(
+            "Configuration operation owners not found".to_string(),
+        )))?
+        .iter()
+        .map(Into::into)
+        .collect::<Vec<_>>();
+
+    // Execute the recovery operation, confirm the configuration operation and create the recovered
+    // configuration at once, so that the recovery is never executed w/o the configuration.
+    let res: Result<(
+        recovery_operation::Data,
+        configuration_operation::Data,
+        configuration::Data,
+    )> = state
+        .client
+        ._transaction()
+        .run(|client| async move {
+            // Execute the recovery operation.
+            let recovery_operation = client
+                .recovery_operation()
+                .update(
+                    recovery_operation::id::equals(recovery_operation.id.clone()),
+                    vec![recovery_operation::status::set(RecoveryOperationStatus::Executed)],
+                )
+                .exec()
+                .await?;
+            info!(?recovery_operation);
+
+            // Confirm the configuration operation of the recovered configuration.
+            let configuration_operation = client
+                .configuration_operation()
+                .update(
+                    configuration_operation::id::equals(configuration_operation.id.clone()),
+                    vec![configuration_operation::status::set(
+                        ConfigurationOperationStatus::Confirmed,
+                    )],
+                )
+                .exec()
+                .await?;
+            info!(?configuration_operation);
+
+            // Create the recovered configuration w/ the same contents as the configuration
+            // operation.
+            let configuration =
+                create_configuration_with_owners(&client, &configuration_operation, &owner_params)
+                    .await?;
+
+            Ok((recovery_operation, configuration_operation, configuration))
+        })
+        .await;
+
+    // If the recovery is not confirmed, return a 500.
+    let (recovery_operation, configuration_operation, configuration) = res.map_err(|err| {
+        error!("Failed to confirm the recovery: {}", err);
+        AppError::InternalError
+    })?;
+    info!(?configuration);
+
+    // -------------------------------------------------------------------------
+    // Kafka
+    // -------------------------------------------------------------------------
+
+    // Produce an activity message.
+    let _ = produce_activity_message(
+        state.producer.clone(),
+        ActivityEntity::ConfigurationOperation,
+        &ActivityMessage {
+            operation: ActivityOperation::Update,
+            log: serde_json::to_value(&configuration_operation)?,
+            params: CustomParams {
+                configuration_operation_id: Some(configuration_operation.id.clone()),
+                wallet_address: Some(configuration_operation.address.clone()),
+                ..Default::default()
+            },
+        },
+    )
+    .await;
+
+    // Produce an activity message.
+    let _ = produce_activity_message(
+        state.producer.clone(),
+        ActivityEntity::RecoveryOperation,
+        &ActivityMessage {
+            operation: ActivityOperation::Update,
+            log: serde_json::to_value(&recovery_operation)?,
+            params: CustomParams {
+                recovery_operation_id: Some(recovery_operation.id.clone()),
+                wallet_address: Some(recovery_operation.address.clone()),
+                ..Default::default()
+            },
+        },
+    )
+    .await;
+
+    // -------------------------------------------------------------------------
+    // Return
+    // -------------------------------------------------------------------------
+
+    // Change the recovery operation to the format that the API expects.
+    let recovery_operation: RecoveryOperation = recovery_operation.into();
+
+    Ok(Json::from(recovery_operation))
+}
@@ -0,0 +1,423 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::types::RecoveryOperation;
+use crate::{
+    error::RouteError,
+    result::{AppError, AppJsonResult},
+    routes::{
+        configuration_operation::create::ConfigurationOperationCreateOwnerParams,
+        owner::utils::{flatten_members, owner_params_to_member},
+        recovery_operation::error::RecoveryOperationError,
+    },
+    state::AppState,
+};
+use autometrics::autometrics;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use ethers_main::{
+    types::{H160, H256},
+    utils::to_checksum,
+};
+use eyre::Result;
+use lightdotso_common::traits::{HexToBytes, VecU8ToHex};
+use lightdotso_db::models::{
+    activity::CustomParams, configuration_operation::create_configuration_operation_with_owners,
+};
+use lightdotso_kafka::{
+    topics::activity::produce_activity_message, types::activity::ActivityMessage,
+};
+use lightdotso_prisma::{
+    configuration, configuration_operation, guardian, recovery_operation,
+    recovery_operation_signature, user, wallet, ActivityEntity, ActivityOperation, OwnerLeafType,
+    RecoveryOperationStatus,
+};
+use lightdotso_sequence::{
+    builder::{config_tree_builder, ConfigMember},
+    config::WalletConfig,
+    recovery::recovery_subdigest,
+    signature::verify_owner_signature,
+    transition::check_config_lockout,
+};
+use lightdotso_tracing::tracing::{error, info};
+use prisma_client_rust::{
+    chrono::{Duration, Utc},
+    Direction,
+};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+/// The delay for the owners to veto the recovery before it can be executed, in seconds.
+pub(crate) const RECOVERY_DELAY_SECONDS: i64 = 2 * 24 * 60 * 60;
+
+// -----------------------------------------------------------------------------
+// Query
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Deserialize, Default, IntoParams)]
+#[serde(rename_all = "snake_case")]
+#[into_params(parameter_in = Query)]
+pub struct PostQuery {
+    /// The address of the wallet to recover.
+    pub address: String,
+}
+
+// -----------------------------------------------------------------------------
+// Params
+// -----------------------------------------------------------------------------
+
+/// Recovery operation post request params
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct RecoveryOperationCreateRequestParams {
+    /// The array of owners of the recovered wallet.
+    #[schema(example = json!([{"address": "0x4fd9D0eE6D6564E80A9Ee00c0163fC952d0A45Ed", "weight": 1}]))]
+    pub owners: Vec<ConfigurationOperationCreateOwnerParams>,
+    /// The signature of the guardian requesting the recovery.
+    pub signature: RecoveryOperationSignatureCreateParams,
+    /// The threshold of the recovered wallet.
+    #[schema(example = 3, default = 1)]
+    pub threshold: u16,
+}
+
+/// Recovery operation signature
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct RecoveryOperationSignatureCreateParams {
+    /// The id of the guardian of the signature.
+    pub guardian_id: String,
+    /// The signature of the recovered image hash in hex.
+    pub signature: String,
+}
+
+// -----------------------------------------------------------------------------
+// Handler
+// -----------------------------------------------------------------------------
+
+/// Request the recovery of the wallet by a guardian
+#[utoipa::path(
+        post,
+        path = "/recovery_operation/create",
+        params(
+            PostQuery
+        ),
+        request_body = RecoveryOperationCreateRequestParams,
+        responses(
+            (status = 200, description = "Recovery operation created successfully", body = RecoveryOperation),
+            (status = 400, description = "Invalid recovery", body = RecoveryOperationError),
+            (status = 404, description = "Recovery operation not found", body = RecoveryOperationError),
+            (status = 500, description = "Recovery operation internal error", body = RecoveryOperationError),
+        )
+    )]
+#[autometrics]
+pub(crate) async fn v1_recovery_operation_create_handler(
+    post_query: Query<PostQuery>,
+    State(state): State<AppState>,
+    Json(params): Json<RecoveryOperationCreateRequestParams>,
+) -> AppJsonResult<RecoveryOperation> {
+    // -------------------------------------------------------------------------
+    // Parse
+    // -------------------------------------------------------------------------
+
+    // Get the post query.
+    let Query(query) = post_query;
+    info!(?query);
+
+    // Parse the address from the post query.
+    let parsed_query_address: H160 = query.address.parse()?;
+    let checksum_address = to_checksum(&parsed_query_address, None);
+
+    let owners = &params.owners;
+    let threshold = params.threshold;
+
+    // Get the signature from the post body.
+    let sig = params.signature;
+
+    // -------------------------------------------------------------------------
+    // DB
+    // -------------------------------------------------------------------------
+
+    // Get the current configuration for the wallet.
+    let configuration = state
+        .client
+        .configuration()
+        .find_first(vec![configuration::address::equals(checksum_address.clone())])
+        .order_by(configuration::checkpoint::order(Direction::Desc))
+        .with(configuration::owners::fetch(vec![]))
+        .exec()
+        .await?;
+
+    // If the configuration is not found, return a 404.
+    let configuration = configuration.ok_or(RouteError::RecoveryOperationError(
+        RecoveryOperationError::NotFound("Configuration not found".to_string()),
+    ))?;
+
+    // Get the guardian from the database.
+    let guardian = state
+        .client
+        .guardian()
+        .find_unique(guardian::id::equals(sig.guardian_id.clone()))
+        .exec()
+        .await?;
+
+    // If the guardian is not found, return a 404.
+    let guardian = guardian.ok_or(RouteError::RecoveryOperationError(
+        RecoveryOperationError::NotFound("Guardian not found".to_string()),
+    ))?;
+
+    // Get the pending recovery operation of the wallet.
+    let pending_recovery_operation = state
+        .client
+        .recovery_operation()
+        .find_first(vec![
+            recovery_operation::address::equals(checksum_address.clone()),
+            recovery_operation::status::equals(RecoveryOperationStatus::Pending),
+        ])
+        .exec()
+        .await?;
+
+    // -------------------------------------------------------------------------
+    // Validate
+    // -------------------------------------------------------------------------
+
+    // Check if the guardian is registered for the wallet.
+    if guardian.wallet_address != checksum_address {
+        return Err(RouteError::RecoveryOperationError(RecoveryOperationError::BadRequest(
+            "Guardian is not registered for the wallet".to_string(),
+        ))
+        .into());
+    }
+
+    // Check if the guardian is in the current configuration, added by the owners w/ the
+    // configuration operation of the guardian.
+    let is_configured = configuration.owners.as_ref().is_some_and(|owners| {
+        owners.iter().any(|owner| owner.parent_index.is_some() && owner.address == guardian.address)
+    });
+    if !is_configured {
+        return Err(RouteError::RecoveryOperationError(RecoveryOperationError::BadRequest(
+            "Guardian is not in the wallet configuration".to_string(),
+        ))
+        .into());
+    }
+
+    // Only one recovery can be pending at a time.
+    if pending_recovery_operation.is_some() {
+        return Err(RouteError::RecoveryOperationError(RecoveryOperationError::BadRequest(
+            "Recovery operation is already pending".to_string(),
+        ))
+        .into());
+    }
+
+    // Check if all of the owners can be parsed to the config members.
+    let mut contracts = vec![];
+    let members = owners
+        .iter()
+        .map(|owner| owner_params_to_member(owner, &mut contracts))
+        .collect::<Result<Vec<ConfigMember>>>()
+        .map_err(|_| AppError::BadRequest)?;
+
+    // Flatten the owners to store them in order.
+    let owner_entries = flatten_members(&members, &contracts);
+
+    // Get the addresses of the owners, w/o the nested configs and the subdigests.
+    let owners_addresses: Vec<H160> = owner_entries
+        .iter()
+        .filter(|entry| entry.leaf_type == OwnerLeafType::Address)
+        .map(|entry| entry.address)
+        .collect();
+
+    // Build the node tree.
+    let tree = config_tree_builder(&members)?;
+
+    // Create a wallet config of the recovered configuration.
+    let mut config = WalletConfig {
+        // The signature type is 0 since it is not computed in the encoding.
+        signature_type: 0,
+        checkpoint: configuration.checkpoint as u32 + 1,
+        threshold,
+        // Can be 1 since it is not computed in the encoding.
+        weight: 1,
+        // Can be 0 since it is not computed in the encoding.
+        image_hash: [0; 32].into(),
+        tree,
+        internal_root: None,
+        internal_recovered_configs: None,
+    };
+
+    // Check that the recovered configuration can ever be signed.
+    check_config_lockout(&config).map_err(|err| {
+        error!("Invalid configuration: {}", err);
+        RouteError::RecoveryOperationError(RecoveryOperationError::BadRequest(err.to_string()))
+    })?;
+
+    // Get the image hash of the recovered configuration.
+    let image_hash = config.regenerate_image_hash([0; 32]).map_err(|_| AppError::NotFound)?;
+    info!("image_hash: {}", image_hash.to_vec().to_hex_string());
+
+    // Parse the image hash to bytes.
+    let image_hash_bytes: H256 = image_hash.into();
+
+    // -------------------------------------------------------------------------
+    // Signature
+    // -------------------------------------------------------------------------
+
+    // Render the chain agnostic subdigest of the update to the recovered image hash.
+    let subdigest = recovery_subdigest(parsed_query_address, image_hash_bytes)?;
+    info!("subdigest: {}", subdigest.to_vec().to_hex_string());
+
+    // Check that the signature is valid for the guardian.
+    let sig_bytes = sig.signature.hex_to_bytes()?;
+    let is_valid =
+        verify_owner_signature(0, guardian.address.parse()?, false, &subdigest, &sig_bytes).await?;
+    if !is_valid {
+        error!("Invalid signature of guardian.address: {}", guardian.address);
+        return Err(RouteError::RecoveryOperationError(RecoveryOperationError::BadRequest(
+            "Invalid signature of the guardian".to_string(),
+        ))
+        .into());
+    }
+
+    // -------------------------------------------------------------------------
+    // DB
+    // -------------------------------------------------------------------------
+
+    // Get the users from the database.
+    let user_data = state
+        .client
+        .user()
+        .find_many(vec![user::address::in_vec(
+            owners_addresses.iter().map(|addr| to_checksum(addr, None)).collect(),
+        )])
+        .exec()
+        .await?;
+    info!(?user_data);
+
+    // Get the owners to store, w/ the users of the owners.
+    let owner_params =
+        owner_entries.iter().map(|entry| entry.to_owner_params(&user_data)).collect::<Vec<_>>();
+
+    // The recovery can only be executed after the delay for the owners to veto.
+    let executable_at = Utc::now() + Duration::seconds(RECOVERY_DELAY_SECONDS);
+
+    // Create the configuration operation of the recovered configuration, w/ the recovery
+    // operation holding the signatures of the guardians until the delay expires, and the signature
+    // of the guardian at once, so that no recovery is left pending w/o a signature.
+    let guardian_id = guardian.id.clone();
+    let res: Result<(recovery_operation::Data, recovery_operation_signature::Data)> = state
+        .client
+        ._transaction()
+        .run(|client| async move {
+            // Create the configuration operation of the recovered configuration to the database.
+            let configuration_operation = create_configuration_operation_with_owners(
+                &client,
+                checksum_address.clone(),
+                configuration.checkpoint + 1,
+                format!("{:?}", image_hash_bytes),
+                threshold as i64,
+                &owner_params,
+            )
+            .await?;
+
+            // Create the recovery operation to the database.
+            let recovery_operation = client
+                .recovery_operation()
+                .create(
+                    configuration.checkpoint + 1,
+                    format!("{:?}", image_hash_bytes),
+                    threshold as i64,
+                    executable_at.into(),
+                    wallet::address::equals(checksum_address.clone()),
+                    vec![recovery_operation::configuration_operation::connect(
+                        configuration_operation::id::equals(configuration_operation.id.clone()),
+                    )],
+                )
+                .exec()
+                .await?;
+            info!(?recovery_operation);
+
+            // Create the signature of the guardian to the database.
+            let recovery_operation_signature = client
+                .recovery_operation_signature()
+                .create(
+                    sig_bytes,
+                    recovery_operation::id::equals(recovery_operation.id.clone()),
+                    vec![recovery_operation_signature::guardian::connect(guardian::id::equals(
+                        guardian_id,
+                    ))],
+                )
+                .exec()
+                .await?;
+            info!(?recovery_operation_signature);
+
+            Ok((recovery_operation, recovery_operation_signature))
+        })
+        .await;
+
+    // If the recovery_operation is not created, return a 500.
+    let (recovery_operation, recovery_operation_signature) = res.map_err(|err| {
+        error!("Failed to create the recovery operation: {}", err);
+        AppError::InternalError
+    })?;
+    info!(?recovery_operation);
+
+    // -------------------------------------------------------------------------
+    // Kafka
+    // -------------------------------------------------------------------------
+
+    // Produce an activity message.
+    let _ = produce_activity_message(
+        state.producer.clone(),
+        ActivityEntity::RecoveryOperation,
+        &ActivityMessage {
+            operation: ActivityOperation::Create,
+            log: serde_json::to_value(&recovery_operation)?,
+            params: CustomParams {
+                recovery_operation_id: Some(recovery_operation.id.clone()),
+                guardian_id: Some(guardian.id.clone()),
+                wallet_address: Some(recovery_operation.address.clone()),
+                ..Default::default()
+            },
+        },
+    )
+    .await;
+
+    // Produce an activity message.
+    let _ = produce_activity_message(
+        state.producer.clone(),
+        ActivityEntity::RecoveryOperationSignature,
+        &ActivityMessage {
+            operation: ActivityOperation::Create,
+            log: serde_json::to_value(&recovery_operation_signature)?,
+            params: CustomParams {
+                recovery_operation_signature_id: Some(recovery_operation_signature.id.clone()),
+                recovery_operation_id: Some(recovery_operation.id.clone()),
+                guardian_id: Some(guardian.id.clone()),
+                wallet_address: Some(recovery_operation.address.clone()),
+                ..Default::default()
+            },
+        },
+    )
+    .await;
+
+    // -------------------------------------------------------------------------
+    // Return
+    // -------------------------------------------------------------------------
+
+    // Change the recovery operation to the format that the API expects.
+    let recovery_operation: RecoveryOperation = recovery_operation.into();
+
+    Ok(Json::from(recovery_operation))
+}
@@ -0,0 +1,305 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::types::RecoveryOperation;
+use crate::{
+    error::RouteError,
+    result::{AppError, AppJsonResult},
+    routes::{
+        recovery_operation::error::RecoveryOperationError,
+        user_operation::signature::configuration_to_wallet_config,
+    },
+    state::AppState,
+};
+use autometrics::autometrics;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use ethers::types::Address;
+use eyre::{eyre, Result};
+use lightdotso_db::models::activity::CustomParams;
+use lightdotso_kafka::{
+    topics::activity::produce_activity_message, types::activity::ActivityMessage,
+};
+use lightdotso_prisma::{
+    configuration, configuration_operation, guardian, recovery_operation,
+    recovery_operation_signature, ActivityEntity, ActivityOperation, ConfigurationOperationStatus,
+    RecoveryOperationStatus,
+};
+use lightdotso_sequence::{builder::ecdsa_signature_leaf, recovery::recovery_signature_builder};
+use lightdotso_tracing::tracing::{error, info};
+use prisma_client_rust::{chrono::Utc, Direction};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+// -----------------------------------------------------------------------------
+// Query
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Deserialize, Default, IntoParams)]
+#[serde(rename_all = "snake_case")]
+#[into_params(parameter_in = Query)]
+pub struct PutQuery {
+    /// The id of the recovery operation to execute.
+    pub recovery_operation_id: String,
+}
+
+// -----------------------------------------------------------------------------
+// Handler
+// -----------------------------------------------------------------------------
+
+/// Execute the recovery of the wallet once the delay expires, building the recovery signature of
+/// the `updateImageHash` user operation, which is confirmed once executed on-chain
+#[utoipa::path(
+        put,
+        path = "/recovery_operation/execute",
+        params(
+            PutQuery
+        ),
+        responses(
+            (status = 200, description = "Recovery operation executed successfully", body = RecoveryOperation),
+            (status = 400, description = "Invalid recovery operation", body = RecoveryOperationError),
+            (status = 404, description = "Recovery operation not found", body = RecoveryOperationError),
+            (status = 500, description = "Recovery operation internal error", body = RecoveryOperationError),
+        )
+    )]
+#[autometrics]
+pub(crate) async fn v1_recovery_operation_execute_handler(
+    put_query: Query<PutQuery>,
+    State(state): State<AppState>,
+) -> AppJsonResult<RecoveryOperation> {
+    // -------------------------------------------------------------------------
+    // Parse
+    // -------------------------------------------------------------------------
+
+    // Get the put query.
+    let Query(query) = put_query;
+    info!(?query);
+
+    // -------------------------------------------------------------------------
+    // DB
+    // -------------------------------------------------------------------------
+
+    // Get the recovery operation from the database.
+    let recovery_operation = state
+        .client
+        .recovery_operation()
+        .find_unique(recovery_operation::id::equals(query.recovery_operation_id))
+        .with(
+            recovery_operation::recovery_operation_signatures::fetch(vec![])
+                .with(recovery_operation_signature::guardian::fetch())
+                .with(recovery_operation_signature::owner::fetch()),
+        )
+        .with(recovery_operation::configuration_operation::fetch())
+        .exec()
+        .await?;
+
+    // If the recovery operation is not found, return a 404.
+    let recovery_operation = recovery_operation.ok_or(RouteError::RecoveryOperationError(
+        RecoveryOperationError::NotFound("Recovery operation not found".to_string()),
+    ))?;
+
+    // -------------------------------------------------------------------------
+    // Validate
+    // -------------------------------------------------------------------------
+
+    // Only the pending recovery can be executed.
+    if recovery_operation.status != RecoveryOperationStatus::Pending {
+        return Err(RouteError::RecoveryOperationError(RecoveryOperationError::BadRequest(
+            format!("Recovery operation is {}", recovery_operation.status),
+        ))
+        .into());
+    }
+
+    // The recovery can only be executed after the delay for the owners to veto.
+    if Utc::now() < recovery_operation.executable_at {
+        return Err(RouteError::RecoveryOperationError(RecoveryOperationError::BadRequest(
+            format!(
+                "Recovery operation is executable at {}",
+                recovery_operation.executable_at.to_rfc3339()
+            ),
+        ))
+        .into());
+    }
+
+    // -------------------------------------------------------------------------
+    // DB
+    // -------------------------------------------------------------------------
+
+    // Get the current configuration for the wallet.
+    let configuration = state
+        .client
+        .configuration()
+        .find_first(vec![configuration::address::equals(recovery_operation.address.clone())])
+        .order_by(configuration::checkpoint::order(Direction::Desc))
+        .with(configuration::owners::fetch(vec![]))
+        .exec()
+        .await?;
+
+    // If the configuration is not found, return a 404.
+    let configuration = configuration.ok_or(RouteError::RecoveryOperationError(
+        RecoveryOperationError::NotFound("Configuration not found".to_string()),
+    ))?;
+
+    // Get the guardians of the wallet from the database.
+    let guardians = state
+        .client
+        .guardian()
+        .find_many(vec![guardian::wallet_address::equals(recovery_operation.address.clone())])
+        .exec()
+        .await?;
+
+    // Get the configuration operation of the recovered configuration.
+    let configuration_operation =
+        recovery_operation.configuration_operation.clone().flatten().ok_or(
+            RouteError::RecoveryOperationError(RecoveryOperationError::NotFound(
+                "Configuration operation not found".to_string(),
+            )),
+        )?;
+
+    // Get the signatures of the guardians and the owners.
+    let recovery_operation_signatures = recovery_operation
+        .recovery_operation_signatures
+        .clone()
+        .ok_or(RouteError::RecoveryOperationError(RecoveryOperationError::NotFound(
+            "Recovery operation signatures not found".to_string(),
+        )))?;
+
+    // -------------------------------------------------------------------------
+    // Validate
+    // -------------------------------------------------------------------------
+
+    // The recovered configuration has to follow the current configuration.
+    if configuration.checkpoint + 1 != recovery_operation.checkpoint {
+        return Err(RouteError::RecoveryOperationError(RecoveryOperationError::BadRequest(
+            "Configuration has changed since the recovery was requested".to_string(),
+        ))
+        .into());
+    }
+
+    // -------------------------------------------------------------------------
+    // Signature
+    // -------------------------------------------------------------------------
+
+    // Get the addresses of the guardians.
+    let guardian_addresses = guardians
+        .iter()
+        .map(|guardian| guardian.address.parse())
+        .collect::<Result<Vec<Address>, _>>()?;
+
+    // Convert the signatures of the guardians and the co-signing owners to the signature leaves.
+    let signature_leaves = recovery_operation_signatures
+        .iter()
+        .map(|sig| {
+            let address = match (sig.guardian.clone().flatten(), sig.owner.clone().flatten()) {
+                (Some(guardian), _) => guardian.address,
+                (_, Some(owner)) if owner.configuration_id == configuration.id => owner.address,
+                (_, Some(_)) => return Err(eyre!("Owner of the signature is not current")),
+                _ => return Err(eyre!("Signer of the signature not found")),
+            };
+            ecdsa_signature_leaf(address.parse()?, &sig.signature)
+        })
+        .collect::<Result<Vec<_>>>()
+        .map_err(|err| {
+            error!("Invalid recovery signature: {}", err);
+            RouteError::RecoveryOperationError(RecoveryOperationError::BadRequest(err.to_string()))
+        })?;
+
+    // Build the recovery signature of the current configuration, co-signed by the guardians and
+    // the owners.
+    let signature = recovery_signature_builder(
+        configuration_to_wallet_config(&configuration)?,
+        &guardian_addresses,
+        signature_leaves,
+    )
+    .and_then(|builder| builder.build())
+    .map_err(|err| {
+        error!("Invalid recovery: {}", err);
+        RouteError::RecoveryOperationError(RecoveryOperationError::BadRequest(err.to_string()))
+    })?;
+
+    // -------------------------------------------------------------------------
+    // DB
+    // -------------------------------------------------------------------------
+
+    // Store the recovery signature, w/ the configuration operation of the recovered configuration
+    // pending until the update of the image hash is confirmed on-chain.
+    let res: Result<recovery_operation::Data> = state
+        .client
+        ._transaction()
+        .run(|client| async move {
+            // Set the recovery signature of the recovery operation.
+            let recovery_operation = client
+                .recovery_operation()
+                .update(
+                    recovery_operation::id::equals(recovery_operation.id.clone()),
+                    vec![recovery_operation::signature::set(Some(signature))],
+                )
+                .exec()
+                .await?;
+            info!(?recovery_operation);
+
+            // Set the configuration operation of the recovered configuration to pending.
+            let configuration_operation = client
+                .configuration_operation()
+                .update(
+                    configuration_operation::id::equals(configuration_operation.id.clone()),
+                    vec![configuration_operation::status::set(
+                        ConfigurationOperationStatus::Pending,
+                    )],
+                )
+                .exec()
+                .await?;
+            info!(?configuration_operation);
+
+            Ok(recovery_operation)
+        })
+        .await;
+
+    // If the recovery signature is not stored, return a 500.
+    let recovery_operation = res.map_err(|err| {
+        error!("Failed to execute the recovery: {}", err);
+        AppError::InternalError
+    })?;
+
+    // -------------------------------------------------------------------------
+    // Kafka
+    // -------------------------------------------------------------------------
+
+    // Produce an activity message.
+    let _ = produce_activity_message(
+        state.producer.clone(),
+        ActivityEntity::RecoveryOperation,
+        &ActivityMessage {
+            operation: ActivityOperation::Update,
+            log: serde_json::to_value(&recovery_operation)?,
+            params: CustomParams {
+                recovery_operation_id: Some(recovery_operation.id.clone()),
+                wallet_address: Some(recovery_operation.address.clone()),
+                ..Default::default()
+            },
+        },
+    )
+    .await;
+
+    // -------------------------------------------------------------------------
+    // Return
+    // -------------------------------------------------------------------------
+
+    // Change the recovery operation to the format that the API expects.
+    let recovery_operation: RecoveryOperation = recovery_operation.into();
+
+    Ok(Json::from(recovery_operation))
+}
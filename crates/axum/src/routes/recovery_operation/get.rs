@@ -0,0 +1,96 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::types::RecoveryOperation;
+use crate::{
+    error::RouteError, result::AppJsonResult,
+    routes::recovery_operation::error::RecoveryOperationError, state::AppState,
+};
+use autometrics::autometrics;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use lightdotso_prisma::recovery_operation;
+use lightdotso_tracing::tracing::info;
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+// -----------------------------------------------------------------------------
+// Query
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Deserialize, Default, IntoParams)]
+#[serde(rename_all = "snake_case")]
+#[into_params(parameter_in = Query)]
+pub struct GetQuery {
+    pub id: String,
+}
+
+// -----------------------------------------------------------------------------
+// Handler
+// -----------------------------------------------------------------------------
+
+/// Get a recovery_operation
+#[utoipa::path(
+        get,
+        path = "/recovery_operation/get",
+        params(
+            GetQuery
+        ),
+        responses(
+            (status = 200, description = "Recovery operation returned successfully", body = RecoveryOperation),
+            (status = 404, description = "Recovery operation not found", body = RecoveryOperationError),
+        )
+    )]
+#[autometrics]
+pub(crate) async fn v1_recovery_operation_get_handler(
+    get_query: Query<GetQuery>,
+    State(state): State<AppState>,
+) -> AppJsonResult<RecoveryOperation> {
+    // -------------------------------------------------------------------------
+    // Parse
+    // -------------------------------------------------------------------------
+
+    // Get the get query.
+    let Query(query) = get_query;
+
+    info!("Get recovery_operation for id: {:?}", query);
+
+    // -------------------------------------------------------------------------
+    // DB
+    // -------------------------------------------------------------------------
+
+    // Get the recovery_operation from the database.
+    let recovery_operation = state
+        .client
+        .recovery_operation()
+        .find_unique(recovery_operation::id::equals(query.id))
+        .exec()
+        .await?;
+
+    // If the recovery_operation is not found, return a 404.
+    let recovery_operation = recovery_operation.ok_or(RouteError::RecoveryOperationError(
+        RecoveryOperationError::NotFound("Recovery operation not found".to_string()),
+    ))?;
+
+    // -------------------------------------------------------------------------
+    // Return
+    // -------------------------------------------------------------------------
+
+    // Change the recovery_operation to the format that the API expects.
+    let recovery_operation: RecoveryOperation = recovery_operation.into();
+
+    Ok(Json::from(recovery_operation))
+}
@@ -0,0 +1,102 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::types::RecoveryOperation;
+use crate::{result::AppJsonResult, state::AppState};
+use autometrics::autometrics;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use ethers_main::{types::H160, utils::to_checksum};
+use lightdotso_prisma::recovery_operation;
+use prisma_client_rust::Direction;
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+// -----------------------------------------------------------------------------
+// Query
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Deserialize, Default, IntoParams)]
+#[serde(rename_all = "snake_case")]
+#[into_params(parameter_in = Query)]
+pub struct ListQuery {
+    /// The offset of the first recovery operation to return.
+    pub offset: Option<i64>,
+    /// The maximum number of recovery operations to return.
+    pub limit: Option<i64>,
+    /// The address of the wallet to filter by.
+    pub address: String,
+}
+
+// -----------------------------------------------------------------------------
+// Handler
+// -----------------------------------------------------------------------------
+
+/// Returns a list of recovery operations of the wallet
+#[utoipa::path(
+        get,
+        path = "/recovery_operation/list",
+        params(
+            ListQuery
+        ),
+        responses(
+            (status = 200, description = "Recovery operations returned successfully", body = [RecoveryOperation]),
+            (status = 500, description = "Recovery operation bad request", body = RecoveryOperationError),
+        )
+    )]
+#[autometrics]
+pub(crate) async fn v1_recovery_operation_list_handler(
+    list_query: Query<ListQuery>,
+    State(state): State<AppState>,
+) -> AppJsonResult<Vec<RecoveryOperation>> {
+    // -------------------------------------------------------------------------
+    // Parse
+    // -------------------------------------------------------------------------
+
+    // Get the list query.
+    let Query(query) = list_query;
+
+    // Parse the address from the list query.
+    let parsed_query_address: H160 = query.address.parse()?;
+
+    // -------------------------------------------------------------------------
+    // DB
+    // -------------------------------------------------------------------------
+
+    // Get the recovery operations from the database, the latest first.
+    let recovery_operations = state
+        .client
+        .recovery_operation()
+        .find_many(vec![recovery_operation::address::equals(to_checksum(
+            &parsed_query_address,
+            None,
+        ))])
+        .order_by(recovery_operation::created_at::order(Direction::Desc))
+        .skip(query.offset.unwrap_or(0))
+        .take(query.limit.unwrap_or(10))
+        .exec()
+        .await?;
+
+    // -------------------------------------------------------------------------
+    // Return
+    // -------------------------------------------------------------------------
+
+    // Change the recovery operations to the format that the API expects.
+    let recovery_operations: Vec<RecoveryOperation> =
+        recovery_operations.into_iter().map(RecoveryOperation::from).collect();
+
+    Ok(Json::from(recovery_operations))
+}
@@ -0,0 +1,66 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+pub(crate) mod confirm;
+pub(crate) mod create;
+pub(crate) mod error;
+pub(crate) mod execute;
+pub(crate) mod get;
+pub(crate) mod list;
+pub(crate) mod sign;
+pub(crate) mod types;
+pub(crate) mod veto;
+
+use crate::state::AppState;
+use autometrics::autometrics;
+use axum::{
+    routing::{get, post, put},
+    Router,
+};
+
+pub(crate) use confirm::{
+    __path_v1_recovery_operation_confirm_handler, v1_recovery_operation_confirm_handler,
+};
+pub(crate) use create::{
+    __path_v1_recovery_operation_create_handler, v1_recovery_operation_create_handler,
+};
+pub(crate) use execute::{
+    __path_v1_recovery_operation_execute_handler, v1_recovery_operation_execute_handler,
+};
+pub(crate) use get::{__path_v1_recovery_operation_get_handler, v1_recovery_operation_get_handler};
+pub(crate) use list::{
+    __path_v1_recovery_operation_list_handler, v1_recovery_operation_list_handler,
+};
+pub(crate) use sign::{
+    __path_v1_recovery_operation_sign_handler, v1_recovery_operation_sign_handler,
+};
+pub(crate) use veto::{
+    __path_v1_recovery_operation_veto_handler, v1_recovery_operation_veto_handler,
+};
+
+// -----------------------------------------------------------------------------
+// Router
+// -----------------------------------------------------------------------------
+
+#[autometrics]
+pub(crate) fn router() -> Router<AppState> {
+    Router::new()
+        .route("/recovery_operation/confirm", put(v1_recovery_operation_confirm_handler))
+        .route("/recovery_operation/create", post(v1_recovery_operation_create_handler))
+        .route("/recovery_operation/execute", put(v1_recovery_operation_execute_handler))
+        .route("/recovery_operation/get", get(v1_recovery_operation_get_handler))
+        .route("/recovery_operation/list", get(v1_recovery_operation_list_handler))
+        .route("/recovery_operation/sign", post(v1_recovery_operation_sign_handler))
+        .route("/recovery_operation/veto", put(v1_recovery_operation_veto_handler))
+}
@@ -0,0 +1,303 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::types::RecoveryOperation;
+use crate::{
+    error::RouteError, result::AppJsonResult,
+    routes::recovery_operation::error::RecoveryOperationError, state::AppState,
+};
+use autometrics::autometrics;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use ethers_main::types::H256;
+use lightdotso_common::traits::{HexToBytes, VecU8ToHex};
+use lightdotso_db::models::activity::CustomParams;
+use lightdotso_kafka::{
+    topics::activity::produce_activity_message, types::activity::ActivityMessage,
+};
+use lightdotso_prisma::{
+    configuration, guardian, owner, recovery_operation, recovery_operation_signature,
+    ActivityEntity, ActivityOperation, OwnerLeafType, OwnerSignerType, RecoveryOperationStatus,
+};
+use lightdotso_sequence::{recovery::recovery_subdigest, signature::verify_owner_signature};
+use lightdotso_tracing::tracing::{error, info};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+// -----------------------------------------------------------------------------
+// Query
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Deserialize, Default, IntoParams)]
+#[serde(rename_all = "snake_case")]
+#[into_params(parameter_in = Query)]
+pub struct PostQuery {
+    /// The id of the recovery operation to sign.
+    pub recovery_operation_id: String,
+}
+
+// -----------------------------------------------------------------------------
+// Params
+// -----------------------------------------------------------------------------
+
+/// Recovery operation signature post request params
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct RecoveryOperationSignRequestParams {
+    /// The signature of the guardian or the owner.
+    pub signature: RecoveryOperationSignSignatureParams,
+}
+
+/// Recovery operation signature of either a guardian or an owner
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct RecoveryOperationSignSignatureParams {
+    /// The id of the guardian of the signature.
+    pub guardian_id: Option<String>,
+    /// The id of the owner of the signature, co-signing the recovery w/ the guardians.
+    pub owner_id: Option<String>,
+    /// The signature of the recovered image hash in hex.
+    pub signature: String,
+}
+
+// -----------------------------------------------------------------------------
+// Handler
+// -----------------------------------------------------------------------------
+
+/// Sign the pending recovery of the wallet by a guardian or an owner
+#[utoipa::path(
+        post,
+        path = "/recovery_operation/sign",
+        params(
+            PostQuery
+        ),
+        request_body = RecoveryOperationSignRequestParams,
+        responses(
+            (status = 200, description = "Recovery operation signed successfully", body = RecoveryOperation),
+            (status = 400, description = "Invalid signature", body = RecoveryOperationError),
+            (status = 404, description = "Recovery operation not found", body = RecoveryOperationError),
+            (status = 500, description = "Recovery operation internal error", body = RecoveryOperationError),
+        )
+    )]
+#[autometrics]
+pub(crate) async fn v1_recovery_operation_sign_handler(
+    post_query: Query<PostQuery>,
+    State(state): State<AppState>,
+    Json(params): Json<RecoveryOperationSignRequestParams>,
+) -> AppJsonResult<RecoveryOperation> {
+    // -------------------------------------------------------------------------
+    // Parse
+    // -------------------------------------------------------------------------
+
+    // Get the post query.
+    let Query(query) = post_query;
+    info!(?query);
+
+    // Get the signature from the post body.
+    let sig = params.signature;
+
+    // -------------------------------------------------------------------------
+    // DB
+    // -------------------------------------------------------------------------
+
+    // Get the recovery operation from the database.
+    let recovery_operation = state
+        .client
+        .recovery_operation()
+        .find_unique(recovery_operation::id::equals(query.recovery_operation_id))
+        .exec()
+        .await?;
+
+    // If the recovery operation is not found, return a 404.
+    let recovery_operation = recovery_operation.ok_or(RouteError::RecoveryOperationError(
+        RecoveryOperationError::NotFound("Recovery operation not found".to_string()),
+    ))?;
+
+    // Get the current configuration of the wallet, signed by the recovery.
+    let configuration = state
+        .client
+        .configuration()
+        .find_unique(configuration::address_checkpoint(
+            recovery_operation.address.clone(),
+            recovery_operation.checkpoint - 1,
+        ))
+        .exec()
+        .await?;
+
+    // If the configuration is not found, return a 404.
+    let configuration = configuration.ok_or(RouteError::RecoveryOperationError(
+        RecoveryOperationError::NotFound("Configuration not found".to_string()),
+    ))?;
+
+    // Get the guardian or the owner of the signature from the database, w/ the address of the
+    // signer.
+    let (guardian, owner, signer_address) = match (sig.guardian_id.clone(), sig.owner_id.clone()) {
+        (Some(guardian_id), None) => {
+            let guardian = state
+                .client
+                .guardian()
+                .find_unique(guardian::id::equals(guardian_id))
+                .exec()
+                .await?;
+
+            // If the guardian is not found, return a 404.
+            let guardian = guardian.ok_or(RouteError::RecoveryOperationError(
+                RecoveryOperationError::NotFound("Guardian not found".to_string()),
+            ))?;
+
+            let signer_address = guardian.address.clone();
+            (Some(guardian), None, signer_address)
+        }
+        (None, Some(owner_id)) => {
+            let owner =
+                state.client.owner().find_unique(owner::id::equals(owner_id)).exec().await?;
+
+            // If the owner is not found, return a 404.
+            let owner = owner.ok_or(RouteError::RecoveryOperationError(
+                RecoveryOperationError::NotFound("Owner not found".to_string()),
+            ))?;
+
+            let signer_address = owner.address.clone();
+            (None, Some(owner), signer_address)
+        }
+        _ => {
+            return Err(RouteError::RecoveryOperationError(RecoveryOperationError::BadRequest(
+                "Either the guardian or the owner has to sign".to_string(),
+            ))
+            .into());
+        }
+    };
+
+    // -------------------------------------------------------------------------
+    // Validate
+    // -------------------------------------------------------------------------
+
+    // Only the pending recovery can be signed.
+    if recovery_operation.status != RecoveryOperationStatus::Pending {
+        return Err(RouteError::RecoveryOperationError(RecoveryOperationError::BadRequest(
+            format!("Recovery operation is {}", recovery_operation.status),
+        ))
+        .into());
+    }
+
+    // Check if the guardian is registered for the wallet.
+    if let Some(guardian) = &guardian {
+        if guardian.wallet_address != recovery_operation.address {
+            return Err(RouteError::RecoveryOperationError(RecoveryOperationError::BadRequest(
+                "Guardian is not registered for the wallet".to_string(),
+            ))
+            .into());
+        }
+    }
+
+    // Check if the owner is an owner of the current configuration, w/ a chain agnostic signature.
+    if let Some(owner) = &owner {
+        if owner.configuration_id != configuration.id {
+            return Err(RouteError::RecoveryOperationError(RecoveryOperationError::BadRequest(
+                "Owner is not an owner of the current configuration".to_string(),
+            ))
+            .into());
+        }
+        if owner.leaf_type != OwnerLeafType::Address || owner.signer_type != OwnerSignerType::Eoa {
+            return Err(RouteError::RecoveryOperationError(RecoveryOperationError::BadRequest(
+                "Only the EOA owners can co-sign the recovery".to_string(),
+            ))
+            .into());
+        }
+    }
+
+    // -------------------------------------------------------------------------
+    // Signature
+    // -------------------------------------------------------------------------
+
+    // Render the chain agnostic subdigest of the update to the recovered image hash.
+    let image_hash: H256 = recovery_operation.image_hash.parse()?;
+    let subdigest = recovery_subdigest(recovery_operation.address.parse()?, image_hash)?;
+    info!("subdigest: {}", subdigest.to_vec().to_hex_string());
+
+    // Check that the signature is valid for the signer.
+    let sig_bytes = sig.signature.hex_to_bytes()?;
+    let is_valid =
+        verify_owner_signature(0, signer_address.parse()?, false, &subdigest, &sig_bytes).await?;
+    if !is_valid {
+        error!("Invalid signature of signer_address: {}", signer_address);
+        return Err(RouteError::RecoveryOperationError(RecoveryOperationError::BadRequest(
+            "Invalid signature of the signer".to_string(),
+        ))
+        .into());
+    }
+
+    // -------------------------------------------------------------------------
+    // DB
+    // -------------------------------------------------------------------------
+
+    // Connect the signature to either the guardian or the owner.
+    let mut create_params = vec![];
+    if let Some(guardian) = &guardian {
+        create_params.push(recovery_operation_signature::guardian::connect(guardian::id::equals(
+            guardian.id.clone(),
+        )));
+    }
+    if let Some(owner) = &owner {
+        create_params.push(recovery_operation_signature::owner::connect(owner::id::equals(
+            owner.id.clone(),
+        )));
+    }
+
+    // Create the signature of the guardian or the owner to the database.
+    let recovery_operation_signature = state
+        .client
+        .recovery_operation_signature()
+        .create(
+            sig_bytes,
+            recovery_operation::id::equals(recovery_operation.id.clone()),
+            create_params,
+        )
+        .exec()
+        .await?;
+    info!(?recovery_operation_signature);
+
+    // -------------------------------------------------------------------------
+    // Kafka
+    // -------------------------------------------------------------------------
+
+    // Produce an activity message.
+    let _ = produce_activity_message(
+        state.producer.clone(),
+        ActivityEntity::RecoveryOperationSignature,
+        &ActivityMessage {
+            operation: ActivityOperation::Create,
+            log: serde_json::to_value(&recovery_operation_signature)?,
+            params: CustomParams {
+                recovery_operation_signature_id: Some(recovery_operation_signature.id.clone()),
+                recovery_operation_id: Some(recovery_operation.id.clone()),
+                guardian_id: guardian.map(|guardian| guardian.id),
+                wallet_address: Some(recovery_operation.address.clone()),
+                ..Default::default()
+            },
+        },
+    )
+    .await;
+
+    // -------------------------------------------------------------------------
+    // Return
+    // -------------------------------------------------------------------------
+
+    // Change the recovery operation to the format that the API expects.
+    let recovery_operation: RecoveryOperation = recovery_operation.into();
+
+    Ok(Json::from(recovery_operation))
+}
@@ -0,0 +1,81 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ethers_main::types::H256;
+use lightdotso_common::traits::VecU8ToHex;
+use lightdotso_prisma::recovery_operation;
+use lightdotso_sequence::transition::update_image_hash_calldata;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+// -----------------------------------------------------------------------------
+// Types
+// -----------------------------------------------------------------------------
+
+/// RecoveryOperation root type.
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub(crate) struct RecoveryOperation {
+    /// The id of the recovery operation.
+    pub id: String,
+    /// The address of the wallet to recover.
+    pub address: String,
+    /// The image hash of the recovered configuration.
+    pub image_hash: String,
+    /// The checkpoint of the recovered configuration.
+    pub checkpoint: i64,
+    /// The threshold of the recovered configuration.
+    pub threshold: i64,
+    /// The time the recovery can be executed at, after the delay for the owners to veto.
+    pub executable_at: String,
+    /// The status of the recovery operation.
+    pub status: String,
+    /// The id of the configuration operation of the recovered configuration.
+    pub configuration_operation_id: Option<String>,
+    /// The recovery signature of the guardians, once executed.
+    pub signature: Option<String>,
+    /// The calldata of `updateImageHash` the wallet calls on itself, once executed.
+    pub calldata: Option<String>,
+}
+
+// -----------------------------------------------------------------------------
+// From
+// -----------------------------------------------------------------------------
+
+/// Implement From<recovery_operation::Data> for RecoveryOperation.
+impl From<recovery_operation::Data> for RecoveryOperation {
+    fn from(recovery_operation: recovery_operation::Data) -> Self {
+        // The calldata is only set once the guardians' signature is built on execution.
+        let calldata = recovery_operation.signature.as_ref().and_then(|_| {
+            recovery_operation
+                .image_hash
+                .parse::<H256>()
+                .ok()
+                .map(|image_hash| update_image_hash_calldata(image_hash).to_hex_string())
+        });
+
+        Self {
+            id: recovery_operation.id,
+            address: recovery_operation.address,
+            image_hash: recovery_operation.image_hash,
+            checkpoint: recovery_operation.checkpoint,
+            threshold: recovery_operation.threshold,
+            executable_at: recovery_operation.executable_at.to_rfc3339(),
+            status: recovery_operation.status.to_string(),
+            configuration_operation_id: recovery_operation.configuration_operation_id,
+            signature: recovery_operation.signature.map(|signature| signature.to_hex_string()),
+            calldata,
+        }
+    }
+}
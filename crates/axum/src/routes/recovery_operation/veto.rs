@@ -0,0 +1,180 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::types::RecoveryOperation;
+use crate::{
+    authentication::authenticate_wallet_user, error::RouteError, result::AppJsonResult,
+    routes::recovery_operation::error::RecoveryOperationError, state::AppState,
+};
+use autometrics::autometrics;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use ethers_main::types::H160;
+use lightdotso_db::models::activity::CustomParams;
+use lightdotso_kafka::{
+    topics::activity::produce_activity_message, types::activity::ActivityMessage,
+};
+use lightdotso_prisma::{
+    configuration_operation, recovery_operation, ActivityEntity, ActivityOperation,
+    ConfigurationOperationStatus, RecoveryOperationStatus,
+};
+use lightdotso_tracing::tracing::info;
+use serde::Deserialize;
+use tower_sessions_core::Session;
+use utoipa::IntoParams;
+
+// -----------------------------------------------------------------------------
+// Query
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Deserialize, Default, IntoParams)]
+#[serde(rename_all = "snake_case")]
+#[into_params(parameter_in = Query)]
+pub struct PutQuery {
+    /// The id of the recovery operation to veto.
+    pub recovery_operation_id: String,
+}
+
+// -----------------------------------------------------------------------------
+// Handler
+// -----------------------------------------------------------------------------
+
+/// Veto the pending recovery of the wallet by an owner
+#[utoipa::path(
+        put,
+        path = "/recovery_operation/veto",
+        params(
+            PutQuery
+        ),
+        responses(
+            (status = 200, description = "Recovery operation vetoed successfully", body = RecoveryOperation),
+            (status = 400, description = "Invalid recovery operation", body = RecoveryOperationError),
+            (status = 404, description = "Recovery operation not found", body = RecoveryOperationError),
+        )
+    )]
+#[autometrics]
+pub(crate) async fn v1_recovery_operation_veto_handler(
+    put_query: Query<PutQuery>,
+    State(state): State<AppState>,
+    mut session: Session,
+) -> AppJsonResult<RecoveryOperation> {
+    // -------------------------------------------------------------------------
+    // Parse
+    // -------------------------------------------------------------------------
+
+    // Get the put query.
+    let Query(query) = put_query;
+    info!(?query);
+
+    // -------------------------------------------------------------------------
+    // DB
+    // -------------------------------------------------------------------------
+
+    // Get the recovery operation from the database.
+    let recovery_operation = state
+        .client
+        .recovery_operation()
+        .find_unique(recovery_operation::id::equals(query.recovery_operation_id))
+        .exec()
+        .await?;
+
+    // If the recovery operation is not found, return a 404.
+    let recovery_operation = recovery_operation.ok_or(RouteError::RecoveryOperationError(
+        RecoveryOperationError::NotFound("Recovery operation not found".to_string()),
+    ))?;
+
+    // -------------------------------------------------------------------------
+    // Authentication
+    // -------------------------------------------------------------------------
+
+    // Check to see if the user is one of the owners of the wallet configurations.
+    let parsed_address: H160 = recovery_operation.address.parse()?;
+    let auth_user_id =
+        authenticate_wallet_user(&state, &mut session, &parsed_address, None, None).await?;
+
+    // -------------------------------------------------------------------------
+    // Validate
+    // -------------------------------------------------------------------------
+
+    // Only the pending recovery can be vetoed, which is until it is executed.
+    if recovery_operation.status != RecoveryOperationStatus::Pending {
+        return Err(RouteError::RecoveryOperationError(RecoveryOperationError::BadRequest(
+            format!("Recovery operation is {}", recovery_operation.status),
+        ))
+        .into());
+    }
+
+    // -------------------------------------------------------------------------
+    // DB
+    // -------------------------------------------------------------------------
+
+    // Veto the recovery operation.
+    let recovery_operation = state
+        .client
+        .recovery_operation()
+        .update(
+            recovery_operation::id::equals(recovery_operation.id.clone()),
+            vec![recovery_operation::status::set(RecoveryOperationStatus::Vetoed)],
+        )
+        .exec()
+        .await?;
+    info!(?recovery_operation);
+
+    // Reject the configuration operation of the recovered configuration.
+    if let Some(configuration_operation_id) = recovery_operation.configuration_operation_id.clone()
+    {
+        let configuration_operation = state
+            .client
+            .configuration_operation()
+            .update(
+                configuration_operation::id::equals(configuration_operation_id),
+                vec![configuration_operation::status::set(ConfigurationOperationStatus::Rejected)],
+            )
+            .exec()
+            .await?;
+        info!(?configuration_operation);
+    }
+
+    // -------------------------------------------------------------------------
+    // Kafka
+    // -------------------------------------------------------------------------
+
+    // Produce an activity message.
+    let _ = produce_activity_message(
+        state.producer.clone(),
+        ActivityEntity::RecoveryOperation,
+        &ActivityMessage {
+            operation: ActivityOperation::Update,
+            log: serde_json::to_value(&recovery_operation)?,
+            params: CustomParams {
+                recovery_operation_id: Some(recovery_operation.id.clone()),
+                user_id: Some(auth_user_id),
+                wallet_address: Some(recovery_operation.address.clone()),
+                ..Default::default()
+            },
+        },
+    )
+    .await;
+
+    // -------------------------------------------------------------------------
+    // Return
+    // -------------------------------------------------------------------------
+
+    // Change the recovery operation to the format that the API expects.
+    let recovery_operation: RecoveryOperation = recovery_operation.into();
+
+    Ok(Json::from(recovery_operation))
+}
@@ -30,9 +30,10 @@ use lightdotso_kafka::{
     topics::activity::produce_activity_message, types::activity::ActivityMessage,
 };
 use lightdotso_prisma::{
-    owner, user_operation, ActivityEntity, ActivityOperation, SignatureProcedure,
+    owner, user_operation, ActivityEntity, ActivityOperation, OwnerLeafType, OwnerSignerType,
+    SignatureProcedure,
 };
-use lightdotso_sequence::{signature::recover_ecdsa_signature, utils::render_subdigest};
+use lightdotso_sequence::{signature::verify_owner_signature, utils::render_subdigest};
 use lightdotso_tracing::tracing::{error, info};
 use serde::{Deserialize, Serialize};
 use utoipa::{IntoParams, ToSchema};
@@ -169,10 +170,6 @@ pub(crate) async fn v1_signature_create_handler(
     )?;
     info!(?subdigest);
 
-    // Recover the signature.
-    let recovered_sig = recover_ecdsa_signature(&sig_bytes, &subdigest, 0)?;
-    info!(?recovered_sig);
-
     // -------------------------------------------------------------------------
     // DB
     // -------------------------------------------------------------------------
@@ -194,12 +191,26 @@ pub(crate) async fn v1_signature_create_handler(
     // If the owner is not found, return a 404.
     let owner = owner.ok_or(AppError::NotFound)?;
 
-    // Check that the recovered signature is the same as the signature sender.
-    if recovered_sig.address != owner.address.parse()? {
-        error!(
-            "recovered_sig.address: {}, owner.address: {}",
-            recovered_sig.address, owner.address
-        );
+    // Only the owners of the address leaves can sign.
+    if owner.leaf_type != OwnerLeafType::Address {
+        return Err(AppError::BadRequest);
+    }
+
+    // The contract owners always sign w/ EIP-1271.
+    let is_contract = owner.signer_type == OwnerSignerType::Erc1271;
+    let procedure = if is_contract { SignatureProcedure::Erc1271 } else { procedure };
+
+    // Check that the signature is valid for the owner.
+    let is_valid = verify_owner_signature(
+        user_operation.chain_id as u64,
+        owner.address.parse()?,
+        is_contract,
+        &subdigest,
+        &sig_bytes,
+    )
+    .await?;
+    if !is_valid {
+        error!("Invalid signature of owner.address: {}", owner.address);
         return Err(AppError::BadRequest);
     }
 
@@ -0,0 +1,182 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::{error::SignatureError, types::SignatureInspection};
+use crate::{
+    authentication::authenticate_user,
+    error::RouteError,
+    result::{AppError, AppJsonResult},
+    state::AppState,
+};
+use autometrics::autometrics;
+use axum::{
+    extract::{Query, State},
+    headers::{authorization::Bearer, Authorization},
+    Json, TypedHeader,
+};
+use ethers::types::{Address, H256};
+use ethers_main::utils::to_checksum;
+use eyre::Result;
+use lightdotso_common::traits::HexToBytes;
+use lightdotso_prisma::{configuration, PrismaClient};
+use lightdotso_sequence::inspect::{inspect_signature, SignatureReport};
+use serde::{Deserialize, Serialize};
+use tower_sessions_core::Session;
+use utoipa::{IntoParams, ToSchema};
+
+// -----------------------------------------------------------------------------
+// Query
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Deserialize, Default, IntoParams)]
+#[serde(rename_all = "snake_case")]
+#[into_params(parameter_in = Query)]
+pub struct PostQuery {
+    /// The user id to inspect as. (for admin purposes only)
+    pub user_id: Option<String>,
+}
+
+// -----------------------------------------------------------------------------
+// Params
+// -----------------------------------------------------------------------------
+
+/// Signature inspect request params
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub struct SignatureInspectRequestParams {
+    /// The address of the wallet.
+    pub address: String,
+    /// The chain id of the signature.
+    pub chain_id: i64,
+    /// The digest signed by the signature in hex.
+    pub digest: String,
+    /// The signature of the wallet in hex.
+    pub signature: String,
+}
+
+// -----------------------------------------------------------------------------
+// Handler
+// -----------------------------------------------------------------------------
+
+/// Inspect a signature of a wallet
+#[utoipa::path(
+        post,
+        path = "/signature/inspect",
+        params(
+            PostQuery
+        ),
+        request_body = SignatureInspectRequestParams,
+        responses(
+            (status = 200, description = "Signature inspected successfully", body = SignatureInspection),
+            (status = 400, description = "Invalid signature", body = SignatureError),
+            (status = 401, description = "Unauthorized", body = AuthError),
+        )
+    )]
+#[autometrics]
+pub(crate) async fn v1_signature_inspect_handler(
+    post_query: Query<PostQuery>,
+    State(state): State<AppState>,
+    mut session: Session,
+    auth: Option<TypedHeader<Authorization<Bearer>>>,
+    Json(params): Json<SignatureInspectRequestParams>,
+) -> AppJsonResult<SignatureInspection> {
+    // -------------------------------------------------------------------------
+    // Parse
+    // -------------------------------------------------------------------------
+
+    // Get the post query.
+    let Query(query) = post_query;
+
+    let address: Address = params.address.parse().map_err(|_| bad_request("Invalid address"))?;
+    let chain_id = u64::try_from(params.chain_id).map_err(|_| bad_request("Invalid chain id"))?;
+    let digest = params.digest.hex_to_bytes32().map_err(|_| bad_request("Invalid digest"))?;
+    let signature =
+        params.signature.hex_to_bytes().map_err(|_| bad_request("Invalid signature"))?;
+
+    // -------------------------------------------------------------------------
+    // Authentication
+    // -------------------------------------------------------------------------
+
+    // Only authenticated users can inspect signatures, as the inspection calls the rpc and
+    // simulates the ERC-6492 deployments.
+    authenticate_user(
+        &state,
+        &mut session,
+        auth.map(|auth| auth.token().to_string()),
+        query.user_id,
+    )
+    .await?;
+
+    // -------------------------------------------------------------------------
+    // Signature
+    // -------------------------------------------------------------------------
+
+    // Decode the signature into the report.
+    let mut report = inspect_signature(address, chain_id, digest, signature.into()).await?;
+
+    // -------------------------------------------------------------------------
+    // DB
+    // -------------------------------------------------------------------------
+
+    // Flag the mismatches against the stored configurations of the wallet.
+    check_stored_configurations(&state.client, &to_checksum(&address, None), &mut report).await?;
+
+    // -------------------------------------------------------------------------
+    // Return
+    // -------------------------------------------------------------------------
+
+    // Change the report to the format that the API expects.
+    let inspection: SignatureInspection = report.into();
+
+    Ok(Json::from(inspection))
+}
+
+// -----------------------------------------------------------------------------
+// Utils
+// -----------------------------------------------------------------------------
+
+/// Create a bad request error for the invalid params.
+fn bad_request(msg: &str) -> AppError {
+    RouteError::SignatureError(SignatureError::BadRequest(msg.to_string())).into()
+}
+
+/// Check the image hash of the report and the chained reports against the stored configuration of
+/// the same checkpoint.
+async fn check_stored_configurations(
+    client: &PrismaClient,
+    address: &str,
+    report: &mut SignatureReport,
+) -> Result<()> {
+    let mut reports = vec![report];
+
+    while let Some(report) = reports.pop() {
+        let configuration = client
+            .configuration()
+            .find_first(vec![
+                configuration::address::equals(address.to_string()),
+                configuration::checkpoint::equals(report.checkpoint as i64),
+            ])
+            .exec()
+            .await?;
+
+        let stored_image_hash = configuration
+            .map(|configuration| configuration.image_hash.parse::<H256>())
+            .transpose()?;
+        report.check_stored_image_hash(stored_image_hash);
+
+        reports.extend(report.chained.iter_mut());
+    }
+
+    Ok(())
+}
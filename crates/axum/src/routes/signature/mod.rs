@@ -15,6 +15,7 @@
 pub(crate) mod create;
 pub(crate) mod error;
 pub(crate) mod get;
+pub(crate) mod inspect;
 pub(crate) mod list;
 pub(crate) mod types;
 
@@ -27,6 +28,7 @@ use axum::{
 
 pub(crate) use create::{__path_v1_signature_create_handler, v1_signature_create_handler};
 pub(crate) use get::{__path_v1_signature_get_handler, v1_signature_get_handler};
+pub(crate) use inspect::{__path_v1_signature_inspect_handler, v1_signature_inspect_handler};
 pub(crate) use list::{__path_v1_signature_list_handler, v1_signature_list_handler};
 
 // -----------------------------------------------------------------------------
@@ -38,5 +40,6 @@ pub(crate) fn router() -> Router<AppState> {
     Router::new()
         .route("/signature/create", post(v1_signature_create_handler))
         .route("/signature/get", get(v1_signature_get_handler))
+        .route("/signature/inspect", post(v1_signature_inspect_handler))
         .route("/signature/list", get(v1_signature_list_handler))
 }
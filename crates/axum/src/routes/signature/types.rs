@@ -12,8 +12,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use ethers_main::utils::to_checksum;
 use lightdotso_common::traits::VecU8ToHex;
 use lightdotso_prisma::signature;
+use lightdotso_sequence::inspect::{SignatureLeafReport, SignatureReport};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
@@ -35,6 +37,54 @@ pub(crate) struct Signature {
     pub created_at: String,
 }
 
+/// Signature inspection leaf type.
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub(crate) struct SignatureInspectionLeaf {
+    /// The depth of the leaf in the branches and the nested configs.
+    pub depth: u32,
+    /// The type of the leaf.
+    pub leaf_type: String,
+    /// The recovered signer of the leaf.
+    pub signer: Option<String>,
+    /// The weight of the signer, or the external weight of the nested config.
+    pub weight: Option<u8>,
+    /// The internal threshold of the nested config.
+    pub threshold: Option<u16>,
+    /// The hash of the node, the subdigest, or the internal root of the nested config.
+    pub hash: Option<String>,
+    /// The weight the leaf contributes.
+    pub contributed_weight: String,
+    /// The cumulative weight after the leaf.
+    pub cumulative_weight: String,
+}
+
+/// Signature inspection type.
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub(crate) struct SignatureInspection {
+    /// The type of the signature.
+    pub signature_type: u8,
+    /// The subdigest signed by the signature.
+    pub subdigest: String,
+    /// The threshold of the configuration.
+    pub threshold: u16,
+    /// The checkpoint of the configuration.
+    pub checkpoint: u32,
+    /// The total weight of the signature.
+    pub weight: String,
+    /// Whether the weight reaches the threshold.
+    pub is_threshold_met: bool,
+    /// The computed image hash of the configuration.
+    pub image_hash: String,
+    /// The decoded leaves of the signature.
+    pub leaves: Vec<SignatureInspectionLeaf>,
+    /// The inspections of the chained signatures.
+    pub chained: Vec<SignatureInspection>,
+    /// The mismatches and the problems of the signature.
+    pub warnings: Vec<String>,
+}
+
 // -----------------------------------------------------------------------------
 // From
 // -----------------------------------------------------------------------------
@@ -50,3 +100,37 @@ impl From<signature::Data> for Signature {
         }
     }
 }
+
+/// Implement From<SignatureLeafReport> for SignatureInspectionLeaf.
+impl From<SignatureLeafReport> for SignatureInspectionLeaf {
+    fn from(leaf: SignatureLeafReport) -> Self {
+        Self {
+            depth: leaf.depth,
+            leaf_type: format!("{:?}", leaf.leaf_type),
+            signer: leaf.signer.map(|signer| to_checksum(&signer, None)),
+            weight: leaf.weight,
+            threshold: leaf.threshold,
+            hash: leaf.hash.map(|hash| format!("{:?}", hash)),
+            contributed_weight: leaf.contributed_weight.to_string(),
+            cumulative_weight: leaf.cumulative_weight.to_string(),
+        }
+    }
+}
+
+/// Implement From<SignatureReport> for SignatureInspection.
+impl From<SignatureReport> for SignatureInspection {
+    fn from(report: SignatureReport) -> Self {
+        Self {
+            signature_type: report.signature_type,
+            subdigest: format!("{:?}", report.subdigest),
+            threshold: report.threshold,
+            checkpoint: report.checkpoint,
+            weight: report.weight.to_string(),
+            is_threshold_met: report.is_threshold_met,
+            image_hash: format!("{:?}", report.image_hash),
+            leaves: report.leaves.into_iter().map(|leaf| leaf.into()).collect(),
+            chained: report.chained.into_iter().map(|report| report.into()).collect(),
+            warnings: report.warnings,
+        }
+    }
+}
@@ -21,25 +21,38 @@ use crate::{
 use autometrics::autometrics;
 use axum::{extract::State, Json};
 use clap::Parser;
-use ethers_main::utils::to_checksum;
+use ethers::providers::Middleware;
+use ethers_main::{abi::Address, types::U256, utils::to_checksum};
+use eyre::Result;
 use lightdotso_common::utils::hex_to_bytes;
+use lightdotso_contracts::provider::get_provider;
 use lightdotso_db::models::{
     activity::CustomParams, interpretation::upsert_interpretation_with_actions,
 };
-use lightdotso_interpreter::config::InterpreterArgs;
+use lightdotso_interpreter::{
+    config::InterpreterArgs,
+    types::InterpretationResponse,
+    warnings::{
+        check_warnings, outflow_recipients, RecipientInfo, SimulationWarning,
+        SimulationWarningSeverity, SimulationWarningType, WarningContext,
+    },
+};
 use lightdotso_kafka::{
     topics::activity::produce_activity_message, types::activity::ActivityMessage,
 };
 use lightdotso_prisma::{
-    asset_change, interpretation, interpretation_action, simulation, wallet, ActivityEntity,
-    ActivityOperation,
+    asset_change, interpretation, interpretation_action, protocol, simulation, simulation_warning,
+    wallet, ActivityEntity, ActivityOperation, ProtocolStatus, SimulationStatus,
+};
+use lightdotso_simulator::types::{
+    AccountOverride, SimulationRequest, SimulationUserOperationRequest, StateOverride,
 };
-use lightdotso_simulator::types::{SimulationRequest, SimulationUserOperationRequest};
 use lightdotso_tracing::tracing::info;
 use prisma_client_rust::or;
 // use lightdotso_tracing::tracing::info;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::HashMap;
 use utoipa::ToSchema;
 
 // -----------------------------------------------------------------------------
@@ -59,6 +72,22 @@ pub(crate) struct SimulationCreateRequestParams {
     pub init_code: String,
     /// The call data of the simulation to update for.
     pub call_data: String,
+    /// The state overrides of the simulation by the account address.
+    #[serde(default)]
+    pub state_overrides: Option<HashMap<String, SimulationCreateAccountOverrideParams>>,
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Default)]
+#[serde(rename_all = "snake_case")]
+pub(crate) struct SimulationCreateAccountOverrideParams {
+    /// The balance of the account to override, in wei as a decimal string.
+    pub balance: Option<String>,
+    /// The nonce of the account to override.
+    pub nonce: Option<u64>,
+    /// The code of the account to override.
+    pub code: Option<String>,
+    /// The storage slots of the account to override.
+    pub storage: Option<HashMap<String, String>>,
 }
 
 // -----------------------------------------------------------------------------
@@ -69,12 +98,44 @@ impl TryFrom<SimulationCreateRequestParams> for SimulationUserOperationRequest {
     type Error = eyre::Report;
 
     fn try_from(params: SimulationCreateRequestParams) -> Result<Self, Self::Error> {
+        let state_overrides = params
+            .state_overrides
+            .map(|overrides| {
+                overrides
+                    .into_iter()
+                    .map(|(address, account)| Ok((address.parse()?, account.try_into()?)))
+                    .collect::<Result<StateOverride, Self::Error>>()
+            })
+            .transpose()?;
+
         Ok(Self {
             chain_id: params.chain_id,
             sender: params.sender.parse()?,
-            nonce: params.nonce,
+            nonce: params.nonce.into(),
             init_code: Some(hex_to_bytes(&params.init_code).unwrap_or_default().into()),
             call_data: Some(hex_to_bytes(&params.call_data).unwrap_or_default().into()),
+            state_overrides,
+        })
+    }
+}
+
+impl TryFrom<SimulationCreateAccountOverrideParams> for AccountOverride {
+    type Error = eyre::Report;
+
+    fn try_from(params: SimulationCreateAccountOverrideParams) -> Result<Self, Self::Error> {
+        Ok(Self {
+            balance: params.balance.map(|balance| U256::from_dec_str(&balance)).transpose()?,
+            nonce: params.nonce,
+            code: params.code.map(|code| hex_to_bytes(&code).map(Into::into)).transpose()?,
+            storage: params
+                .storage
+                .map(|storage| {
+                    storage
+                        .into_iter()
+                        .map(|(slot, value)| Ok((slot.parse()?, value.parse()?)))
+                        .collect::<Result<HashMap<_, _>, Self::Error>>()
+                })
+                .transpose()?,
         })
     }
 }
@@ -125,6 +186,20 @@ pub(crate) async fn v1_simulation_create_handler(
         upsert_interpretation_with_actions(state.client.clone(), res.clone(), None, None).await?;
     info!(?interpretation);
 
+    // -------------------------------------------------------------------------
+    // Warnings
+    // -------------------------------------------------------------------------
+
+    // Check the simulation for the risky patterns.
+    let warnings = check_simulation_warnings(
+        &state,
+        &res,
+        simulation_request_op.chain_id,
+        simulation_request_op.sender,
+    )
+    .await?;
+    info!(?warnings);
+
     // -------------------------------------------------------------------------
     // DB
     // -------------------------------------------------------------------------
@@ -138,16 +213,50 @@ pub(crate) async fn v1_simulation_create_handler(
             res.gas_used as i64,
             res.success,
             json!({}),
-            simulation_request_op.nonce as i64,
+            params.nonce as i64,
             simulation_request_op.init_code.unwrap_or_default().to_vec(),
             simulation_request_op.call_data.unwrap_or_default().to_vec(),
             interpretation::id::equals(interpretation.id.clone()),
             wallet::address::equals(to_checksum(&simulation_request_op.sender, None)),
-            vec![],
+            vec![simulation::status::set(if warnings.is_empty() {
+                SimulationStatus::None
+            } else {
+                SimulationStatus::Warning
+            })],
         )
         .exec()
         .await?;
 
+    // Create the warnings of the simulation, one per type, address and token.
+    for warning in warnings.iter() {
+        let token = warning.token.map(|token| to_checksum(&token, None));
+        let warning_id = format!(
+            "{}:{}:{}:{}",
+            simulation.id,
+            warning.warning_type,
+            to_checksum(&warning.address, None),
+            token.clone().unwrap_or_default()
+        );
+        state
+            .client
+            .simulation_warning()
+            .create(
+                warning_id,
+                vec![
+                    simulation_warning::r#type::set(to_db_warning_type(warning.warning_type)),
+                    simulation_warning::severity::set(to_db_warning_severity(warning.severity)),
+                    simulation_warning::address::set(to_checksum(&warning.address, None)),
+                    simulation_warning::token::set(token),
+                    simulation_warning::message::set(warning.message.clone()),
+                    simulation_warning::simulations::connect(vec![simulation::id::equals(
+                        simulation.id.clone(),
+                    )]),
+                ],
+            )
+            .exec()
+            .await?;
+    }
+
     // Get the simulation from the database.
     let simulation = state
         .client
@@ -165,9 +274,11 @@ pub(crate) async fn v1_simulation_create_handler(
                 .with(
                     interpretation::asset_changes::fetch(vec![])
                         .with(asset_change::interpretation_action::fetch())
-                        .with(asset_change::token::fetch()),
+                        .with(asset_change::token::fetch())
+                        .with(asset_change::protocol::fetch()),
                 ),
         )
+        .with(simulation::warnings::fetch(vec![]))
         .exec()
         .await?;
 
@@ -204,3 +315,87 @@ pub(crate) async fn v1_simulation_create_handler(
 
     Ok(Json::from(simulation))
 }
+
+// -----------------------------------------------------------------------------
+// Utils
+// -----------------------------------------------------------------------------
+
+/// Check the interpretation for the risky patterns, trusting the known protocols of the chain.
+async fn check_simulation_warnings(
+    state: &AppState,
+    res: &InterpretationResponse,
+    chain_id: u64,
+    sender: Address,
+) -> Result<Vec<SimulationWarning>> {
+    let mut ctx = WarningContext::new(sender);
+
+    // Trust the known protocols of the chain.
+    let protocols = state
+        .client
+        .protocol()
+        .find_many(vec![
+            protocol::chain_id::equals(chain_id as i64),
+            protocol::status::in_vec(vec![ProtocolStatus::Trustworthy, ProtocolStatus::Known]),
+        ])
+        .exec()
+        .await?;
+    ctx.trusted.extend(protocols.iter().filter_map(|protocol| protocol.address.parse().ok()));
+
+    // Get the state of the recipients before the simulation, skipping the failed lookups.
+    let provider = get_provider(chain_id).await?;
+    for recipient in outflow_recipients(res, sender) {
+        let code = provider.get_code(recipient, None).await;
+        let nonce = provider.get_transaction_count(recipient, None).await;
+        if let (Ok(code), Ok(nonce)) = (code, nonce) {
+            ctx.recipients.insert(
+                recipient,
+                RecipientInfo {
+                    is_contract: !code.is_empty(),
+                    is_fresh: code.is_empty() && nonce.is_zero(),
+                },
+            );
+        }
+    }
+
+    Ok(check_warnings(res, &ctx))
+}
+
+/// Convert the warning type to the database enum.
+fn to_db_warning_type(
+    warning_type: SimulationWarningType,
+) -> lightdotso_prisma::SimulationWarningType {
+    match warning_type {
+        SimulationWarningType::UnlimitedApproval => {
+            lightdotso_prisma::SimulationWarningType::UnlimitedApproval
+        }
+        SimulationWarningType::ApprovalForAll => {
+            lightdotso_prisma::SimulationWarningType::ApprovalForAll
+        }
+        SimulationWarningType::FreshRecipient => {
+            lightdotso_prisma::SimulationWarningType::FreshRecipient
+        }
+        SimulationWarningType::UnverifiedContract => {
+            lightdotso_prisma::SimulationWarningType::UnverifiedContract
+        }
+        SimulationWarningType::ConfigurationChange => {
+            lightdotso_prisma::SimulationWarningType::ConfigurationChange
+        }
+        SimulationWarningType::UnknownDelegateCall => {
+            lightdotso_prisma::SimulationWarningType::UnknownDelegatecall
+        }
+        SimulationWarningType::LargeOutflow => {
+            lightdotso_prisma::SimulationWarningType::LargeOutflow
+        }
+    }
+}
+
+/// Convert the warning severity to the database enum.
+fn to_db_warning_severity(
+    severity: SimulationWarningSeverity,
+) -> lightdotso_prisma::SimulationWarningSeverity {
+    match severity {
+        SimulationWarningSeverity::Low => lightdotso_prisma::SimulationWarningSeverity::Low,
+        SimulationWarningSeverity::Medium => lightdotso_prisma::SimulationWarningSeverity::Medium,
+        SimulationWarningSeverity::High => lightdotso_prisma::SimulationWarningSeverity::High,
+    }
+}
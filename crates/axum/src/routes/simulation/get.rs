@@ -82,9 +82,11 @@ pub(crate) async fn v1_simulation_get_handler(
             simulation::interpretation::fetch().with(interpretation::actions::fetch(vec![])).with(
                 interpretation::asset_changes::fetch(vec![])
                     .with(asset_change::interpretation_action::fetch())
-                    .with(asset_change::token::fetch()),
+                    .with(asset_change::token::fetch())
+                    .with(asset_change::protocol::fetch()),
             ),
         )
+        .with(simulation::warnings::fetch(vec![]))
         .exec()
         .await?;
 
@@ -103,9 +103,11 @@ pub(crate) async fn v1_simulation_list_handler(
             simulation::interpretation::fetch().with(interpretation::actions::fetch(vec![])).with(
                 interpretation::asset_changes::fetch(vec![])
                     .with(asset_change::interpretation_action::fetch())
-                    .with(asset_change::token::fetch()),
+                    .with(asset_change::token::fetch())
+                    .with(asset_change::protocol::fetch()),
             ),
         )
+        .with(simulation::warnings::fetch(vec![]))
         .skip(query.offset.unwrap_or(0))
         .take(query.limit.unwrap_or(10))
         .exec()
@@ -13,7 +13,7 @@
 // limitations under the License.
 
 use crate::routes::interpretation::types::Interpretation;
-use lightdotso_prisma::simulation;
+use lightdotso_prisma::{simulation, simulation_warning};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
@@ -37,6 +37,26 @@ pub(crate) struct Simulation {
     status: String,
     /// The interpretation of the simulation.
     interpretation: Option<Interpretation>,
+    /// The warnings of the risky patterns in the simulation.
+    warnings: Vec<SimulationWarning>,
+}
+
+/// Simulation warning root type.
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub(crate) struct SimulationWarning {
+    /// The id of the warning.
+    id: String,
+    /// The type of the warning.
+    warning_type: String,
+    /// The severity of the warning.
+    severity: String,
+    /// The address the warning is about.
+    address: String,
+    /// The token the warning is about.
+    token: Option<String>,
+    /// The message of the warning.
+    message: String,
 }
 
 // -----------------------------------------------------------------------------
@@ -53,6 +73,24 @@ impl From<simulation::Data> for Simulation {
             success: simulation.success,
             status: simulation.status.to_string(),
             interpretation: simulation.interpretation.map(|int| Interpretation::from(*int)),
+            warnings: simulation
+                .warnings
+                .map(|warnings| warnings.into_iter().map(SimulationWarning::from).collect())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Implement From<simulation_warning::Data> for SimulationWarning.
+impl From<simulation_warning::Data> for SimulationWarning {
+    fn from(warning: simulation_warning::Data) -> Self {
+        Self {
+            id: warning.id,
+            warning_type: warning.r#type.to_string(),
+            severity: warning.severity.to_string(),
+            address: warning.address,
+            token: warning.token,
+            message: warning.message,
         }
     }
 }
@@ -82,7 +82,8 @@ pub(crate) async fn v1_transaction_get_handler(
             transaction::interpretation::fetch().with(interpretation::actions::fetch(vec![])).with(
                 interpretation::asset_changes::fetch(vec![])
                     .with(asset_change::interpretation_action::fetch())
-                    .with(asset_change::token::fetch()),
+                    .with(asset_change::token::fetch())
+                    .with(asset_change::protocol::fetch()),
             ),
         )
         .exec()
@@ -118,7 +118,8 @@ pub(crate) async fn v1_transaction_list_handler(
                 .with(
                     interpretation::asset_changes::fetch(vec![])
                         .with(asset_change::interpretation_action::fetch())
-                        .with(asset_change::token::fetch()),
+                        .with(asset_change::token::fetch())
+                        .with(asset_change::protocol::fetch()),
                 ),
         )
         .skip(query.offset.unwrap_or(0))
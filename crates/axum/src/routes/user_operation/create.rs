@@ -16,8 +16,9 @@
 
 use super::types::UserOperation;
 use crate::{
+    error::RouteError,
     result::{AppError, AppJsonResult},
-    routes::signature::create::SignatureCreateParams,
+    routes::{signature::create::SignatureCreateParams, user_operation::error::UserOperationError},
     state::AppState,
 };
 use autometrics::autometrics;
@@ -25,6 +26,7 @@ use axum::{
     extract::{Query, State},
     Json,
 };
+use ethers::types::{serde_helpers::deserialize_number, Address, U256};
 use ethers_main::{
     types::H160,
     utils::{hex, to_checksum},
@@ -32,8 +34,9 @@ use ethers_main::{
 use eyre::{Report, Result};
 use lightdotso_common::{traits::HexToBytes, utils::hex_to_bytes};
 use lightdotso_contracts::{
-    constants::ENTRYPOINT_V060_ADDRESS, paymaster::decode_paymaster_and_data,
-    types::UserOperation as BaseUserOperation,
+    constants::ENTRYPOINT_V060_ADDRESS,
+    paymaster::decode_paymaster_and_data,
+    types::{EntryPointVersion, UserOperation as BaseUserOperation},
 };
 use lightdotso_db::models::activity::CustomParams;
 use lightdotso_kafka::{
@@ -42,16 +45,18 @@ use lightdotso_kafka::{
 };
 use lightdotso_prisma::{
     chain, configuration, owner, paymaster, paymaster_operation, user_operation,
-    user_operation_merkle, wallet, ActivityEntity, ActivityOperation, SignatureProcedure,
+    user_operation_merkle, wallet, ActivityEntity, ActivityOperation, OwnerSignerType,
+    SignatureProcedure,
+};
+use lightdotso_sequence::{
+    merkle::UserOperationMerkleTree, signature::verify_owner_signature, utils::render_subdigest,
 };
-use lightdotso_sequence::{signature::recover_ecdsa_signature, utils::render_subdigest};
 use lightdotso_tracing::tracing::{error, info};
 use lightdotso_utils::is_testnet;
 use prisma_client_rust::{
     chrono::{DateTime, NaiveDateTime, Utc},
     Direction,
 };
-use rs_merkle::{Hasher as MerkleHasher, MerkleTree};
 use serde::{Deserialize, Serialize};
 use utoipa::{IntoParams, ToSchema};
 
@@ -59,46 +64,6 @@ use utoipa::{IntoParams, ToSchema};
 // Generic
 // -----------------------------------------------------------------------------
 
-#[derive(Clone)]
-pub struct KeccakAlgorithm {}
-
-// Custom implementation of the MerkleHasher trait for the KeccakAlgorithm
-// Code from: https://github.com/arslanpixpel/dex-bridge/blob/a2bf3dc2a1ad2c5baea1db1876e8da56d32aafcd/relayer/src/merkle.rs#L87-L118
-// License: MIT
-
-impl MerkleHasher for KeccakAlgorithm {
-    type Hash = [u8; 32];
-
-    fn hash(data: &[u8]) -> [u8; 32] {
-        use sha3::Digest;
-        sha3::Keccak256::digest(data).into()
-    }
-
-    // The OpenZeppelin contract computes the hash of inner nodes by ordering them
-    // lexicographically first. So we must override the default implementation.
-    fn concat_and_hash(left: &Self::Hash, right: Option<&Self::Hash>) -> Self::Hash {
-        use sha3::Digest;
-        let mut hasher = sha3::Keccak256::new();
-        match right {
-            Some(right_node) => {
-                if left <= right_node {
-                    hasher.update(left);
-                    hasher.update(right_node);
-                } else {
-                    hasher.update(right_node);
-                    hasher.update(left);
-                }
-                hasher.finalize().into()
-            }
-            None => *left,
-        }
-    }
-
-    fn hash_size() -> usize {
-        std::mem::size_of::<Self::Hash>()
-    }
-}
-
 // -----------------------------------------------------------------------------
 // Query
 // -----------------------------------------------------------------------------
@@ -151,12 +116,47 @@ pub(crate) struct UserOperationCreateParams {
     nonce: i64,
     init_code: String,
     call_data: String,
-    call_gas_limit: i64,
-    verification_gas_limit: i64,
-    pre_verification_gas: i64,
-    max_fee_per_gas: i64,
-    max_priority_fee_per_gas: i64,
+    // The gas and fee fields accept numbers, and decimal or hex strings for 256-bit quantities.
+    #[serde(deserialize_with = "deserialize_number")]
+    #[schema(value_type = String)]
+    call_gas_limit: U256,
+    #[serde(deserialize_with = "deserialize_number")]
+    #[schema(value_type = String)]
+    verification_gas_limit: U256,
+    #[serde(deserialize_with = "deserialize_number")]
+    #[schema(value_type = String)]
+    pre_verification_gas: U256,
+    #[serde(deserialize_with = "deserialize_number")]
+    #[schema(value_type = String)]
+    max_fee_per_gas: U256,
+    #[serde(deserialize_with = "deserialize_number")]
+    #[schema(value_type = String)]
+    max_priority_fee_per_gas: U256,
     paymaster_and_data: String,
+    // The optional entry point of the user operation, defaults to the v0.6.0 entry point.
+    #[serde(default)]
+    entry_point: Option<String>,
+}
+
+impl UserOperationCreateParams {
+    /// Get the entry point of the user operation, which decides the encoding of the hash.
+    /// Only the supported entry points are accepted.
+    fn entry_point(&self) -> Result<Address> {
+        let entry_point = match &self.entry_point {
+            Some(entry_point) => entry_point.parse()?,
+            None => *ENTRYPOINT_V060_ADDRESS,
+        };
+        EntryPointVersion::try_from(entry_point)?;
+
+        Ok(entry_point)
+    }
+
+    /// Get the entry point of the user operation, w/ the unsupported entry points as a bad request.
+    fn validated_entry_point(&self) -> Result<Address, RouteError> {
+        self.entry_point().map_err(|err| {
+            RouteError::UserOperationError(UserOperationError::BadRequest(err.to_string()))
+        })
+    }
 }
 
 impl TryFrom<UserOperationCreateParams> for BaseUserOperation {
@@ -168,11 +168,11 @@ impl TryFrom<UserOperationCreateParams> for BaseUserOperation {
             nonce: op.nonce.into(),
             init_code: hex_to_bytes(&op.init_code)?.into(),
             call_data: hex_to_bytes(&op.call_data)?.into(),
-            call_gas_limit: op.call_gas_limit.into(),
-            verification_gas_limit: op.verification_gas_limit.into(),
-            pre_verification_gas: op.pre_verification_gas.into(),
-            max_fee_per_gas: op.max_fee_per_gas.into(),
-            max_priority_fee_per_gas: op.max_priority_fee_per_gas.into(),
+            call_gas_limit: op.call_gas_limit,
+            verification_gas_limit: op.verification_gas_limit,
+            pre_verification_gas: op.pre_verification_gas,
+            max_fee_per_gas: op.max_fee_per_gas,
+            max_priority_fee_per_gas: op.max_priority_fee_per_gas,
             paymaster_and_data: hex_to_bytes(&op.paymaster_and_data)?.into(),
             signature: vec![].into(),
         })
@@ -222,8 +222,9 @@ pub(crate) async fn v1_user_operation_create_handler(
     let user_operation_hash = params.user_operation.clone().hash;
     let sig = params.signature;
 
+    let entry_point = user_operation.validated_entry_point()?;
     let base_user_operation = BaseUserOperation::try_from(user_operation.clone())?;
-    let base_hash = base_user_operation.op_hash(*ENTRYPOINT_V060_ADDRESS, chain_id as u64);
+    let base_hash = base_user_operation.op_hash(entry_point, chain_id as u64)?;
 
     // Assert that the hex hash of base_hash is the same as the user_operation_hash (prefix 0x)
     if (format!("0x{}", hex::encode(base_hash)) != user_operation_hash) {
@@ -264,9 +265,6 @@ pub(crate) async fn v1_user_operation_create_handler(
     )?;
     info!(?subdigest);
 
-    let recovered_sig = recover_ecdsa_signature(&sig_bytes, &subdigest, 0)?;
-    info!(?recovered_sig);
-
     // -------------------------------------------------------------------------
     // DB
     // -------------------------------------------------------------------------
@@ -294,12 +292,22 @@ pub(crate) async fn v1_user_operation_create_handler(
         None => return Err(AppError::NotFound),
     };
 
-    // Check that the recovered signature is the same as the signature sender.
-    if recovered_sig.address != owner.address.parse()? {
-        error!(
-            "recovered_sig.address: {}, owner.address: {}",
-            recovered_sig.address, owner.address
-        );
+    // The contract owners always sign w/ EIP-1271.
+    let is_contract = owner.signer_type == OwnerSignerType::Erc1271;
+    let procedure =
+        if is_contract { SignatureProcedure::Erc1271 } else { SignatureProcedure::OnChain };
+
+    // Check that the signature is valid for the owner.
+    let is_valid = verify_owner_signature(
+        chain_id as u64,
+        owner.address.parse()?,
+        is_contract,
+        &subdigest,
+        &sig_bytes,
+    )
+    .await?;
+    if !is_valid {
+        error!("Invalid signature of owner.address: {}", owner.address);
         return Err(AppError::BadRequest);
     }
 
@@ -426,16 +434,16 @@ pub(crate) async fn v1_user_operation_create_handler(
                 let user_operation = client
                     .user_operation()
                     .create(
-                        to_checksum(&ENTRYPOINT_V060_ADDRESS, None),
+                        to_checksum(&entry_point, None),
                         user_operation.hash,
                         user_operation.nonce,
                         user_operation.init_code.hex_to_bytes()?,
                         user_operation.call_data.hex_to_bytes()?,
-                        user_operation.call_gas_limit,
-                        user_operation.verification_gas_limit,
-                        user_operation.pre_verification_gas,
-                        user_operation.max_fee_per_gas,
-                        user_operation.max_priority_fee_per_gas,
+                        user_operation.call_gas_limit.to_string(),
+                        user_operation.verification_gas_limit.to_string(),
+                        user_operation.pre_verification_gas.to_string(),
+                        user_operation.max_fee_per_gas.to_string(),
+                        user_operation.max_priority_fee_per_gas.to_string(),
                         user_operation.paymaster_and_data.hex_to_bytes()?,
                         chain::id::equals(chain_id),
                         wallet::address::equals(user_operation.sender),
@@ -450,7 +458,7 @@ pub(crate) async fn v1_user_operation_create_handler(
                     .create(
                         sig.signature.hex_to_bytes()?,
                         sig.signature_type,
-                        SignatureProcedure::OnChain,
+                        procedure,
                         owner::id::equals(sig.owner_id),
                         user_operation::hash::equals(user_operation_hash),
                         vec![],
@@ -583,7 +591,8 @@ pub(crate) async fn v1_user_operation_create_batch_handler(
         let user_operation_hash = user_operation.clone().hash;
 
         let base_user_operation = BaseUserOperation::try_from(user_operation.clone())?;
-        let base_hash = base_user_operation.op_hash(*ENTRYPOINT_V060_ADDRESS, chain_id as u64);
+        let base_hash = base_user_operation
+            .op_hash(user_operation.validated_entry_point()?, chain_id as u64)?;
 
         // Assert that the hex hash of base_hash is the same as the user_operation_hash
         if (format!("0x{}", hex::encode(base_hash)) != user_operation_hash) {
@@ -624,30 +633,22 @@ pub(crate) async fn v1_user_operation_create_batch_handler(
     let sorted_user_operations = user_operations.clone();
 
     // Then, get the hashes of the user operations.
-    let mut leaf_hashes: Vec<[u8; 32]> = sorted_user_operations
+    let leaf_hashes: Vec<[u8; 32]> = sorted_user_operations
         .iter()
         .map(|user_operation| {
             let base_user_operation = BaseUserOperation::try_from(user_operation.clone()).unwrap();
             let base_hash = base_user_operation
-                .op_hash(*ENTRYPOINT_V060_ADDRESS, user_operation.chain_id as u64);
+                .op_hash(user_operation.entry_point().unwrap(), user_operation.chain_id as u64)
+                .unwrap();
             base_hash.0
         })
         .collect();
 
-    // Sort the leaf hashes.
-    leaf_hashes.sort();
-
-    // If the number of leaf hashes is not divisible by 2, add a empty hash to the end.
-    // if leaf_hashes.len() % 2 != 0 {
-    //     leaf_hashes.push([0; 32]);
-    // }
-
-    // Create the merkle tree from the hashes.
-    let merkle_tree: MerkleTree<KeccakAlgorithm> =
-        MerkleTree::<KeccakAlgorithm>::from_leaves(&leaf_hashes);
+    // Create the merkle tree from the hashes, w/ the leaf hashes sorted.
+    let merkle_tree = UserOperationMerkleTree::new(&leaf_hashes)?;
 
     // Get the merkle root from the merkle tree.
-    let merkle_root = format!("0x{}", merkle_tree.root_hex().unwrap_or_default());
+    let merkle_root = format!("0x{}", hex::encode(merkle_tree.root()?));
     info!(?merkle_root);
 
     // Check that the merkle root is the same as the one provided.
@@ -671,9 +672,6 @@ pub(crate) async fn v1_user_operation_create_batch_handler(
     )?;
     info!(?subdigest);
 
-    let recovered_sig = recover_ecdsa_signature(&sig_bytes, &subdigest, 0)?;
-    info!(?recovered_sig);
-
     // -------------------------------------------------------------------------
     // DB
     // -------------------------------------------------------------------------
@@ -701,13 +699,30 @@ pub(crate) async fn v1_user_operation_create_batch_handler(
         None => return Err(AppError::NotFound),
     };
 
-    // Check that the recovered signature is the same as the signature sender.
-    if recovered_sig.address != owner.address.parse()? {
-        error!(
-            "recovered_sig.address: {}, owner.address: {}",
-            recovered_sig.address, owner.address
-        );
-        return Err(AppError::BadRequest);
+    // The contract owners always sign w/ EIP-1271.
+    let is_contract = owner.signer_type == OwnerSignerType::Erc1271;
+    let procedure =
+        if is_contract { SignatureProcedure::Erc1271 } else { SignatureProcedure::OnChain };
+
+    // Check that the signature is valid for the owner, on every chain of the user operations for
+    // the contract owners.
+    let mut chain_ids: Vec<i64> =
+        user_operations.iter().map(|user_operation| user_operation.chain_id).collect();
+    chain_ids.sort();
+    chain_ids.dedup();
+    for chain_id in chain_ids {
+        let is_valid = verify_owner_signature(
+            chain_id as u64,
+            owner.address.parse()?,
+            is_contract,
+            &subdigest,
+            &sig_bytes,
+        )
+        .await?;
+        if !is_valid {
+            error!("Invalid signature of owner.address: {} on chain {}", owner.address, chain_id);
+            return Err(AppError::BadRequest);
+        }
     }
 
     // -------------------------------------------------------------------------
@@ -852,16 +867,16 @@ pub(crate) async fn v1_user_operation_create_batch_handler(
         let chained_sig = sig.clone();
 
         // Get the rundler hash for the user operation.
+        let entry_point = user_operation.validated_entry_point()?;
         let base_user_operation = BaseUserOperation::try_from(user_operation.clone())?;
-        let base_hash = base_user_operation.op_hash(*ENTRYPOINT_V060_ADDRESS, chain_id as u64);
+        let base_hash = base_user_operation.op_hash(entry_point, chain_id as u64)?;
 
         // Get the merkle proof for the user operation.
         let merkle_proof = merkle_tree
-            .proof(&[leaf_hashes.iter().position(|x| x == &base_hash.0).unwrap()])
-            .proof_hashes_hex()
+            .proof(&base_hash.0)?
             // Prepend 0x to each hash
             .iter()
-            .map(|x| format!("0x{}", x))
+            .map(|x| format!("0x{}", hex::encode(x)))
             .collect::<Vec<String>>();
 
         // Create the user operation in the database w/ the sig.
@@ -875,16 +890,16 @@ pub(crate) async fn v1_user_operation_create_batch_handler(
                 let user_operation = client
                     .user_operation()
                     .create(
-                        to_checksum(&ENTRYPOINT_V060_ADDRESS, None),
+                        to_checksum(&entry_point, None),
                         user_operation.hash,
                         user_operation.nonce,
                         user_operation.init_code.hex_to_bytes()?,
                         user_operation.call_data.hex_to_bytes()?,
-                        user_operation.call_gas_limit,
-                        user_operation.verification_gas_limit,
-                        user_operation.pre_verification_gas,
-                        user_operation.max_fee_per_gas,
-                        user_operation.max_priority_fee_per_gas,
+                        user_operation.call_gas_limit.to_string(),
+                        user_operation.verification_gas_limit.to_string(),
+                        user_operation.pre_verification_gas.to_string(),
+                        user_operation.max_fee_per_gas.to_string(),
+                        user_operation.max_priority_fee_per_gas.to_string(),
                         user_operation.paymaster_and_data.hex_to_bytes()?,
                         chain::id::equals(chain_id),
                         wallet::address::equals(user_operation.sender),
@@ -899,7 +914,7 @@ pub(crate) async fn v1_user_operation_create_batch_handler(
                     .create(
                         chained_sig.signature.hex_to_bytes()?,
                         chained_sig.signature_type,
-                        SignatureProcedure::OnChain,
+                        procedure,
                         owner::id::equals(chained_sig.owner_id),
                         user_operation::hash::equals(user_operation_hash),
                         vec![],
@@ -964,7 +979,11 @@ pub(crate) async fn v1_user_operation_create_batch_handler(
                         &NodeMessage {
                             hash: BaseUserOperation::try_from(user_operation.clone())
                                 .unwrap()
-                                .op_hash(*ENTRYPOINT_V060_ADDRESS, user_operation.chain_id as u64),
+                                .op_hash(
+                                    user_operation.entry_point().unwrap(),
+                                    user_operation.chain_id as u64,
+                                )
+                                .unwrap(),
                         },
                     )
                     .await;
@@ -1032,12 +1051,13 @@ mod tests {
             nonce: 1,
             init_code: "0x1234".to_string(),
             call_data: "0x5678".to_string(),
-            call_gas_limit: 1,
-            verification_gas_limit: 1,
-            pre_verification_gas: 1,
-            max_fee_per_gas: 1,
-            max_priority_fee_per_gas: 1,
+            call_gas_limit: 1.into(),
+            verification_gas_limit: 1.into(),
+            pre_verification_gas: 1.into(),
+            max_fee_per_gas: 1.into(),
+            max_priority_fee_per_gas: 1.into(),
             paymaster_and_data: "0x1234".to_string(),
+            entry_point: None,
         };
 
         let result = BaseUserOperation::try_from(user_op);
@@ -1046,92 +1066,31 @@ mod tests {
     }
 
     #[test]
-    fn test_merkle_tree() {
-        let hashes = [
-            "0x0000000000000000000000000000000000000000000000000000000000000003",
-            "0x0000000000000000000000000000000000000000000000000000000000000001",
-            "0x0000000000000000000000000000000000000000000000000000000000000002",
-        ];
-
-        let mut leaf_hashes: Vec<[u8; 32]> =
-            hashes.iter().map(|hash| hash.hex_to_bytes32().unwrap()).collect();
-
-        leaf_hashes.sort();
-
-        for hash in leaf_hashes.iter() {
-            println!("0x{}", hex::encode(hash));
-        }
-
-        let mut merkle_tree: MerkleTree<KeccakAlgorithm> = MerkleTree::<KeccakAlgorithm>::new();
-
-        for hash in leaf_hashes.into_iter() {
-            merkle_tree.insert(hash);
-        }
-
-        merkle_tree.commit();
-
-        let merkle_root = format!("0x{}", merkle_tree.root_hex().unwrap_or_default());
+    fn test_entry_point() {
+        let mut user_op = UserOperationCreateParams {
+            chain_id: 1,
+            hash: "0x9e1a7c8".to_string(),
+            sender: "0x4fd9D0eE6D6564E80A9Ee00c0163fC952d0A45Ed".to_string(),
+            nonce: 1,
+            init_code: "0x1234".to_string(),
+            call_data: "0x5678".to_string(),
+            call_gas_limit: 1.into(),
+            verification_gas_limit: 1.into(),
+            pre_verification_gas: 1.into(),
+            max_fee_per_gas: 1.into(),
+            max_priority_fee_per_gas: 1.into(),
+            paymaster_and_data: "0x1234".to_string(),
+            entry_point: None,
+        };
+        assert_eq!(user_op.entry_point().unwrap(), *ENTRYPOINT_V060_ADDRESS);
 
+        user_op.entry_point = Some("0x0000000071727De22E5E9d8BAf0edAc6f37da032".to_string());
         assert_eq!(
-            merkle_root,
-            "0x9b0225f2c6f59eeaf8302811ea290e95258763189b82dc033158e99a6ef45a87"
+            user_op.entry_point().unwrap(),
+            "0x0000000071727De22E5E9d8BAf0edAc6f37da032".parse().unwrap()
         );
-    }
 
-    #[test]
-    fn test_merkle_tree_simple() {
-        let hashes = [
-            "0x0000000000000000000000000000000000000000000000000000000000000001",
-            "0x0000000000000000000000000000000000000000000000000000000000000002",
-        ];
-
-        let leaf_hashes: Vec<[u8; 32]> =
-            hashes.iter().map(|hash| hash.hex_to_bytes32().unwrap()).collect();
-
-        // leaf_hashes.sort();
-
-        for hash in leaf_hashes.iter() {
-            println!("0x{}", hex::encode(hash));
-        }
-
-        let merkle_tree: MerkleTree<KeccakAlgorithm> =
-            MerkleTree::<KeccakAlgorithm>::from_leaves(&leaf_hashes.to_vec());
-
-        let merkle_root = format!("0x{}", merkle_tree.root_hex().unwrap_or_default());
-
-        assert_eq!(
-            merkle_root,
-            "0xe90b7bceb6e7df5418fb78d8ee546e97c83a08bbccc01a0644d599ccd2a7c2e0"
-        );
+        user_op.entry_point = Some("0x66a15edcc3b50a663e72f1457ffd49b9ae284ddc".to_string());
+        assert!(user_op.entry_point().is_err());
     }
-
-    // #[test]
-    // fn test_merkle_tree_simple_deep() {
-    //     let hashes = [
-    //         "0x0000000000000000000000000000000000000000000000000000000000000001",
-    //         "0x0000000000000000000000000000000000000000000000000000000000000002",
-    //         "0x0000000000000000000000000000000000000000000000000000000000000003",
-    //         "0x0000000000000000000000000000000000000000000000000000000000000004",
-    //         "0x0000000000000000000000000000000000000000000000000000000000000005",
-    //     ];
-
-    //     let mut leaf_hashes: Vec<[u8; 32]> =
-    //         hashes.iter().map(|hash| hash.hex_to_bytes32().unwrap()).collect();
-
-    //     leaf_hashes.sort();
-
-    //     for hash in leaf_hashes.iter() {
-    //         println!("0x{}", hex::encode(hash));
-    //     }
-
-    //     let merkle_tree: MerkleTree<KeccakAlgorithm> =
-    //         MerkleTree::<KeccakAlgorithm>::from_leaves(&leaf_hashes.to_vec());
-
-    //     let merkle_root = format!("0x{}", merkle_tree.root_hex().unwrap_or_default());
-
-    //     assert_eq!(
-    //         merkle_root,
-    //         "0xf6c00687a2a50c87101e36eddc215e458f8ca89ee0fb3be978e73e0ea380b768"
-    //     );
-    // }
 }
@@ -159,7 +159,8 @@ pub(crate) async fn v1_user_operation_list_handler(
                 .with(
                     interpretation::asset_changes::fetch(vec![])
                         .with(asset_change::interpretation_action::fetch())
-                        .with(asset_change::token::fetch()),
+                        .with(asset_change::token::fetch())
+                        .with(asset_change::protocol::fetch()),
                 ),
         )
         .exec()
@@ -17,7 +17,10 @@
 
 use crate::{
     result::{AppError, AppJsonResult},
-    routes::{owner::types::Owner, signature::types::Signature},
+    routes::{
+        owner::utils::{unflatten_members, OwnerEntry},
+        signature::types::Signature,
+    },
     state::AppState,
 };
 use autometrics::autometrics;
@@ -26,20 +29,24 @@ use axum::{
     Json,
 };
 use const_hex::hex;
+use ethers::{providers::Middleware, types::Address};
 use eyre::{eyre, Result};
 use lightdotso_common::traits::{HexToBytes, VecU8ToHex};
+use lightdotso_contracts::provider::get_provider;
 use lightdotso_prisma::{
-    configuration, owner, signature, user_operation, user_operation_merkle_proof,
+    configuration, owner, signature, user_operation, user_operation_merkle_proof, OwnerLeafType,
+    OwnerSignerType,
 };
 use lightdotso_sequence::{
-    builder::rooted_node_builder,
+    builder::{
+        config_tree_builder, ecdsa_signature_leaf, erc_1271_signature_leaf, SignatureBuilder,
+    },
     config::WalletConfig,
+    erc6492::{decode_erc_6492_signature, is_erc_6492_signature},
     merkle::render_merkle,
-    types::{
-        AddressSignatureLeaf, ECDSASignatureLeaf, SignatureLeaf, Signer, SignerNode,
-        ECDSA_SIGNATURE_LENGTH,
-    },
-    utils::parse_hex_to_bytes32,
+    signature::verify_erc_1271_signature,
+    types::{SignatureLeaf, WalletSignatureType},
+    utils::{hash_image_bytes32, parse_hex_to_bytes32, render_subdigest},
 };
 use lightdotso_tracing::tracing::info;
 use prisma_client_rust::Direction;
@@ -190,201 +197,41 @@ pub(crate) async fn v1_user_operation_signature_handler(
     uproot_configurations.sort_by(|a, b| b.checkpoint.cmp(&a.checkpoint));
     info!(?uproot_configurations);
 
-    let mut owners = op_configuration.owners.ok_or(AppError::NotFound)?;
-    owners.sort_by(|a, b| a.index.cmp(&b.index));
-    info!(?owners);
-
-    // Map the signatures into type from the user_operation
-    let owners: Vec<Owner> = owners.into_iter().map(Owner::from).collect();
-
-    // Convert the owners to SignerNode.
-    let owner_nodes: Result<Vec<SignerNode>> = owners
-        .iter()
-        .map(|owner| {
-            Ok(SignerNode {
-                signer: Some(Signer {
-                    weight: Some(owner.weight.try_into()?),
-                    leaf: SignatureLeaf::AddressSignature(AddressSignatureLeaf {
-                        address: owner.address.parse()?,
-                    }),
-                }),
-                left: None,
-                right: None,
-            })
-        })
-        .collect();
-
-    // Build the node tree.
-    let mut tree = rooted_node_builder(owner_nodes?)?;
-    info!(?tree);
-
-    // Conver the signatures to SignerNode.
-    let signer_nodes: Result<Vec<SignerNode>> = signatures
-        .iter()
-        .map(|sig| {
-            // Filter the owner with the same id from `owners`
-            let owner = owners
-                .iter()
-                .find(|&owner| owner.id == sig.owner_id)
-                .ok_or(eyre!("Owner not found"))?;
-
-            let mut signature_slice = [0; ECDSA_SIGNATURE_LENGTH];
-            let bytes = sig.signature.hex_to_bytes()?;
-            signature_slice.copy_from_slice(&bytes[0..bytes.len() - 1]);
-            let signature_type = match bytes.last() {
-                Some(&0x1) => {
-                    lightdotso_sequence::types::ECDSASignatureType::ECDSASignatureTypeEIP712
-                }
-                _ => lightdotso_sequence::types::ECDSASignatureType::ECDSASignatureTypeEthSign,
-            };
+    // Get the chain id and the sender of the user operation.
+    let chain_id = user_operation.chain_id as u64;
+    let sender = user_operation.sender.parse()?;
 
-            Ok(SignerNode {
-                signer: Some(Signer {
-                    weight: Some(owner.weight.try_into()?),
-                    leaf: SignatureLeaf::ECDSASignature(ECDSASignatureLeaf {
-                        address: owner.address.parse()?,
-                        signature: signature_slice.try_into()?,
-                        signature_type,
-                    }),
-                }),
-                left: None,
-                right: None,
-            })
-        })
-        .collect();
-    tree.replace_node(signer_nodes?);
-    info!(?tree);
-
-    // If the uproot configurations are not empty, then we need to uproot the wallet configuration.
-    // For each uproot configuration, fetch the owners, and convert them to SignerNode. Then, append
-    // to the recovered_configs vector.
-    let recovered_configs = uproot_configurations
-        .into_iter()
-        .map(|recovered_configuration| {
-            let mut recovered_config_owners = recovered_configuration
-                .owners
-                .ok_or(AppError::NotFound)
-                .map_err(|_err| eyre!("Error fetching recovered configuration owners"))?;
-            recovered_config_owners.sort_by(|a, b| a.index.cmp(&b.index));
-            info!(?recovered_config_owners);
-
-            // Map the signatures into type from the user_operation
-            let recovered_config_owners: Vec<Owner> =
-                recovered_config_owners.into_iter().map(Owner::from).collect();
-
-            // Convert the owners to SignerNode.
-            let owner_nodes: Result<Vec<SignerNode>> = recovered_config_owners
-                .iter()
-                .map(|owner| {
-                    Ok(SignerNode {
-                        signer: Some(Signer {
-                            weight: Some(owner.weight.try_into()?),
-                            leaf: SignatureLeaf::AddressSignature(AddressSignatureLeaf {
-                                address: owner.address.parse()?,
-                            }),
-                        }),
-                        left: None,
-                        right: None,
-                    })
-                })
-                .collect();
-            let owner_nodes = owner_nodes?;
-
-            // Build the node tree.
-            let mut tree = rooted_node_builder(owner_nodes)?;
-            info!(?tree);
-
-            // Get the configuration signatures from the matching recovered configuration.
-            let upgrade_signatures = recovered_configuration
-                .configuration_operation_signatures
-                .ok_or(AppError::NotFound)
-                .map_err(|_err| eyre!("Error fetching recovered configuration signatures"))?;
-
-            // If the upgrade signatures is empty, return an error.
-            if upgrade_signatures.is_empty() {
-                return Err(eyre!("Upgrade signatures are empty"));
-            }
-
-            // Conver the signatures to SignerNode.
-            let signer_nodes: Result<Vec<SignerNode>> = upgrade_signatures
-                .iter()
-                .map(|sig| {
-                    // Filter the owner with the same id from `owners`
-                    let owner = recovered_config_owners
-                        .iter()
-                        .find(|&owner| owner.id == sig.clone().owner_id)
-                        .ok_or(eyre!("Owner not found"))?;
-
-                    let mut signature_slice = [0; ECDSA_SIGNATURE_LENGTH];
-                    let bytes = &sig.signature;
-                    signature_slice.copy_from_slice(&bytes[0..bytes.len() - 1]);
-                    let signature_type = match bytes.last() {
-                        Some(&0x1) => {
-                            lightdotso_sequence::types::ECDSASignatureType::ECDSASignatureTypeEIP712
-                        }
-                        _ => lightdotso_sequence::types::ECDSASignatureType::ECDSASignatureTypeEthSign,
-                    };
-
-                    Ok(SignerNode {
-                        signer: Some(Signer {
-                            weight: Some(owner.weight.try_into()?),
-                            leaf: SignatureLeaf::ECDSASignature(ECDSASignatureLeaf {
-                                address: owner.address.parse()?,
-                                signature: signature_slice.try_into()?,
-                                signature_type,
-                            }),
-                        }),
-                        left: None,
-                        right: None,
-                    })
-                })
-                .collect();
-
-            tree.replace_node(signer_nodes?);
-            info!(?tree);
-
-            let wallet_config = WalletConfig {
-                checkpoint: recovered_configuration.checkpoint as u32,
-                threshold: recovered_configuration.threshold as u16,
-                // Weight is not used in the signature.
-                weight: 0,
-                image_hash: recovered_configuration.image_hash.hex_to_bytes32()?.into(),
-                tree,
-                // All recovered configurations are in chain agnostic mode.
-                signature_type: 2,
-                // Internal fields are not used in the signature.
-                internal_root: None,
-                internal_recovered_configs: None,
-            };
+    // Get the merkle root from the user operation, if any.
+    let merkle_root = user_operation
+        .clone()
+        .user_operation_merkle_proofs
+        .unwrap()
+        .first()
+        .map(|proof| parse_hex_to_bytes32(&proof.user_operation_merkle_root))
+        .transpose()?;
+
+    // Render the subdigest that the owners signed, which is chain agnostic for the merkle root.
+    let subdigest = match merkle_root {
+        Some(merkle_root) => render_subdigest(0, sender, merkle_root)?,
+        None => render_subdigest(chain_id, sender, user_operation.hash.hex_to_bytes32()?)?,
+    };
 
-            Ok(wallet_config)
-        })
-        .collect::<Result<Vec<WalletConfig>>>()?;
-    info!(?recovered_configs);
+    // Get the signatures of the operation from the owners of the configuration.
+    let mut op_signatures = vec![];
+    for sig in signatures.iter() {
+        let owner = op_configuration
+            .owners
+            .as_ref()
+            .and_then(|owners| owners.iter().find(|owner| owner.id == sig.owner_id))
+            .ok_or(eyre!("Owner not found"))?;
+
+        op_signatures.push(
+            owner_signature_leaf(chain_id, &subdigest, owner, &sig.signature.hex_to_bytes()?)
+                .await?,
+        );
+    }
 
-    let wallet_config = WalletConfig {
-        checkpoint: op_configuration.checkpoint as u32,
-        threshold: op_configuration.threshold as u16,
-        // Weight is not used in the signature.
-        weight: 0,
-        image_hash: op_configuration.image_hash.hex_to_bytes32()?.into(),
-        tree,
-        // The default signature type is chain dependent.
-        signature_type: if user_operation.clone().user_operation_merkle_proofs.unwrap().is_empty() {
-            // Chain dependent if the user operation merkle proofs is empty.
-            1
-        } else {
-            // Chain agnostic if the user operation merkle proofs is not empty.
-            2
-        },
-        // Internal fields are not used in the signature.
-        internal_root: None,
-        internal_recovered_configs: if recovered_configs.is_empty() {
-            None
-        } else {
-            Some(recovered_configs)
-        },
-    };
+    let wallet_config = configuration_to_wallet_config(&op_configuration)?;
     info!(?wallet_config);
 
     // Check if the configuration is valid.
@@ -396,20 +243,39 @@ pub(crate) async fn v1_user_operation_signature_handler(
         return Err(AppError::BadRequest);
     }
 
+    let mut builder =
+        SignatureBuilder::new(wallet_config).signatures(op_signatures).signature_type(
+            // The default signature type is chain dependent.
+            if merkle_root.is_none() {
+                // Chain dependent if the user operation merkle proofs is empty.
+                WalletSignatureType::Dynamic
+            } else {
+                // Chain agnostic if the user operation merkle proofs is not empty.
+                WalletSignatureType::NoChainId
+            },
+        );
+
+    // If the uproot configurations are not empty, then we need to chain the signature from the
+    // upgrade signatures of each uproot configuration.
+    let mut next_image_hash = op_configuration.image_hash.hex_to_bytes32()?;
+    for recovered_configuration in uproot_configurations.iter() {
+        builder = builder.chain(
+            upgrade_signature_builder(chain_id, sender, &next_image_hash, recovered_configuration)
+                .await?,
+        );
+        next_image_hash = recovered_configuration.image_hash.hex_to_bytes32()?;
+    }
+
     // -------------------------------------------------------------------------
     // Return
     // -------------------------------------------------------------------------
 
     // Get the encoded user operation.
-    let sig = if wallet_config.internal_recovered_configs.is_none() {
-        wallet_config.encode()?.to_hex_string()
-    } else {
-        wallet_config.encode_chained_wallet()?.to_hex_string()
-    };
+    let sig = builder.build()?.to_hex_string();
     info!(?sig);
 
     // If a merkle proof is not empty, then the concatenated merkle proof is returned.
-    if !user_operation.clone().user_operation_merkle_proofs.unwrap().is_empty() {
+    if let Some(merkle_root) = merkle_root {
         // Get the merkle proof from the user operation.
         let merkle_proof = user_operation
             .clone()
@@ -419,18 +285,6 @@ pub(crate) async fn v1_user_operation_signature_handler(
             .map(|proof| parse_hex_to_bytes32(&proof.proof))
             .collect::<Result<Vec<[u8; 32]>>>()?;
         info!(?merkle_proof);
-
-        // Get the merkle root from the user operation.
-        let merkle_root = parse_hex_to_bytes32(
-            &user_operation
-                .clone()
-                .user_operation_merkle_proofs
-                .unwrap()
-                .first()
-                .unwrap()
-                .user_operation_merkle_root
-                .clone(),
-        )?;
         info!(?merkle_root);
 
         // Decode the signature.
@@ -445,3 +299,114 @@ pub(crate) async fn v1_user_operation_signature_handler(
 
     Ok(Json::from(sig))
 }
+
+// -----------------------------------------------------------------------------
+// Utils
+// -----------------------------------------------------------------------------
+
+/// Build the signature of the configuration over the image hash of the next configuration, from
+/// the upgrade signatures of its owners.
+pub(crate) async fn upgrade_signature_builder(
+    chain_id: u64,
+    sender: Address,
+    next_image_hash: &[u8; 32],
+    configuration: &configuration::Data,
+) -> Result<SignatureBuilder> {
+    // Get the configuration signatures from the matching configuration.
+    let upgrade_signatures = configuration
+        .configuration_operation_signatures
+        .as_ref()
+        .ok_or(eyre!("Error fetching recovered configuration signatures"))?;
+
+    // If the upgrade signatures is empty, return an error.
+    if upgrade_signatures.is_empty() {
+        return Err(eyre!("Upgrade signatures are empty"));
+    }
+
+    // The upgrade signatures are signed over the image hash of the next configuration.
+    let upgrade_subdigest = render_subdigest(0, sender, hash_image_bytes32(next_image_hash)?)?;
+
+    let mut upgrade_signature_leaves = vec![];
+    for sig in upgrade_signatures.iter() {
+        let owner = configuration
+            .owners
+            .as_ref()
+            .and_then(|owners| owners.iter().find(|owner| owner.id == sig.owner_id))
+            .ok_or(eyre!("Owner not found"))?;
+
+        upgrade_signature_leaves
+            .push(owner_signature_leaf(chain_id, &upgrade_subdigest, owner, &sig.signature).await?);
+    }
+
+    Ok(SignatureBuilder::new(configuration_to_wallet_config(configuration)?)
+        .signatures(upgrade_signature_leaves))
+}
+
+/// Convert the signature of the owner to the signature leaf, verifying the EIP-1271 signatures of
+/// the contract owners on the chain of the user operation.
+async fn owner_signature_leaf(
+    chain_id: u64,
+    subdigest: &[u8; 32],
+    owner: &owner::Data,
+    signature: &[u8],
+) -> Result<SignatureLeaf> {
+    // Only the address leaves are signing, the nested configs are signed by their owners.
+    if owner.leaf_type != OwnerLeafType::Address {
+        return Err(eyre!("Owner {} is not signing", owner.id));
+    }
+
+    let address = owner.address.parse()?;
+
+    match owner.signer_type {
+        OwnerSignerType::Erc1271 => {
+            // The wallet calls `isValidSignature` of the owner on-chain w/o unwrapping ERC-6492,
+            // so the wrapped signature is unwrapped once the owner is deployed on the chain.
+            let signature = if is_erc_6492_signature(signature) {
+                let provider = get_provider(chain_id).await?;
+                if provider.get_code(address, None).await?.is_empty() {
+                    return Err(eyre!(
+                        "Contract owner {:?} is not deployed on chain {}",
+                        address,
+                        chain_id
+                    ));
+                }
+                decode_erc_6492_signature(signature)?.2
+            } else {
+                signature.to_vec()
+            };
+
+            if !verify_erc_1271_signature(chain_id, address, subdigest, &signature).await? {
+                return Err(eyre!("Invalid EIP-1271 signature of {:?}", address));
+            }
+            Ok(erc_1271_signature_leaf(address, &signature))
+        }
+        OwnerSignerType::Eoa => ecdsa_signature_leaf(address, signature),
+    }
+}
+
+/// Convert the configuration w/ the owners to the wallet config.
+pub(crate) fn configuration_to_wallet_config(
+    configuration: &configuration::Data,
+) -> Result<WalletConfig> {
+    let owners =
+        configuration.owners.clone().ok_or(eyre!("Error fetching configuration owners"))?;
+    info!(?owners);
+
+    // Convert the owners to the config members, w/ the nested configs and the subdigests.
+    let owner_entries = owners.iter().map(OwnerEntry::try_from).collect::<Result<Vec<_>>>()?;
+    let members = unflatten_members(&owner_entries)?;
+
+    Ok(WalletConfig {
+        checkpoint: configuration.checkpoint as u32,
+        threshold: configuration.threshold as u16,
+        // Weight is set by the signature builder.
+        weight: 0,
+        image_hash: configuration.image_hash.hex_to_bytes32()?.into(),
+        tree: config_tree_builder(&members)?,
+        // The signature type is set by the signature builder.
+        signature_type: 1,
+        // Internal fields are not used in the signature.
+        internal_root: None,
+        internal_recovered_configs: None,
+    })
+}
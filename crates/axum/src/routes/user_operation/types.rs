@@ -36,11 +36,11 @@ pub(crate) struct UserOperationCreate {
     nonce: i64,
     init_code: String,
     call_data: String,
-    call_gas_limit: i64,
-    verification_gas_limit: i64,
-    pre_verification_gas: i64,
-    max_fee_per_gas: i64,
-    max_priority_fee_per_gas: i64,
+    call_gas_limit: String,
+    verification_gas_limit: String,
+    pre_verification_gas: String,
+    max_fee_per_gas: String,
+    max_priority_fee_per_gas: String,
     paymaster_and_data: String,
 }
 
@@ -77,15 +77,15 @@ pub(crate) struct UserOperation {
     /// The call data of the user operation.
     call_data: String,
     /// The call gas of the user operation.
-    call_gas_limit: i64,
+    call_gas_limit: String,
     /// The verification gas of the user operation.
-    verification_gas_limit: i64,
+    verification_gas_limit: String,
     /// The pre verification gas of the user operation.
-    pre_verification_gas: i64,
+    pre_verification_gas: String,
     /// The maximum fee per gas of the user operation.
-    max_fee_per_gas: i64,
+    max_fee_per_gas: String,
     /// The maximum priority fee per gas of the user operation.
-    max_priority_fee_per_gas: i64,
+    max_priority_fee_per_gas: String,
     /// The paymaster and data of the user operation.
     paymaster_and_data: String,
     /// The status of the user operation.
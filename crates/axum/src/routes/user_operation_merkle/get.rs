@@ -98,7 +98,8 @@ pub(crate) async fn v1_user_operation_merkle_get_handler(
                         .with(
                             interpretation::asset_changes::fetch(vec![])
                                 .with(asset_change::interpretation_action::fetch())
-                                .with(asset_change::token::fetch()),
+                                .with(asset_change::token::fetch())
+                                .with(asset_change::protocol::fetch()),
                         ),
                 ),
         )
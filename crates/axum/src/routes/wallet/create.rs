@@ -18,7 +18,13 @@ use crate::{
     admin::token_is_valid,
     error::RouteError,
     result::{AppError, AppJsonResult, AppResult},
-    routes::{auth::error::AuthError, wallet::types::Wallet},
+    routes::{
+        auth::error::AuthError,
+        owner::utils::{
+            flatten_members, owner_params_to_member, verify_contract_owners, OwnerParams,
+        },
+        wallet::types::Wallet,
+    },
     state::AppState,
 };
 use autometrics::autometrics;
@@ -37,13 +43,14 @@ use lightdotso_db::models::activity::CustomParams;
 use lightdotso_kafka::{
     topics::activity::produce_activity_message, types::activity::ActivityMessage,
 };
-use lightdotso_prisma::{wallet, ActivityEntity, ActivityOperation, InviteCodeStatus};
+use lightdotso_prisma::{
+    wallet, ActivityEntity, ActivityOperation, InviteCodeStatus, OwnerLeafType,
+};
 use lightdotso_redis::query::wallet::add_to_wallets;
 use lightdotso_sequence::{
-    builder::rooted_node_builder,
+    builder::{config_tree_builder, ConfigMember},
     config::WalletConfig,
     hash::get_address,
-    types::{AddressSignatureLeaf, SignatureLeaf, Signer, SignerNode},
 };
 use lightdotso_tracing::tracing::{error, info, trace};
 use serde::{Deserialize, Serialize};
@@ -89,14 +96,48 @@ pub struct WalletCreateRequestParams {
 }
 
 /// Wallet owner.
+/// The owner is either an address, a nested config w/ the `threshold` of its `owners`, or a
+/// pre-approved `subdigest`.
 #[derive(Serialize, Deserialize, ToSchema, Clone)]
 #[serde(rename_all = "snake_case")]
 #[schema(example = json!({"address": "0x4fd9D0eE6D6564E80A9Ee00c0163fC952d0A45Ed", "weight": 1}))]
 pub(crate) struct WalletCreateOwnerParams {
     /// The address of the owner.
+    #[serde(default)]
     pub address: String,
-    /// The weight of the owner.
+    /// The weight of the owner, or the external weight of the nested config.
+    #[serde(default)]
     pub weight: u8,
+    /// Whether the owner is a contract that signs w/ EIP-1271.
+    #[serde(default)]
+    pub is_contract: bool,
+    /// The internal threshold of the nested config.
+    pub threshold: Option<u16>,
+    /// The owners of the nested config.
+    pub owners: Option<Vec<WalletCreateOwnerParams>>,
+    /// The pre-approved subdigest, which is approved w/o any signature.
+    pub subdigest: Option<String>,
+}
+
+impl OwnerParams for WalletCreateOwnerParams {
+    fn address(&self) -> &str {
+        &self.address
+    }
+    fn weight(&self) -> u8 {
+        self.weight
+    }
+    fn is_contract(&self) -> bool {
+        self.is_contract
+    }
+    fn threshold(&self) -> Option<u16> {
+        self.threshold
+    }
+    fn owners(&self) -> Option<&[Self]> {
+        self.owners.as_deref()
+    }
+    fn subdigest(&self) -> Option<&str> {
+        self.subdigest.as_deref()
+    }
 }
 
 // -----------------------------------------------------------------------------
@@ -143,11 +184,23 @@ pub(crate) async fn v1_wallet_create_handler(
     // Validate
     // -------------------------------------------------------------------------
 
-    // Check if all of the owner address can be parsed to H160.
-    let owners_addresses = owners
+    // Check if all of the owners can be parsed to the config members.
+    let mut contracts = vec![];
+    let members = owners
         .iter()
-        .map(|owner| owner.address.parse::<H160>())
-        .collect::<Result<Vec<H160>, _>>()?;
+        .map(|owner| owner_params_to_member(owner, &mut contracts))
+        .collect::<Result<Vec<ConfigMember>>>()
+        .map_err(|_| AppError::BadRequest)?;
+
+    // Flatten the owners to store them in order.
+    let owner_entries = flatten_members(&members, &contracts);
+
+    // Get the addresses of the owners, w/o the nested configs and the subdigests.
+    let owners_addresses: Vec<H160> = owner_entries
+        .iter()
+        .filter(|entry| entry.leaf_type == OwnerLeafType::Address)
+        .map(|entry| entry.address)
+        .collect();
 
     // Check if the threshold is greater than 0
     if params.threshold == 0 {
@@ -157,23 +210,8 @@ pub(crate) async fn v1_wallet_create_handler(
     // Parse the salt to bytes.
     let salt_bytes: H256 = params.salt.parse()?;
 
-    // Conver the owners to SignerNode.
-    let owner_nodes: Vec<SignerNode> = owners
-        .iter()
-        .map(|owner| SignerNode {
-            signer: Some(Signer {
-                weight: Some(owner.weight),
-                leaf: SignatureLeaf::AddressSignature(AddressSignatureLeaf {
-                    address: owner.address.parse().unwrap(),
-                }),
-            }),
-            left: None,
-            right: None,
-        })
-        .collect();
-
     // Build the node tree.
-    let tree = rooted_node_builder(owner_nodes)?;
+    let tree = config_tree_builder(&members)?;
 
     // Create a wallet config
     let mut config = WalletConfig {
@@ -212,6 +250,11 @@ pub(crate) async fn v1_wallet_create_handler(
         return Err(eyre!("Invalid configuration").into());
     }
 
+    // Check that the contract owners are contracts on-chain.
+    if !verify_contract_owners(state.client.clone(), new_wallet_address, &contracts).await? {
+        return Err(AppError::BadRequest);
+    }
+
     // -------------------------------------------------------------------------
     // Return
     // -------------------------------------------------------------------------
@@ -273,13 +316,10 @@ pub(crate) async fn v1_wallet_create_handler(
         .client
         .user()
         .create_many(
-            owners
+            owners_addresses
                 .iter()
                 .map(|owner| {
-                    lightdotso_prisma::user::create_unchecked(
-                        to_checksum(&owner.address.parse::<H160>().unwrap(), None),
-                        vec![],
-                    )
+                    lightdotso_prisma::user::create_unchecked(to_checksum(owner, None), vec![])
                 })
                 .collect(),
         )
@@ -321,29 +361,32 @@ pub(crate) async fn v1_wallet_create_handler(
             let owner_data = client
                 .owner()
                 .create_many(
-                    owners
+                    owner_entries
                         .iter()
-                        .enumerate()
-                        .map(|(index, owner)| {
+                        .map(|entry| {
                             lightdotso_prisma::owner::create_unchecked(
-                                to_checksum(&owner.address.parse::<H160>().unwrap(), None),
-                                owner.weight.into(),
-                                index as i32,
+                                entry.checksum_address(),
+                                entry.weight.into(),
+                                entry.index,
                                 configuration_data.clone().id,
-                                vec![lightdotso_prisma::owner::user_id::set(Some(
-                                    user_data
-                                        .iter()
-                                        .find(|user| {
-                                            user.address ==
-                                                to_checksum(
-                                                    &owner.address.parse::<H160>().unwrap(),
-                                                    None,
-                                                )
-                                        })
-                                        .unwrap()
-                                        .id
-                                        .clone(),
-                                ))],
+                                vec![
+                                    lightdotso_prisma::owner::signer_type::set(entry.signer_type),
+                                    lightdotso_prisma::owner::leaf_type::set(entry.leaf_type),
+                                    lightdotso_prisma::owner::threshold::set(
+                                        entry.threshold.map(Into::into),
+                                    ),
+                                    lightdotso_prisma::owner::subdigest::set(entry.subdigest_hex()),
+                                    lightdotso_prisma::owner::parent_index::set(entry.parent_index),
+                                    lightdotso_prisma::owner::user_id::set(
+                                        user_data
+                                            .iter()
+                                            .find(|user| {
+                                                entry.leaf_type == OwnerLeafType::Address &&
+                                                    user.address == entry.checksum_address()
+                                            })
+                                            .map(|user| user.id.clone()),
+                                    ),
+                                ],
                             )
                         })
                         .collect(),
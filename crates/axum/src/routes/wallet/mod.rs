@@ -16,8 +16,10 @@ pub(crate) mod create;
 pub(crate) mod error;
 pub(crate) mod get;
 pub(crate) mod list;
+pub(crate) mod signature;
 pub(crate) mod types;
 pub(crate) mod update;
+pub(crate) mod version;
 
 use crate::state::AppState;
 use autometrics::autometrics;
@@ -32,7 +34,9 @@ pub(crate) use list::{
     __path_v1_wallet_list_count_handler, __path_v1_wallet_list_handler,
     v1_wallet_list_count_handler, v1_wallet_list_handler,
 };
+pub(crate) use signature::{__path_v1_wallet_signature_handler, v1_wallet_signature_handler};
 pub(crate) use update::{__path_v1_wallet_update_handler, v1_wallet_update_handler};
+pub(crate) use version::{__path_v1_wallet_version_handler, v1_wallet_version_handler};
 
 // -----------------------------------------------------------------------------
 // Router
@@ -44,6 +48,8 @@ pub(crate) fn router() -> Router<AppState> {
         .route("/wallet/get", get(v1_wallet_get_handler))
         .route("/wallet/list", get(v1_wallet_list_handler))
         .route("/wallet/list/count", get(v1_wallet_list_count_handler))
+        .route("/wallet/signature", get(v1_wallet_signature_handler))
+        .route("/wallet/version", get(v1_wallet_version_handler))
         .route("/wallet/create", post(v1_wallet_create_handler))
         .route("/wallet/update", put(v1_wallet_update_handler))
 }
@@ -0,0 +1,181 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    error::RouteError,
+    result::AppJsonResult,
+    routes::{
+        user_operation::signature::configuration_to_wallet_config, wallet::error::WalletError,
+    },
+    state::AppState,
+};
+use autometrics::autometrics;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use ethers::{
+    providers::Middleware,
+    types::{Address, H256},
+};
+use ethers_main::utils::{hex, to_checksum};
+use lightdotso_common::traits::HexToBytes;
+use lightdotso_contracts::provider::get_provider;
+use lightdotso_prisma::{configuration, wallet};
+use lightdotso_sequence::{
+    erc6492::{is_erc_6492_signature, wrap_light_wallet_signature},
+    hash::get_address_with_factory_from_chain,
+};
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+// -----------------------------------------------------------------------------
+// Query
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Deserialize, Default, IntoParams)]
+#[serde(rename_all = "snake_case")]
+#[into_params(parameter_in = Query)]
+pub struct SignatureQuery {
+    /// The address of the wallet.
+    pub address: String,
+    /// The chain id of the wallet.
+    pub chain_id: i64,
+    /// The hex signature of the wallet.
+    pub signature: String,
+}
+
+// -----------------------------------------------------------------------------
+// Handler
+// -----------------------------------------------------------------------------
+
+/// Get the signature of a wallet verifiable before the deployment
+#[utoipa::path(
+        get,
+        path = "/wallet/signature",
+        params(
+            SignatureQuery
+        ),
+        responses(
+            (status = 200, description = "Wallet signature returned successfully", body = String),
+            (status = 400, description = "Invalid configuration", body = WalletError),
+            (status = 404, description = "Wallet not found", body = WalletError),
+        )
+    )]
+#[autometrics]
+pub(crate) async fn v1_wallet_signature_handler(
+    signature_query: Query<SignatureQuery>,
+    State(state): State<AppState>,
+) -> AppJsonResult<String> {
+    // -------------------------------------------------------------------------
+    // Parse
+    // -------------------------------------------------------------------------
+
+    // Get the signature query.
+    let Query(query) = signature_query;
+
+    let parsed_query_address: Address = query.address.parse()?;
+    let checksum_address = to_checksum(&parsed_query_address, None);
+    let signature = query.signature.hex_to_bytes()?;
+
+    // -------------------------------------------------------------------------
+    // Signature
+    // -------------------------------------------------------------------------
+
+    // If the signature is already wrapped, return it as is.
+    if is_erc_6492_signature(&signature) {
+        return Ok(Json::from(query.signature));
+    }
+
+    // Get the code of the wallet.
+    let provider = get_provider(query.chain_id as u64).await?;
+    let code = provider.get_code(parsed_query_address, None).await?;
+
+    // If the wallet is already deployed, the signature is verifiable w/ EIP-1271 as is.
+    if !code.is_empty() {
+        return Ok(Json::from(query.signature));
+    }
+
+    // -------------------------------------------------------------------------
+    // DB
+    // -------------------------------------------------------------------------
+
+    // Get the wallet from the database.
+    let wallet = state
+        .client
+        .wallet()
+        .find_unique(wallet::address::equals(checksum_address.clone()))
+        .exec()
+        .await?;
+
+    // If the wallet is not found, return a 404.
+    let wallet = wallet
+        .ok_or(RouteError::WalletError(WalletError::NotFound("Wallet not found".to_string())))?;
+
+    // Get the initial configuration of the wallet, which the factory deploys the wallet with.
+    let configuration = state
+        .client
+        .configuration()
+        .find_first(vec![
+            configuration::address::equals(checksum_address),
+            configuration::checkpoint::equals(0),
+        ])
+        .with(configuration::owners::fetch(vec![]))
+        .exec()
+        .await?;
+
+    // If the configuration is not found, return a 404.
+    let configuration = configuration.ok_or(RouteError::WalletError(WalletError::NotFound(
+        "Configuration not found".to_string(),
+    )))?;
+
+    // -------------------------------------------------------------------------
+    // Validate
+    // -------------------------------------------------------------------------
+
+    // Check that the image hash is the one of the owners of the initial configuration.
+    let image_hash: H256 = configuration.image_hash.parse()?;
+    let mut config = configuration_to_wallet_config(&configuration)?;
+    let config_image_hash: H256 = config.regenerate_image_hash([0; 32])?.into();
+    if config_image_hash != image_hash {
+        return Err(RouteError::WalletError(WalletError::InvalidConfiguration(format!(
+            "Image hash {:?} doesn't match the initial configuration {:?}",
+            image_hash, config_image_hash
+        )))
+        .into());
+    }
+
+    // Check that the factory deploys the wallet w/ the image hash and the salt.
+    let factory: Address = wallet.factory_address.parse()?;
+    let salt: H256 = wallet.salt.parse()?;
+    let (address, _) =
+        get_address_with_factory_from_chain(query.chain_id as u64, factory, image_hash, salt)
+            .await?;
+    if address != parsed_query_address {
+        return Err(RouteError::WalletError(WalletError::InvalidConfiguration(format!(
+            "Wallet {:?} isn't deployed from the initial configuration, got {:?}",
+            parsed_query_address, address
+        )))
+        .into());
+    }
+
+    // -------------------------------------------------------------------------
+    // Return
+    // -------------------------------------------------------------------------
+
+    // Wrap the signature w/ the deployment of the wallet from the factory.
+    let wrapped = wrap_light_wallet_signature(factory, image_hash, salt, &signature);
+
+    Ok(Json::from(format!("0x{}", hex::encode(wrapped))))
+}
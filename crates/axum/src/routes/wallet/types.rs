@@ -12,7 +12,12 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use ethers::types::Address;
+use ethers_main::utils::to_checksum;
 use lightdotso_prisma::wallet;
+use lightdotso_sequence::version::{
+    WalletVersion as SequenceWalletVersion, WalletVersionDetection,
+};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
@@ -32,6 +37,24 @@ pub(crate) struct Wallet {
     pub name: String,
     /// The salt of the wallet.
     pub salt: String,
+    /// The version of the wallet, named after the factory which deployed it.
+    pub version: Option<String>,
+}
+
+/// Wallet version detection.
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+#[serde(rename_all = "snake_case")]
+pub(crate) struct WalletVersion {
+    /// The address of the wallet.
+    pub address: String,
+    /// The chain id the version is detected on.
+    pub chain_id: i64,
+    /// The version of the wallet, if the factory or the implementation is known.
+    pub version: Option<String>,
+    /// The address of the implementation of the deployed wallet.
+    pub implementation_address: Option<String>,
+    /// Whether the wallet is deployed on the chain.
+    pub is_deployed: bool,
 }
 
 // -----------------------------------------------------------------------------
@@ -45,7 +68,28 @@ impl From<wallet::Data> for Wallet {
             address: wallet.address.to_string(),
             factory_address: wallet.factory_address.to_string(),
             name: wallet.name.to_string(),
+            version: wallet
+                .factory_address
+                .parse::<Address>()
+                .ok()
+                .and_then(SequenceWalletVersion::from_factory_address)
+                .map(|version| version.to_string()),
             salt: wallet.salt.to_string(),
         }
     }
 }
+
+/// Implement From<(String, i64, WalletVersionDetection)> for WalletVersion.
+impl From<(String, i64, WalletVersionDetection)> for WalletVersion {
+    fn from((address, chain_id, detection): (String, i64, WalletVersionDetection)) -> Self {
+        Self {
+            address,
+            chain_id,
+            version: detection.version.map(|version| version.to_string()),
+            implementation_address: detection
+                .implementation
+                .map(|implementation| to_checksum(&implementation, None)),
+            is_deployed: detection.is_deployed,
+        }
+    }
+}
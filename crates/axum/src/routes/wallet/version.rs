@@ -0,0 +1,112 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use super::types::WalletVersion;
+use crate::{
+    error::RouteError, result::AppJsonResult, routes::wallet::error::WalletError, state::AppState,
+};
+use autometrics::autometrics;
+use axum::{
+    extract::{Query, State},
+    Json,
+};
+use ethers::types::Address;
+use ethers_main::utils::to_checksum;
+use lightdotso_prisma::wallet;
+use lightdotso_sequence::version::detect_wallet_version;
+use serde::Deserialize;
+use utoipa::IntoParams;
+
+// -----------------------------------------------------------------------------
+// Query
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Deserialize, Default, IntoParams)]
+#[serde(rename_all = "snake_case")]
+#[into_params(parameter_in = Query)]
+pub struct VersionQuery {
+    /// The address of the wallet.
+    pub address: String,
+    /// The chain id of the wallet.
+    pub chain_id: i64,
+}
+
+// -----------------------------------------------------------------------------
+// Handler
+// -----------------------------------------------------------------------------
+
+/// Detect the version of a wallet
+#[utoipa::path(
+        get,
+        path = "/wallet/version",
+        params(
+            VersionQuery
+        ),
+        responses(
+            (status = 200, description = "Wallet version returned successfully", body = WalletVersion),
+            (status = 400, description = "Wallet version bad request", body = WalletError),
+            (status = 500, description = "Wallet version internal error", body = WalletError),
+        )
+    )]
+#[autometrics]
+pub(crate) async fn v1_wallet_version_handler(
+    version_query: Query<VersionQuery>,
+    State(state): State<AppState>,
+) -> AppJsonResult<WalletVersion> {
+    // -------------------------------------------------------------------------
+    // Parse
+    // -------------------------------------------------------------------------
+
+    // Get the version query.
+    let Query(query) = version_query;
+
+    let parsed_query_address: Address = query.address.parse()?;
+    let checksum_address = to_checksum(&parsed_query_address, None);
+
+    // Reject the negative chain ids, which would wrap around as the chain id of the rpc.
+    let chain_id = u64::try_from(query.chain_id).map_err(|_| {
+        RouteError::WalletError(WalletError::BadRequest(format!(
+            "Invalid chain id: {}",
+            query.chain_id
+        )))
+    })?;
+
+    // -------------------------------------------------------------------------
+    // DB
+    // -------------------------------------------------------------------------
+
+    // Get the wallet from the database, for the factory of the undeployed wallet.
+    let wallet = state
+        .client
+        .wallet()
+        .find_unique(wallet::address::equals(checksum_address.clone()))
+        .exec()
+        .await?;
+
+    // Get the factory of the wallet, if the wallet is indexed.
+    let factory = wallet.and_then(|wallet| wallet.factory_address.parse::<Address>().ok());
+
+    // -------------------------------------------------------------------------
+    // Version
+    // -------------------------------------------------------------------------
+
+    // Detect the version of the wallet on the chain.
+    let detection = detect_wallet_version(chain_id, parsed_query_address, factory).await?;
+
+    // -------------------------------------------------------------------------
+    // Return
+    // -------------------------------------------------------------------------
+
+    Ok(Json::from(WalletVersion::from((checksum_address, query.chain_id, detection))))
+}
@@ -21,6 +21,7 @@ use ethers::{
     prelude::Provider,
     providers::{Http, Middleware},
     types::U256,
+    utils::format_ether,
 };
 use eyre::{eyre, Result};
 use lightdotso_client::crypto::get_native_token_price;
@@ -101,21 +102,22 @@ impl Billing {
         info!("gas_price: {}", gas_price);
 
         // Calculate the gas limit
-        let max_gas_limit =
-            msg.pre_verification_gas + msg.verification_gas_limit + msg.call_gas_limit;
+        let max_gas_limit = U256::from(msg.pre_verification_gas) +
+            U256::from(msg.verification_gas_limit) +
+            U256::from(msg.call_gas_limit);
 
         // Log the gas limit
         info!("max_gas_limit: {}", max_gas_limit);
 
         // Multiply the gas price by the gas limit, denominated in ether 1e-18
-        let max_gas_consumed = gas_price * U256::from(max_gas_limit);
+        let max_gas_consumed =
+            gas_price.checked_mul(max_gas_limit).ok_or(eyre!("Gas consumed overflow"))?;
 
         // Log the gas consumed
         info!("max_gas_consumed: {}", max_gas_consumed);
 
         // Calculate the total cost
-        let total_cost_usd =
-            (max_gas_consumed.as_u64() as f64) / 10_u64.pow(18) as f64 * currency_price_usd;
+        let total_cost_usd = wei_to_usd(max_gas_consumed, currency_price_usd)?;
 
         // Log the total cost
         info!("total_cost_usd: {}", total_cost_usd);
@@ -218,6 +220,12 @@ impl Billing {
     }
 }
 
+/// Convert the amount in wei to USD w/ the price of the native currency.
+/// The amount is formatted in ether first, so that it doesn't truncate above `u64::MAX` wei.
+fn wei_to_usd(amount: U256, currency_price_usd: f64) -> Result<f64> {
+    Ok(format_ether(amount).parse::<f64>()? * currency_price_usd)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -232,9 +240,16 @@ mod tests {
 
         println!("max_gas_consumed: {}", max_gas_consumed);
 
-        let total_cost_usd =
-            (max_gas_consumed.as_u64() as f64) / 10_u64.pow(18) as f64 * currency_price_usd;
+        let total_cost_usd = wei_to_usd(max_gas_consumed, currency_price_usd).unwrap();
 
         println!("total_cost_usd: {}", total_cost_usd);
     }
+
+    #[test]
+    fn test_wei_to_usd_above_u64() {
+        // 100 ETH, above `u64::MAX` wei
+        let total_cost_usd = wei_to_usd(U256::exp10(20), 3000_f64).unwrap();
+
+        assert_eq!(total_cost_usd, 300_000_f64);
+    }
 }
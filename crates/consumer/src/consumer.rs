@@ -22,9 +22,9 @@ use crate::{
         covalent::covalent_consumer, error_transaction::error_transaction_consumer,
         interpretation::interpretation_consumer, node::node_consumer,
         notification::notification_consumer, paymaster_operation::paymaster_operation_consumer,
-        portfolio::portfolio_consumer, routescan::routescan_consumer,
-        transaction::transaction_consumer, unknown::unknown_consumer,
-        user_operation::user_operation_consumer,
+        portfolio::portfolio_consumer, reconciliation::reconciliation_consumer,
+        routescan::routescan_consumer, transaction::transaction_consumer,
+        unknown::unknown_consumer, user_operation::user_operation_consumer,
     },
 };
 use clap::Parser;
@@ -36,8 +36,9 @@ use lightdotso_kafka::{
     get_consumer, get_producer,
     namespace::{
         ACTIVITY, BILLING_OPERATION, COVALENT, ERROR_TRANSACTION, INTERPRETATION, NODE,
-        NOTIFICATION, PAYMASTER_OPERATION, PORTFOLIO, RETRY_TRANSACTION, RETRY_TRANSACTION_0,
-        RETRY_TRANSACTION_1, RETRY_TRANSACTION_2, ROUTESCAN, TRANSACTION, USER_OPERATION,
+        NOTIFICATION, PAYMASTER_OPERATION, PORTFOLIO, RECONCILIATION, RETRY_TRANSACTION,
+        RETRY_TRANSACTION_0, RETRY_TRANSACTION_1, RETRY_TRANSACTION_2, ROUTESCAN, TRANSACTION,
+        USER_OPERATION,
     },
 };
 use lightdotso_node::config::NodeArgs;
@@ -115,6 +116,12 @@ impl Consumer {
         // Create the node
         let node = node_args.create().await?;
 
+        // Run the in-process bundler of the node
+        tokio::spawn({
+            let node = node.clone();
+            async move { node.run().await }
+        });
+
         // Create the notifier
         let notifier = notifier_args.create().await?;
 
@@ -208,6 +215,15 @@ impl Consumer {
                             }
                             let _ = self.consumer.commit_message(&m, CommitMode::Async);
                         }
+                        topic if topic == RECONCILIATION.to_string() => {
+                            let res = reconciliation_consumer(&m, db.clone()).await;
+                            // If the consumer failed
+                            if let Err(e) = res {
+                                // Log the error
+                                warn!("Reconciliation consumer failed with error: {:?}", e);
+                            }
+                            let _ = self.consumer.commit_message(&m, CommitMode::Async);
+                        }
                         topic if topic == ROUTESCAN.to_string() => {
                             let res = routescan_consumer(&m, db.clone()).await;
                             // If the consumer failed
@@ -47,6 +47,11 @@ impl FromStrExt for ActivityEntity {
             "SIMULATION" => Ok(Self::Simulation),
             "TRANSACTION" => Ok(Self::Transaction),
             "USER_OPERATION" => Ok(Self::UserOperation),
+            "CONFIGURATION_OPERATION" => Ok(Self::ConfigurationOperation),
+            "GUARDIAN" => Ok(Self::Guardian),
+            "RECOVERY_OPERATION" => Ok(Self::RecoveryOperation),
+            "CONFIGURATION_OPERATION_SIGNATURE" => Ok(Self::ConfigurationOperationSignature),
+            "RECOVERY_OPERATION_SIGNATURE" => Ok(Self::RecoveryOperationSignature),
             _ => Err(eyre!("no match for input string")),
         }
     }
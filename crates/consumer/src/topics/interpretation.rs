@@ -15,6 +15,7 @@
 #![allow(clippy::unwrap_used)]
 
 use clap::Parser;
+use ethers::types::U256;
 use eyre::Result;
 use lightdotso_db::models::{
     interpretation::upsert_interpretation_with_actions, transaction::get_transaction_with_logs,
@@ -74,7 +75,7 @@ pub async fn interpretation_consumer(
                     .transaction
                     .clone()
                     .value
-                    .and_then(|value| value.parse::<u64>().ok()),
+                    .and_then(|value| U256::from_dec_str(&value).ok()),
                 traces: vec![],
                 logs: transaction_with_logs.logs,
             };
@@ -21,6 +21,7 @@ pub mod node;
 pub mod notification;
 pub mod paymaster_operation;
 pub mod portfolio;
+pub mod reconciliation;
 pub mod routescan;
 pub mod transaction;
 pub mod unknown;
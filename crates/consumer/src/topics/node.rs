@@ -18,7 +18,7 @@ use ethers::{types::Address, utils::to_checksum};
 use eyre::Result;
 use lightdotso_client::get_user_operation_signature;
 use lightdotso_common::traits::VecU8ToHex;
-use lightdotso_contracts::{constants::ENTRYPOINT_V060_ADDRESS, light_wallet::get_light_wallet};
+use lightdotso_contracts::light_wallet::get_light_wallet;
 use lightdotso_db::models::user_operation::get_user_operation_with_chain_id;
 use lightdotso_kafka::types::node::NodeMessage;
 use lightdotso_node::node::Node;
@@ -46,7 +46,8 @@ pub async fn node_consumer(
         let hash = payload.hash;
 
         // Get the unique user operation from the db
-        let (mut uop, chain_id) = get_user_operation_with_chain_id(db.clone(), hash).await?;
+        let (mut uop, chain_id, entry_point) =
+            get_user_operation_with_chain_id(db.clone(), hash).await?;
 
         // Inner function to get the configuration id
         async fn get_configuration_id(
@@ -90,28 +91,17 @@ pub async fn node_consumer(
         uop.signature = signature.into();
 
         // Simulate the user operation
-        let res_catch = node
-            .simulate_user_operation_with_backon(chain_id, *ENTRYPOINT_V060_ADDRESS, &uop)
-            .await;
+        let res_catch = node.simulate_user_operation_with_backon(chain_id, entry_point, &uop).await;
 
         // Log the response
         info!("res_catch: {:?}", res_catch);
 
-        // Simulate the user operation with the tracer
-        let res_catch = node
-            .simulate_user_operation_with_tracer_with_backon(
-                chain_id,
-                *ENTRYPOINT_V060_ADDRESS,
-                &uop,
-            )
-            .await;
-
-        // Log the response
-        info!("res_catch: {:?}", res_catch);
+        // Validate the user operation once w/ the tracer and the validation rules, and reject it
+        // on any failure before relaying it
+        node.validate_user_operation(chain_id, entry_point, &uop).await?;
 
         // Attempt to submit the user operation to the node
-        let res =
-            node.send_user_operation_with_backon(chain_id, *ENTRYPOINT_V060_ADDRESS, &uop).await?;
+        let res = node.send_user_operation_with_backon(chain_id, entry_point, &uop).await?;
 
         // Log the response
         info!("res: {:?}", res);
@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use eyre::Result;
+use eyre::{eyre, Result};
 use lightdotso_contracts::paymaster::{decode_paymaster_and_data, get_paymaster};
 use lightdotso_db::models::paymaster_operation::create_paymaster_operation;
 use lightdotso_kafka::{
@@ -71,9 +71,12 @@ pub async fn paymaster_operation_consumer(
                     sender: payload.sender,
                     chain_id: payload.chain_id,
                     paymaster_operation_id: paymaster_operation.id,
-                    pre_verification_gas: payload.pre_verification_gas.as_u64(),
-                    verification_gas_limit: payload.verification_gas_limit.as_u64(),
-                    call_gas_limit: payload.call_gas_limit.as_u64(),
+                    pre_verification_gas: u64::try_from(payload.pre_verification_gas)
+                        .map_err(|err| eyre!("Invalid pre verification gas: {}", err))?,
+                    verification_gas_limit: u64::try_from(payload.verification_gas_limit)
+                        .map_err(|err| eyre!("Invalid verification gas limit: {}", err))?,
+                    call_gas_limit: u64::try_from(payload.call_gas_limit)
+                        .map_err(|err| eyre!("Invalid call gas limit: {}", err))?,
                 },
             )
             .await?;
@@ -0,0 +1,61 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use eyre::Result;
+use lightdotso_db::models::configuration::{
+    reconcile_wallet_configurations, upsert_configuration_reconciliations, ReconciliationStatus,
+};
+use lightdotso_kafka::types::reconciliation::ReconciliationMessage;
+use lightdotso_prisma::PrismaClient;
+use lightdotso_tracing::tracing::info;
+use rdkafka::{message::BorrowedMessage, Message};
+use std::sync::Arc;
+
+pub async fn reconciliation_consumer(
+    msg: &BorrowedMessage<'_>,
+    db: Arc<PrismaClient>,
+) -> Result<()> {
+    // Convert the payload to a string
+    let payload_opt = msg.payload_view::<str>();
+    info!("payload_opt: {:?}", payload_opt);
+
+    // If the payload is valid
+    if let Some(Ok(payload)) = payload_opt {
+        // Parse the payload into a JSON object, `ReconciliationMessage`
+        let payload: ReconciliationMessage = serde_json::from_slice(payload.as_bytes())?;
+
+        // Reconcile the on-chain image hashes of the wallet
+        let reconciliations = reconcile_wallet_configurations(db.clone(), payload.address).await?;
+
+        // Log the chains that are not synced w/ the latest configuration
+        for reconciliation in reconciliations
+            .iter()
+            .filter(|reconciliation| reconciliation.status != ReconciliationStatus::Synced)
+        {
+            info!(
+                "Wallet {:?} on chain {} is {:?} at checkpoint {:?} of {}",
+                payload.address,
+                reconciliation.chain_id,
+                reconciliation.status,
+                reconciliation.onchain_configuration.as_ref().map(|c| c.checkpoint),
+                reconciliation.latest_configuration.checkpoint
+            );
+        }
+
+        // Persist the reconciliations of the wallet
+        upsert_configuration_reconciliations(db, payload.address, &reconciliations).await?;
+    }
+
+    Ok(())
+}
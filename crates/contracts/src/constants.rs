@@ -94,6 +94,14 @@ lazy_static! {
       "0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789".parse().unwrap();
 }
 
+// The entrypoint addresses
+lazy_static! {
+    #[derive(Debug)]
+    pub static ref ENTRYPOINT_V070_ADDRESS: Address =
+      // v0.7.0
+      "0x0000000071727De22E5E9d8BAf0edAc6f37da032".parse().unwrap();
+}
+
 // The alchemy v0.6.0 gas manager address
 // From: https://docs.alchemy.com/reference/gas-manager-deployment-addresses
 lazy_static! {
@@ -123,5 +131,9 @@ mod tests {
             to_checksum(&ENTRYPOINT_V060_ADDRESS, None),
             "0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789".to_string(),
         );
+        assert_eq!(
+            to_checksum(&ENTRYPOINT_V070_ADDRESS, None),
+            "0x0000000071727De22E5E9d8BAf0edAc6f37da032".to_string(),
+        );
     }
 }
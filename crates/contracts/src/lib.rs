@@ -14,10 +14,13 @@
 
 pub mod constants;
 pub mod entrypoint;
+pub mod entrypoint_v070;
 pub mod erc1271;
 pub mod light_wallet;
+pub mod light_wallet_factory;
 pub mod paymaster;
 pub mod provider;
+pub mod token_paymaster;
 pub mod tracer;
 pub mod types;
 pub mod user_operation;
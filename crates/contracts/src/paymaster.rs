@@ -13,15 +13,20 @@
 // limitations under the License.
 
 use ethers::{
+    abi::{encode, Token},
     contract::abigen,
     providers::{Http, Provider},
-    types::Address,
+    types::{Address, U256},
+    utils::keccak256,
 };
 use eyre::{eyre, Context, Result};
 use prisma_client_rust::chrono::NaiveDateTime;
 use std::convert::TryInto;
 
-use crate::{constants::ALCHEMY_V060_GAS_MANAGER_ADDRESS, provider::get_provider};
+use crate::{
+    constants::ALCHEMY_V060_GAS_MANAGER_ADDRESS, provider::get_provider,
+    types::UserOperationRequest,
+};
 
 abigen!(LightPaymaster, "abi/LightPaymaster.json",);
 
@@ -87,11 +92,109 @@ pub fn decode_paymaster_and_data(msg: Vec<u8>) -> Result<(Address, u64, u64, Vec
     Ok((verifying_paymaster_address, valid_until, valid_after, signature))
 }
 
+/// Construct the paymaster and data from the verifying paymaster address, the validity window and
+/// the signature of the verifying signer. The inverse of `decode_paymaster_and_data`.
+pub fn encode_paymaster_and_data(
+    verifying_paymaster_address: Address,
+    valid_until: u64,
+    valid_after: u64,
+    signature: &[u8],
+) -> Vec<u8> {
+    [
+        verifying_paymaster_address.as_bytes().to_vec(),
+        encode(&[Token::Uint(valid_until.into()), Token::Uint(valid_after.into())]),
+        signature.to_vec(),
+    ]
+    .concat()
+}
+
+/// The length of the paymaster and data of the `LightPaymaster`, w/ the 65 bytes signature.
+pub const PAYMASTER_AND_DATA_LENGTH: usize = 20 + 64 + 65;
+
+/// Pack the user operation up to the paymaster and data, as `pack` of the `VerifyingPaymaster`
+/// copies the abi encoded user operation from the calldata.
+/// The offsets in the head depend on the length of the paymaster and data, so it has to be the
+/// length of the signed paymaster and data.
+fn pack_user_operation(
+    user_operation: &UserOperationRequest,
+    paymaster_and_data_length: usize,
+) -> Result<Vec<u8>> {
+    // The gas fields are signed over, so they must be finalized before the hash is computed.
+    let gas = |value: Option<U256>, name: &str| {
+        value.ok_or_else(|| eyre!("The user operation is missing the {}", name))
+    };
+
+    let encoded = encode(&[Token::Tuple(vec![
+        Token::Address(user_operation.sender),
+        Token::Uint(user_operation.nonce),
+        Token::Bytes(user_operation.init_code.to_vec()),
+        Token::Bytes(user_operation.call_data.to_vec()),
+        Token::Uint(gas(user_operation.call_gas_limit, "call_gas_limit")?),
+        Token::Uint(gas(user_operation.verification_gas_limit, "verification_gas_limit")?),
+        Token::Uint(gas(user_operation.pre_verification_gas, "pre_verification_gas")?),
+        Token::Uint(gas(user_operation.max_fee_per_gas, "max_fee_per_gas")?),
+        Token::Uint(gas(user_operation.max_priority_fee_per_gas, "max_priority_fee_per_gas")?),
+        Token::Bytes(vec![0; paymaster_and_data_length]),
+        Token::Bytes(user_operation.signature.to_vec()),
+    ])]);
+
+    // Skip the offset of the tuple, and copy up to the length of the paymaster and data.
+    let tuple = &encoded[32..];
+    let paymaster_and_data_offset = U256::from_big_endian(&tuple[9 * 32..10 * 32]).as_usize();
+
+    Ok(tuple[..paymaster_and_data_offset].to_vec())
+}
+
+/// Get the hash the verifying signer of the paymaster signs for the user operation.
+/// Mirrors `getHash` of the `LightPaymaster` so that the hash can be computed w/o a rpc call.
+/// From: https://github.com/eth-infinitism/account-abstraction/blob/releases/v0.6/contracts/samples/VerifyingPaymaster.sol
+/// License: GPL-3.0
+pub fn get_paymaster_hash(
+    user_operation: &UserOperationRequest,
+    chain_id: u64,
+    verifying_paymaster_address: Address,
+    sender_nonce: U256,
+    valid_until: u64,
+    valid_after: u64,
+) -> Result<[u8; 32]> {
+    Ok(keccak256(encode(&[
+        Token::Bytes(pack_user_operation(user_operation, PAYMASTER_AND_DATA_LENGTH)?),
+        Token::Uint(chain_id.into()),
+        Token::Address(verifying_paymaster_address),
+        Token::Uint(sender_nonce),
+        Token::Uint(valid_until.into()),
+        Token::Uint(valid_after.into()),
+    ])))
+}
+
+/// Construct the paymaster and data of the token paymaster, which charges the gas in the token at
+/// the exchange rate, denominated in the token units per 1 ether of the native gas.
+pub fn encode_token_paymaster_and_data(
+    token_paymaster_address: Address,
+    valid_until: u64,
+    valid_after: u64,
+    token: Address,
+    exchange_rate: U256,
+    signature: &[u8],
+) -> Vec<u8> {
+    [
+        token_paymaster_address.as_bytes().to_vec(),
+        encode(&[
+            Token::Uint(valid_until.into()),
+            Token::Uint(valid_after.into()),
+            Token::Address(token),
+            Token::Uint(exchange_rate),
+        ]),
+        signature.to_vec(),
+    ]
+    .concat()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::constants::LIGHT_PAYMASTER_ADDRESSES;
-    use ethers::utils::hex;
+    use ethers::{types::Signature, utils::hex};
     use eyre::Result;
 
     #[ignore]
@@ -134,6 +237,132 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_encode_paymaster_and_data() -> Result<()> {
+        let verifying_paymaster_address = LIGHT_PAYMASTER_ADDRESSES[2];
+        let signature = vec![1u8; 65];
+
+        // Encode the paymaster and data.
+        let msg =
+            encode_paymaster_and_data(verifying_paymaster_address, 0xdeadbeef, 0x1234, &signature);
+        assert_eq!(msg.len(), 20 + 64 + 65);
+
+        // Decode the paymaster and data back.
+        let (decoded_address, valid_until, valid_after, decoded_signature) =
+            decode_paymaster_and_data(msg)?;
+
+        // Assert that the result matches the encoded value
+        assert_eq!(decoded_address, verifying_paymaster_address);
+        assert_eq!(valid_until, 0xdeadbeef);
+        assert_eq!(valid_after, 0x1234);
+        assert_eq!(decoded_signature, signature);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_paymaster_hash() -> Result<()> {
+        // The user operation of the `testFork_paymaster_getHash` of the contracts, on mainnet.
+        let mut user_operation = UserOperationRequest {
+            sender: Address::zero(),
+            nonce: 0.into(),
+            init_code: Default::default(),
+            call_data: Default::default(),
+            call_gas_limit: Some(0.into()),
+            verification_gas_limit: Some(0.into()),
+            pre_verification_gas: Some(0.into()),
+            max_fee_per_gas: Some(0.into()),
+            max_priority_fee_per_gas: Some(0.into()),
+            paymaster_and_data: None,
+            signature: Default::default(),
+        };
+        let paymaster = LIGHT_PAYMASTER_ADDRESSES[0];
+
+        let hash = get_paymaster_hash(&user_operation, 1, paymaster, 0.into(), 0, 0)?;
+        assert_eq!(
+            hex::encode(hash),
+            "b2072a8f48b9b898d026920dc502740e4786e67eca4ab132ff4336a78f7e73f8"
+        );
+
+        // The hash is bound to the chain, the sender nonce and the validity window.
+        assert_ne!(hash, get_paymaster_hash(&user_operation, 10, paymaster, 0.into(), 0, 0)?);
+        assert_ne!(hash, get_paymaster_hash(&user_operation, 1, paymaster, 1.into(), 0, 0)?);
+        assert_ne!(hash, get_paymaster_hash(&user_operation, 1, paymaster, 0.into(), 1, 0)?);
+
+        // The paymaster and data itself is not signed over.
+        user_operation.paymaster_and_data = Some(vec![1u8; 20].into());
+        assert_eq!(hash, get_paymaster_hash(&user_operation, 1, paymaster, 0.into(), 0, 0)?);
+
+        // The gas fields must be set.
+        user_operation.call_gas_limit = None;
+        assert!(get_paymaster_hash(&user_operation, 1, paymaster, 0.into(), 0, 0).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_paymaster_hash_polygon() -> Result<()> {
+        // The user operation of the #1 polygonscan tx below, sponsored by the v2 paymaster.
+        let user_operation = UserOperationRequest {
+            sender: "0xe9586d5bb60179b9b364c19ce17b995cbe51ce52".parse()?,
+            nonce: 0.into(),
+            init_code: hex::decode("0000000000756d3e6464f5efe7e413a0af1c7474183815c882d5d90f13ae7d2866bf58606eb960783bc6d181f0f729f74fae246ea1cf89c00000000000000000000000000000000000000000000000000000018c0fab8bb8")?.into(),
+            call_data: hex::decode("b61d27f60000000000000000000000004fd9d0ee6d6564e80a9ee00c0163fc952d0a45ed0000000000000000000000000000000000000000000000000de0b6b3a764000000000000000000000000000000000000000000000000000000000000000000600000000000000000000000000000000000000000000000000000000000000000")?.into(),
+            call_gas_limit: Some(4514240.into()),
+            verification_gas_limit: Some(1854272.into()),
+            pre_verification_gas: Some(1854272.into()),
+            max_fee_per_gas: Some(56674171701_u64.into()),
+            max_priority_fee_per_gas: Some(48087546673_u64.into()),
+            paymaster_and_data: None,
+            signature: Default::default(),
+        };
+        let paymaster_and_data = hex::decode("000000000003193facb32d1c120719892b7ae977000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000656459c5a8db79acb7d63838be51e9fef633a7bb5cb024c5b00120f3288869d0e052e5395eba458e03a3107e25bab992a1154bf19cb6ca75be8fdcd48845840c2ece416f1b")?;
+        let (paymaster, valid_until, valid_after, signature) =
+            decode_paymaster_and_data(paymaster_and_data)?;
+
+        let hash = get_paymaster_hash(
+            &user_operation,
+            137,
+            paymaster,
+            0.into(),
+            valid_until,
+            valid_after,
+        )?;
+        assert_eq!(
+            hex::encode(hash),
+            "bcd6811f447a720580ed502c00e0c4bc127a38825b17f9db436e4c449497a69b"
+        );
+
+        // The signature of the hash recovers to the verifying signer of the v2 paymaster.
+        let signer = Signature::try_from(signature.as_slice())?.recover(hash.to_vec())?;
+        assert_eq!(signer, "0xEEdeadba8cAC470fDCe318892a07aBE26Aa4ab17".parse::<Address>()?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_encode_token_paymaster_and_data() -> Result<()> {
+        let token_paymaster_address = Address::from_low_u64_be(1);
+        let token = Address::from_low_u64_be(2);
+        let exchange_rate = U256::from(3_300_000_000_u64);
+        let signature = vec![1u8; 65];
+
+        // Encode the token paymaster and data.
+        let msg = encode_token_paymaster_and_data(
+            token_paymaster_address,
+            0xdeadbeef,
+            0x1234,
+            token,
+            exchange_rate,
+            &signature,
+        );
+        assert_eq!(msg.len(), 20 + 128 + 65);
+        assert_eq!(&msg[..20], token_paymaster_address.as_bytes());
+        assert_eq!(&msg[148..], signature.as_slice());
+
+        Ok(())
+    }
+
     // #1
     // https://polygonscan.com/tx/0x3e0b0fbe2036274e96157534a3ab82327113f11881a9a3d34c5dbabc5034d25b
     // Calldata:
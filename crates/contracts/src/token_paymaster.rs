@@ -0,0 +1,118 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use ethers::{
+    contract::abigen,
+    providers::{Http, Provider},
+    types::{Address, U256},
+};
+use eyre::{eyre, Result};
+
+use crate::{provider::get_provider, types::UserOperationRequest};
+
+abigen!(LightTokenPaymaster, "abi/LightTokenPaymaster.json",);
+
+/// The length of the paymaster and data of the token paymaster, w/ the 65 bytes signature.
+pub const TOKEN_PAYMASTER_AND_DATA_LENGTH: usize = 20 + 128 + 65;
+
+pub async fn get_token_paymaster(
+    chain_id: u64,
+    token_paymaster_address: Address,
+) -> Result<LightTokenPaymaster<Provider<Http>>> {
+    // Get the provider.
+    let provider = get_provider(chain_id).await?;
+
+    // Get the contract.
+    let contract = LightTokenPaymaster::new(token_paymaster_address, provider.into());
+
+    // Return the contract.
+    Ok(contract)
+}
+
+/// Get the hash the verifying signer of the token paymaster signs for the user operation, from
+/// `getHash` of the token paymaster, which binds the sender nonce, the token and the exchange rate
+/// charged.
+/// The hash covers the user operation up to the paymaster and data, so the paymaster and data must
+/// have the length of the signed one.
+pub async fn get_token_paymaster_hash(
+    token_paymaster: &LightTokenPaymaster<Provider<Http>>,
+    user_operation: &UserOperationRequest,
+    valid_until: u64,
+    valid_after: u64,
+    token: Address,
+    exchange_rate: U256,
+) -> Result<[u8; 32]> {
+    // The gas fields are signed over, so they must be finalized before the hash is computed.
+    let gas = |value: Option<U256>, name: &str| {
+        value.ok_or_else(|| eyre!("The user operation is missing the {}", name))
+    };
+
+    let paymaster_and_data = user_operation.paymaster_and_data.clone().unwrap_or_default();
+    if paymaster_and_data.len() != TOKEN_PAYMASTER_AND_DATA_LENGTH {
+        return Err(eyre!("Invalid token paymaster and data length: {}", paymaster_and_data.len()));
+    }
+
+    let hash = token_paymaster
+        .get_hash(
+            UserOperation {
+                sender: user_operation.sender,
+                nonce: user_operation.nonce,
+                init_code: user_operation.init_code.clone(),
+                call_data: user_operation.call_data.clone(),
+                call_gas_limit: gas(user_operation.call_gas_limit, "call_gas_limit")?,
+                verification_gas_limit: gas(
+                    user_operation.verification_gas_limit,
+                    "verification_gas_limit",
+                )?,
+                pre_verification_gas: gas(
+                    user_operation.pre_verification_gas,
+                    "pre_verification_gas",
+                )?,
+                max_fee_per_gas: gas(user_operation.max_fee_per_gas, "max_fee_per_gas")?,
+                max_priority_fee_per_gas: gas(
+                    user_operation.max_priority_fee_per_gas,
+                    "max_priority_fee_per_gas",
+                )?,
+                paymaster_and_data,
+                signature: user_operation.signature.clone(),
+            },
+            valid_until,
+            valid_after,
+            token,
+            exchange_rate,
+        )
+        .call()
+        .await?;
+
+    Ok(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[ignore]
+    #[tokio::test]
+    async fn test_get_token_paymaster() {
+        let chain_id = 137;
+        // Get the address
+        let token_paymaster_address = Address::from_low_u64_be(1);
+
+        let res = get_token_paymaster(chain_id, token_paymaster_address).await;
+        assert!(res.is_ok());
+
+        // If you want to test the details of the resulting contract:
+        let contract = res.unwrap();
+        assert_eq!(contract.address(), token_paymaster_address);
+    }
+}
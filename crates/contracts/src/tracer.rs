@@ -26,9 +26,10 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use ethers::types::GethTrace;
+use ethers::types::{Address, Bytes, GethTrace, H256, U256};
 use eyre::format_err;
 use serde::Deserialize;
+use std::collections::HashMap;
 
 // From: https://github.com/silius-rs/silius/blob/a266eb22b46a86647556c4c8248663b3e25a5235/crates/contracts/src/executor_tracer.rs#L5-L8
 // License: Apache-2.0
@@ -223,3 +224,215 @@ pub const EXECUTOR_TRACER: &str = r#"
     },
   } 
 "#;
+
+// From: https://github.com/eth-infinitism/bundler/blob/a6c6f8b46bd4b6efd5e9e8ba4d7ab1feb4d8ff1c/packages/validation-manager/src/BundlerCollectorTracer.ts
+// License: GPL-3.0
+
+/// The storage access of a contract in a top level call of the validation.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct StorageAccessInfo {
+    /// The slots read, w/ the value before the first read
+    pub reads: HashMap<H256, String>,
+    /// The slots written, w/ the number of writes
+    pub writes: HashMap<H256, u64>,
+}
+
+/// The code size of an address accessed w/ `EXTCODE*` or `*CALL` opcodes.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct ContractSizeInfo {
+    #[serde(rename = "contractSize")]
+    pub contract_size: u64,
+    pub opcode: String,
+}
+
+/// A call entered in a top level call of the validation.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct CallEntry {
+    #[serde(rename = "type")]
+    pub typ: String,
+    pub from: Option<Address>,
+    pub to: Option<Address>,
+    pub method: Option<String>,
+    pub value: Option<U256>,
+}
+
+/// The info collected for a top level call of the entry point, e.g. `validateUserOp`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct TopLevelCallInfo {
+    #[serde(rename = "topLevelMethodSig")]
+    pub top_level_method_sig: Bytes,
+    #[serde(rename = "topLevelTargetAddress")]
+    pub top_level_target_address: Address,
+    pub opcodes: HashMap<String, u64>,
+    pub access: HashMap<Address, StorageAccessInfo>,
+    #[serde(rename = "contractSize")]
+    pub contract_size: HashMap<Address, ContractSizeInfo>,
+    #[serde(default)]
+    pub calls: Vec<CallEntry>,
+    #[serde(default)]
+    pub oog: bool,
+    pub output: Option<Bytes>,
+}
+
+/// The result of the `VALIDATION_TRACER`, used to check the ERC-7562 validation rules.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+pub struct ValidationTracerResult {
+    #[serde(rename = "callsFromEntryPoint")]
+    pub calls_from_entry_point: Vec<TopLevelCallInfo>,
+    pub keccak: Vec<Bytes>,
+    pub debug: Vec<String>,
+}
+
+impl TryFrom<GethTrace> for ValidationTracerResult {
+    type Error = eyre::Error;
+    fn try_from(val: GethTrace) -> Result<Self, Self::Error> {
+        match val {
+            GethTrace::Known(val) => Err(format_err!("Invalid geth trace: {val:?}")),
+            GethTrace::Unknown(val) => serde_json::from_value(val.clone())
+                .map_err(|error| format_err!("Failed to parse geth trace: {error}, {val:#}")),
+        }
+    }
+}
+
+/// The tracer collecting the opcodes, storage access, code sizes and calls of each top level call
+/// of the entry point during the validation, which stops at the `BeforeExecution` event.
+pub const VALIDATION_TRACER: &str = r#"
+{
+    callsFromEntryPoint: [],
+    currentLevel: null,
+    keccak: [],
+    debug: [],
+    lastOp: "",
+    topLevelCallCounter: 0,
+    stopCollecting: false,
+    _depth: 0,
+    _beforeExecutionTopics0:
+      "bb47ee3e183a558b1a2ff0874b079f3fc5478b7454eacf2bfc5af2ff5878f972",
+
+    _countSlot: function (list, key) {
+      list[key] = (list[key] || 0) + 1;
+    },
+
+    _isAllowedPrecompile: function (address) {
+      var addressInt = parseInt(toHex(address));
+      return addressInt > 0 && addressInt < 10;
+    },
+
+    fault: function fault(log, db) {
+      this.debug.push(
+        "fault depth=" + log.getDepth() + " gas=" + log.getGas() + " cost=" + log.getCost()
+      );
+    },
+    result: function result(ctx, db) {
+      return {
+        callsFromEntryPoint: this.callsFromEntryPoint,
+        keccak: this.keccak,
+        debug: this.debug,
+      };
+    },
+
+    enter: function enter(frame) {
+      this._depth++;
+      if (this.stopCollecting || this.currentLevel === null) return;
+      var value = frame.getValue();
+      this.currentLevel.calls.push({
+        type: frame.getType(),
+        from: toHex(frame.getFrom()),
+        to: toHex(frame.getTo()),
+        method: toHex(frame.getInput()).slice(0, 10),
+        value: value === undefined ? null : "0x" + value.toString(16),
+      });
+    },
+    exit: function exit(frame) {
+      this._depth--;
+      if (this.stopCollecting || this.currentLevel === null) return;
+      if (this._depth === 0) this.currentLevel.output = toHex(frame.getOutput());
+    },
+
+    step: function step(log, db) {
+      if (this.stopCollecting) return;
+      var opcode = log.op.toString();
+
+      if (log.getDepth() === 1) {
+        if (opcode === "CALL" || opcode === "STATICCALL") {
+          var target = toAddress(log.stack.peek(1).toString(16));
+          var ofs = parseInt(log.stack.peek(opcode === "CALL" ? 3 : 2).toString());
+          this.currentLevel = this.callsFromEntryPoint[this.topLevelCallCounter] = {
+            topLevelMethodSig: toHex(log.memory.slice(ofs, ofs + 4)),
+            topLevelTargetAddress: toHex(target),
+            opcodes: {},
+            access: {},
+            contractSize: {},
+            calls: [],
+            oog: false,
+            output: null,
+          };
+          this.topLevelCallCounter++;
+        } else if (opcode === "LOG1") {
+          var topics0 = log.stack.peek(2).toString(16);
+          if (topics0 === this._beforeExecutionTopics0) this.stopCollecting = true;
+        }
+        this.lastOp = "";
+        return;
+      }
+      if (this.currentLevel === null) return;
+
+      if (log.getGas() < log.getCost()) this.currentLevel.oog = true;
+
+      // Record the code size of the addresses accessed w/ `EXTCODE*` and `*CALL`
+      if (opcode.match(/^(EXT.*|CALL|CALLCODE|DELEGATECALL|STATICCALL)$/) !== null) {
+        var idx = opcode.startsWith("EXT") ? 0 : 1;
+        var addr = toAddress(log.stack.peek(idx).toString(16));
+        var addrHex = toHex(addr);
+        if (
+          this.currentLevel.contractSize[addrHex] === undefined &&
+          !this._isAllowedPrecompile(addr)
+        ) {
+          this.currentLevel.contractSize[addrHex] = {
+            contractSize: db.getCode(addr).length,
+            opcode: opcode,
+          };
+        }
+      }
+
+      // The `GAS` opcode is only allowed if followed by `*CALL`
+      if (this.lastOp === "GAS" && !opcode.includes("CALL")) {
+        this._countSlot(this.currentLevel.opcodes, "GAS");
+      }
+      if (
+        opcode !== "GAS" &&
+        opcode.match(/^(DUP\d+|PUSH\d+|SWAP\d+|POP|ADD|SUB|MUL|DIV|EQ|LTE?|S?GTE?|SLT|SH[LR]|AND|OR|NOT|ISZERO)$/) === null
+      ) {
+        this._countSlot(this.currentLevel.opcodes, opcode);
+      }
+      this.lastOp = opcode;
+
+      if (opcode === "SLOAD" || opcode === "SSTORE") {
+        var slot = toWord(log.stack.peek(0).toString(16));
+        var slotHex = toHex(slot);
+        var contract = log.contract.getAddress();
+        var contractHex = toHex(contract);
+        var access = this.currentLevel.access[contractHex];
+        if (access === undefined) {
+          access = { reads: {}, writes: {} };
+          this.currentLevel.access[contractHex] = access;
+        }
+        if (opcode === "SLOAD") {
+          if (access.reads[slotHex] === undefined && access.writes[slotHex] === undefined) {
+            access.reads[slotHex] = toHex(db.getState(contract, slot));
+          }
+        } else {
+          this._countSlot(access.writes, slotHex);
+        }
+      }
+
+      if (opcode === "KECCAK256" || opcode === "SHA3") {
+        var offset = parseInt(log.stack.peek(0).toString());
+        var len = parseInt(log.stack.peek(1).toString());
+        if (len > 20 && len < 512) {
+          this.keccak.push(toHex(log.memory.slice(offset, offset + len)));
+        }
+      }
+    },
+  }
+"#;
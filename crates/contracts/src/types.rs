@@ -14,11 +14,17 @@
 
 #![allow(clippy::unwrap_used)]
 
-use crate::entrypoint::entry_point::UserOperation as EntryPointUserOperation;
+use crate::{
+    constants::{ENTRYPOINT_V060_ADDRESS, ENTRYPOINT_V070_ADDRESS},
+    entrypoint::entry_point::UserOperation as EntryPointUserOperation,
+    entrypoint_v070::entry_point_v070::PackedUserOperation as EntryPointPackedUserOperation,
+    user_operation::{pack_paymaster_and_data, pack_uints, unpack_paymaster_and_data},
+};
 use ethers::{
     types::{Address, Bytes, Log, Transaction, TransactionReceipt, H256, U256},
     utils::hex,
 };
+use eyre::eyre;
 use lightdotso_prisma::user_operation;
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -75,6 +81,40 @@ pub struct UserOperationWithTransactionAndReceiptLogs {
     pub is_reverted: bool,
 }
 
+/// The version of the entry point, derived from the entry point address.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EntryPointVersion {
+    /// The v0.6.0 entry point w/ the unpacked `UserOperation` layout.
+    V060,
+    /// The v0.7.0 entry point w/ the `PackedUserOperation` layout.
+    V070,
+}
+
+impl EntryPointVersion {
+    /// Get the canonical address of the entry point for the version.
+    pub fn address(&self) -> Address {
+        match self {
+            EntryPointVersion::V060 => *ENTRYPOINT_V060_ADDRESS,
+            EntryPointVersion::V070 => *ENTRYPOINT_V070_ADDRESS,
+        }
+    }
+}
+
+impl TryFrom<Address> for EntryPointVersion {
+    type Error = eyre::Report;
+
+    /// Only the canonical v0.6.0 and v0.7.0 deployments are supported.
+    fn try_from(entry_point: Address) -> Result<Self, Self::Error> {
+        if entry_point == *ENTRYPOINT_V060_ADDRESS {
+            Ok(EntryPointVersion::V060)
+        } else if entry_point == *ENTRYPOINT_V070_ADDRESS {
+            Ok(EntryPointVersion::V070)
+        } else {
+            Err(eyre!("Unsupported entry point: {:?}", entry_point))
+        }
+    }
+}
+
 /// Thee paymaster and data returned by the paymaster.
 /// This is a separate struct to allow for easy serialization and deserialization.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -97,6 +137,22 @@ pub struct GasAndPaymasterAndData {
     pub paymaster_and_data: Bytes,
 }
 
+/// The gas and paymaster and data of the token paymaster, w/ the quote charged in the token.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenPaymasterAndData {
+    pub call_gas_limit: U256,
+    pub verification_gas_limit: U256,
+    pub pre_verification_gas: U256,
+    pub paymaster_and_data: Bytes,
+    /// The token the gas is charged in.
+    pub token: Address,
+    /// The token units charged per 1 ether of the native gas.
+    pub exchange_rate: U256,
+    /// The max token units charged for the user operation.
+    pub max_token_cost: U256,
+}
+
 /// The biconomy gas and paymaster and data returned by the paymaster.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -166,24 +222,130 @@ impl fmt::Debug for UserOperation {
     }
 }
 
-impl From<user_operation::Data> for UserOperation {
-    fn from(user_operation: user_operation::Data) -> Self {
-        Self {
-            sender: user_operation.sender.parse().unwrap(),
+/// The gas and fee columns are stored as decimal strings of the `uint256`.
+impl TryFrom<user_operation::Data> for UserOperation {
+    type Error = eyre::Report;
+
+    fn try_from(user_operation: user_operation::Data) -> Result<Self, Self::Error> {
+        Ok(Self {
+            sender: user_operation.sender.parse()?,
             nonce: user_operation.nonce.into(),
             init_code: user_operation.init_code.into(),
             call_data: user_operation.call_data.into(),
-            call_gas_limit: user_operation.call_gas_limit.into(),
-            verification_gas_limit: user_operation.verification_gas_limit.into(),
-            pre_verification_gas: user_operation.pre_verification_gas.into(),
-            max_fee_per_gas: user_operation.max_fee_per_gas.into(),
-            max_priority_fee_per_gas: user_operation.max_priority_fee_per_gas.into(),
+            call_gas_limit: U256::from_dec_str(&user_operation.call_gas_limit)?,
+            verification_gas_limit: U256::from_dec_str(&user_operation.verification_gas_limit)?,
+            pre_verification_gas: U256::from_dec_str(&user_operation.pre_verification_gas)?,
+            max_fee_per_gas: U256::from_dec_str(&user_operation.max_fee_per_gas)?,
+            max_priority_fee_per_gas: U256::from_dec_str(&user_operation.max_priority_fee_per_gas)?,
             paymaster_and_data: user_operation.paymaster_and_data.into(),
             signature: user_operation.signature.unwrap_or_default().into(),
+        })
+    }
+}
+
+/// The gas and paymaster and data returned by the paymaster for the v0.7.0 entry point.
+/// The paymaster fields are split, and are packed back into `paymaster_and_data` on conversion.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GasAndPaymasterAndDataV070 {
+    pub call_gas_limit: U256,
+    pub verification_gas_limit: U256,
+    pub pre_verification_gas: U256,
+    pub paymaster: Address,
+    pub paymaster_verification_gas_limit: U256,
+    pub paymaster_post_op_gas_limit: U256,
+    pub paymaster_data: Bytes,
+}
+
+impl From<GasAndPaymasterAndDataV070> for GasAndPaymasterAndData {
+    fn from(data: GasAndPaymasterAndDataV070) -> Self {
+        Self {
+            call_gas_limit: data.call_gas_limit,
+            verification_gas_limit: data.verification_gas_limit,
+            pre_verification_gas: data.pre_verification_gas,
+            paymaster_and_data: pack_paymaster_and_data(
+                data.paymaster,
+                data.paymaster_verification_gas_limit,
+                data.paymaster_post_op_gas_limit,
+                &data.paymaster_data,
+            ),
+        }
+    }
+}
+
+/// Packed user operation for the v0.7.0 entry point.
+/// The gas limits and the gas fees are packed into two `bytes32` each, and the
+/// `paymaster_and_data` includes the paymaster verification and post op gas limits.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackedUserOperation {
+    pub sender: Address,
+    pub nonce: U256,
+    pub init_code: Bytes,
+    pub call_data: Bytes,
+    pub account_gas_limits: H256,
+    pub pre_verification_gas: U256,
+    pub gas_fees: H256,
+    pub paymaster_and_data: Bytes,
+    pub signature: Bytes,
+}
+
+impl From<UserOperation> for PackedUserOperation {
+    /// The `paymaster_and_data` of the user operation is expected to already be in the v0.7.0
+    /// layout, i.e. `paymaster || verificationGasLimit || postOpGasLimit || paymasterData`.
+    fn from(user_operation: UserOperation) -> Self {
+        Self {
+            sender: user_operation.sender,
+            nonce: user_operation.nonce,
+            init_code: user_operation.init_code,
+            call_data: user_operation.call_data,
+            account_gas_limits: pack_uints(
+                user_operation.verification_gas_limit,
+                user_operation.call_gas_limit,
+            ),
+            pre_verification_gas: user_operation.pre_verification_gas,
+            gas_fees: pack_uints(
+                user_operation.max_priority_fee_per_gas,
+                user_operation.max_fee_per_gas,
+            ),
+            paymaster_and_data: user_operation.paymaster_and_data,
+            signature: user_operation.signature,
+        }
+    }
+}
+
+impl From<PackedUserOperation> for EntryPointPackedUserOperation {
+    fn from(user_operation: PackedUserOperation) -> Self {
+        Self {
+            sender: user_operation.sender,
+            nonce: user_operation.nonce,
+            init_code: user_operation.init_code,
+            call_data: user_operation.call_data,
+            account_gas_limits: user_operation.account_gas_limits.into(),
+            pre_verification_gas: user_operation.pre_verification_gas,
+            gas_fees: user_operation.gas_fees.into(),
+            paymaster_and_data: user_operation.paymaster_and_data,
+            signature: user_operation.signature,
         }
     }
 }
 
+impl fmt::Debug for PackedUserOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PackedUserOperation")
+            .field("sender", &format!("{:#x}", self.sender))
+            .field("nonce", &format!("{:#x}", self.nonce))
+            .field("init_code", &format!("0x{}", hex::encode(&self.init_code)))
+            .field("call_data", &format!("0x{}", hex::encode(&self.call_data)))
+            .field("account_gas_limits", &format!("{:#x}", self.account_gas_limits))
+            .field("pre_verification_gas", &format!("{:#x}", self.pre_verification_gas))
+            .field("gas_fees", &format!("{:#x}", self.gas_fees))
+            .field("paymaster_and_data", &format!("0x{}", hex::encode(&self.paymaster_and_data)))
+            .field("signature", &format!("0x{}", hex::encode(&self.signature)))
+            .finish()
+    }
+}
+
 /// User operation required for the request.
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -201,6 +363,102 @@ pub struct UserOperationRequest {
     pub signature: Bytes,
 }
 
+impl From<UserOperation> for UserOperationRequest {
+    fn from(user_operation: UserOperation) -> Self {
+        Self {
+            sender: user_operation.sender,
+            nonce: user_operation.nonce,
+            init_code: user_operation.init_code,
+            call_data: user_operation.call_data,
+            call_gas_limit: Some(user_operation.call_gas_limit),
+            verification_gas_limit: Some(user_operation.verification_gas_limit),
+            pre_verification_gas: Some(user_operation.pre_verification_gas),
+            max_fee_per_gas: Some(user_operation.max_fee_per_gas),
+            max_priority_fee_per_gas: Some(user_operation.max_priority_fee_per_gas),
+            paymaster_and_data: Some(user_operation.paymaster_and_data),
+            signature: user_operation.signature,
+        }
+    }
+}
+
+/// User operation required for the request to a v0.7.0 entry point.
+/// The `init_code` is split into the factory and the factory data, and the `paymaster_and_data`
+/// into the paymaster, the paymaster gas limits and the paymaster data.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationRequestV070 {
+    pub sender: Address,
+    pub nonce: U256,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub factory: Option<Address>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub factory_data: Option<Bytes>,
+    pub call_data: Bytes,
+    pub call_gas_limit: Option<U256>,
+    pub verification_gas_limit: Option<U256>,
+    pub pre_verification_gas: Option<U256>,
+    pub max_fee_per_gas: Option<U256>,
+    pub max_priority_fee_per_gas: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paymaster: Option<Address>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paymaster_verification_gas_limit: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paymaster_post_op_gas_limit: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub paymaster_data: Option<Bytes>,
+    pub signature: Bytes,
+}
+
+impl From<UserOperationRequest> for UserOperationRequestV070 {
+    fn from(user_operation: UserOperationRequest) -> Self {
+        // Split the init code into the factory and the factory data.
+        let (factory, factory_data) = if user_operation.init_code.len() >= 20 {
+            (
+                Some(Address::from_slice(&user_operation.init_code[..20])),
+                Some(user_operation.init_code[20..].to_vec().into()),
+            )
+        } else {
+            (None, None)
+        };
+
+        // Split the paymaster and data into the paymaster fields.
+        let (
+            paymaster,
+            paymaster_verification_gas_limit,
+            paymaster_post_op_gas_limit,
+            paymaster_data,
+        ) = match user_operation
+            .paymaster_and_data
+            .as_ref()
+            .and_then(|data| unpack_paymaster_and_data(data).ok())
+        {
+            Some((paymaster, verification_gas_limit, post_op_gas_limit, data)) => {
+                (Some(paymaster), Some(verification_gas_limit), Some(post_op_gas_limit), Some(data))
+            }
+            None => (None, None, None, None),
+        };
+
+        Self {
+            sender: user_operation.sender,
+            nonce: user_operation.nonce,
+            factory,
+            factory_data,
+            call_data: user_operation.call_data,
+            call_gas_limit: user_operation.call_gas_limit,
+            verification_gas_limit: user_operation.verification_gas_limit,
+            pre_verification_gas: user_operation.pre_verification_gas,
+            max_fee_per_gas: user_operation.max_fee_per_gas,
+            max_priority_fee_per_gas: user_operation.max_priority_fee_per_gas,
+            paymaster,
+            paymaster_verification_gas_limit,
+            paymaster_post_op_gas_limit,
+            paymaster_data,
+            signature: user_operation.signature,
+        }
+    }
+}
+
 /// User operation required for the request.
 #[derive(Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -285,6 +543,56 @@ mod tests {
         assert_eq!(result, "0");
     }
 
+    #[test]
+    fn test_entry_point_version_from_address() {
+        assert_eq!(
+            EntryPointVersion::try_from(*ENTRYPOINT_V060_ADDRESS).unwrap(),
+            EntryPointVersion::V060
+        );
+        assert_eq!(
+            EntryPointVersion::try_from(*ENTRYPOINT_V070_ADDRESS).unwrap(),
+            EntryPointVersion::V070
+        );
+        assert!(EntryPointVersion::try_from(Address::zero()).is_err());
+        assert_eq!(EntryPointVersion::V070.address(), *ENTRYPOINT_V070_ADDRESS);
+    }
+
+    #[test]
+    fn test_user_operation_request_v070_split() {
+        let paymaster: Address = "0x0123456789abcdef0123456789abcdef01234567".parse().unwrap();
+        let paymaster_and_data = pack_paymaster_and_data(
+            paymaster,
+            100000.into(),
+            50000.into(),
+            &vec![0xde, 0xad].into(),
+        );
+
+        let request = UserOperationRequest {
+            sender: Address::zero(),
+            nonce: U256::zero(),
+            init_code: "0x6942069420694206942069420694206942069420beef".parse().unwrap(),
+            call_data: Bytes::default(),
+            call_gas_limit: None,
+            verification_gas_limit: None,
+            pre_verification_gas: None,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            paymaster_and_data: Some(paymaster_and_data),
+            signature: Bytes::default(),
+        };
+        let request: UserOperationRequestV070 = request.into();
+
+        assert_eq!(
+            request.factory,
+            Some("0x6942069420694206942069420694206942069420".parse().unwrap())
+        );
+        assert_eq!(request.factory_data, Some(vec![0xbe, 0xef].into()));
+        assert_eq!(request.paymaster, Some(paymaster));
+        assert_eq!(request.paymaster_verification_gas_limit, Some(100000.into()));
+        assert_eq!(request.paymaster_post_op_gas_limit, Some(50000.into()));
+        assert_eq!(request.paymaster_data, Some(vec![0xde, 0xad].into()));
+    }
+
     #[test]
     fn test_u256_to_string_large_number() {
         let number = U256::from(1_000_000_000_000_000_000_000_000_u128);
@@ -39,7 +39,10 @@
 // You should have received a copy of the GNU General Public License along with Rundler.
 // If not, see https://www.gnu.org/licenses/.
 
-use crate::{tracer::LogInfo, types::UserOperation};
+use crate::{
+    tracer::LogInfo,
+    types::{EntryPointVersion, PackedUserOperation, UserOperation},
+};
 use const_hex::hex;
 use core::fmt::Debug;
 use ethers::{
@@ -89,6 +92,9 @@ pub fn parse_user_op_event<T: Debug + EthLogDecode>(event: &LogInfo) -> Result<T
 /// Number of bytes in the fixed size portion of an ABI encoded user operation
 const PACKED_USER_OPERATION_FIXED_LEN: usize = 480;
 
+/// Offset of the paymaster data in the v0.7.0 `paymasterAndData`
+const PAYMASTER_DATA_OFFSET: usize = 52;
+
 /// Unique identifier for a user operation from a given sender
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct UserOperationId {
@@ -101,13 +107,15 @@ impl UserOperation {
     ///
     /// The hash is used to uniquely identify a user operation in the entry point.
     /// It does not include the signature field.
-    pub fn op_hash(&self, entry_point: Address, chain_id: u64) -> H256 {
-        keccak256(encode(&[
-            Token::FixedBytes(keccak256(self.pack_for_hash()).to_vec()),
-            Token::Address(entry_point),
-            Token::Uint(chain_id.into()),
-        ]))
-        .into()
+    /// The encoding is picked from the entry point address, see [`EntryPointVersion`], and the
+    /// unsupported entry points are rejected.
+    pub fn op_hash(&self, entry_point: Address, chain_id: u64) -> Result<H256> {
+        let packed = match EntryPointVersion::try_from(entry_point)? {
+            EntryPointVersion::V060 => self.pack_for_hash(),
+            EntryPointVersion::V070 => self.pack_for_hash_v070(),
+        };
+
+        Ok(hash_with_entry_point(&packed, entry_point, chain_id))
     }
 
     /// Get the unique identifier for this user operation from its sender
@@ -173,6 +181,115 @@ impl UserOperation {
         ])
         .into()
     }
+
+    /// Gets the byte array representation of the user operation in the v0.7.0 packed layout
+    pub fn pack_for_hash_v070(&self) -> Bytes {
+        PackedUserOperation::from(self.clone()).pack_for_hash()
+    }
+}
+
+impl PackedUserOperation {
+    /// Hash a packed user operation with the given entry point and chain ID.
+    pub fn op_hash(&self, entry_point: Address, chain_id: u64) -> H256 {
+        hash_with_entry_point(&self.pack_for_hash(), entry_point, chain_id)
+    }
+
+    /// Get the address of the paymaster entity associated with this user operation, if any
+    pub fn paymaster(&self) -> Option<Address> {
+        UserOperation::get_address_from_field(&self.paymaster_and_data)
+    }
+
+    /// Get the verification gas limit and the call gas limit of the user operation
+    pub fn gas_limits(&self) -> (U256, U256) {
+        unpack_uints(self.account_gas_limits)
+    }
+
+    /// Get the max priority fee per gas and the max fee per gas of the user operation
+    pub fn gas_fees(&self) -> (U256, U256) {
+        unpack_uints(self.gas_fees)
+    }
+
+    /// Gets the byte array representation of the user operation to be used in the signature
+    /// From: https://github.com/eth-infinitism/account-abstraction/blob/7af70c8993a6f42973f520ae0752386a5032abe7/contracts/core/UserOperationLib.sol#L54-L75
+    /// License: GPL-3.0
+    pub fn pack_for_hash(&self) -> Bytes {
+        let hash_init_code = keccak256(self.init_code.clone());
+        let hash_call_data = keccak256(self.call_data.clone());
+        let hash_paymaster_and_data = keccak256(self.paymaster_and_data.clone());
+
+        encode(&[
+            Token::Address(self.sender),
+            Token::Uint(self.nonce),
+            Token::FixedBytes(hash_init_code.to_vec()),
+            Token::FixedBytes(hash_call_data.to_vec()),
+            Token::FixedBytes(self.account_gas_limits.as_bytes().to_vec()),
+            Token::Uint(self.pre_verification_gas),
+            Token::FixedBytes(self.gas_fees.as_bytes().to_vec()),
+            Token::FixedBytes(hash_paymaster_and_data.to_vec()),
+        ])
+        .into()
+    }
+}
+
+/// Hash the packed user operation w/ the entry point and chain ID
+fn hash_with_entry_point(packed: &Bytes, entry_point: Address, chain_id: u64) -> H256 {
+    keccak256(encode(&[
+        Token::FixedBytes(keccak256(packed).to_vec()),
+        Token::Address(entry_point),
+        Token::Uint(chain_id.into()),
+    ]))
+    .into()
+}
+
+/// Packs two uint128 values into a single bytes32, w/ `high` in the upper 16 bytes
+/// Used for the `accountGasLimits` and the `gasFees` of the v0.7.0 entry point
+pub fn pack_uints(high: U256, low: U256) -> H256 {
+    let mut packed = [0u8; 32];
+    high.low_u128().to_be_bytes().iter().enumerate().for_each(|(i, b)| packed[i] = *b);
+    low.low_u128().to_be_bytes().iter().enumerate().for_each(|(i, b)| packed[16 + i] = *b);
+    H256::from(packed)
+}
+
+/// Unpacks a bytes32 into two uint128 values, returned as `(high, low)`
+pub fn unpack_uints(packed: H256) -> (U256, U256) {
+    let bytes = packed.as_bytes();
+    (U256::from_big_endian(&bytes[..16]), U256::from_big_endian(&bytes[16..]))
+}
+
+/// Packs the split paymaster fields into the v0.7.0 `paymasterAndData` layout
+/// `paymaster (20) || paymasterVerificationGasLimit (16) || paymasterPostOpGasLimit (16) ||
+/// paymasterData`
+pub fn pack_paymaster_and_data(
+    paymaster: Address,
+    verification_gas_limit: U256,
+    post_op_gas_limit: U256,
+    paymaster_data: &Bytes,
+) -> Bytes {
+    let mut packed = paymaster.as_bytes().to_vec();
+    packed.extend_from_slice(&verification_gas_limit.low_u128().to_be_bytes());
+    packed.extend_from_slice(&post_op_gas_limit.low_u128().to_be_bytes());
+    packed.extend_from_slice(paymaster_data);
+    packed.into()
+}
+
+/// Unpacks the v0.7.0 `paymasterAndData` into the paymaster, the paymaster verification gas
+/// limit, the paymaster post op gas limit and the paymaster data
+pub fn unpack_paymaster_and_data(
+    paymaster_and_data: &Bytes,
+) -> Result<(Address, U256, U256, Bytes)> {
+    if paymaster_and_data.len() < PAYMASTER_DATA_OFFSET {
+        return Err(eyre!(
+            "paymaster and data is too short for v0.7.0: {} bytes",
+            paymaster_and_data.len()
+        ));
+    }
+
+    let paymaster = Address::from_slice(&paymaster_and_data[..20]);
+    let verification_gas_limit = U256::from_big_endian(&paymaster_and_data[20..36]);
+    let post_op_gas_limit = U256::from_big_endian(&paymaster_and_data[36..52]);
+    let paymaster_data = paymaster_and_data[PAYMASTER_DATA_OFFSET..].to_vec().into();
+
+    Ok((paymaster, verification_gas_limit, post_op_gas_limit, paymaster_data))
 }
 
 /// Calculates the size a byte array padded to the next largest multiple of 32
@@ -183,6 +300,7 @@ fn pad_len(b: &Bytes) -> usize {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::constants::ENTRYPOINT_V070_ADDRESS;
     use ethers::types::{Bytes, U256};
 
     #[test]
@@ -221,7 +339,7 @@ mod tests {
         };
         let entry_point = "0x66a15edcc3b50a663e72f1457ffd49b9ae284ddc".parse().unwrap();
         let chain_id = 1337;
-        let hash = operation.op_hash(entry_point, chain_id);
+        let hash = hash_with_entry_point(&operation.pack_for_hash(), entry_point, chain_id);
         assert_eq!(
             hash,
             "0xdca97c3b49558ab360659f6ead939773be8bf26631e61bb17045bb70dc983b2d".parse().unwrap()
@@ -271,10 +389,87 @@ mod tests {
         };
         let entry_point = "0x66a15edcc3b50a663e72f1457ffd49b9ae284ddc".parse().unwrap();
         let chain_id = 1337;
-        let hash = operation.op_hash(entry_point, chain_id);
+        let hash = hash_with_entry_point(&operation.pack_for_hash(), entry_point, chain_id);
         assert_eq!(
             hash,
             "0x484add9e4d8c3172d11b5feb6a3cc712280e176d278027cfa02ee396eb28afa1".parse().unwrap()
         );
     }
+
+    #[test]
+    fn test_hash_v070_zeroed() {
+        // Testing a user operation hash against the v0.7.0 `UserOperationLib.encode` with the
+        // canonical v0.7.0 entrypoint address and chain ID 1.
+        let operation = UserOperation {
+            sender: Address::zero(),
+            nonce: U256::zero(),
+            init_code: Bytes::default(),
+            call_data: Bytes::default(),
+            call_gas_limit: U256::zero(),
+            verification_gas_limit: U256::zero(),
+            pre_verification_gas: U256::zero(),
+            max_fee_per_gas: U256::zero(),
+            max_priority_fee_per_gas: U256::zero(),
+            paymaster_and_data: Bytes::default(),
+            signature: Bytes::default(),
+        };
+        let hash = operation.op_hash(*ENTRYPOINT_V070_ADDRESS, 1).unwrap();
+        assert_eq!(
+            hash,
+            "0x2d445b1b5eeb67df472622c81edd16083e261f188eb34661b4fdc947625608c1".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_hash_v070_packed() {
+        // Same user operation as `test_hash`, in the v0.7.0 packed layout.
+        //
+        // Hash: 0xa182d3e3340c64ea312a11d766bee39cbd1a709175cd4da963608b8c9315a187
+        let operation = UserOperation {
+            sender: "0x1306b01bc3e4ad202612d3843387e94737673f53".parse().unwrap(),
+            nonce: 8942.into(),
+            init_code: "0x6942069420694206942069420694206942069420".parse().unwrap(),
+            call_data: "0x0000000000000000000000000000000000000000080085".parse().unwrap(),
+            call_gas_limit: 10000.into(),
+            verification_gas_limit: 100000.into(),
+            pre_verification_gas: 100.into(),
+            max_fee_per_gas: 99999.into(),
+            max_priority_fee_per_gas: 9999999.into(),
+            paymaster_and_data:
+                "0x0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef"
+                    .parse()
+                    .unwrap(),
+            signature: "0xda0929f527cded8d0a1eaf2e8861d7f7e2d8160b7b13942f99dd367df4473a"
+                .parse()
+                .unwrap(),
+        };
+        let packed = PackedUserOperation::from(operation);
+        assert_eq!(packed.gas_limits(), (100000.into(), 10000.into()));
+        assert_eq!(packed.gas_fees(), (9999999.into(), 99999.into()));
+
+        let entry_point = "0x66a15edcc3b50a663e72f1457ffd49b9ae284ddc".parse().unwrap();
+        let hash = packed.op_hash(entry_point, 1337);
+        assert_eq!(
+            hash,
+            "0xa182d3e3340c64ea312a11d766bee39cbd1a709175cd4da963608b8c9315a187".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_pack_paymaster_and_data_roundtrip() {
+        let paymaster: Address = "0x0000000000000000000000000000000000000042".parse().unwrap();
+        let data: Bytes = vec![1, 2, 3].into();
+
+        let packed = pack_paymaster_and_data(paymaster, 12345.into(), 678.into(), &data);
+        assert_eq!(packed.len(), PAYMASTER_DATA_OFFSET + 3);
+
+        let (unpacked_paymaster, verification_gas_limit, post_op_gas_limit, unpacked_data) =
+            unpack_paymaster_and_data(&packed).unwrap();
+        assert_eq!(unpacked_paymaster, paymaster);
+        assert_eq!(verification_gas_limit, 12345.into());
+        assert_eq!(post_op_gas_limit, 678.into());
+        assert_eq!(unpacked_data, data);
+
+        assert!(unpack_paymaster_and_data(&vec![0u8; 20].into()).is_err());
+    }
 }
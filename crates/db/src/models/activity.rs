@@ -17,8 +17,8 @@ use autometrics::autometrics;
 use axum::extract::Json;
 use lightdotso_prisma::{
     activity, configuration_operation_signature, feedback, notification, paymaster,
-    paymaster_operation, signature, simulation, transaction, user_operation, ActivityEntity,
-    ActivityOperation,
+    paymaster_operation, recovery_operation_signature, signature, simulation, transaction,
+    user_operation, ActivityEntity, ActivityOperation,
 };
 use lightdotso_tracing::tracing::info;
 use serde::{Deserialize, Serialize};
@@ -55,8 +55,11 @@ pub struct CustomParams {
     // Add-ons
     // Mutable
     pub configuration_operation_id: Option<String>,
+    pub guardian_id: Option<String>,
+    pub recovery_operation_id: Option<String>,
     // Immutable
     pub configuration_operation_signature_id: Option<String>,
+    pub recovery_operation_signature_id: Option<String>,
 }
 
 // -----------------------------------------------------------------------------
@@ -184,6 +187,14 @@ pub async fn create_activity_with_user_and_wallet(
         params.push(activity::configuration_operation_id::set(Some(configuration_operation_id)));
     }
 
+    if let Some(guardian_id) = custom_params.guardian_id {
+        params.push(activity::guardian_id::set(Some(guardian_id)));
+    }
+
+    if let Some(recovery_operation_id) = custom_params.recovery_operation_id {
+        params.push(activity::recovery_operation_id::set(Some(recovery_operation_id)));
+    }
+
     // -------------------------------------------------------------------------
     // Immutable
     // -------------------------------------------------------------------------
@@ -196,6 +207,12 @@ pub async fn create_activity_with_user_and_wallet(
         ));
     }
 
+    if let Some(recovery_operation_signature_id) = custom_params.recovery_operation_signature_id {
+        params.push(activity::recovery_operation_signature::connect(
+            recovery_operation_signature::id::equals(recovery_operation_signature_id),
+        ));
+    }
+
     let activity = db.activity().create(entity, operation, log, params).exec().await?;
 
     Ok(Json::from(activity))
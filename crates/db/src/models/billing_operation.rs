@@ -14,14 +14,16 @@
 
 #![allow(clippy::unwrap_used)]
 
-use crate::types::Database;
+use crate::{models::sponsorship_reservation::settle_sponsorship_reservation, types::Database};
 use autometrics::autometrics;
 use ethers::utils::to_checksum;
 use eyre::{eyre, Result};
 use lightdotso_prisma::{
-    billing, paymaster_operation, wallet, wallet_billing, BillingOperationStatus,
+    billing, billing_operation, paymaster_operation, wallet, wallet_billing,
+    BillingOperationStatus, PrismaClient,
 };
 use lightdotso_tracing::tracing::info;
+use prisma_client_rust::chrono::{DateTime, Utc};
 
 // -----------------------------------------------------------------------------
 // Create
@@ -54,18 +56,90 @@ pub async fn create_billing_operation(
         .ok_or(eyre!("Billing not found"))?;
     info!(?billing);
 
-    let billing_operation = db
-        .billing_operation()
-        .create(
-            pending_usd,
-            BillingOperationStatus::Pending,
-            billing::id::equals(billing.id),
-            paymaster_operation::id::equals(paymaster_operation_id),
-            vec![],
-        )
+    // Get the chain of the paymaster operation, to settle the reservation of the sponsorship.
+    let chain_id = db
+        .paymaster_operation()
+        .find_unique(paymaster_operation::id::equals(paymaster_operation_id.clone()))
+        .with(paymaster_operation::paymaster::fetch())
         .exec()
-        .await?;
+        .await?
+        .and_then(|paymaster_operation| paymaster_operation.paymaster)
+        .ok_or(eyre!("Paymaster operation not found"))?
+        .chain_id;
+
+    let billing_operation: Result<billing_operation::Data> = db
+        ._transaction()
+        .run(|client| async move {
+            let billing_operation = client
+                .billing_operation()
+                .create(
+                    pending_usd,
+                    BillingOperationStatus::Pending,
+                    billing::id::equals(billing.id),
+                    paymaster_operation::id::equals(paymaster_operation_id),
+                    vec![],
+                )
+                .exec()
+                .await?;
+
+            // The cost of the sponsorship is now in the billing operation.
+            settle_sponsorship_reservation(&client, sender_address, chain_id).await?;
+
+            Ok(billing_operation)
+        })
+        .await;
+    let billing_operation = billing_operation?;
     info!(?billing_operation);
 
     Ok(())
 }
+
+// -----------------------------------------------------------------------------
+// Get
+// -----------------------------------------------------------------------------
+
+/// Get the billing of the wallet
+#[autometrics]
+pub async fn get_wallet_billing(
+    client: &PrismaClient,
+    wallet_address: ethers::types::H160,
+) -> Result<Option<billing::Data>> {
+    info!("Getting wallet billing");
+
+    let wallet_billing = client
+        .wallet_billing()
+        .find_unique(wallet_billing::wallet_address::equals(to_checksum(&wallet_address, None)))
+        .with(wallet_billing::billing::fetch())
+        .exec()
+        .await?;
+
+    Ok(wallet_billing.and_then(|wb| wb.billing.map(|billing| *billing)))
+}
+
+/// Get the billing operations spent on the user operations of the wallet since the timestamp
+#[autometrics]
+pub async fn get_wallet_spent_billing_operations(
+    client: &PrismaClient,
+    wallet_address: ethers::types::H160,
+    since: DateTime<Utc>,
+) -> Result<Vec<billing_operation::Data>> {
+    info!("Getting wallet spent billing operations");
+
+    let billing_operations = client
+        .billing_operation()
+        .find_many(vec![
+            billing_operation::paymaster_operation::is(vec![paymaster_operation::sender::equals(
+                to_checksum(&wallet_address, None),
+            )]),
+            billing_operation::status::in_vec(vec![
+                BillingOperationStatus::Pending,
+                BillingOperationStatus::Payment,
+                BillingOperationStatus::Sponsored,
+            ]),
+            billing_operation::created_at::gte(since.into()),
+        ])
+        .exec()
+        .await?;
+
+    Ok(billing_operations)
+}
@@ -0,0 +1,291 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{models::wallet::get_wallet_chain_ids, types::Database};
+use autometrics::autometrics;
+use ethers::{
+    providers::Middleware,
+    types::{H160, H256},
+    utils::to_checksum,
+};
+use eyre::Result;
+use lightdotso_contracts::{light_wallet::get_light_wallet, provider::get_provider};
+use lightdotso_prisma::{
+    configuration, configuration_reconciliation, wallet, ConfigurationReconciliationStatus,
+};
+use lightdotso_tracing::tracing::{info, warn};
+use prisma_client_rust::Direction;
+use serde::{Deserialize, Serialize};
+
+// -----------------------------------------------------------------------------
+// Types
+// -----------------------------------------------------------------------------
+
+/// The status of the on-chain image hash against the configuration history.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReconciliationStatus {
+    /// The on-chain image hash is the latest configuration.
+    Synced,
+    /// The on-chain image hash is an older configuration.
+    Lagging,
+    /// The on-chain image hash is not in the configuration history.
+    Unknown,
+    /// The wallet is not deployed on the chain.
+    Undeployed,
+}
+
+impl From<ReconciliationStatus> for ConfigurationReconciliationStatus {
+    fn from(status: ReconciliationStatus) -> Self {
+        match status {
+            ReconciliationStatus::Synced => ConfigurationReconciliationStatus::Synced,
+            ReconciliationStatus::Lagging => ConfigurationReconciliationStatus::Lagging,
+            ReconciliationStatus::Unknown => ConfigurationReconciliationStatus::Unknown,
+            ReconciliationStatus::Undeployed => ConfigurationReconciliationStatus::Undeployed,
+        }
+    }
+}
+
+/// The reconciliation of the wallet configuration on a chain.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ChainReconciliation {
+    /// The chain id of the wallet.
+    pub chain_id: i64,
+    /// The status of the on-chain image hash.
+    pub status: ReconciliationStatus,
+    /// The image hash of the wallet on the chain.
+    pub onchain_image_hash: Option<H256>,
+    /// The configuration of the on-chain image hash, if any.
+    pub onchain_configuration: Option<configuration::Data>,
+    /// The latest configuration of the wallet.
+    pub latest_configuration: configuration::Data,
+}
+
+// -----------------------------------------------------------------------------
+// Reconcile
+// -----------------------------------------------------------------------------
+
+/// Reconcile the on-chain image hash of the wallet against the configuration history on each of
+/// the chains the wallet has operated on.
+#[autometrics]
+pub async fn reconcile_wallet_configurations(
+    db: Database,
+    address: H160,
+) -> Result<Vec<ChainReconciliation>> {
+    info!("Reconciling wallet configurations at address: {:?}", address);
+
+    let checksum_address = to_checksum(&address, None);
+
+    // Get the configuration history of the wallet.
+    let configurations = db
+        .configuration()
+        .find_many(vec![configuration::address::equals(checksum_address.clone())])
+        .order_by(configuration::checkpoint::order(Direction::Desc))
+        .exec()
+        .await?;
+
+    // If the wallet has no configurations, there is nothing to reconcile.
+    let Some(latest_configuration) = configurations.first().cloned() else {
+        return Ok(vec![]);
+    };
+
+    // Get the chains the wallet has any record on.
+    let chain_ids = get_wallet_chain_ids(db, address).await?;
+
+    // The image hashes of the configuration history, from the latest one.
+    let image_hashes = configurations
+        .iter()
+        .map(|configuration| configuration.image_hash.parse::<H256>().ok())
+        .collect::<Vec<_>>();
+
+    let mut reconciliations = vec![];
+    for chain_id in chain_ids {
+        // Get the code of the wallet on the chain.
+        let provider = get_provider(chain_id as u64).await?;
+        let code = provider.get_code(address, None).await?;
+
+        // Get the image hash of the wallet on the chain, if the wallet is deployed.
+        // If the wallet is not deployed, the initial configuration is used on the chain.
+        let onchain_image_hash: Option<H256> = if code.is_empty() {
+            None
+        } else {
+            let wallet = get_light_wallet(chain_id as u64, address).await?;
+            Some(wallet.image_hash().await?.into())
+        };
+
+        // Find the configuration of the on-chain image hash in the history.
+        let (status, index) = get_reconciliation_status(&image_hashes, onchain_image_hash);
+
+        if status != ReconciliationStatus::Synced {
+            warn!(
+                "Wallet {:?} is {:?} on chain {} w/ image hash {:?}",
+                address, status, chain_id, onchain_image_hash
+            );
+        }
+
+        reconciliations.push(ChainReconciliation {
+            chain_id,
+            status,
+            onchain_image_hash,
+            onchain_configuration: index.map(|index| configurations[index].clone()),
+            latest_configuration: latest_configuration.clone(),
+        });
+    }
+
+    Ok(reconciliations)
+}
+
+/// Get the status of the on-chain image hash against the image hashes of the configuration
+/// history, ordered from the latest configuration, w/ the index of the matching configuration.
+/// The on-chain image hash is `None` if the wallet is not deployed on the chain.
+pub fn get_reconciliation_status(
+    image_hashes: &[Option<H256>],
+    onchain_image_hash: Option<H256>,
+) -> (ReconciliationStatus, Option<usize>) {
+    let Some(onchain_image_hash) = onchain_image_hash else {
+        return (ReconciliationStatus::Undeployed, None);
+    };
+
+    match image_hashes.iter().position(|image_hash| *image_hash == Some(onchain_image_hash)) {
+        Some(0) => (ReconciliationStatus::Synced, Some(0)),
+        Some(index) => (ReconciliationStatus::Lagging, Some(index)),
+        None => (ReconciliationStatus::Unknown, None),
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Upsert
+// -----------------------------------------------------------------------------
+
+/// Upsert the reconciliations of the wallet configuration on each of the chains.
+#[autometrics]
+pub async fn upsert_configuration_reconciliations(
+    db: Database,
+    address: H160,
+    reconciliations: &[ChainReconciliation],
+) -> Result<Vec<configuration_reconciliation::Data>> {
+    info!("Upserting configuration reconciliations at address: {:?}", address);
+
+    let checksum_address = to_checksum(&address, None);
+
+    let mut res = vec![];
+    for reconciliation in reconciliations {
+        // The optional fields are set on both the create and the update.
+        let optional_params = || {
+            vec![
+                configuration_reconciliation::onchain_image_hash::set(
+                    reconciliation.onchain_image_hash.map(|image_hash| format!("{:?}", image_hash)),
+                ),
+                configuration_reconciliation::onchain_checkpoint::set(
+                    reconciliation
+                        .onchain_configuration
+                        .as_ref()
+                        .map(|configuration| configuration.checkpoint),
+                ),
+            ]
+        };
+
+        let mut update_params = optional_params();
+        update_params.push(configuration_reconciliation::status::set(reconciliation.status.into()));
+        update_params.push(configuration_reconciliation::latest_checkpoint::set(
+            reconciliation.latest_configuration.checkpoint,
+        ));
+
+        let configuration_reconciliation = db
+            .configuration_reconciliation()
+            .upsert(
+                configuration_reconciliation::address_chain_id(
+                    checksum_address.clone(),
+                    reconciliation.chain_id,
+                ),
+                configuration_reconciliation::create(
+                    reconciliation.chain_id,
+                    reconciliation.status.into(),
+                    reconciliation.latest_configuration.checkpoint,
+                    wallet::address::equals(checksum_address.clone()),
+                    optional_params(),
+                ),
+                update_params,
+            )
+            .exec()
+            .await?;
+        res.push(configuration_reconciliation);
+    }
+
+    Ok(res)
+}
+
+// -----------------------------------------------------------------------------
+// Get
+// -----------------------------------------------------------------------------
+
+/// Get the stored reconciliations of the wallet configuration on each of the chains.
+#[autometrics]
+pub async fn get_configuration_reconciliations(
+    db: Database,
+    address: H160,
+) -> Result<Vec<configuration_reconciliation::Data>> {
+    info!("Getting configuration reconciliations at address: {:?}", address);
+
+    Ok(db
+        .configuration_reconciliation()
+        .find_many(vec![configuration_reconciliation::address::equals(to_checksum(&address, None))])
+        .exec()
+        .await?)
+}
+
+// -----------------------------------------------------------------------------
+// Tests
+// -----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_reconciliation_status() {
+        let image_hashes: Vec<Option<H256>> =
+            (1..=3).rev().map(|i| Some(H256::from_low_u64_be(i))).collect();
+
+        // The on-chain image hash is the latest configuration.
+        assert_eq!(
+            get_reconciliation_status(&image_hashes, Some(H256::from_low_u64_be(3))),
+            (ReconciliationStatus::Synced, Some(0))
+        );
+
+        // The on-chain image hash is an older configuration.
+        assert_eq!(
+            get_reconciliation_status(&image_hashes, Some(H256::from_low_u64_be(1))),
+            (ReconciliationStatus::Lagging, Some(2))
+        );
+
+        // The on-chain image hash is not in the configuration history.
+        assert_eq!(
+            get_reconciliation_status(&image_hashes, Some(H256::from_low_u64_be(4))),
+            (ReconciliationStatus::Unknown, None)
+        );
+
+        // The wallet is not deployed on the chain.
+        assert_eq!(
+            get_reconciliation_status(&image_hashes, None),
+            (ReconciliationStatus::Undeployed, None)
+        );
+
+        // The unparsable image hashes of the history are never matched.
+        assert_eq!(
+            get_reconciliation_status(&[None], Some(H256::zero())),
+            (ReconciliationStatus::Unknown, None)
+        );
+    }
+}
@@ -0,0 +1,175 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use eyre::Result;
+use lightdotso_prisma::{
+    configuration, configuration_operation, configuration_operation_owner, owner, wallet,
+    OwnerLeafType, OwnerSignerType, PrismaClient,
+};
+use lightdotso_tracing::tracing::info;
+
+// -----------------------------------------------------------------------------
+// Types
+// -----------------------------------------------------------------------------
+
+/// The owner of the configuration flattened in order, as stored for both the configuration
+/// operation and the configuration.
+#[derive(Clone, Debug)]
+pub struct ConfigurationOwnerParams {
+    /// The checksummed address of the owner, the zero address for the nested configs and the
+    /// subdigests.
+    pub address: String,
+    /// The weight of the owner, or the external weight of the nested config.
+    pub weight: i64,
+    /// The index of the owner in the configuration.
+    pub index: i32,
+    /// The signer type of the owner.
+    pub signer_type: OwnerSignerType,
+    /// The leaf type of the owner.
+    pub leaf_type: OwnerLeafType,
+    /// The internal threshold of the nested config.
+    pub threshold: Option<i64>,
+    /// The pre-approved subdigest in hex.
+    pub subdigest: Option<String>,
+    /// The index of the nested config the owner belongs to.
+    pub parent_index: Option<i32>,
+    /// The user of the owner, if any.
+    pub user_id: Option<String>,
+}
+
+impl From<&configuration_operation_owner::Data> for ConfigurationOwnerParams {
+    fn from(owner: &configuration_operation_owner::Data) -> Self {
+        Self {
+            address: owner.address.clone(),
+            weight: owner.weight,
+            index: owner.index,
+            signer_type: owner.signer_type,
+            leaf_type: owner.leaf_type,
+            threshold: owner.threshold,
+            subdigest: owner.subdigest.clone(),
+            parent_index: owner.parent_index,
+            user_id: owner.user_id.clone(),
+        }
+    }
+}
+
+// -----------------------------------------------------------------------------
+// Create
+// -----------------------------------------------------------------------------
+
+/// Create the configuration operation w/ the owners of the next configuration.
+/// Expects to be run in a transaction, w/ the client of the transaction.
+pub async fn create_configuration_operation_with_owners(
+    client: &PrismaClient,
+    address: String,
+    checkpoint: i64,
+    image_hash: String,
+    threshold: i64,
+    owners: &[ConfigurationOwnerParams],
+) -> Result<configuration_operation::Data> {
+    // Create the configuration operation to the database.
+    let configuration_operation = client
+        .configuration_operation()
+        .create(checkpoint, image_hash, threshold, wallet::address::equals(address), vec![])
+        .exec()
+        .await?;
+    info!(?configuration_operation);
+
+    // Create the owners to the database.
+    let owner_data = client
+        .configuration_operation_owner()
+        .create_many(
+            owners
+                .iter()
+                .map(|owner| {
+                    configuration_operation_owner::create_unchecked(
+                        owner.address.clone(),
+                        owner.weight,
+                        owner.index,
+                        configuration_operation.id.clone(),
+                        vec![
+                            configuration_operation_owner::signer_type::set(owner.signer_type),
+                            configuration_operation_owner::leaf_type::set(owner.leaf_type),
+                            configuration_operation_owner::threshold::set(owner.threshold),
+                            configuration_operation_owner::subdigest::set(owner.subdigest.clone()),
+                            configuration_operation_owner::parent_index::set(owner.parent_index),
+                            // The nested configs and the subdigests have no user.
+                            configuration_operation_owner::user_id::set(owner.user_id.clone()),
+                        ],
+                    )
+                })
+                .collect(),
+        )
+        .exec()
+        .await?;
+    info!(?owner_data);
+
+    Ok(configuration_operation)
+}
+
+/// Create the configuration w/ the same contents as the confirmed configuration operation.
+/// Expects to be run in a transaction, w/ the client of the transaction.
+pub async fn create_configuration_with_owners(
+    client: &PrismaClient,
+    configuration_operation: &configuration_operation::Data,
+    owners: &[ConfigurationOwnerParams],
+) -> Result<configuration::Data> {
+    // Create the configuration to the database.
+    let configuration = client
+        .configuration()
+        .create(
+            configuration_operation.address.clone(),
+            configuration_operation.checkpoint,
+            configuration_operation.image_hash.clone(),
+            configuration_operation.threshold,
+            vec![configuration::configuration_operation::connect(
+                configuration_operation::id::equals(configuration_operation.id.clone()),
+            )],
+        )
+        .exec()
+        .await?;
+    info!(?configuration);
+
+    // Create the owners to the database.
+    let owner_data = client
+        .owner()
+        .create_many(
+            owners
+                .iter()
+                .map(|owner| {
+                    owner::create_unchecked(
+                        owner.address.clone(),
+                        owner.weight,
+                        owner.index,
+                        configuration.id.clone(),
+                        vec![
+                            owner::signer_type::set(owner.signer_type),
+                            owner::leaf_type::set(owner.leaf_type),
+                            owner::threshold::set(owner.threshold),
+                            owner::subdigest::set(owner.subdigest.clone()),
+                            owner::parent_index::set(owner.parent_index),
+                            // The nested configs and the subdigests have no user.
+                            owner::user_id::set(owner.user_id.clone()),
+                        ],
+                    )
+                })
+                .collect(),
+        )
+        .skip_duplicates()
+        .exec()
+        .await?;
+    info!(?owner_data);
+
+    Ok(configuration)
+}
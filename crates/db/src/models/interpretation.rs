@@ -18,10 +18,10 @@ use crate::types::Database;
 use autometrics::autometrics;
 use ethers::utils::to_checksum;
 use eyre::Result;
-use lightdotso_interpreter::types::{AssetTokenType, InterpretationResponse};
+use lightdotso_interpreter::types::{AssetProtocol, AssetTokenType, InterpretationResponse};
 use lightdotso_prisma::{
-    asset_change, chain, interpretation, interpretation_action, token, transaction, user_operation,
-    TokenType,
+    asset_change, chain, interpretation, interpretation_action, protocol, token, transaction,
+    user_operation, ProtocolStatus, TokenType,
 };
 use lightdotso_tracing::tracing::info;
 
@@ -195,12 +195,46 @@ pub async fn upsert_interpretation_with_actions(
     let interpretation = db.interpretation().create(interpretation_params).exec().await?;
     info!(?interpretation);
 
+    // Create all possible protocols one by one
+    for asset_protocol in res.asset_changes.iter().filter_map(|change| change.protocol.clone()) {
+        let id = protocol_id(&asset_protocol, res.chain_id);
+        // Fails gracefully if the protocol already exists
+        let protocol_creation = db
+            .protocol()
+            .upsert(
+                protocol::id::equals(id.clone()),
+                protocol::create(
+                    id,
+                    to_checksum(&asset_protocol.address, None),
+                    asset_protocol.protocol.to_string(),
+                    chain::id::equals(res.chain_id as i64),
+                    vec![protocol::status::set(ProtocolStatus::Known)],
+                ),
+                vec![],
+            )
+            .exec()
+            .await;
+        info!(?protocol_creation);
+    }
+
     // Create all possible asset changes
     let asset_change_params = res
         .clone()
         .asset_changes
         .into_iter()
         .map(|change| {
+            // Connect the asset change to the protocol, if any
+            let protocol_params = change
+                .protocol
+                .as_ref()
+                .map(|asset_protocol| {
+                    asset_change::protocol::connect(protocol::id::equals(protocol_id(
+                        asset_protocol,
+                        res.chain_id,
+                    )))
+                })
+                .into_iter();
+
             (
                 to_checksum(&change.address, None),
                 format!("{}", change.amount),
@@ -250,7 +284,10 @@ pub async fn upsert_interpretation_with_actions(
                                 .id,
                         ),
                     ),
-                ],
+                ]
+                .into_iter()
+                .chain(protocol_params)
+                .collect::<Vec<_>>(),
             )
         })
         .collect::<Vec<_>>();
@@ -271,3 +308,8 @@ pub async fn upsert_interpretation_with_actions(
 
     Ok(interpretation)
 }
+
+/// Get the id of the protocol from the chain id and the address of the contract.
+fn protocol_id(asset_protocol: &AssetProtocol, chain_id: u64) -> String {
+    format!("{}:{}", chain_id, to_checksum(&asset_protocol.address, None))
+}
@@ -14,9 +14,13 @@
 
 pub mod activity;
 pub mod billing_operation;
+pub mod configuration;
+pub mod configuration_operation;
 pub mod interpretation;
 pub mod log;
 pub mod paymaster_operation;
+pub mod sponsorship_reservation;
+pub mod token;
 pub mod transaction;
 pub mod user_operation;
 pub mod wallet;
@@ -19,7 +19,7 @@ use autometrics::autometrics;
 use ethers::utils::to_checksum;
 use eyre::Result;
 use lightdotso_prisma::{
-    chain, paymaster, paymaster_operation, user_operation, wallet, UserOperationStatus,
+    chain, log, paymaster, paymaster_operation, user_operation, wallet, UserOperationStatus,
 };
 use lightdotso_tracing::tracing::info;
 use prisma_client_rust::{
@@ -132,3 +132,33 @@ pub async fn get_most_recent_paymaster_operation_with_sender(
 
     Ok(None)
 }
+
+/// Get the paymaster operations of the paymaster since the timestamp, w/ the sponsored user
+/// operations and their logs
+#[autometrics]
+pub async fn get_paymaster_operations_with_user_operations(
+    db: Database,
+    chain_id: i64,
+    paymaster_address: ethers::types::H160,
+    since: DateTime<Utc>,
+) -> Result<Vec<paymaster_operation::Data>> {
+    info!("Getting paymaster operations w/ user operations");
+
+    let paymaster_operations = db
+        .paymaster_operation()
+        .find_many(vec![
+            paymaster_operation::paymaster::is(vec![
+                paymaster::address::equals(to_checksum(&paymaster_address, None)),
+                paymaster::chain_id::equals(chain_id),
+            ]),
+            paymaster_operation::created_at::gte(since.into()),
+        ])
+        .with(
+            paymaster_operation::user_operation::fetch()
+                .with(user_operation::logs::fetch(vec![]).with(log::topics::fetch(vec![]))),
+        )
+        .exec()
+        .await?;
+
+    Ok(paymaster_operations)
+}
@@ -0,0 +1,144 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::types::Database;
+use autometrics::autometrics;
+use ethers::utils::to_checksum;
+use eyre::Result;
+use lightdotso_prisma::{sponsorship_reservation, PrismaClient};
+use lightdotso_tracing::tracing::info;
+use prisma_client_rust::{
+    chrono::{DateTime, Utc},
+    raw, Direction, PrismaValue,
+};
+
+// -----------------------------------------------------------------------------
+// Lock
+// -----------------------------------------------------------------------------
+
+/// Lock the wallet until the end of the transaction, so that the sponsorships of the wallet are
+/// reserved one at a time. The lock is upserted, as the sender may not have a wallet yet
+#[autometrics]
+pub async fn lock_wallet(client: &PrismaClient, wallet_address: ethers::types::H160) -> Result<()> {
+    info!("Locking wallet");
+
+    // The insert locks the row of the wallet, whether or not it already exists.
+    client
+        ._execute_raw(raw!(
+            "INSERT INTO SponsorshipLock (walletAddress, createdAt, updatedAt) VALUES ({}, NOW(3), NOW(3)) ON DUPLICATE KEY UPDATE updatedAt = NOW(3)",
+            PrismaValue::String(to_checksum(&wallet_address, None))
+        ))
+        .exec()
+        .await?;
+
+    Ok(())
+}
+
+// -----------------------------------------------------------------------------
+// Create
+// -----------------------------------------------------------------------------
+
+/// Create a new sponsorship reservation
+#[autometrics]
+pub async fn create_sponsorship_reservation(
+    client: &PrismaClient,
+    wallet_address: ethers::types::H160,
+    chain_id: i64,
+    cost_usd: f64,
+    expires_at: DateTime<Utc>,
+) -> Result<sponsorship_reservation::Data> {
+    info!("Creating new sponsorship reservation");
+
+    let sponsorship_reservation = client
+        .sponsorship_reservation()
+        .create(chain_id, cost_usd, expires_at.into(), to_checksum(&wallet_address, None), vec![])
+        .exec()
+        .await?;
+    info!(?sponsorship_reservation);
+
+    Ok(sponsorship_reservation)
+}
+
+// -----------------------------------------------------------------------------
+// Get
+// -----------------------------------------------------------------------------
+
+/// Get the unexpired sponsorship reservations of the wallet
+#[autometrics]
+pub async fn get_wallet_sponsorship_reservations(
+    client: &PrismaClient,
+    wallet_address: ethers::types::H160,
+    now: DateTime<Utc>,
+) -> Result<Vec<sponsorship_reservation::Data>> {
+    info!("Getting wallet sponsorship reservations");
+
+    let sponsorship_reservations = client
+        .sponsorship_reservation()
+        .find_many(vec![
+            sponsorship_reservation::wallet_address::equals(to_checksum(&wallet_address, None)),
+            sponsorship_reservation::expires_at::gt(now.into()),
+        ])
+        .exec()
+        .await?;
+
+    Ok(sponsorship_reservations)
+}
+
+// -----------------------------------------------------------------------------
+// Delete
+// -----------------------------------------------------------------------------
+
+/// Delete the sponsorship reservation, releasing the reserved cost
+#[autometrics]
+pub async fn delete_sponsorship_reservation(db: Database, id: String) -> Result<()> {
+    info!("Deleting sponsorship reservation");
+
+    db.sponsorship_reservation()
+        .delete_many(vec![sponsorship_reservation::id::equals(id)])
+        .exec()
+        .await?;
+
+    Ok(())
+}
+
+/// Settle the oldest unexpired sponsorship reservation of the wallet on the chain, as the cost of
+/// the sponsorship is now in the billing operation
+#[autometrics]
+pub async fn settle_sponsorship_reservation(
+    client: &PrismaClient,
+    wallet_address: ethers::types::H160,
+    chain_id: i64,
+) -> Result<()> {
+    info!("Settling sponsorship reservation");
+
+    let sponsorship_reservation = client
+        .sponsorship_reservation()
+        .find_first(vec![
+            sponsorship_reservation::wallet_address::equals(to_checksum(&wallet_address, None)),
+            sponsorship_reservation::chain_id::equals(chain_id),
+            sponsorship_reservation::expires_at::gt(Utc::now().into()),
+        ])
+        .order_by(sponsorship_reservation::created_at::order(Direction::Asc))
+        .exec()
+        .await?;
+
+    if let Some(sponsorship_reservation) = sponsorship_reservation {
+        client
+            .sponsorship_reservation()
+            .delete_many(vec![sponsorship_reservation::id::equals(sponsorship_reservation.id)])
+            .exec()
+            .await?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,48 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::types::Database;
+use autometrics::autometrics;
+use ethers::utils::to_checksum;
+use eyre::Result;
+use lightdotso_prisma::{token, token_price};
+use lightdotso_tracing::tracing::info;
+use prisma_client_rust::Direction;
+
+// -----------------------------------------------------------------------------
+// Get
+// -----------------------------------------------------------------------------
+
+/// Get the token w/ the latest price
+#[autometrics]
+pub async fn get_token_with_latest_price(
+    db: Database,
+    chain_id: i64,
+    token_address: ethers::types::H160,
+) -> Result<Option<token::Data>> {
+    info!("Getting token w/ the latest price");
+
+    let token = db
+        .token()
+        .find_unique(token::address_chain_id(to_checksum(&token_address, None), chain_id))
+        .with(
+            token::prices::fetch(vec![])
+                .order_by(token_price::timestamp::order(Direction::Desc))
+                .take(1),
+        )
+        .exec()
+        .await?;
+
+    Ok(token)
+}
@@ -22,7 +22,7 @@ use crate::{
 use autometrics::autometrics;
 use axum::extract::Json;
 use ethers::{
-    types::{Bytes, Log, H256, U256},
+    types::{Address, Bytes, Log, H256, U256},
     utils::to_checksum,
 };
 use eyre::Result;
@@ -91,11 +91,11 @@ pub async fn upsert_user_operation(
                 uow.nonce.unwrap_or(0.into()).low_u64() as i64,
                 uow.init_code.clone().unwrap_or_else(|| vec![].into()).to_vec(),
                 uow.call_data.clone().unwrap_or_else(|| vec![].into()).to_vec(),
-                uow.call_gas_limit.unwrap_or(0.into()).low_u64() as i64,
-                uow.verification_gas_limit.unwrap_or(0.into()).low_u64() as i64,
-                uow.pre_verification_gas.unwrap_or(0.into()).low_u64() as i64,
-                uow.max_fee_per_gas.unwrap_or(0.into()).low_u64() as i64,
-                uow.max_priority_fee_per_gas.unwrap_or(0.into()).low_u64() as i64,
+                uow.call_gas_limit.unwrap_or(0.into()).to_string(),
+                uow.verification_gas_limit.unwrap_or(0.into()).to_string(),
+                uow.pre_verification_gas.unwrap_or(0.into()).to_string(),
+                uow.max_fee_per_gas.unwrap_or(0.into()).to_string(),
+                uow.max_priority_fee_per_gas.unwrap_or(0.into()).to_string(),
                 uow.paymaster_and_data.clone().unwrap_or_else(|| vec![].into()).to_vec(),
                 chain::id::equals(chain_id),
                 wallet::address::equals(to_checksum(&uow.light_wallet, None)),
@@ -242,7 +242,7 @@ pub async fn upsert_user_operation_logs(
 pub async fn get_user_operation_with_chain_id(
     db: Database,
     user_operation_hash: ethers::types::H256,
-) -> Result<(UserOperation, u64)> {
+) -> Result<(UserOperation, u64, Address)> {
     info!("Getting user operation");
 
     // Get the user operation
@@ -255,11 +255,14 @@ pub async fn get_user_operation_with_chain_id(
     // If user operation is none, throw an error
     let user_operation_data = user_operation.ok_or_else(|| DbError::NotFound)?;
 
+    // Parse the entry point the user operation was created for
+    let entry_point: Address = user_operation_data.entry_point.parse()?;
+
     // Convert the user operation into a UserOperation
-    let user_operation = user_operation_data.clone().into();
+    let user_operation = user_operation_data.clone().try_into()?;
 
     // Return the user operation
-    Ok((user_operation, user_operation_data.chain_id as u64))
+    Ok((user_operation, user_operation_data.chain_id as u64, entry_point))
 }
 
 pub async fn get_user_operation_with_logs(
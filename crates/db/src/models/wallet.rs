@@ -15,9 +15,16 @@
 use crate::types::{AppJsonResult, Database};
 use autometrics::autometrics;
 use axum::extract::Json;
-use ethers::{types::H256, utils::to_checksum};
-use lightdotso_prisma::wallet;
+use ethers::{
+    providers::Middleware,
+    types::{H160, H256},
+    utils::to_checksum,
+};
+use eyre::Result;
+use lightdotso_contracts::provider::get_provider;
+use lightdotso_prisma::{transaction, user_operation, wallet, wallet_balance};
 use lightdotso_tracing::tracing::info;
+use std::collections::BTreeSet;
 
 // -----------------------------------------------------------------------------
 // Upsert
@@ -52,6 +59,70 @@ pub async fn upsert_wallet_with_configuration(
     Ok(Json::from(wallet))
 }
 
+// -----------------------------------------------------------------------------
+// Get
+// -----------------------------------------------------------------------------
+
+/// Get the chains the wallet has any record on, from the user operations, the indexed transactions
+/// and the balances of the wallet.
+#[autometrics]
+pub async fn get_wallet_chain_ids(db: Database, address: H160) -> Result<BTreeSet<i64>> {
+    info!("Getting wallet chain ids at address: {:?}", address);
+
+    let checksum_address = to_checksum(&address, None);
+
+    // Get the chains of the user operations of the wallet.
+    let user_operations = db
+        .user_operation()
+        .find_many(vec![user_operation::sender::equals(checksum_address.clone())])
+        .exec()
+        .await?;
+
+    // Get the chains of the transactions indexed for the wallet, e.g. the deployments by others.
+    let transactions = db
+        .transaction()
+        .find_many(vec![transaction::wallets::some(vec![wallet::address::equals(
+            checksum_address.clone(),
+        )])])
+        .exec()
+        .await?;
+
+    // Get the chains of the latest balances of the wallet.
+    let wallet_balances = db
+        .wallet_balance()
+        .find_many(vec![
+            wallet_balance::wallet_address::equals(checksum_address),
+            wallet_balance::is_latest::equals(true),
+        ])
+        .exec()
+        .await?;
+
+    Ok(user_operations
+        .iter()
+        .map(|op| op.chain_id)
+        .chain(transactions.iter().map(|tx| tx.chain_id))
+        .chain(wallet_balances.iter().map(|balance| balance.chain_id))
+        .collect())
+}
+
+/// Get the chains the wallet is deployed on, w/ the code of the wallet on each of the chains it
+/// has any record on.
+#[autometrics]
+pub async fn get_wallet_deployed_chain_ids(db: Database, address: H160) -> Result<Vec<u64>> {
+    let mut chain_ids = vec![];
+    for chain_id in get_wallet_chain_ids(db, address).await? {
+        let provider = get_provider(chain_id as u64).await?;
+        let code = provider.get_code(address, None).await?;
+        if !code.is_empty() {
+            chain_ids.push(chain_id as u64);
+        } else {
+            info!("Wallet {:?} is not deployed on chain {}", address, chain_id);
+        }
+    }
+
+    Ok(chain_ids)
+}
+
 // -----------------------------------------------------------------------------
 // Tests
 // -----------------------------------------------------------------------------
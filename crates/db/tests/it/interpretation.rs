@@ -74,6 +74,7 @@ async fn test_integration_upsert_interpretation_with_actions() -> Result<()> {
                     token_id: None,
                     token_type: AssetTokenType::Erc1155,
                 },
+                protocol: None,
             },
             AssetChange {
                 address: Address::zero(),
@@ -89,6 +90,7 @@ async fn test_integration_upsert_interpretation_with_actions() -> Result<()> {
                     token_id: Some(1.into()),
                     token_type: AssetTokenType::Erc1155,
                 },
+                protocol: None,
             },
             AssetChange {
                 address: Address::zero(),
@@ -104,8 +106,10 @@ async fn test_integration_upsert_interpretation_with_actions() -> Result<()> {
                     token_id: Some(1.into()),
                     token_type: AssetTokenType::Erc1155,
                 },
+                protocol: None,
             },
         ],
+        chains: vec![],
     };
 
     // Get a transaction with logs.
@@ -138,6 +142,7 @@ async fn test_integration_upsert_interpretation_with_actions_empty() -> Result<(
         exit_reason: InstructionResult::Stop,
         actions: vec![],
         asset_changes: vec![],
+        chains: vec![],
     };
 
     // Get a transaction with logs.
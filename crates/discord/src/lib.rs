@@ -52,3 +52,24 @@ pub async fn notify_create_wallet(
 
     Ok(())
 }
+
+pub async fn notify_low_paymaster_deposit(
+    webhook: &str,
+    paymaster: &str,
+    chain_id: &str,
+    deposit: &str,
+    runway_hours: &str,
+) -> Result<()> {
+    let embed = Embed::fake(|e| {
+        e.title("Paymaster Deposit Low")
+            .description(format!(
+                "Paymaster: {}\nChainid: {}\nDeposit: {}\nRunway: {} hours\n",
+                paymaster, chain_id, deposit, runway_hours
+            ))
+            .color(0xff0000)
+    });
+
+    notify(webhook, embed).await?;
+
+    Ok(())
+}
@@ -0,0 +1,133 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::{
+    adapter::Adapter,
+    adapters::lending::lending_asset_change,
+    constants::{
+        InterpretationActionType, InterpretationProtocol, AAVE_V3_BORROW_EVENT_TOPIC,
+        AAVE_V3_POOL_ABI, AAVE_V3_REPAY_EVENT_TOPIC, AAVE_V3_SUPPLY_EVENT_TOPIC,
+    },
+    types::{AdapterResponse, AssetProtocol, InterpretationRequest},
+};
+use async_trait::async_trait;
+use ethers_main::{
+    abi::Address,
+    contract::BaseContract,
+    types::{Log, U256},
+};
+use eyre::Result;
+use lightdotso_simulator::evm::Evm;
+use lightdotso_tracing::tracing::{info, warn};
+
+#[derive(Clone)]
+pub(crate) struct AaveV3Adapter {
+    abi: BaseContract,
+}
+
+impl AaveV3Adapter {
+    pub fn new() -> Self {
+        let pool_abi: BaseContract = AAVE_V3_POOL_ABI.clone();
+        AaveV3Adapter { abi: pool_abi }
+    }
+
+    /// Decode the action, the account, the reserve and the amount of the log.
+    fn decode_log(&self, log: &Log) -> Result<(InterpretationActionType, Address, Address, U256)> {
+        let topics = log.clone().topics;
+        let data = log.clone().data;
+
+        if log.topics[0] == *AAVE_V3_SUPPLY_EVENT_TOPIC {
+            let (reserve, _user, on_behalf_of, amount, _referral_code): (
+                Address,
+                Address,
+                Address,
+                U256,
+                u16,
+            ) = self.abi.decode_event("Supply", topics, data)?;
+            Ok((InterpretationActionType::LendingSupply, on_behalf_of, reserve, amount))
+        } else if log.topics[0] == *AAVE_V3_BORROW_EVENT_TOPIC {
+            let (
+                reserve,
+                _user,
+                on_behalf_of,
+                amount,
+                _interest_rate_mode,
+                _borrow_rate,
+                _referral_code,
+            ): (Address, Address, Address, U256, u8, U256, u16) =
+                self.abi.decode_event("Borrow", topics, data)?;
+            Ok((InterpretationActionType::LendingBorrow, on_behalf_of, reserve, amount))
+        } else {
+            let (reserve, user, _repayer, amount, _use_a_tokens): (
+                Address,
+                Address,
+                Address,
+                U256,
+                bool,
+            ) = self.abi.decode_event("Repay", topics, data)?;
+            Ok((InterpretationActionType::LendingRepay, user, reserve, amount))
+        }
+    }
+}
+
+/// Whether the log is a Supply, Borrow or Repay event of the Aave V3 pool.
+fn is_aave_v3_log(log: &Log) -> bool {
+    log.topics.len() == 4 &&
+        (log.topics[0] == *AAVE_V3_SUPPLY_EVENT_TOPIC ||
+            log.topics[0] == *AAVE_V3_BORROW_EVENT_TOPIC ||
+            log.topics[0] == *AAVE_V3_REPAY_EVENT_TOPIC)
+}
+
+#[async_trait]
+impl Adapter for AaveV3Adapter {
+    fn matches(&self, request: InterpretationRequest) -> bool {
+        request.logs.iter().any(is_aave_v3_log)
+    }
+    async fn query(
+        &self,
+        _evm: &mut Evm,
+        request: InterpretationRequest,
+    ) -> Result<AdapterResponse> {
+        // Get all the logs that match the Aave V3 pool events
+        let logs = request.logs.iter().filter(|log| is_aave_v3_log(log)).collect::<Vec<_>>();
+        info!("logs: {:?}", logs);
+
+        let mut actions = Vec::new();
+        let mut asset_changes = Vec::new();
+
+        for log in logs {
+            // Skip the logs that don't decode as the Aave V3 events
+            let (action_type, address, reserve, amount) = match self.decode_log(log) {
+                Ok(decoded) => decoded,
+                Err(e) => {
+                    warn!("Failed to decode the Aave V3 log: {:?}", e);
+                    continue;
+                }
+            };
+
+            let (action, asset_change) = lending_asset_change(
+                action_type,
+                AssetProtocol { address: log.address, protocol: InterpretationProtocol::AaveV3 },
+                address,
+                reserve,
+                amount,
+            );
+
+            // Add the actions and asset changes to the vectors
+            actions.push(action);
+            asset_changes.push(asset_change);
+        }
+
+        Ok(AdapterResponse { actions, asset_changes })
+    }
+}
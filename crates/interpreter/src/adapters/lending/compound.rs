@@ -0,0 +1,181 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::{
+    adapter::Adapter,
+    adapters::{call_address, lending::lending_asset_change},
+    constants::{
+        InterpretationActionType, InterpretationProtocol, COMPOUND_BORROW_EVENT_TOPIC,
+        COMPOUND_CETH_ADDRESS, COMPOUND_CTOKEN_ABI, COMPOUND_MINT_EVENT_TOPIC,
+        COMPOUND_REPAY_BORROW_EVENT_TOPIC,
+    },
+    types::{AdapterResponse, AssetProtocol, InterpretationRequest},
+};
+use async_trait::async_trait;
+use ethers_main::{
+    abi::Address,
+    contract::BaseContract,
+    types::{Log, U256},
+};
+use eyre::Result;
+use lightdotso_simulator::evm::Evm;
+use lightdotso_tracing::tracing::{info, warn};
+
+#[derive(Clone)]
+pub(crate) struct CompoundAdapter {
+    abi: BaseContract,
+}
+
+impl CompoundAdapter {
+    pub fn new() -> Self {
+        let ctoken_abi: BaseContract = COMPOUND_CTOKEN_ABI.clone();
+        CompoundAdapter { abi: ctoken_abi }
+    }
+
+    /// Decode the action, the account and the amount of the log.
+    fn decode_log(&self, log: &Log) -> Result<(InterpretationActionType, Address, U256)> {
+        let topics = log.clone().topics;
+        let data = log.clone().data;
+
+        if log.topics[0] == *COMPOUND_MINT_EVENT_TOPIC {
+            let (minter, mint_amount, _mint_tokens): (Address, U256, U256) =
+                self.abi.decode_event("Mint", topics, data)?;
+            Ok((InterpretationActionType::LendingSupply, minter, mint_amount))
+        } else if log.topics[0] == *COMPOUND_BORROW_EVENT_TOPIC {
+            let (borrower, borrow_amount, _account_borrows, _total_borrows): (
+                Address,
+                U256,
+                U256,
+                U256,
+            ) = self.abi.decode_event("Borrow", topics, data)?;
+            Ok((InterpretationActionType::LendingBorrow, borrower, borrow_amount))
+        } else {
+            let (_payer, borrower, repay_amount, _account_borrows, _total_borrows): (
+                Address,
+                Address,
+                U256,
+                U256,
+                U256,
+            ) = self.abi.decode_event("RepayBorrow", topics, data)?;
+            Ok((InterpretationActionType::LendingRepay, borrower, repay_amount))
+        }
+    }
+}
+
+/// Whether the log is a Mint, Borrow or RepayBorrow event of the Compound cToken.
+/// The cToken events don't have indexed params, unlike the Uniswap V2 Mint event w/ the same
+/// signature.
+fn is_compound_log(log: &Log) -> bool {
+    log.topics.len() == 1 &&
+        (log.topics[0] == *COMPOUND_MINT_EVENT_TOPIC ||
+            log.topics[0] == *COMPOUND_BORROW_EVENT_TOPIC ||
+            log.topics[0] == *COMPOUND_REPAY_BORROW_EVENT_TOPIC)
+}
+
+#[async_trait]
+impl Adapter for CompoundAdapter {
+    fn matches(&self, request: InterpretationRequest) -> bool {
+        request.logs.iter().any(is_compound_log)
+    }
+    async fn query(
+        &self,
+        evm: &mut Evm,
+        request: InterpretationRequest,
+    ) -> Result<AdapterResponse> {
+        // Get all the logs that match the Compound cToken events
+        let logs = request.logs.iter().filter(|log| is_compound_log(log)).collect::<Vec<_>>();
+        info!("logs: {:?}", logs);
+
+        let mut actions = Vec::new();
+        let mut asset_changes = Vec::new();
+
+        for log in logs {
+            // Skip the logs that don't decode as the Compound events
+            let (action_type, address, amount) = match self.decode_log(log) {
+                Ok(decoded) => decoded,
+                Err(e) => {
+                    warn!("Failed to decode the Compound log: {:?}", e);
+                    continue;
+                }
+            };
+
+            // Get the underlying token of the cToken, the native token for cETH w/o underlying
+            let underlying = if log.address == *COMPOUND_CETH_ADDRESS {
+                Address::zero()
+            } else {
+                call_address(evm, &self.abi, log.address, "underlying").await?
+            };
+
+            let (action, asset_change) = lending_asset_change(
+                action_type,
+                AssetProtocol {
+                    address: log.address,
+                    protocol: InterpretationProtocol::CompoundV2,
+                },
+                address,
+                underlying,
+                amount,
+            );
+
+            // Add the actions and asset changes to the vectors
+            actions.push(action);
+            asset_changes.push(asset_change);
+        }
+
+        Ok(AdapterResponse { actions, asset_changes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::TRANSFER_EVENT_TOPIC;
+    use ethers_main::types::H256;
+
+    #[test]
+    fn test_is_compound_log() {
+        let log = Log { topics: vec![*COMPOUND_MINT_EVENT_TOPIC], ..Default::default() };
+        assert!(is_compound_log(&log));
+
+        // The Uniswap V2 Mint event has the indexed sender
+        let log =
+            Log { topics: vec![*COMPOUND_MINT_EVENT_TOPIC, H256::zero()], ..Default::default() };
+        assert!(!is_compound_log(&log));
+
+        let log = Log { topics: vec![*TRANSFER_EVENT_TOPIC], ..Default::default() };
+        assert!(!is_compound_log(&log));
+    }
+
+    #[test]
+    fn test_decode_log() {
+        let adapter = CompoundAdapter::new();
+        let borrower = Address::repeat_byte(1);
+
+        let log = Log {
+            topics: vec![*COMPOUND_BORROW_EVENT_TOPIC],
+            data: ethers_main::abi::encode(&[
+                ethers_main::abi::Token::Address(borrower),
+                ethers_main::abi::Token::Uint(100.into()),
+                ethers_main::abi::Token::Uint(100.into()),
+                ethers_main::abi::Token::Uint(1000.into()),
+            ])
+            .into(),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            adapter.decode_log(&log).unwrap(),
+            (InterpretationActionType::LendingBorrow, borrower, 100.into())
+        );
+    }
+}
@@ -0,0 +1,44 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+pub(crate) mod aave;
+pub(crate) mod compound;
+
+use crate::{
+    constants::{InterpretationActionType, InterpretationProtocol},
+    types::{AssetChange, AssetProtocol, AssetToken, AssetTokenType, InterpretationAction},
+};
+use ethers_main::{abi::Address, types::U256};
+
+/// Get the action and the asset change of a supply, borrow or repay of the lending pool.
+pub(crate) fn lending_asset_change(
+    action_type: InterpretationActionType,
+    protocol: AssetProtocol,
+    address: Address,
+    token: Address,
+    amount: U256,
+) -> (InterpretationAction, AssetChange) {
+    let action = InterpretationAction { action_type, address: Some(address) };
+
+    let asset_change = AssetChange {
+        address,
+        action: action.clone(),
+        token: AssetToken { address: token, token_id: None, token_type: AssetTokenType::Erc20 },
+        before_amount: None,
+        after_amount: None,
+        amount,
+        protocol: Some(protocol),
+    };
+
+    (action, asset_change)
+}
@@ -12,18 +12,197 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub(crate) mod lending;
+pub(crate) mod swap;
 pub(crate) mod transfer;
+pub(crate) mod wrap;
 
 use crate::{
     adapter::Adapter,
-    adapters::transfer::{
-        erc1155::ERC1155Adapter, erc20::ERC20Adapter, erc721::ERC721Adapter, eth::EthAdapter,
+    adapters::{
+        lending::{aave::AaveV3Adapter, compound::CompoundAdapter},
+        swap::{uniswap_v2::UniswapV2Adapter, uniswap_v3::UniswapV3Adapter},
+        transfer::{
+            erc1155::ERC1155Adapter, erc20::ERC20Adapter, erc721::ERC721Adapter, eth::EthAdapter,
+        },
+        wrap::weth::WethAdapter,
     },
+    constants::InterpretationActionType,
+    types::{AdapterResponse, AssetChange},
 };
+use ethers_main::{abi::Address, contract::BaseContract};
+use eyre::Result;
 use lazy_static::lazy_static;
+use lightdotso_simulator::evm::Evm;
 
 lazy_static! {
     #[derive(Clone)]
     pub static ref ADAPTERS: Vec<Box<dyn Adapter + Sync + Send>> =
-        vec![Box::new(EthAdapter::new()), Box::new(ERC20Adapter::new()), Box::new(ERC721Adapter::new()), Box::new(ERC1155Adapter::new())];
+        vec![Box::new(EthAdapter::new()), Box::new(ERC20Adapter::new()), Box::new(ERC721Adapter::new()), Box::new(ERC1155Adapter::new()), Box::new(UniswapV2Adapter::new()), Box::new(UniswapV3Adapter::new()), Box::new(WethAdapter::new()), Box::new(AaveV3Adapter::new()), Box::new(CompoundAdapter::new())];
+}
+
+/// Call the view method of the contract w/o arguments, which returns an address.
+pub(crate) async fn call_address(
+    evm: &mut Evm,
+    abi: &BaseContract,
+    contract: Address,
+    method: &str,
+) -> Result<Address> {
+    // Encode the method to call
+    let calldata = abi.encode(method, ())?;
+
+    // Call the contract method
+    let res = evm.call_raw(Address::zero(), contract, Some(0.into()), Some(calldata)).await?;
+
+    // Decode the output
+    let address: Address = abi.decode_output(method, res.return_data)?;
+
+    Ok(address)
+}
+
+/// Whether the action sends the asset out of the holder, or `None` if it doesn't move an ERC20.
+fn is_outgoing(action_type: &InterpretationActionType) -> Option<bool> {
+    match action_type {
+        InterpretationActionType::ERC20Send |
+        InterpretationActionType::SwapSend |
+        InterpretationActionType::LendingSupply |
+        InterpretationActionType::LendingRepay => Some(true),
+        InterpretationActionType::ERC20Receive |
+        InterpretationActionType::SwapReceive |
+        InterpretationActionType::LendingBorrow => Some(false),
+        _ => None,
+    }
+}
+
+/// Whether the asset change of the protocol is the same movement as the ERC20 transfer.
+fn is_same_movement(protocol_change: &AssetChange, transfer_change: &AssetChange) -> bool {
+    transfer_change.protocol.is_none() &&
+        transfer_change.address == protocol_change.address &&
+        transfer_change.token.address == protocol_change.token.address &&
+        transfer_change.amount == protocol_change.amount &&
+        is_outgoing(&transfer_change.action.action_type).is_some() &&
+        is_outgoing(&transfer_change.action.action_type) ==
+            is_outgoing(&protocol_change.action.action_type)
+}
+
+/// Merge the ERC20 transfer asset changes into the asset changes of the protocols that move the
+/// same tokens, so that a swap or a supply isn't also shown as the transfers underneath it.
+/// The protocol asset change keeps the balances of the transfer.
+pub(crate) fn merge_protocol_asset_changes(responses: &mut [AdapterResponse]) {
+    // Get the positions of the transfer asset changes merged into the protocol asset changes
+    let mut merged: Vec<(usize, usize)> = vec![];
+    let mut balances = vec![];
+    for (i, response) in responses.iter().enumerate() {
+        for (j, change) in response.asset_changes.iter().enumerate() {
+            if change.protocol.is_none() {
+                continue;
+            }
+            let transfer = responses.iter().enumerate().find_map(|(k, res)| {
+                res.asset_changes.iter().enumerate().find_map(|(l, transfer_change)| {
+                    (!merged.contains(&(k, l)) && is_same_movement(change, transfer_change))
+                        .then_some((k, l, transfer_change))
+                })
+            });
+            if let Some((k, l, transfer_change)) = transfer {
+                merged.push((k, l));
+                balances.push((
+                    (i, j),
+                    transfer_change.before_amount,
+                    transfer_change.after_amount,
+                ));
+            }
+        }
+    }
+
+    // Keep the balances of the transfers on the protocol asset changes
+    for ((i, j), before_amount, after_amount) in balances {
+        let change = &mut responses[i].asset_changes[j];
+        change.before_amount = before_amount;
+        change.after_amount = after_amount;
+    }
+
+    // Remove the merged transfer asset changes
+    for (k, response) in responses.iter_mut().enumerate() {
+        let mut l = 0;
+        response.asset_changes.retain(|_| {
+            let keep = !merged.contains(&(k, l));
+            l += 1;
+            keep
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        constants::InterpretationProtocol,
+        types::{AssetProtocol, AssetToken, AssetTokenType, InterpretationAction},
+    };
+    use ethers_main::types::U256;
+
+    fn asset_change(
+        action_type: InterpretationActionType,
+        address: Address,
+        amount: U256,
+        protocol: Option<AssetProtocol>,
+    ) -> AssetChange {
+        AssetChange {
+            address,
+            action: InterpretationAction { action_type, address: Some(address) },
+            token: AssetToken {
+                address: Address::repeat_byte(9),
+                token_id: None,
+                token_type: AssetTokenType::Erc20,
+            },
+            before_amount: protocol.is_none().then_some(U256::from(1000)),
+            after_amount: protocol.is_none().then_some(U256::from(900)),
+            amount,
+            protocol,
+        }
+    }
+
+    #[test]
+    fn test_merge_protocol_asset_changes() {
+        let user = Address::repeat_byte(1);
+        let pool = Address::repeat_byte(2);
+        let protocol =
+            Some(AssetProtocol { address: pool, protocol: InterpretationProtocol::UniswapV2 });
+
+        let mut responses = vec![
+            AdapterResponse {
+                actions: vec![],
+                asset_changes: vec![
+                    asset_change(InterpretationActionType::ERC20Send, user, 100.into(), None),
+                    asset_change(InterpretationActionType::ERC20Receive, pool, 100.into(), None),
+                    // A separate transfer of the same amount to the user
+                    asset_change(InterpretationActionType::ERC20Receive, user, 100.into(), None),
+                ],
+            },
+            AdapterResponse {
+                actions: vec![],
+                asset_changes: vec![asset_change(
+                    InterpretationActionType::SwapSend,
+                    user,
+                    100.into(),
+                    protocol.clone(),
+                )],
+            },
+        ];
+        merge_protocol_asset_changes(&mut responses);
+
+        // Only the send of the user is merged into the swap
+        assert_eq!(responses[0].asset_changes.len(), 2);
+        assert_eq!(responses[0].asset_changes[0].address, pool);
+        assert_eq!(
+            responses[0].asset_changes[1].action.action_type,
+            InterpretationActionType::ERC20Receive
+        );
+
+        // The swap keeps the balances of the transfer
+        let swap = &responses[1].asset_changes[0];
+        assert_eq!(swap.protocol, protocol);
+        assert_eq!(swap.before_amount, Some(1000.into()));
+        assert_eq!(swap.after_amount, Some(900.into()));
+    }
 }
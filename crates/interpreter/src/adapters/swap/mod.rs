@@ -0,0 +1,139 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+pub(crate) mod uniswap_v2;
+pub(crate) mod uniswap_v3;
+
+use crate::{
+    constants::{InterpretationActionType, InterpretationProtocol, TRANSFER_EVENT_TOPIC},
+    types::{AssetChange, AssetProtocol, AssetToken, AssetTokenType, InterpretationAction},
+};
+use ethers_main::{
+    abi::Address,
+    types::{Log, U256},
+};
+
+/// Get the sender of the input token of the pool from the ERC20 Transfer logs, so that each hop
+/// of a multi-hop swap is attributed to the address that paid it.
+pub(crate) fn get_transfer_sender(
+    logs: &[Log],
+    token: Address,
+    pool: Address,
+    amount: U256,
+) -> Option<Address> {
+    logs.iter()
+        .find(|log| {
+            log.address == token &&
+                log.topics.len() == 3 &&
+                log.topics[0] == *TRANSFER_EVENT_TOPIC &&
+                Address::from(log.topics[2]) == pool &&
+                log.data.len() == 32 &&
+                U256::from_big_endian(&log.data) == amount
+        })
+        .map(|log| Address::from(log.topics[1]))
+}
+
+/// A swap of a pool, decoded from the swap event.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct Swap {
+    /// The address of the pool
+    pub pool: Address,
+    /// The address that sent the input token
+    pub from: Address,
+    /// The address that received the output token
+    pub to: Address,
+    /// The input token
+    pub token_in: Address,
+    /// The amount of the input token
+    pub amount_in: U256,
+    /// The output token
+    pub token_out: Address,
+    /// The amount of the output token
+    pub amount_out: U256,
+}
+
+impl Swap {
+    /// Get the actions and the asset changes of the swap.
+    pub fn interpret(
+        &self,
+        protocol: InterpretationProtocol,
+    ) -> (Vec<InterpretationAction>, Vec<AssetChange>) {
+        let asset_protocol = AssetProtocol { address: self.pool, protocol };
+
+        // Get the actions for the sender and the recipient
+        let send_action = InterpretationAction {
+            action_type: InterpretationActionType::SwapSend,
+            address: Some(self.from),
+        };
+        let receive_action = InterpretationAction {
+            action_type: InterpretationActionType::SwapReceive,
+            address: Some(self.to),
+        };
+
+        // Get the asset changes for the sender and the recipient
+        let send_asset_change = AssetChange {
+            address: self.from,
+            action: send_action.clone(),
+            token: AssetToken {
+                address: self.token_in,
+                token_id: None,
+                token_type: AssetTokenType::Erc20,
+            },
+            before_amount: None,
+            after_amount: None,
+            amount: self.amount_in,
+            protocol: Some(asset_protocol.clone()),
+        };
+        let receive_asset_change = AssetChange {
+            address: self.to,
+            action: receive_action.clone(),
+            token: AssetToken {
+                address: self.token_out,
+                token_id: None,
+                token_type: AssetTokenType::Erc20,
+            },
+            before_amount: None,
+            after_amount: None,
+            amount: self.amount_out,
+            protocol: Some(asset_protocol),
+        };
+
+        (vec![send_action, receive_action], vec![send_asset_change, receive_asset_change])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers_main::types::H256;
+
+    #[test]
+    fn test_get_transfer_sender() {
+        let token = Address::repeat_byte(9);
+        let router = Address::repeat_byte(1);
+        let pool = Address::repeat_byte(2);
+
+        let mut data = vec![0u8; 32];
+        U256::from(100).to_big_endian(&mut data);
+        let logs = vec![Log {
+            address: token,
+            topics: vec![*TRANSFER_EVENT_TOPIC, H256::from(router), H256::from(pool)],
+            data: data.into(),
+            ..Default::default()
+        }];
+
+        assert_eq!(get_transfer_sender(&logs, token, pool, 100.into()), Some(router));
+        assert_eq!(get_transfer_sender(&logs, token, pool, 99.into()), None);
+        assert_eq!(get_transfer_sender(&logs, token, router, 100.into()), None);
+    }
+}
@@ -0,0 +1,105 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::{
+    adapter::Adapter,
+    adapters::{
+        call_address,
+        swap::{get_transfer_sender, Swap},
+    },
+    constants::{InterpretationProtocol, UNISWAP_V2_PAIR_ABI, UNISWAP_V2_SWAP_EVENT_TOPIC},
+    types::{AdapterResponse, InterpretationRequest},
+};
+use async_trait::async_trait;
+use ethers_main::{abi::Address, contract::BaseContract, types::U256};
+use eyre::Result;
+use lightdotso_simulator::evm::Evm;
+use lightdotso_tracing::tracing::{info, warn};
+
+#[derive(Clone)]
+pub(crate) struct UniswapV2Adapter {
+    abi: BaseContract,
+}
+
+impl UniswapV2Adapter {
+    pub fn new() -> Self {
+        let pair_abi: BaseContract = UNISWAP_V2_PAIR_ABI.clone();
+        UniswapV2Adapter { abi: pair_abi }
+    }
+}
+
+#[async_trait]
+impl Adapter for UniswapV2Adapter {
+    fn matches(&self, request: InterpretationRequest) -> bool {
+        request
+            .logs
+            .iter()
+            .any(|log| log.topics.len() == 3 && log.topics[0] == *UNISWAP_V2_SWAP_EVENT_TOPIC)
+    }
+    async fn query(
+        &self,
+        evm: &mut Evm,
+        request: InterpretationRequest,
+    ) -> Result<AdapterResponse> {
+        // Get all the logs that match the Uniswap V2 Swap event
+        let logs = request
+            .logs
+            .iter()
+            .filter(|log| log.topics.len() == 3 && log.topics[0] == *UNISWAP_V2_SWAP_EVENT_TOPIC)
+            .collect::<Vec<_>>();
+        info!("logs: {:?}", logs);
+
+        let mut actions = Vec::new();
+        let mut asset_changes = Vec::new();
+
+        for log in logs {
+            // Decode the amounts and the recipient from the log, skipping the logs that aren't a
+            // Uniswap V2 swap
+            let decoded: Result<(Address, U256, U256, U256, U256, Address), _> =
+                self.abi.decode_event("Swap", log.clone().topics, log.clone().data);
+            let (sender, amount0_in, amount1_in, amount0_out, amount1_out, to) = match decoded {
+                Ok(decoded) => decoded,
+                Err(e) => {
+                    warn!("Failed to decode the Uniswap V2 Swap log: {:?}", e);
+                    continue;
+                }
+            };
+
+            // Get the tokens of the pair
+            let token0 = call_address(evm, &self.abi, log.address, "token0").await?;
+            let token1 = call_address(evm, &self.abi, log.address, "token1").await?;
+
+            // The input token is the one the pair received
+            let (token_in, amount_in, token_out, amount_out) = if !amount0_in.is_zero() {
+                (token0, amount0_in, token1, amount1_out)
+            } else {
+                (token1, amount1_in, token0, amount0_out)
+            };
+
+            // The input token is sent to the pair beforehand, e.g. by the previous hop
+            let from = get_transfer_sender(&request.logs, token_in, log.address, amount_in)
+                .unwrap_or(sender);
+
+            let swap =
+                Swap { pool: log.address, from, to, token_in, amount_in, token_out, amount_out };
+            let (swap_actions, swap_asset_changes) =
+                swap.interpret(InterpretationProtocol::UniswapV2);
+
+            // Add the actions and asset changes to the vectors
+            actions.extend(swap_actions);
+            asset_changes.extend(swap_asset_changes);
+        }
+
+        Ok(AdapterResponse { actions, asset_changes })
+    }
+}
@@ -0,0 +1,141 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::{
+    adapter::Adapter,
+    adapters::{
+        call_address,
+        swap::{get_transfer_sender, Swap},
+    },
+    constants::{InterpretationProtocol, UNISWAP_V3_POOL_ABI, UNISWAP_V3_SWAP_EVENT_TOPIC},
+    types::{AdapterResponse, InterpretationRequest},
+};
+use async_trait::async_trait;
+use ethers_main::{
+    abi::Address,
+    contract::BaseContract,
+    types::{I256, U256},
+};
+use eyre::{eyre, Result};
+use lightdotso_simulator::evm::Evm;
+use lightdotso_tracing::tracing::{info, warn};
+
+#[derive(Clone)]
+pub(crate) struct UniswapV3Adapter {
+    abi: BaseContract,
+}
+
+impl UniswapV3Adapter {
+    pub fn new() -> Self {
+        let pool_abi: BaseContract = UNISWAP_V3_POOL_ABI.clone();
+        UniswapV3Adapter { abi: pool_abi }
+    }
+}
+
+/// Decode the signed amounts of the pool from the data of the Swap event.
+/// A positive amount is received by the pool, and a negative amount is sent by the pool.
+fn decode_amounts(data: &[u8]) -> Result<(I256, I256)> {
+    if data.len() < 64 {
+        return Err(eyre!("Invalid Swap event data"));
+    }
+    let amount0 = I256::from_raw(U256::from_big_endian(&data[0..32]));
+    let amount1 = I256::from_raw(U256::from_big_endian(&data[32..64]));
+    Ok((amount0, amount1))
+}
+
+#[async_trait]
+impl Adapter for UniswapV3Adapter {
+    fn matches(&self, request: InterpretationRequest) -> bool {
+        request
+            .logs
+            .iter()
+            .any(|log| log.topics.len() == 3 && log.topics[0] == *UNISWAP_V3_SWAP_EVENT_TOPIC)
+    }
+    async fn query(
+        &self,
+        evm: &mut Evm,
+        request: InterpretationRequest,
+    ) -> Result<AdapterResponse> {
+        // Get all the logs that match the Uniswap V3 Swap event
+        let logs = request
+            .logs
+            .iter()
+            .filter(|log| log.topics.len() == 3 && log.topics[0] == *UNISWAP_V3_SWAP_EVENT_TOPIC)
+            .collect::<Vec<_>>();
+        info!("logs: {:?}", logs);
+
+        let mut actions = Vec::new();
+        let mut asset_changes = Vec::new();
+
+        for log in logs {
+            // Get the caller and the recipient from the log
+            let sender = Address::from(log.topics[1]);
+            let to = Address::from(log.topics[2]);
+
+            // Decode the amounts from the log, skipping the logs that aren't a Uniswap V3 swap
+            let (amount0, amount1) = match decode_amounts(&log.data) {
+                Ok(amounts) => amounts,
+                Err(e) => {
+                    warn!("Failed to decode the Uniswap V3 Swap log: {:?}", e);
+                    continue;
+                }
+            };
+
+            // Get the tokens of the pool
+            let token0 = call_address(evm, &self.abi, log.address, "token0").await?;
+            let token1 = call_address(evm, &self.abi, log.address, "token1").await?;
+
+            // The input token is the one w/ the positive amount
+            let (token_in, amount_in, token_out, amount_out) = if amount0.is_positive() {
+                (token0, amount0.unsigned_abs(), token1, amount1.unsigned_abs())
+            } else {
+                (token1, amount1.unsigned_abs(), token0, amount0.unsigned_abs())
+            };
+
+            // The input token is paid by the caller or on its behalf, e.g. by the previous hop
+            let from = get_transfer_sender(&request.logs, token_in, log.address, amount_in)
+                .unwrap_or(sender);
+
+            let swap =
+                Swap { pool: log.address, from, to, token_in, amount_in, token_out, amount_out };
+            let (swap_actions, swap_asset_changes) =
+                swap.interpret(InterpretationProtocol::UniswapV3);
+
+            // Add the actions and asset changes to the vectors
+            actions.extend(swap_actions);
+            asset_changes.extend(swap_asset_changes);
+        }
+
+        Ok(AdapterResponse { actions, asset_changes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_amounts() {
+        // The pool receives 1 ETH of token0, and sends 3,000 USDC of token1
+        let mut data = vec![0u8; 64];
+        U256::exp10(18).to_big_endian(&mut data[0..32]);
+        I256::from(-3_000_000_000i64).into_raw().to_big_endian(&mut data[32..64]);
+
+        let (amount0, amount1) = decode_amounts(&data).unwrap();
+        assert_eq!(amount0.unsigned_abs(), U256::exp10(18));
+        assert!(amount1.is_negative());
+        assert_eq!(amount1.unsigned_abs(), U256::from(3_000_000_000u64));
+
+        assert!(decode_amounts(&[0u8; 32]).is_err());
+    }
+}
@@ -161,6 +161,7 @@ impl Adapter for ERC1155Adapter {
                 before_amount: before_from_balance,
                 after_amount: after_from_balance,
                 amount: value,
+                protocol: None,
             };
 
             // Get the asset changes for the `to` address
@@ -171,6 +172,7 @@ impl Adapter for ERC1155Adapter {
                 before_amount: before_to_balance,
                 after_amount: after_to_balance,
                 amount: value,
+                protocol: None,
             };
 
             // Add the actions and asset changes to the vectors
@@ -263,6 +265,7 @@ impl Adapter for ERC1155Adapter {
                     before_amount: before_from_balance,
                     after_amount: after_from_balance,
                     amount: *value,
+                    protocol: None,
                 };
 
                 // Get the asset changes for the `to` address
@@ -273,6 +276,7 @@ impl Adapter for ERC1155Adapter {
                     before_amount: before_to_balance,
                     after_amount: after_to_balance,
                     amount: *value,
+                    protocol: None,
                 };
 
                 // Add the asset changes to the vector
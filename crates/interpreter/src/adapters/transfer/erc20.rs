@@ -142,6 +142,7 @@ impl Adapter for ERC20Adapter {
                 before_amount: before_from_balance,
                 after_amount: after_from_balance,
                 amount: value,
+                protocol: None,
             };
 
             // Get the asset changes for the `to` address
@@ -156,6 +157,7 @@ impl Adapter for ERC20Adapter {
                 before_amount: before_to_balance,
                 after_amount: after_to_balance,
                 amount: value,
+                protocol: None,
             };
 
             // Add the actions and asset changes to the vectors
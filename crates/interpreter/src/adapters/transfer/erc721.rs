@@ -98,6 +98,7 @@ impl Adapter for ERC721Adapter {
                 before_amount: Some(1.into()),
                 after_amount: Some(0.into()),
                 amount: 1.into(),
+                protocol: None,
             };
 
             // Get the asset changes for the `to` address
@@ -112,6 +113,7 @@ impl Adapter for ERC721Adapter {
                 before_amount: Some(0.into()),
                 after_amount: Some(1.into()),
                 amount: 0.into(),
+                protocol: None,
             };
 
             // Add the actions and asset changes to the vectors
@@ -40,7 +40,7 @@ impl EthAdapter {
 impl Adapter for EthAdapter {
     fn matches(&self, request: InterpretationRequest) -> bool {
         // If the request has a value larger than 0, then it is a native transfer
-        request.value.map_or(false, |v| v > 0) ||
+        request.value.map_or(false, |v| !v.is_zero()) ||
         // If the traces have a value larger than 0, then it is a native transfer
             request.traces.iter().any(|t| t.value.map_or(false, |v| v > 0.into()))
     }
@@ -91,6 +91,7 @@ impl Adapter for EthAdapter {
                 before_amount: Some(before_from_balance),
                 after_amount: Some(after_from_balance),
                 amount: trace.value.unwrap(),
+                protocol: None,
             };
 
             // Get the asset changes for the to address
@@ -101,6 +102,7 @@ impl Adapter for EthAdapter {
                 before_amount: Some(before_to_balance),
                 after_amount: Some(after_to_balance),
                 amount: trace.value.unwrap(),
+                protocol: None,
             };
 
             // Add the actions and asset changes to the vectors
@@ -150,7 +152,8 @@ impl Adapter for EthAdapter {
                 token: token.clone(),
                 before_amount: before_from_balance,
                 after_amount: after_from_balance,
-                amount: request.value.unwrap().into(),
+                amount: request.value.unwrap(),
+                protocol: None,
             };
 
             // Get the asset changes for the to address
@@ -160,7 +163,8 @@ impl Adapter for EthAdapter {
                 token: token.clone(),
                 before_amount: before_to_balance,
                 after_amount: after_to_balance,
-                amount: request.value.unwrap().into(),
+                amount: request.value.unwrap(),
+                protocol: None,
             };
 
             // Add the actions and asset changes to the vectors
@@ -195,7 +199,7 @@ mod tests {
     fn test_check_value_greater_than_zero() {
         let eth_adapter = EthAdapter::new();
 
-        let request = InterpretationRequest { value: Some(10), ..Default::default() };
+        let request = InterpretationRequest { value: Some(10.into()), ..Default::default() };
 
         // Assume that the matches function returns true if value is greater than 0
         assert!(eth_adapter.matches(request));
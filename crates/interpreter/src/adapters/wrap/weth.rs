@@ -0,0 +1,112 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::{
+    adapter::Adapter,
+    constants::{
+        InterpretationActionType, InterpretationProtocol, WETH_ABI, WETH_DEPOSIT_EVENT_TOPIC,
+        WETH_WITHDRAWAL_EVENT_TOPIC,
+    },
+    types::{
+        AdapterResponse, AssetChange, AssetProtocol, AssetToken, AssetTokenType,
+        InterpretationAction, InterpretationRequest,
+    },
+};
+use async_trait::async_trait;
+use ethers_main::{abi::Address, contract::BaseContract, types::U256};
+use eyre::Result;
+use lightdotso_simulator::evm::Evm;
+use lightdotso_tracing::tracing::info;
+
+#[derive(Clone)]
+pub(crate) struct WethAdapter {
+    abi: BaseContract,
+}
+
+impl WethAdapter {
+    pub fn new() -> Self {
+        let weth_abi: BaseContract = WETH_ABI.clone();
+        WethAdapter { abi: weth_abi }
+    }
+}
+
+#[async_trait]
+impl Adapter for WethAdapter {
+    fn matches(&self, request: InterpretationRequest) -> bool {
+        request.logs.iter().any(|log| {
+            log.topics.len() == 2 &&
+                (log.topics[0] == *WETH_DEPOSIT_EVENT_TOPIC ||
+                    log.topics[0] == *WETH_WITHDRAWAL_EVENT_TOPIC)
+        })
+    }
+    async fn query(
+        &self,
+        _evm: &mut Evm,
+        request: InterpretationRequest,
+    ) -> Result<AdapterResponse> {
+        // Get all the logs that match the WETH Deposit or Withdrawal event
+        let logs = request
+            .logs
+            .iter()
+            .filter(|log| {
+                log.topics.len() == 2 &&
+                    (log.topics[0] == *WETH_DEPOSIT_EVENT_TOPIC ||
+                        log.topics[0] == *WETH_WITHDRAWAL_EVENT_TOPIC)
+            })
+            .collect::<Vec<_>>();
+        info!("logs: {:?}", logs);
+
+        let mut actions = Vec::new();
+        let mut asset_changes = Vec::new();
+
+        for log in logs {
+            // Get the action and the event name of the log
+            let (action_type, event) = if log.topics[0] == *WETH_DEPOSIT_EVENT_TOPIC {
+                (InterpretationActionType::NativeWrap, "Deposit")
+            } else {
+                (InterpretationActionType::NativeUnwrap, "Withdrawal")
+            };
+
+            // Get the account and the amount from the log
+            let (address, amount): (Address, U256) =
+                self.abi.decode_event(event, log.clone().topics, log.clone().data)?;
+
+            // Get the action for the account
+            let action = InterpretationAction { action_type, address: Some(address) };
+
+            // Get the asset change of the wrapped token for the account
+            let asset_change = AssetChange {
+                address,
+                action: action.clone(),
+                token: AssetToken {
+                    address: log.address,
+                    token_id: None,
+                    token_type: AssetTokenType::Erc20,
+                },
+                before_amount: None,
+                after_amount: None,
+                amount,
+                protocol: Some(AssetProtocol {
+                    address: log.address,
+                    protocol: InterpretationProtocol::Weth,
+                }),
+            };
+
+            // Add the actions and asset changes to the vectors
+            actions.push(action);
+            asset_changes.push(asset_change);
+        }
+
+        Ok(AdapterResponse { actions, asset_changes })
+    }
+}
@@ -26,6 +26,9 @@ pub struct InterpreterArgs {
     /// The etherscan API key
     #[clap(long, env = "ETHERSCAN_KEYS")]
     pub etherscan_key: Option<String>,
+    /// The path of the state snapshot to interpret on, instead of the live RPC fork
+    #[clap(long, env = "INTERPRETER_SNAPSHOT")]
+    pub snapshot: Option<String>,
 }
 
 impl InterpreterArgs {
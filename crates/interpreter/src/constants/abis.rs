@@ -49,3 +49,58 @@ lazy_static! {
         .expect("Failed to parse ABI"),
     );
 }
+
+lazy_static! {
+    pub static ref UNISWAP_V2_PAIR_ABI: BaseContract = BaseContract::from(
+        parse_abi(&[
+            "event Swap(address indexed sender, uint256 amount0In, uint256 amount1In, uint256 amount0Out, uint256 amount1Out, address indexed to)",
+            "function token0() external view returns (address)",
+            "function token1() external view returns (address)",
+        ])
+        .expect("Failed to parse ABI"),
+    );
+}
+
+lazy_static! {
+    pub static ref UNISWAP_V3_POOL_ABI: BaseContract = BaseContract::from(
+        parse_abi(&[
+            "event Swap(address indexed sender, address indexed recipient, int256 amount0, int256 amount1, uint160 sqrtPriceX96, uint128 liquidity, int24 tick)",
+            "function token0() external view returns (address)",
+            "function token1() external view returns (address)",
+        ])
+        .expect("Failed to parse ABI"),
+    );
+}
+
+lazy_static! {
+    pub static ref WETH_ABI: BaseContract = BaseContract::from(
+        parse_abi(&[
+            "event Deposit(address indexed dst, uint256 wad)",
+            "event Withdrawal(address indexed src, uint256 wad)",
+        ])
+        .expect("Failed to parse ABI"),
+    );
+}
+
+lazy_static! {
+    pub static ref AAVE_V3_POOL_ABI: BaseContract = BaseContract::from(
+        parse_abi(&[
+            "event Supply(address indexed reserve, address user, address indexed onBehalfOf, uint256 amount, uint16 indexed referralCode)",
+            "event Borrow(address indexed reserve, address user, address indexed onBehalfOf, uint256 amount, uint8 interestRateMode, uint256 borrowRate, uint16 indexed referralCode)",
+            "event Repay(address indexed reserve, address indexed user, address indexed repayer, uint256 amount, bool useATokens)",
+        ])
+        .expect("Failed to parse ABI"),
+    );
+}
+
+lazy_static! {
+    pub static ref COMPOUND_CTOKEN_ABI: BaseContract = BaseContract::from(
+        parse_abi(&[
+            "event Mint(address minter, uint256 mintAmount, uint256 mintTokens)",
+            "event Borrow(address borrower, uint256 borrowAmount, uint256 accountBorrows, uint256 totalBorrows)",
+            "event RepayBorrow(address payer, address borrower, uint256 repayAmount, uint256 accountBorrows, uint256 totalBorrows)",
+            "function underlying() external view returns (address)",
+        ])
+        .expect("Failed to parse ABI"),
+    );
+}
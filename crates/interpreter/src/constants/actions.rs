@@ -61,6 +61,20 @@ pub enum InterpretationActionType {
     ERC1155Mint,
     #[strum(serialize = "ERC1155_BURN")]
     ERC1155Burn,
+    #[strum(serialize = "SWAP_SEND")]
+    SwapSend,
+    #[strum(serialize = "SWAP_RECEIVE")]
+    SwapReceive,
+    #[strum(serialize = "NATIVE_WRAP")]
+    NativeWrap,
+    #[strum(serialize = "NATIVE_UNWRAP")]
+    NativeUnwrap,
+    #[strum(serialize = "LENDING_SUPPLY")]
+    LendingSupply,
+    #[strum(serialize = "LENDING_BORROW")]
+    LendingBorrow,
+    #[strum(serialize = "LENDING_REPAY")]
+    LendingRepay,
 }
 
 #[cfg(test)]
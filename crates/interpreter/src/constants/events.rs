@@ -41,3 +41,115 @@ lazy_static! {
         H256::from_str("0x4a39dc06d4c0dbc64b70af90fd698a233a518aa5d07e595d983b8c0526c8f7fb")
             .expect("Failed to parse address");
 }
+
+lazy_static! {
+    pub static ref APPROVAL_EVENT_TOPIC: H256 =
+        // https://www.4byte.directory/event-signatures/?bytes_signature=0x8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925
+        // Approval(address,address,uint256)
+        H256::from_str("0x8c5be1e5ebec7d5bd14f71427d1e84f3dd0314c0f7b2291e5b200ac8c7c3b925")
+            .expect("Failed to parse address");
+}
+
+lazy_static! {
+    pub static ref APPROVAL_FOR_ALL_EVENT_TOPIC: H256 =
+        // https://www.4byte.directory/event-signatures/?bytes_signature=0x17307eab39ab6107e8899845ad3d59bd9653f200f220920489ca2b5937696c31
+        // ApprovalForAll(address,address,bool)
+        H256::from_str("0x17307eab39ab6107e8899845ad3d59bd9653f200f220920489ca2b5937696c31")
+            .expect("Failed to parse address");
+}
+
+lazy_static! {
+    pub static ref IMAGE_HASH_UPDATED_EVENT_TOPIC: H256 =
+        // https://www.4byte.directory/event-signatures/?bytes_signature=0x307ed6bd941ee9fc80f369c94af5fa11e25bab5102a6140191756c5474a30bfa
+        // ImageHashUpdated(bytes32)
+        H256::from_str("0x307ed6bd941ee9fc80f369c94af5fa11e25bab5102a6140191756c5474a30bfa")
+            .expect("Failed to parse address");
+}
+
+lazy_static! {
+    pub static ref IMPLEMENTATION_UPDATED_EVENT_TOPIC: H256 =
+        // https://www.4byte.directory/event-signatures/?bytes_signature=0x310ba5f1d2ed074b51e2eccd052a47ae9ab7c6b800d1fca3db3999d6a592ca03
+        // ImplementationUpdated(address)
+        H256::from_str("0x310ba5f1d2ed074b51e2eccd052a47ae9ab7c6b800d1fca3db3999d6a592ca03")
+            .expect("Failed to parse address");
+}
+
+lazy_static! {
+    pub static ref UNISWAP_V2_SWAP_EVENT_TOPIC: H256 =
+        // https://www.4byte.directory/event-signatures/?bytes_signature=0xd78ad95fa46c994b6551d0da85fc275fe613ce37657fb8d5e3d130840159d822
+        // Swap(address,uint256,uint256,uint256,uint256,address)
+        H256::from_str("0xd78ad95fa46c994b6551d0da85fc275fe613ce37657fb8d5e3d130840159d822")
+            .expect("Failed to parse address");
+}
+
+lazy_static! {
+    pub static ref UNISWAP_V3_SWAP_EVENT_TOPIC: H256 =
+        // https://www.4byte.directory/event-signatures/?bytes_signature=0xc42079f94a6350d7e6235f29174924f928cc2ac818eb64fed8004e115fbcca67
+        // Swap(address,address,int256,int256,uint160,uint128,int24)
+        H256::from_str("0xc42079f94a6350d7e6235f29174924f928cc2ac818eb64fed8004e115fbcca67")
+            .expect("Failed to parse address");
+}
+
+lazy_static! {
+    pub static ref WETH_DEPOSIT_EVENT_TOPIC: H256 =
+        // https://www.4byte.directory/event-signatures/?bytes_signature=0xe1fffcc4923d04b559f4d29a8bfc6cda04eb5b0d3c460751c2402c5c5cc9109c
+        // Deposit(address,uint256)
+        H256::from_str("0xe1fffcc4923d04b559f4d29a8bfc6cda04eb5b0d3c460751c2402c5c5cc9109c")
+            .expect("Failed to parse address");
+}
+
+lazy_static! {
+    pub static ref WETH_WITHDRAWAL_EVENT_TOPIC: H256 =
+        // https://www.4byte.directory/event-signatures/?bytes_signature=0x7fcf532c15f0a6db0bd6d0e038bea71d30d808c7d98cb3bf7268a95bf5081b65
+        // Withdrawal(address,uint256)
+        H256::from_str("0x7fcf532c15f0a6db0bd6d0e038bea71d30d808c7d98cb3bf7268a95bf5081b65")
+            .expect("Failed to parse address");
+}
+
+lazy_static! {
+    pub static ref AAVE_V3_SUPPLY_EVENT_TOPIC: H256 =
+        // https://www.4byte.directory/event-signatures/?bytes_signature=0x2b627736bca15cd5381dcf80b0bf11fd197d01a037c52b927a881a10fb73ba61
+        // Supply(address,address,address,uint256,uint16)
+        H256::from_str("0x2b627736bca15cd5381dcf80b0bf11fd197d01a037c52b927a881a10fb73ba61")
+            .expect("Failed to parse address");
+}
+
+lazy_static! {
+    pub static ref AAVE_V3_BORROW_EVENT_TOPIC: H256 =
+        // https://www.4byte.directory/event-signatures/?bytes_signature=0xb3d084820fb1a9decffb176436bd02558d15fac9b0ddfed8c465bc7359d7dce0
+        // Borrow(address,address,address,uint256,uint8,uint256,uint16)
+        H256::from_str("0xb3d084820fb1a9decffb176436bd02558d15fac9b0ddfed8c465bc7359d7dce0")
+            .expect("Failed to parse address");
+}
+
+lazy_static! {
+    pub static ref AAVE_V3_REPAY_EVENT_TOPIC: H256 =
+        // https://www.4byte.directory/event-signatures/?bytes_signature=0xa534c8dbe71f871f9f3530e97a74601fea17b426cae02e1c5aee42c96c784051
+        // Repay(address,address,address,uint256,bool)
+        H256::from_str("0xa534c8dbe71f871f9f3530e97a74601fea17b426cae02e1c5aee42c96c784051")
+            .expect("Failed to parse address");
+}
+
+lazy_static! {
+    pub static ref COMPOUND_MINT_EVENT_TOPIC: H256 =
+        // https://www.4byte.directory/event-signatures/?bytes_signature=0x4c209b5fc8ad50758f13e2e1088ba56a560dff690a1c6fef26394f4c03821c4f
+        // Mint(address,uint256,uint256)
+        H256::from_str("0x4c209b5fc8ad50758f13e2e1088ba56a560dff690a1c6fef26394f4c03821c4f")
+            .expect("Failed to parse address");
+}
+
+lazy_static! {
+    pub static ref COMPOUND_BORROW_EVENT_TOPIC: H256 =
+        // https://www.4byte.directory/event-signatures/?bytes_signature=0x13ed6866d4e1ee6da46f845c46d7e54120883d75c5ea9a2dacc1c4ca8984ab80
+        // Borrow(address,uint256,uint256,uint256)
+        H256::from_str("0x13ed6866d4e1ee6da46f845c46d7e54120883d75c5ea9a2dacc1c4ca8984ab80")
+            .expect("Failed to parse address");
+}
+
+lazy_static! {
+    pub static ref COMPOUND_REPAY_BORROW_EVENT_TOPIC: H256 =
+        // https://www.4byte.directory/event-signatures/?bytes_signature=0x1a2a22cb034d26d1854bdc6666a5b91fe25efbbb5dcad3b0355478d6f5c362a1
+        // RepayBorrow(address,address,uint256,uint256,uint256)
+        H256::from_str("0x1a2a22cb034d26d1854bdc6666a5b91fe25efbbb5dcad3b0355478d6f5c362a1")
+            .expect("Failed to parse address");
+}
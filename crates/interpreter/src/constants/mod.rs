@@ -15,7 +15,9 @@
 pub mod abis;
 pub mod actions;
 pub mod events;
+pub mod protocols;
 
 pub use abis::*;
 pub use actions::*;
 pub use events::*;
+pub use protocols::*;
@@ -0,0 +1,69 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+#![allow(clippy::expect_used)]
+
+use ethers_main::types::Address;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use strum_macros::{Display, EnumString, EnumVariantNames, IntoStaticStr};
+
+lazy_static! {
+    pub static ref COMPOUND_CETH_ADDRESS: Address =
+        // https://etherscan.io/address/0x4Ddc2D193948926D02f9B1fE9e1daa0718270ED5
+        // The cToken of the native token, w/o `underlying`
+        Address::from_str("0x4Ddc2D193948926D02f9B1fE9e1daa0718270ED5")
+            .expect("Failed to parse address");
+}
+
+/// The protocols the interpreter recognizes the interactions with.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    EnumVariantNames,
+    Display,
+    EnumString,
+    IntoStaticStr,
+)]
+pub enum InterpretationProtocol {
+    #[strum(serialize = "UNISWAP_V2")]
+    UniswapV2,
+    #[strum(serialize = "UNISWAP_V3")]
+    UniswapV3,
+    #[strum(serialize = "WETH")]
+    Weth,
+    #[strum(serialize = "AAVE_V3")]
+    AaveV3,
+    #[strum(serialize = "COMPOUND_V2")]
+    CompoundV2,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_round_trip() {
+        let original = InterpretationProtocol::UniswapV3;
+        let serialized = original.to_string();
+        assert_eq!(serialized, "UNISWAP_V3");
+        assert_eq!(InterpretationProtocol::from_str(&serialized).unwrap(), original);
+    }
+}
@@ -17,9 +17,12 @@
 
 use crate::{
     adapter::Adapter,
-    adapters::ADAPTERS,
+    adapters::{merge_protocol_asset_changes, ADAPTERS},
     config::InterpreterArgs,
-    types::{AdapterResponse, CallTrace, InterpretationRequest, InterpretationResponse},
+    types::{
+        AdapterResponse, CallTrace, InterpretationChainResponse, InterpretationRequest,
+        InterpretationResponse,
+    },
 };
 use eyre::{eyre, Result};
 use foundry_config::Chain;
@@ -30,7 +33,8 @@ use foundry_evm::trace::{
 use lightdotso_contracts::provider::get_provider;
 use lightdotso_simulator::{
     evm::Evm,
-    simulator::{simulate, simulate_bundle},
+    simulator::{simulate, simulate_bundle, simulate_bundle_with_snapshot, simulate_with_snapshot},
+    snapshot::StateSnapshot,
     types::SimulationRequest,
 };
 use revm::interpreter::InstructionResult;
@@ -39,6 +43,7 @@ pub struct Interpreter<'a> {
     adapters: &'a [Box<dyn Adapter + Sync + Send>],
     decoder: CallTraceDecoder,
     etherscan_identifier: Option<EtherscanIdentifier>,
+    snapshot: Option<String>,
 }
 
 impl Interpreter<'_> {
@@ -60,12 +65,20 @@ impl Interpreter<'_> {
 
         let adapters = &ADAPTERS[..];
 
-        Interpreter { decoder, etherscan_identifier, adapters }
+        Interpreter { decoder, etherscan_identifier, adapters, snapshot: args.clone().snapshot }
     }
 
     pub async fn interpret(&self, request: InterpretationRequest) -> Result<Vec<AdapterResponse>> {
-        let fork_url = get_provider(request.chain_id).await?.url().to_string();
-        let mut evm = Evm::new(None, fork_url, request.block_number, request.gas_limit, true).await;
+        let mut evm = match &self.snapshot {
+            Some(path) => {
+                let snapshot = StateSnapshot::load(path)?;
+                Evm::new_from_snapshot(None, &snapshot, request.gas_limit, true).await?
+            }
+            None => {
+                let fork_url = get_provider(request.chain_id).await?.url().to_string();
+                Evm::new(None, fork_url, request.block_number, request.gas_limit, true).await
+            }
+        };
         let mut response = vec![];
 
         for adapter in self.adapters {
@@ -75,6 +88,9 @@ impl Interpreter<'_> {
             }
         }
 
+        // Merge the transfers underneath the swaps and the lending into their asset changes
+        merge_protocol_asset_changes(&mut response);
+
         Ok(response)
     }
 
@@ -113,6 +129,7 @@ impl Interpreter<'_> {
             exit_reason: InstructionResult::Stop,
             actions,
             asset_changes,
+            chains: vec![],
         })
     }
 
@@ -121,7 +138,12 @@ impl Interpreter<'_> {
         request: SimulationRequest,
     ) -> Result<InterpretationResponse> {
         // Simulate the user operation
-        let res = simulate(request.clone()).await?;
+        let res = match &self.snapshot {
+            Some(path) => {
+                simulate_with_snapshot(request.clone(), &StateSnapshot::load(path)?).await?
+            }
+            None => simulate(request.clone()).await?,
+        };
 
         // Run the interpreter
         let _format_trace = self.format_trace(res.arena.clone()).await?;
@@ -162,6 +184,7 @@ impl Interpreter<'_> {
             exit_reason: res.exit_reason,
             actions,
             asset_changes,
+            chains: vec![],
         })
     }
 
@@ -170,7 +193,12 @@ impl Interpreter<'_> {
         requests: Vec<SimulationRequest>,
     ) -> Result<InterpretationResponse> {
         // Simulate the user operation
-        let simulation_results = simulate_bundle(requests.clone()).await?;
+        let simulation_results = match &self.snapshot {
+            Some(path) => {
+                simulate_bundle_with_snapshot(requests.clone(), &StateSnapshot::load(path)?).await?
+            }
+            None => simulate_bundle(requests.clone()).await?,
+        };
 
         // Prepare a vector to hold the InterpretationResponse objects
         let mut interpretation_responses = Vec::new();
@@ -228,9 +256,31 @@ impl Interpreter<'_> {
                 exit_reason: res.exit_reason,
                 actions,
                 asset_changes,
+                chains: vec![],
             });
         }
 
+        // Summarize the results per chain, in the order the chains first appear
+        let mut chains: Vec<InterpretationChainResponse> = Vec::new();
+        for res in interpretation_responses.iter() {
+            let idx = match chains.iter().position(|chain| chain.chain_id == res.chain_id) {
+                Some(idx) => idx,
+                None => {
+                    chains.push(InterpretationChainResponse {
+                        chain_id: res.chain_id,
+                        block_number: res.block_number,
+                        success: true,
+                        ..Default::default()
+                    });
+                    chains.len() - 1
+                }
+            };
+            let chain = &mut chains[idx];
+            chain.success &= res.success;
+            chain.gas_used += res.gas_used;
+            chain.asset_changes.extend(res.asset_changes.clone());
+        }
+
         // Flatten the vector to single InterpretationResponse
         let res = interpretation_responses.clone().into_iter().fold(
             InterpretationResponse::default(),
@@ -257,6 +307,7 @@ impl Interpreter<'_> {
                 .map(|res| res.block_number)
                 .unwrap_or_default(),
             success: interpretation_responses.iter().all(|res| res.success),
+            chains,
             ..res
         };
 
@@ -18,3 +18,4 @@ pub mod config;
 pub mod constants;
 pub mod interpreter;
 pub mod types;
+pub mod warnings;
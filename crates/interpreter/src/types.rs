@@ -12,7 +12,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::constants::InterpretationActionType;
+use crate::constants::{InterpretationActionType, InterpretationProtocol};
 use ethers_main::{
     abi::{Address, Uint},
     types::{Bytes, Log},
@@ -39,7 +39,7 @@ pub struct InterpretationRequest {
     /// Call data of the transaction
     pub call_data: Option<Bytes>,
     /// Value to send
-    pub value: Option<u64>,
+    pub value: Option<Uint>,
     /// Trace of the transaction
     pub traces: Vec<CallTrace>,
     /// Logs of the transaction
@@ -82,6 +82,23 @@ pub struct InterpretationResponse {
     pub actions: Vec<InterpretationAction>,
     /// Changes in the assets of the transaction
     pub asset_changes: Vec<AssetChange>,
+    /// Results of the bundle per chain, empty if not a bundle
+    #[serde(default)]
+    pub chains: Vec<InterpretationChainResponse>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct InterpretationChainResponse {
+    /// Chain ID of the simulation
+    pub chain_id: u64,
+    /// Block number the chain was forked at
+    pub block_number: u64,
+    /// Whether all transactions on the chain were successful
+    pub success: bool,
+    /// Gas used by the transactions on the chain
+    pub gas_used: u64,
+    /// Changes in the assets of the transactions on the chain
+    pub asset_changes: Vec<AssetChange>,
 }
 
 impl Default for InterpretationResponse {
@@ -96,6 +113,7 @@ impl Default for InterpretationResponse {
             exit_reason: InstructionResult::Stop,
             actions: Vec::new(),
             asset_changes: Vec::new(),
+            chains: Vec::new(),
         }
     }
 }
@@ -130,6 +148,17 @@ pub struct AssetChange {
     pub action: InterpretationAction,
     /// The token that was transferred
     pub token: AssetToken,
+    /// The protocol the asset was exchanged w/, if any
+    #[serde(default)]
+    pub protocol: Option<AssetProtocol>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AssetProtocol {
+    /// Address of the protocol contract
+    pub address: Address,
+    /// The protocol of the contract
+    pub protocol: InterpretationProtocol,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
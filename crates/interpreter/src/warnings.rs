@@ -0,0 +1,518 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    constants::{
+        InterpretationActionType, APPROVAL_EVENT_TOPIC, APPROVAL_FOR_ALL_EVENT_TOPIC,
+        IMAGE_HASH_UPDATED_EVENT_TOPIC, IMPLEMENTATION_UPDATED_EVENT_TOPIC,
+    },
+    types::{AssetChange, InterpretationResponse},
+};
+use ethers_main::{abi::Address, types::U256};
+use foundry_evm::CallKind;
+use lightdotso_contracts::constants::{
+    LIGHT_WALLET_FACTORY_IMPLEMENTATION_ADDRESS, LIGHT_WALLET_FACTORY_IMPLEMENTATION_V010_ADDRESS,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use strum_macros::{Display, EnumString, IntoStaticStr};
+
+/// The share of the balance, in percent, an outflow of the wallet has to reach to be flagged.
+pub const LARGE_OUTFLOW_PERCENT: u64 = 50;
+
+/// The risky pattern a simulation was flagged for.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    Hash,
+    Display,
+    EnumString,
+    IntoStaticStr,
+)]
+pub enum SimulationWarningType {
+    /// An ERC20 approval of an unlimited amount
+    #[strum(serialize = "UNLIMITED_APPROVAL")]
+    UnlimitedApproval,
+    /// An approval of all the tokens of an ERC721 or ERC1155 collection
+    #[strum(serialize = "APPROVAL_FOR_ALL")]
+    ApprovalForAll,
+    /// An outflow to an address w/o any code or transactions
+    #[strum(serialize = "FRESH_RECIPIENT")]
+    FreshRecipient,
+    /// An outflow to a contract that isn't a known protocol
+    #[strum(serialize = "UNVERIFIED_CONTRACT")]
+    UnverifiedContract,
+    /// A change of the image hash or the implementation of the wallet
+    #[strum(serialize = "CONFIGURATION_CHANGE")]
+    ConfigurationChange,
+    /// A delegatecall from the wallet to an unknown target
+    #[strum(serialize = "UNKNOWN_DELEGATECALL")]
+    UnknownDelegateCall,
+    /// An outflow of a large share of the balance of the wallet
+    #[strum(serialize = "LARGE_OUTFLOW")]
+    LargeOutflow,
+}
+
+/// The severity of the warning, which decides whether the UI blocks or confirms the operation.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Serialize,
+    Deserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Display,
+    EnumString,
+    IntoStaticStr,
+)]
+pub enum SimulationWarningSeverity {
+    #[strum(serialize = "LOW")]
+    Low,
+    #[strum(serialize = "MEDIUM")]
+    Medium,
+    #[strum(serialize = "HIGH")]
+    High,
+}
+
+impl SimulationWarningType {
+    /// Get the severity of the warning type.
+    pub fn severity(&self) -> SimulationWarningSeverity {
+        match self {
+            SimulationWarningType::FreshRecipient => SimulationWarningSeverity::Low,
+            SimulationWarningType::UnlimitedApproval |
+            SimulationWarningType::UnverifiedContract |
+            SimulationWarningType::LargeOutflow => SimulationWarningSeverity::Medium,
+            SimulationWarningType::ApprovalForAll |
+            SimulationWarningType::ConfigurationChange |
+            SimulationWarningType::UnknownDelegateCall => SimulationWarningSeverity::High,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SimulationWarning {
+    /// The type of the warning
+    pub warning_type: SimulationWarningType,
+    /// The severity of the warning
+    pub severity: SimulationWarningSeverity,
+    /// The address the warning is about, e.g. the spender or the recipient
+    pub address: Address,
+    /// The token the warning is about, e.g. the approved or the sent token
+    pub token: Option<Address>,
+    /// The human readable description of the warning
+    pub message: String,
+}
+
+impl SimulationWarning {
+    pub fn new(
+        warning_type: SimulationWarningType,
+        address: Address,
+        token: Option<Address>,
+        message: String,
+    ) -> Self {
+        Self { warning_type, severity: warning_type.severity(), address, token, message }
+    }
+}
+
+/// The on-chain state of a recipient of the outflows, before the simulation.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RecipientInfo {
+    /// Whether the recipient has code
+    pub is_contract: bool,
+    /// Whether the recipient has neither code nor transactions
+    pub is_fresh: bool,
+}
+
+/// The context the warnings of the simulation are checked against.
+#[derive(Debug, Clone)]
+pub struct WarningContext {
+    /// The wallet of the simulation
+    pub wallet: Address,
+    /// The contracts trusted as spenders, recipients and delegatecall targets
+    pub trusted: HashSet<Address>,
+    /// The on-chain state of the recipients of the outflows
+    pub recipients: HashMap<Address, RecipientInfo>,
+    /// The share of the balance, in percent, an outflow has to reach to be flagged
+    pub large_outflow_percent: u64,
+}
+
+impl WarningContext {
+    /// Create the context for the wallet, trusting the wallet implementations.
+    pub fn new(wallet: Address) -> Self {
+        let trusted = HashSet::from([
+            Address::from_slice(LIGHT_WALLET_FACTORY_IMPLEMENTATION_ADDRESS.as_bytes()),
+            Address::from_slice(LIGHT_WALLET_FACTORY_IMPLEMENTATION_V010_ADDRESS.as_bytes()),
+        ]);
+
+        Self {
+            wallet,
+            trusted,
+            recipients: HashMap::new(),
+            large_outflow_percent: LARGE_OUTFLOW_PERCENT,
+        }
+    }
+}
+
+/// Whether the action sends the asset out of the holder.
+fn is_send(action_type: &InterpretationActionType) -> bool {
+    matches!(
+        action_type,
+        InterpretationActionType::NativeSend |
+            InterpretationActionType::ERC20Send |
+            InterpretationActionType::ERC721Send |
+            InterpretationActionType::ERC1155Send
+    )
+}
+
+/// Whether the action receives the asset to the holder.
+fn is_receive(action_type: &InterpretationActionType) -> bool {
+    matches!(
+        action_type,
+        InterpretationActionType::NativeReceive |
+            InterpretationActionType::ERC20Receive |
+            InterpretationActionType::ERC721Receive |
+            InterpretationActionType::ERC1155Receive
+    )
+}
+
+/// Get the sends of the assets out of the wallet.
+fn outflows<'a>(
+    res: &'a InterpretationResponse,
+    wallet: Address,
+) -> impl Iterator<Item = &'a AssetChange> {
+    res.asset_changes
+        .iter()
+        .filter(move |change| change.address == wallet && is_send(&change.action.action_type))
+}
+
+/// Get the recipients of the assets sent out of the wallet, to look up w/ `RecipientInfo`.
+pub fn outflow_recipients(res: &InterpretationResponse, wallet: Address) -> Vec<Address> {
+    let tokens: HashSet<Address> =
+        outflows(res, wallet).map(|change| change.token.address).collect();
+
+    let mut recipients = vec![];
+    for change in res.asset_changes.iter() {
+        if change.address != wallet &&
+            is_receive(&change.action.action_type) &&
+            tokens.contains(&change.token.address) &&
+            !recipients.contains(&change.address)
+        {
+            recipients.push(change.address);
+        }
+    }
+    recipients
+}
+
+/// Check the interpretation and the call trace of the simulation for risky patterns.
+pub fn check_warnings(
+    res: &InterpretationResponse,
+    ctx: &WarningContext,
+) -> Vec<SimulationWarning> {
+    let mut warnings = vec![];
+
+    // Check the approvals of the wallet
+    for log in res.logs.iter() {
+        if log.topics.len() != 3 || Address::from(log.topics[1]) != ctx.wallet {
+            continue;
+        }
+        let spender = Address::from(log.topics[2]);
+        if ctx.trusted.contains(&spender) || log.data.len() < 32 {
+            continue;
+        }
+        let value = U256::from_big_endian(&log.data[0..32]);
+
+        // An approval of at least half of the max uint256 is effectively unlimited
+        if log.topics[0] == *APPROVAL_EVENT_TOPIC && value >= U256::MAX >> 1 {
+            warnings.push(SimulationWarning::new(
+                SimulationWarningType::UnlimitedApproval,
+                spender,
+                Some(log.address),
+                format!("Unlimited approval of the token {:?} to {:?}", log.address, spender),
+            ));
+        }
+        if log.topics[0] == *APPROVAL_FOR_ALL_EVENT_TOPIC && !value.is_zero() {
+            warnings.push(SimulationWarning::new(
+                SimulationWarningType::ApprovalForAll,
+                spender,
+                Some(log.address),
+                format!("Approval of all tokens of {:?} to {:?}", log.address, spender),
+            ));
+        }
+    }
+
+    // Check the configuration changes of the wallet
+    for log in res.logs.iter().filter(|log| log.address == ctx.wallet && !log.topics.is_empty()) {
+        if log.topics[0] == *IMAGE_HASH_UPDATED_EVENT_TOPIC {
+            warnings.push(SimulationWarning::new(
+                SimulationWarningType::ConfigurationChange,
+                ctx.wallet,
+                None,
+                "The signers of the wallet are updated".to_string(),
+            ));
+        }
+        if log.topics[0] == *IMPLEMENTATION_UPDATED_EVENT_TOPIC {
+            warnings.push(SimulationWarning::new(
+                SimulationWarningType::ConfigurationChange,
+                ctx.wallet,
+                None,
+                "The implementation of the wallet is updated".to_string(),
+            ));
+        }
+    }
+
+    // Check the delegatecalls of the wallet
+    for trace in res.traces.iter() {
+        if trace.call_type == CallKind::DelegateCall &&
+            trace.from == ctx.wallet &&
+            !ctx.trusted.contains(&trace.to)
+        {
+            warnings.push(SimulationWarning::new(
+                SimulationWarningType::UnknownDelegateCall,
+                trace.to,
+                None,
+                format!("The wallet delegatecalls to the unknown contract {:?}", trace.to),
+            ));
+        }
+    }
+
+    // Check the recipients of the outflows
+    for recipient in outflow_recipients(res, ctx.wallet) {
+        let Some(info) = ctx.recipients.get(&recipient) else {
+            continue;
+        };
+        if info.is_fresh {
+            warnings.push(SimulationWarning::new(
+                SimulationWarningType::FreshRecipient,
+                recipient,
+                None,
+                format!("The recipient {:?} has never been used", recipient),
+            ));
+        } else if info.is_contract && !ctx.trusted.contains(&recipient) {
+            warnings.push(SimulationWarning::new(
+                SimulationWarningType::UnverifiedContract,
+                recipient,
+                None,
+                format!("The recipient {:?} is an unverified contract", recipient),
+            ));
+        }
+    }
+
+    // Check the share of the balance of the outflows
+    for change in outflows(res, ctx.wallet) {
+        let Some(before_amount) = change.before_amount.filter(|amount| !amount.is_zero()) else {
+            continue;
+        };
+        if change.amount.saturating_mul(100.into()) >=
+            before_amount.saturating_mul(ctx.large_outflow_percent.into())
+        {
+            warnings.push(SimulationWarning::new(
+                SimulationWarningType::LargeOutflow,
+                change.token.address,
+                Some(change.token.address),
+                format!(
+                    "The wallet sends {}% or more of its balance of the token {:?}",
+                    ctx.large_outflow_percent, change.token.address
+                ),
+            ));
+        }
+    }
+
+    // Remove the duplicates of the same type, address and token
+    let mut seen = HashSet::new();
+    warnings.retain(|warning| seen.insert((warning.warning_type, warning.address, warning.token)));
+
+    warnings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{AssetToken, AssetTokenType, CallTrace, InterpretationAction};
+    use ethers_main::types::{Log, H256};
+
+    fn topic(address: Address) -> H256 {
+        H256::from(address)
+    }
+
+    fn asset_change(
+        address: Address,
+        action_type: InterpretationActionType,
+        amount: u64,
+        before_amount: Option<u64>,
+    ) -> AssetChange {
+        AssetChange {
+            address,
+            before_amount: before_amount.map(Into::into),
+            after_amount: None,
+            amount: amount.into(),
+            action: InterpretationAction { action_type, address: Some(address) },
+            token: AssetToken {
+                address: Address::repeat_byte(0xee),
+                token_id: None,
+                token_type: AssetTokenType::Erc20,
+            },
+            protocol: None,
+        }
+    }
+
+    #[test]
+    fn test_approvals() {
+        let wallet = Address::repeat_byte(1);
+        let spender = Address::repeat_byte(2);
+        let ctx = WarningContext::new(wallet);
+
+        let mut max = [0u8; 32];
+        U256::MAX.to_big_endian(&mut max);
+        let res = InterpretationResponse {
+            logs: vec![
+                Log {
+                    topics: vec![*APPROVAL_EVENT_TOPIC, topic(wallet), topic(spender)],
+                    data: max.to_vec().into(),
+                    ..Default::default()
+                },
+                Log {
+                    topics: vec![*APPROVAL_FOR_ALL_EVENT_TOPIC, topic(wallet), topic(spender)],
+                    data: H256::from_low_u64_be(1).as_bytes().to_vec().into(),
+                    ..Default::default()
+                },
+                // A limited approval isn't flagged
+                Log {
+                    topics: vec![*APPROVAL_EVENT_TOPIC, topic(wallet), topic(spender)],
+                    data: H256::from_low_u64_be(100).as_bytes().to_vec().into(),
+                    address: Address::repeat_byte(3),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+
+        let warnings = check_warnings(&res, &ctx);
+        assert_eq!(
+            warnings.iter().map(|warning| warning.warning_type).collect::<Vec<_>>(),
+            vec![SimulationWarningType::UnlimitedApproval, SimulationWarningType::ApprovalForAll]
+        );
+        assert_eq!(warnings[1].severity, SimulationWarningSeverity::High);
+    }
+
+    #[test]
+    fn test_approvals_of_different_tokens() {
+        let wallet = Address::repeat_byte(1);
+        let spender = Address::repeat_byte(2);
+        let ctx = WarningContext::new(wallet);
+
+        let mut max = [0u8; 32];
+        U256::MAX.to_big_endian(&mut max);
+        let approval = |token: Address| Log {
+            address: token,
+            topics: vec![*APPROVAL_EVENT_TOPIC, topic(wallet), topic(spender)],
+            data: max.to_vec().into(),
+            ..Default::default()
+        };
+        let res = InterpretationResponse {
+            logs: vec![
+                approval(Address::repeat_byte(3)),
+                approval(Address::repeat_byte(4)),
+                approval(Address::repeat_byte(3)),
+            ],
+            ..Default::default()
+        };
+
+        // The approvals of the different tokens to the same spender are kept apart
+        let warnings = check_warnings(&res, &ctx);
+        assert_eq!(
+            warnings.iter().map(|warning| warning.token).collect::<Vec<_>>(),
+            vec![Some(Address::repeat_byte(3)), Some(Address::repeat_byte(4))]
+        );
+    }
+
+    #[test]
+    fn test_delegatecall_and_configuration_change() {
+        let wallet = Address::repeat_byte(1);
+        let ctx = WarningContext::new(wallet);
+        let implementation =
+            Address::from_slice(LIGHT_WALLET_FACTORY_IMPLEMENTATION_ADDRESS.as_bytes());
+
+        let res = InterpretationResponse {
+            logs: vec![Log {
+                address: wallet,
+                topics: vec![*IMAGE_HASH_UPDATED_EVENT_TOPIC],
+                ..Default::default()
+            }],
+            traces: vec![
+                // The delegatecall to the wallet implementation is trusted
+                CallTrace {
+                    call_type: CallKind::DelegateCall,
+                    from: wallet,
+                    to: implementation,
+                    value: None,
+                },
+                CallTrace {
+                    call_type: CallKind::DelegateCall,
+                    from: wallet,
+                    to: Address::repeat_byte(4),
+                    value: None,
+                },
+            ],
+            ..Default::default()
+        };
+
+        let warnings = check_warnings(&res, &ctx);
+        assert_eq!(
+            warnings.iter().map(|warning| warning.warning_type).collect::<Vec<_>>(),
+            vec![
+                SimulationWarningType::ConfigurationChange,
+                SimulationWarningType::UnknownDelegateCall
+            ]
+        );
+        assert_eq!(warnings[1].address, Address::repeat_byte(4));
+    }
+
+    #[test]
+    fn test_outflows() {
+        let wallet = Address::repeat_byte(1);
+        let recipient = Address::repeat_byte(2);
+        let mut ctx = WarningContext::new(wallet);
+
+        let res = InterpretationResponse {
+            asset_changes: vec![
+                asset_change(wallet, InterpretationActionType::ERC20Send, 60, Some(100)),
+                asset_change(recipient, InterpretationActionType::ERC20Receive, 60, Some(0)),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(outflow_recipients(&res, wallet), vec![recipient]);
+
+        // The recipient w/o any code or transactions
+        ctx.recipients.insert(recipient, RecipientInfo { is_contract: false, is_fresh: true });
+        let warnings = check_warnings(&res, &ctx);
+        assert_eq!(
+            warnings.iter().map(|warning| warning.warning_type).collect::<Vec<_>>(),
+            vec![SimulationWarningType::FreshRecipient, SimulationWarningType::LargeOutflow]
+        );
+
+        // The trusted contract w/ a small share of the balance isn't flagged
+        ctx.recipients.insert(recipient, RecipientInfo { is_contract: true, is_fresh: false });
+        ctx.trusted.insert(recipient);
+        ctx.large_outflow_percent = 80;
+        assert!(check_warnings(&res, &ctx).is_empty());
+    }
+}
@@ -31,6 +31,7 @@ async fn test_integration_erc1155_transfer() -> Result<()> {
         gas_limit: u64::MAX,
         // Tx was on 16306969
         block_number: Some(16306968),
+        state_overrides: None,
     };
 
     // Parse the command line arguments
@@ -33,6 +33,7 @@ async fn test_integration_erc20_transfer() -> Result<()> {
         gas_limit: u64::MAX,
         // Tx was on 13704035
         block_number: Some(13704034),
+        state_overrides: None,
     };
 
     // Parse the command line arguments
@@ -31,6 +31,7 @@ async fn test_integration_erc721_transfer() -> Result<()> {
         gas_limit: u64::MAX,
         // Tx was on 16318897
         block_number: Some(16318896),
+        state_overrides: None,
     };
 
     // Parse the command line arguments
@@ -59,6 +60,7 @@ async fn test_integration_erc721_mint() -> Result<()> {
         gas_limit: u64::MAX,
         // Tx was on 13834190
         block_number: Some(13834189),
+        state_overrides: None,
     };
 
     // Parse the command line arguments
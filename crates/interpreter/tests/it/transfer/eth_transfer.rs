@@ -26,9 +26,10 @@ async fn test_integration_eth_transfer() -> Result<()> {
         // fiveoutofnine.eth
         to: "0xA85572Cd96f1643458f17340b6f0D6549Af482F5".parse()?,
         data: None,
-        value: Some(1),
+        value: Some(1.into()),
         gas_limit: u64::MAX,
         block_number: None,
+        state_overrides: None,
     };
 
     // Parse the command line arguments
@@ -46,6 +47,40 @@ async fn test_integration_eth_transfer() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread")]
+async fn test_integration_eth_transfer_snapshot() -> Result<()> {
+    let request = SimulationRequest {
+        chain_id: 1,
+        // kaki.eth
+        from: "0x4fd9D0eE6D6564E80A9Ee00c0163fC952d0A45Ed".parse()?,
+        // fiveoutofnine.eth
+        to: "0xA85572Cd96f1643458f17340b6f0D6549Af482F5".parse()?,
+        data: None,
+        value: Some(1.into()),
+        gas_limit: u64::MAX,
+        block_number: Some(18000000),
+        state_overrides: None,
+    };
+
+    // Run the interpreter on the pinned state w/o the network
+    let args = InterpreterArgs {
+        snapshot: Some(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/tests/snapshots/eth_transfer.json").to_string(),
+        ),
+        ..Default::default()
+    };
+
+    // Run the interpreter
+    let res = args.run(vec![request]).await?;
+
+    println!("{:?}", res);
+
+    assert!(res.success);
+    assert!(!res.asset_changes.is_empty());
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread")]
 async fn test_integration_light_eth_transfer() -> Result<()> {
     let request = SimulationRequest {
@@ -55,9 +90,10 @@ async fn test_integration_light_eth_transfer() -> Result<()> {
         // kaki.eth
         to: "0x4fd9D0eE6D6564E80A9Ee00c0163fC952d0A45Ed".parse()?,
         data: None,
-        value: Some(1),
+        value: Some(1.into()),
         gas_limit: u64::MAX,
         block_number: Some(114445705),
+        state_overrides: None,
     };
 
     // Parse the command line arguments
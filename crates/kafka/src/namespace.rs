@@ -49,6 +49,11 @@ lazy_static! {
     pub static ref PORTFOLIO: String = "portfolio".to_string();
 }
 
+// The reconciliation namesapce
+lazy_static! {
+    pub static ref RECONCILIATION: String = "reconciliation".to_string();
+}
+
 // The routescan namesapce
 lazy_static! {
     pub static ref ROUTESCAN: String = "routescan".to_string();
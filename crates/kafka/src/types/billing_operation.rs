@@ -21,6 +21,8 @@ use serde_json::{json, Value};
 // Types
 // -----------------------------------------------------------------------------
 
+/// The gas limits are kept as JSON numbers, so that the messages already in the topic stay
+/// readable; the producer rejects the gas limits above `u64::MAX`.
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BillingOperationMessage {
     pub chain_id: u64,
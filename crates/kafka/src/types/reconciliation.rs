@@ -0,0 +1,41 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::traits::ToJson;
+use ethers::{types::H160, utils::to_checksum};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+// -----------------------------------------------------------------------------
+// Types
+// -----------------------------------------------------------------------------
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReconciliationMessage {
+    pub address: H160,
+}
+
+// -----------------------------------------------------------------------------
+// Traits
+// -----------------------------------------------------------------------------
+
+impl ToJson for ReconciliationMessage {
+    fn to_json(&self) -> String {
+        let msg_value: Value = json!({
+            "address": to_checksum(&self.address, None),
+        });
+
+        msg_value.to_string()
+    }
+}
@@ -0,0 +1,329 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::mempool::MempoolEntry;
+use ethers::{
+    abi::AbiDecode,
+    middleware::SignerMiddleware,
+    providers::{Http, Middleware, PendingTransaction, Provider, RpcError},
+    signers::Signer,
+    types::{transaction::eip2718::TypedTransaction, Address, BlockNumber, H256, U256},
+};
+use eyre::{eyre, Result};
+use lightdotso_contracts::{
+    entrypoint::{EntryPoint, FailedOp},
+    entrypoint_v070::{EntryPointV070, FailedOp as FailedOpV070, FailedOpWithRevert},
+    types::{EntryPointVersion, PackedUserOperation, UserOperation},
+    user_operation::unpack_paymaster_and_data,
+};
+use lightdotso_tracing::tracing::{info, warn};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+// From: https://github.com/alchemyplatform/rundler/blob/b253c4870b069ffdc16a8ca936fe9ad24e1ac44d/crates/sim/src/gas/gas.rs
+// License: GNU Lesser General Public License v3.0
+
+/// The fixed gas overhead of the bundle transaction
+const BUNDLE_TRANSACTION_GAS_OVERHEAD: u64 = 21_000;
+
+/// The gas overhead of each user operation in the bundle
+const BUNDLE_USER_OPERATION_GAS_OVERHEAD: u64 = 5_000;
+
+/// The status of a bundled user operation.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BundleStatus {
+    /// The bundle transaction was submitted and is waiting to be included
+    Pending(H256),
+    /// The bundle transaction was included in the block
+    Included(H256, u64),
+    /// The bundle transaction was included, but reverted
+    Reverted(H256),
+    /// The bundle transaction was dropped from the mempool of the chain
+    Dropped(H256),
+    /// The user operation was rejected by the entry point w/ the reason
+    Rejected(String),
+}
+
+impl BundleStatus {
+    /// Whether the status won't change anymore.
+    pub fn is_final(&self) -> bool {
+        !matches!(self, BundleStatus::Pending(_))
+    }
+}
+
+/// A bundle of user operations ready to be submitted to the entry point.
+#[derive(Clone, Debug)]
+pub struct Bundle {
+    /// The `handleOps` transaction, or `None` if every user operation was rejected
+    pub tx: Option<TypedTransaction>,
+    /// The user operations in the bundle
+    pub entries: Vec<MempoolEntry>,
+    /// The user operations rejected by the entry point, w/ the revert reason
+    pub rejected: Vec<(MempoolEntry, String)>,
+}
+
+/// The bundler, which builds and submits `handleOps` bundles w/ the signer.
+#[derive(Clone, Debug)]
+pub struct Bundler<S> {
+    /// The signer of the bundle transactions
+    signer: S,
+    /// The beneficiary of the bundle fees
+    beneficiary: Option<Address>,
+}
+
+impl<S: Signer + Clone + 'static> Bundler<S> {
+    pub fn new(signer: S, beneficiary: Option<Address>) -> Self {
+        Self { signer, beneficiary }
+    }
+
+    /// Get the beneficiary of the bundle fees, defaults to the signer address.
+    pub fn beneficiary(&self) -> Address {
+        self.beneficiary.unwrap_or(self.signer.address())
+    }
+
+    /// Build a bundle from the entries w/ `eth_call` of `handleOps`.
+    /// The user operations rejected w/ `FailedOp` are removed one by one until the bundle
+    /// succeeds. Any other error, e.g. of the RPC, is returned w/o rejecting the user operations,
+    /// so that they are retried on the next bundle.
+    pub async fn build_bundle(
+        &self,
+        provider: &Provider<Http>,
+        entry_point: Address,
+        entries: Vec<MempoolEntry>,
+    ) -> Result<Bundle> {
+        let mut entries = entries;
+        let mut rejected = vec![];
+
+        while !entries.is_empty() {
+            let tx = self.handle_ops_tx(provider, entry_point, &entries)?;
+
+            // Simulate the bundle w/ `eth_call` from the signer
+            let res = provider.call(&tx, None).await;
+            let error_data = match res {
+                Ok(_) => return Ok(Bundle { tx: Some(tx), entries, rejected }),
+                Err(err) => err
+                    .as_error_response()
+                    .and_then(|e| e.as_revert_data())
+                    .ok_or(eyre!("Failed to call handleOps: {:?}", err))?,
+            };
+
+            // Remove the user operation that the entry point rejected
+            let (op_index, reason) = decode_failed_op(&error_data)
+                .ok_or(eyre!("handleOps reverted w/o FailedOp: {:?}", error_data))?;
+            if op_index >= entries.len() {
+                return Err(eyre!("FailedOp index out of bounds: {}", op_index));
+            }
+            let entry = entries.remove(op_index);
+            warn!("Rejected user operation {:?}: {}", entry.hash, reason);
+            rejected.push((entry, reason));
+        }
+
+        // Every user operation was rejected
+        Ok(Bundle { tx: None, entries, rejected })
+    }
+
+    /// Sign and submit the bundle w/ the pending nonce of the signer, w/o waiting for the
+    /// receipt, see [`wait_for_bundle`].
+    pub async fn send_bundle(
+        &self,
+        provider: &Provider<Http>,
+        chain_id: u64,
+        bundle: &Bundle,
+    ) -> Result<H256> {
+        let mut tx = bundle.tx.clone().ok_or(eyre!("The bundle has no user operations"))?;
+
+        // Use the pending nonce, so that the previous bundles still waiting for the receipt
+        // aren't replaced
+        let nonce = provider
+            .get_transaction_count(self.signer.address(), Some(BlockNumber::Pending.into()))
+            .await?;
+        tx.set_nonce(nonce);
+
+        let client =
+            SignerMiddleware::new(provider.clone(), self.signer.clone().with_chain_id(chain_id));
+
+        let pending = client
+            .send_transaction(tx, None)
+            .await
+            .map_err(|e| eyre!("Failed to send the bundle: {e:?}"))?;
+        let tx_hash = *pending;
+        info!("Sent bundle {:?} w/ {} user operations", tx_hash, bundle.entries.len());
+
+        Ok(tx_hash)
+    }
+
+    /// Encode the `handleOps` transaction for the entry point version.
+    fn handle_ops_tx(
+        &self,
+        provider: &Provider<Http>,
+        entry_point: Address,
+        entries: &[MempoolEntry],
+    ) -> Result<TypedTransaction> {
+        let client = Arc::new(provider.clone());
+        let user_operations: Vec<UserOperation> =
+            entries.iter().map(|entry| entry.user_operation.clone()).collect();
+
+        let mut tx: TypedTransaction = match EntryPointVersion::try_from(entry_point)? {
+            EntryPointVersion::V060 => {
+                EntryPoint::new(entry_point, client)
+                    .handle_ops(
+                        user_operations.iter().cloned().map(Into::into).collect(),
+                        self.beneficiary(),
+                    )
+                    .tx
+            }
+            EntryPointVersion::V070 => {
+                EntryPointV070::new(entry_point, client)
+                    .handle_ops(
+                        user_operations
+                            .iter()
+                            .cloned()
+                            .map(|op| PackedUserOperation::from(op).into())
+                            .collect(),
+                        self.beneficiary(),
+                    )
+                    .tx
+            }
+        };
+        tx.set_from(self.signer.address());
+        tx.set_gas(bundle_gas_limit(entry_point, &user_operations)?);
+
+        // Pay at most the lowest fees of the bundled user operations, so that the bundler is
+        // always compensated for the gas by the entry point.
+        if let TypedTransaction::Eip1559(ref mut inner) = tx {
+            inner.max_fee_per_gas = user_operations.iter().map(|op| op.max_fee_per_gas).min();
+            inner.max_priority_fee_per_gas =
+                user_operations.iter().map(|op| op.max_priority_fee_per_gas).min();
+        }
+
+        Ok(tx)
+    }
+}
+
+/// Wait for the receipt of the bundle transaction, and get the status of the bundled user
+/// operations.
+pub async fn wait_for_bundle(provider: &Provider<Http>, tx_hash: H256) -> Result<BundleStatus> {
+    let receipt = PendingTransaction::new(tx_hash, provider).await?;
+    info!("Bundle receipt: {:?}", receipt);
+
+    let status = match receipt {
+        Some(receipt) if receipt.status == Some(1.into()) => {
+            BundleStatus::Included(tx_hash, receipt.block_number.unwrap_or_default().as_u64())
+        }
+        Some(_) => BundleStatus::Reverted(tx_hash),
+        None => BundleStatus::Dropped(tx_hash),
+    };
+
+    Ok(status)
+}
+
+/// Get the gas limit of the bundle from the gas limits of the user operations.
+pub fn bundle_gas_limit(entry_point: Address, user_operations: &[UserOperation]) -> Result<U256> {
+    let version = EntryPointVersion::try_from(entry_point)?;
+
+    Ok(user_operations.iter().fold(U256::from(BUNDLE_TRANSACTION_GAS_OVERHEAD), |acc, op| {
+        let paymaster_gas = match version {
+            // The v0.6.0 entry point uses the verification gas limit for the paymaster
+            // validation and the post op
+            EntryPointVersion::V060 if op.paymaster().is_some() => op.verification_gas_limit * 2,
+            EntryPointVersion::V060 => U256::zero(),
+            // The v0.7.0 entry point has the paymaster gas limits in the paymaster and data
+            EntryPointVersion::V070 => unpack_paymaster_and_data(&op.paymaster_and_data)
+                .map(|(_, verification_gas_limit, post_op_gas_limit, _)| {
+                    verification_gas_limit + post_op_gas_limit
+                })
+                .unwrap_or_default(),
+        };
+
+        acc + BUNDLE_USER_OPERATION_GAS_OVERHEAD +
+            op.pre_verification_gas +
+            op.verification_gas_limit +
+            op.call_gas_limit +
+            paymaster_gas
+    }))
+}
+
+/// Decode the `FailedOp` revert of `handleOps` into the index and the reason.
+pub fn decode_failed_op(error_data: &[u8]) -> Option<(usize, String)> {
+    if let Ok(failed_op) = FailedOp::decode(error_data) {
+        return Some((failed_op.op_index.as_usize(), failed_op.reason));
+    }
+    if let Ok(failed_op) = FailedOpV070::decode(error_data) {
+        return Some((failed_op.op_index.as_usize(), failed_op.reason));
+    }
+    if let Ok(failed_op) = FailedOpWithRevert::decode(error_data) {
+        return Some((failed_op.op_index.as_usize(), failed_op.reason));
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::{abi::AbiEncode, types::Bytes};
+    use lightdotso_contracts::constants::{ENTRYPOINT_V060_ADDRESS, ENTRYPOINT_V070_ADDRESS};
+
+    fn user_operation(paymaster_and_data: Bytes) -> UserOperation {
+        UserOperation {
+            sender: Address::zero(),
+            nonce: U256::zero(),
+            init_code: Bytes::default(),
+            call_data: Bytes::default(),
+            call_gas_limit: 100_000.into(),
+            verification_gas_limit: 50_000.into(),
+            pre_verification_gas: 40_000.into(),
+            max_fee_per_gas: U256::zero(),
+            max_priority_fee_per_gas: U256::zero(),
+            paymaster_and_data,
+            signature: Bytes::default(),
+        }
+    }
+
+    #[test]
+    fn test_bundle_gas_limit() {
+        let op = user_operation(Bytes::default());
+        assert_eq!(
+            bundle_gas_limit(*ENTRYPOINT_V060_ADDRESS, &[op.clone(), op]).unwrap(),
+            (21_000 + 2 * (5_000 + 40_000 + 50_000 + 100_000)).into()
+        );
+
+        // The paymaster triples the verification gas limit on v0.6.0
+        let op = user_operation(Address::repeat_byte(1).as_bytes().to_vec().into());
+        assert_eq!(
+            bundle_gas_limit(*ENTRYPOINT_V060_ADDRESS, &[op]).unwrap(),
+            (21_000 + 5_000 + 40_000 + 3 * 50_000 + 100_000).into()
+        );
+
+        // The paymaster gas limits are packed on v0.7.0
+        let op = user_operation(lightdotso_contracts::user_operation::pack_paymaster_and_data(
+            Address::repeat_byte(1),
+            30_000.into(),
+            20_000.into(),
+            &Bytes::default(),
+        ));
+        assert_eq!(
+            bundle_gas_limit(*ENTRYPOINT_V070_ADDRESS, &[op]).unwrap(),
+            (21_000 + 5_000 + 40_000 + 50_000 + 100_000 + 30_000 + 20_000).into()
+        );
+    }
+
+    #[test]
+    fn test_decode_failed_op() {
+        let error_data =
+            FailedOp { op_index: 1.into(), reason: "AA23 reverted".to_string() }.encode();
+        assert_eq!(decode_failed_op(&error_data), Some((1, "AA23 reverted".to_string())));
+
+        assert_eq!(decode_failed_op(&[0xde, 0xad, 0xbe, 0xef]), None);
+    }
+}
@@ -14,11 +14,28 @@
 
 use crate::node::Node;
 use clap::Parser;
+use ethers::types::Address;
 use eyre::Result;
 use lightdotso_tracing::tracing::info;
 
 #[derive(Debug, Clone, Parser, Default)]
-pub struct NodeArgs {}
+pub struct NodeArgs {
+    /// The flag of whether the in-process bundler is enabled.
+    #[arg(long, default_value_t = false)]
+    #[clap(long, env = "NODE_BUNDLER_ENABLED")]
+    pub bundler_enabled: bool,
+    /// The interval in milliseconds between the bundles.
+    #[arg(long, default_value_t = 2000)]
+    #[clap(long, env = "NODE_BUNDLE_INTERVAL_MS")]
+    pub bundle_interval_ms: u64,
+    /// The max number of user operations in a bundle.
+    #[arg(long, default_value_t = 8)]
+    #[clap(long, env = "NODE_MAX_BUNDLE_SIZE")]
+    pub max_bundle_size: usize,
+    /// The beneficiary of the bundle fees, defaults to the signer address.
+    #[clap(long, env = "NODE_BENEFICIARY")]
+    pub beneficiary: Option<Address>,
+}
 
 impl NodeArgs {
     pub async fn create(&self) -> Result<Node> {
@@ -28,7 +45,7 @@ impl NodeArgs {
         // Print the config
         info!("Config: {:?}", self);
 
-        let node = Node::new(self).await;
+        let node = Node::new(self).await?;
 
         Ok(node)
     }
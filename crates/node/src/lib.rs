@@ -12,5 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+pub mod bundler;
 pub mod config;
+pub mod mempool;
 pub mod node;
+pub mod validation;
@@ -0,0 +1,362 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::bundler::BundleStatus;
+use ethers::types::{Address, H256, U256};
+use eyre::{eyre, Result};
+use lightdotso_contracts::{types::UserOperation, user_operation::UserOperationId};
+use lightdotso_redis::redis::{Client, Commands, Connection};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// The percentage a replacement user operation has to bump its fees by.
+/// From: https://github.com/eth-infinitism/bundler-spec-tests
+const REPLACEMENT_FEE_PERCENT_INCREASE: u64 = 10;
+
+/// The key of the chain ids w/ a shared mempool
+const MEMPOOL_CHAINS_KEY: &str = "node:mempool:chains";
+
+/// A validated user operation waiting in the mempool.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MempoolEntry {
+    /// The hash of the user operation
+    pub hash: H256,
+    /// The entry point the user operation was validated against
+    pub entry_point: Address,
+    /// The user operation
+    pub user_operation: UserOperation,
+    /// The unix timestamp in milliseconds the user operation was added to the mempool
+    pub added_at: u64,
+}
+
+impl MempoolEntry {
+    /// Create the entry of the user operation, added to the mempool now.
+    pub fn new(hash: H256, entry_point: Address, user_operation: UserOperation) -> Result<Self> {
+        let added_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis() as u64;
+
+        Ok(Self { hash, entry_point, user_operation, added_at })
+    }
+}
+
+/// The mempool of validated user operations for a single chain.
+#[derive(Clone, Debug, Default)]
+pub struct Mempool {
+    /// The entries by the user operation hash
+    entries: HashMap<H256, MempoolEntry>,
+    /// The user operation hash by the sender and nonce
+    ids: HashMap<UserOperationId, H256>,
+}
+
+impl Mempool {
+    /// Construct the mempool from the entries already validated and replaced.
+    pub fn from_entries(entries: impl IntoIterator<Item = MempoolEntry>) -> Self {
+        let mut mempool = Self::default();
+        for entry in entries {
+            mempool.ids.insert(entry.user_operation.id(), entry.hash);
+            mempool.entries.insert(entry.hash, entry);
+        }
+        mempool
+    }
+
+    /// Add a validated user operation to the mempool.
+    /// A user operation w/ the same sender and nonce is only replaced if the fees are bumped.
+    pub fn add(&mut self, entry: MempoolEntry) -> Result<H256> {
+        let id = entry.user_operation.id();
+
+        if let Some(existing_hash) = self.ids.get(&id).copied() {
+            if existing_hash == entry.hash {
+                return Ok(entry.hash);
+            }
+
+            let existing = &self.entries[&existing_hash].user_operation;
+            if !is_replacement_underpriced(existing, &entry.user_operation) {
+                self.entries.remove(&existing_hash);
+            } else {
+                return Err(eyre!(
+                    "Replacement user operation must increase fees by at least {}%",
+                    REPLACEMENT_FEE_PERCENT_INCREASE
+                ));
+            }
+        }
+
+        let hash = entry.hash;
+        self.ids.insert(id, hash);
+        self.entries.insert(hash, entry);
+
+        Ok(hash)
+    }
+
+    /// Remove a user operation from the mempool by its hash.
+    pub fn remove(&mut self, hash: &H256) -> Option<MempoolEntry> {
+        let entry = self.entries.remove(hash)?;
+        self.ids.remove(&entry.user_operation.id());
+        Some(entry)
+    }
+
+    /// Get the hash of the user operation in the mempool w/ the same sender and nonce.
+    pub fn get_hash(&self, id: &UserOperationId) -> Option<H256> {
+        self.ids.get(id).copied()
+    }
+
+    /// Get a user operation from the mempool by its hash.
+    pub fn get(&self, hash: &H256) -> Option<&MempoolEntry> {
+        self.entries.get(hash)
+    }
+
+    /// Get the best user operations for the next bundle to the entry point.
+    /// Takes the lowest nonce of each sender, ordered by the max priority fee.
+    pub fn best(&self, entry_point: Address, max: usize) -> Vec<MempoolEntry> {
+        let mut lowest_nonces: HashMap<Address, &MempoolEntry> = HashMap::new();
+        for entry in self.entries.values().filter(|entry| entry.entry_point == entry_point) {
+            let sender = entry.user_operation.sender;
+            match lowest_nonces.get(&sender) {
+                Some(existing) if existing.user_operation.nonce <= entry.user_operation.nonce => {}
+                _ => {
+                    lowest_nonces.insert(sender, entry);
+                }
+            }
+        }
+
+        let mut candidates: Vec<&MempoolEntry> = lowest_nonces.into_values().collect();
+        candidates.sort_by(|a, b| {
+            b.user_operation
+                .max_priority_fee_per_gas
+                .cmp(&a.user_operation.max_priority_fee_per_gas)
+                .then(a.added_at.cmp(&b.added_at))
+        });
+
+        candidates.into_iter().take(max).cloned().collect()
+    }
+
+    /// Get the entry points w/ pending user operations.
+    pub fn entry_points(&self) -> HashSet<Address> {
+        self.entries.values().map(|entry| entry.entry_point).collect()
+    }
+
+    /// Get the number of user operations in the mempool.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the mempool is empty.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// The mempools of the chains and the bundle statuses of their user operations, shared in redis
+/// across the replicas, so that the user operations sent to any replica are bundled by the
+/// replica holding the bundler lock of the chain.
+#[derive(Clone)]
+pub struct SharedMempool {
+    client: Arc<Client>,
+}
+
+impl SharedMempool {
+    pub fn new(client: Client) -> Self {
+        Self { client: Arc::new(client) }
+    }
+
+    fn connection(&self) -> Result<Connection> {
+        Ok(self.client.get_connection()?)
+    }
+
+    /// Get the chain ids w/ a mempool.
+    pub fn chain_ids(&self) -> Result<Vec<u64>> {
+        Ok(self.connection()?.smembers(MEMPOOL_CHAINS_KEY)?)
+    }
+
+    /// Load the mempool of the chain.
+    pub fn load(&self, chain_id: u64) -> Result<Mempool> {
+        let entries: HashMap<String, String> = self.connection()?.hgetall(mempool_key(chain_id))?;
+
+        let entries = entries
+            .values()
+            .map(|entry| serde_json::from_str::<MempoolEntry>(entry))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Mempool::from_entries(entries))
+    }
+
+    /// Add a validated user operation to the mempool of the chain, replacing the user operation
+    /// w/ the same sender and nonce if the fees are bumped.
+    pub fn add(&self, chain_id: u64, entry: MempoolEntry) -> Result<H256> {
+        let mut mempool = self.load(chain_id)?;
+        let replaced = mempool.get_hash(&entry.user_operation.id());
+        let value = serde_json::to_string(&entry)?;
+        let hash = mempool.add(entry)?;
+
+        let key = mempool_key(chain_id);
+        let mut pipe = lightdotso_redis::redis::pipe();
+        pipe.atomic().hset(&key, format!("{:?}", hash), value).ignore();
+        if let Some(replaced) = replaced.filter(|replaced| *replaced != hash) {
+            pipe.hdel(&key, format!("{:?}", replaced)).ignore();
+        }
+        pipe.sadd(MEMPOOL_CHAINS_KEY, chain_id).ignore();
+        pipe.query::<()>(&mut self.connection()?)?;
+
+        Ok(hash)
+    }
+
+    /// Remove the user operations from the mempool of the chain.
+    pub fn remove(&self, chain_id: u64, hashes: &[H256]) -> Result<()> {
+        if hashes.is_empty() {
+            return Ok(());
+        }
+
+        let fields: Vec<String> = hashes.iter().map(|hash| format!("{:?}", hash)).collect();
+        let _: () = self.connection()?.hdel(mempool_key(chain_id), fields)?;
+
+        Ok(())
+    }
+
+    /// Set the bundle status of the user operation, kept for the TTL.
+    pub fn set_status(&self, hash: H256, status: &BundleStatus, ttl: Duration) -> Result<()> {
+        let _: () = self.connection()?.set_ex(
+            status_key(hash),
+            serde_json::to_string(status)?,
+            ttl.as_secs() as usize,
+        )?;
+
+        Ok(())
+    }
+
+    /// Remove the bundle status of the user operation, as it is unknown.
+    pub fn remove_status(&self, hash: H256) -> Result<()> {
+        let _: () = self.connection()?.del(status_key(hash))?;
+
+        Ok(())
+    }
+
+    /// Get the bundle status of the user operation.
+    pub fn get_status(&self, hash: H256) -> Result<Option<BundleStatus>> {
+        let status: Option<String> = self.connection()?.get(status_key(hash))?;
+
+        Ok(status.map(|status| serde_json::from_str(&status)).transpose()?)
+    }
+}
+
+/// The key of the shared mempool of the chain
+fn mempool_key(chain_id: u64) -> String {
+    format!("node:mempool:{}", chain_id)
+}
+
+/// The key of the shared bundle status of the user operation
+fn status_key(hash: H256) -> String {
+    format!("node:status:{:?}", hash)
+}
+
+/// Whether the replacement doesn't bump both fees of the existing user operation enough.
+fn is_replacement_underpriced(existing: &UserOperation, replacement: &UserOperation) -> bool {
+    let min_fee = |fee: U256| fee + fee * REPLACEMENT_FEE_PERCENT_INCREASE / 100;
+
+    replacement.max_fee_per_gas < min_fee(existing.max_fee_per_gas) ||
+        replacement.max_priority_fee_per_gas < min_fee(existing.max_priority_fee_per_gas)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::types::Bytes;
+    use lightdotso_contracts::constants::ENTRYPOINT_V060_ADDRESS;
+
+    fn entry(sender: u64, nonce: u64, fee: u64) -> MempoolEntry {
+        let user_operation = UserOperation {
+            sender: Address::from_low_u64_be(sender),
+            nonce: nonce.into(),
+            init_code: Bytes::default(),
+            call_data: Bytes::default(),
+            call_gas_limit: U256::zero(),
+            verification_gas_limit: U256::zero(),
+            pre_verification_gas: U256::zero(),
+            max_fee_per_gas: fee.into(),
+            max_priority_fee_per_gas: fee.into(),
+            paymaster_and_data: Bytes::default(),
+            signature: Bytes::default(),
+        };
+        let hash = user_operation.op_hash(*ENTRYPOINT_V060_ADDRESS, 1).unwrap();
+
+        MempoolEntry::new(hash, *ENTRYPOINT_V060_ADDRESS, user_operation).unwrap()
+    }
+
+    #[test]
+    fn test_mempool_add_and_remove() {
+        let mut mempool = Mempool::default();
+
+        let hash = mempool.add(entry(1, 0, 100)).unwrap();
+        assert_eq!(mempool.len(), 1);
+        assert!(mempool.get(&hash).is_some());
+
+        assert!(mempool.remove(&hash).is_some());
+        assert!(mempool.is_empty());
+    }
+
+    #[test]
+    fn test_mempool_replacement() {
+        let mut mempool = Mempool::default();
+        mempool.add(entry(1, 0, 100)).unwrap();
+
+        // Same sender and nonce w/ an insufficient bump is rejected.
+        assert!(mempool.add(entry(1, 0, 105)).is_err());
+        assert_eq!(mempool.len(), 1);
+
+        // Same sender and nonce w/ a 10% bump replaces the existing one.
+        let hash = mempool.add(entry(1, 0, 110)).unwrap();
+        assert_eq!(mempool.len(), 1);
+        assert_eq!(mempool.get(&hash).unwrap().user_operation.max_fee_per_gas, 110.into());
+    }
+
+    #[test]
+    fn test_mempool_from_entries() {
+        let mut mempool = Mempool::default();
+        mempool.add(entry(1, 0, 100)).unwrap();
+        mempool.add(entry(2, 0, 200)).unwrap();
+
+        // The entries round trip the serialization of the shared mempool
+        let entries: Vec<MempoolEntry> = mempool
+            .best(*ENTRYPOINT_V060_ADDRESS, 2)
+            .iter()
+            .map(|entry| serde_json::from_str(&serde_json::to_string(entry).unwrap()).unwrap())
+            .collect();
+        let mut loaded = Mempool::from_entries(entries);
+        assert_eq!(loaded.len(), 2);
+
+        // The replacements are still checked against the loaded entries
+        let existing = entry(1, 0, 100);
+        assert_eq!(loaded.get_hash(&existing.user_operation.id()), Some(existing.hash));
+        assert!(loaded.add(entry(1, 0, 105)).is_err());
+    }
+
+    #[test]
+    fn test_mempool_best() {
+        let mut mempool = Mempool::default();
+        mempool.add(entry(1, 0, 100)).unwrap();
+        mempool.add(entry(1, 1, 300)).unwrap();
+        mempool.add(entry(2, 0, 200)).unwrap();
+        mempool.add(entry(3, 0, 50)).unwrap();
+
+        // Only the lowest nonce of each sender is bundled, ordered by the fee.
+        let best = mempool.best(*ENTRYPOINT_V060_ADDRESS, 2);
+        assert_eq!(best.len(), 2);
+        assert_eq!(best[0].user_operation.sender, Address::from_low_u64_be(2));
+        assert_eq!(best[1].user_operation.sender, Address::from_low_u64_be(1));
+        assert_eq!(best[1].user_operation.nonce, 0.into());
+
+        assert!(mempool.best(Address::from_low_u64_be(42), 2).is_empty());
+    }
+}
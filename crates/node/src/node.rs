@@ -26,21 +26,32 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use crate::config::NodeArgs;
+use crate::{
+    bundler::{wait_for_bundle, BundleStatus, Bundler},
+    config::NodeArgs,
+    mempool::{MempoolEntry, SharedMempool},
+    validation::{check_validation_rules, StakeInfo, ValidationContext, ValidationViolation},
+};
 use backon::{ExponentialBuilder, Retryable};
 use ethers::{
+    abi::AbiDecode,
     providers::Middleware,
+    signers::AwsSigner,
     types::{
         spoof, transaction::eip2718::TypedTransaction, Address, BlockNumber, Bytes,
-        GethDebugTracerType, GethDebugTracingCallOptions, GethDebugTracingOptions, H256,
+        GethDebugTracerType, GethDebugTracingCallOptions, GethDebugTracingOptions, GethTrace, H256,
     },
 };
-use eyre::{eyre, ContextCompat, Result};
+use eyre::{eyre, ContextCompat, Result, WrapErr};
 use lightdotso_contracts::{
     entrypoint::{get_entrypoint, UserOperationEventFilter, UserOperationRevertReasonFilter},
+    entrypoint_v070::{get_entrypoint_v070, FailedOp as FailedOpV070, FailedOpWithRevert},
     provider::get_provider,
-    tracer::{ExecutorTracerResult, EXECUTOR_TRACER},
-    types::UserOperation,
+    tracer::{ExecutorTracerResult, ValidationTracerResult, EXECUTOR_TRACER, VALIDATION_TRACER},
+    types::{
+        EntryPointVersion, PackedUserOperation, UserOperation, UserOperationRequest,
+        UserOperationRequestV070,
+    },
     user_operation::parse_user_op_event,
     utils::{decode_simulate_handle_ops_revert, get_revert_bytes},
 };
@@ -48,22 +59,255 @@ use lightdotso_jsonrpsee::{
     handle_response,
     types::{Request, Response},
 };
-use lightdotso_tracing::tracing::info;
+use lightdotso_redis::{get_redis_client, lock::LockManager};
+use lightdotso_signer::connect::connect_to_kms;
+use lightdotso_tracing::tracing::{error, info, warn};
 use serde_json::json;
+use std::{sync::Arc, time::Duration};
+
+/// The number of milliseconds the bundler lock of a chain is held for w/o being extended
+const BUNDLER_LOCK_TTL_MS: usize = 30_000;
+
+/// The duration the bundle statuses are kept for, to be queried after the bundle
+const BUNDLE_STATUS_TTL: Duration = Duration::from_secs(60 * 60);
 
 #[derive(Clone)]
-pub struct Node {}
+pub struct Node {
+    /// The node arguments
+    args: NodeArgs,
+    /// The in-process bundler, if enabled
+    bundler: Option<Arc<Bundler<AwsSigner>>>,
+    /// The lock manager electing the replica that bundles each chain, if the bundler is enabled
+    lock_manager: Option<Arc<LockManager>>,
+    /// The mempools and the bundle statuses shared across the replicas, if the bundler is enabled
+    mempool: Option<SharedMempool>,
+}
 
 impl Node {
-    pub async fn new(_args: &NodeArgs) -> Self {
+    pub async fn new(args: &NodeArgs) -> Result<Self> {
         info!("Node new, starting");
 
+        // Connect to the KMS signer of the bundler, if enabled
+        // The node fails to start instead of silently falling back to the external bundler
+        let (bundler, lock_manager, mempool) = if args.bundler_enabled {
+            let signer =
+                connect_to_kms().await.wrap_err("Failed to connect to the bundler signer")?;
+            let redis_client =
+                get_redis_client().wrap_err("Failed to connect to the bundler redis")?;
+
+            (
+                Some(Arc::new(Bundler::new(signer, args.beneficiary))),
+                Some(Arc::new(LockManager::new(vec![redis_client.clone()]))),
+                Some(SharedMempool::new(redis_client)),
+            )
+        } else {
+            (None, None, None)
+        };
+
         // Create the node
-        Self {}
+        Ok(Self { args: args.clone(), bundler, lock_manager, mempool })
     }
 
+    /// Run the bundler loop, which bundles the mempool of each chain at the interval.
     pub async fn run(&self) {
         info!("Node run, starting");
+
+        let (bundler, lock_manager, mempool) =
+            match (&self.bundler, &self.lock_manager, &self.mempool) {
+                (Some(bundler), Some(lock_manager), Some(mempool)) => {
+                    (bundler.clone(), lock_manager.clone(), mempool.clone())
+                }
+                _ => {
+                    info!("Bundler not enabled, skipping");
+                    return;
+                }
+            };
+
+        let interval = Duration::from_millis(self.args.bundle_interval_ms.max(100));
+        loop {
+            tokio::time::sleep(interval).await;
+
+            let chain_ids = match mempool.chain_ids() {
+                Ok(chain_ids) => chain_ids,
+                Err(e) => {
+                    error!("Failed to get the chains of the mempool: {:?}", e);
+                    continue;
+                }
+            };
+            for chain_id in chain_ids {
+                // Only the replica holding the lock of the chain bundles it, so that the replicas
+                // don't race on the nonce of the signer
+                let resource = format!("node:bundler:{}", chain_id);
+                let lock = match lock_manager.lock(resource.as_bytes(), BUNDLER_LOCK_TTL_MS).await {
+                    Ok(lock) => lock,
+                    Err(_) => {
+                        info!("Chain {} is bundled by another replica, skipping", chain_id);
+                        continue;
+                    }
+                };
+
+                // Extend the lock while the chain is bundled, and stop bundling if it's lost
+                let heartbeat = async {
+                    loop {
+                        tokio::time::sleep(Duration::from_millis(BUNDLER_LOCK_TTL_MS as u64 / 3))
+                            .await;
+                        if lock_manager.extend(&lock, BUNDLER_LOCK_TTL_MS).await.is_err() {
+                            return;
+                        }
+                    }
+                };
+                tokio::select! {
+                    res = self.bundle_chain(&bundler, &mempool, chain_id) => {
+                        if let Err(e) = res {
+                            error!("Failed to bundle chain {}: {:?}", chain_id, e);
+                        }
+                    }
+                    _ = heartbeat => {
+                        error!("Lost the bundler lock of chain {}, stopped bundling", chain_id);
+                    }
+                }
+
+                lock_manager.unlock(&lock).await;
+            }
+        }
+    }
+
+    /// Bundle the best user operations in the mempool of the chain for each entry point.
+    /// The receipts of the bundles are tracked in the background, so that the other chains aren't
+    /// blocked while the bundles are included.
+    async fn bundle_chain(
+        &self,
+        bundler: &Bundler<AwsSigner>,
+        mempool: &SharedMempool,
+        chain_id: u64,
+    ) -> Result<()> {
+        let batches: Vec<(Address, Vec<MempoolEntry>)> = {
+            let mempool = mempool.load(chain_id)?;
+            mempool
+                .entry_points()
+                .into_iter()
+                .map(|entry_point| {
+                    (entry_point, mempool.best(entry_point, self.args.max_bundle_size.max(1)))
+                })
+                .collect()
+        };
+
+        // Get provider
+        let provider = get_provider(chain_id).await?;
+
+        for (entry_point, entries) in batches {
+            if entries.is_empty() {
+                continue;
+            }
+
+            // Build the bundle, and keep the user operations in the mempool on errors other than
+            // the rejections of the entry point, to retry on the next bundle
+            let bundle = match bundler.build_bundle(&provider, entry_point, entries).await {
+                Ok(bundle) => bundle,
+                Err(e) => {
+                    warn!("Failed to build the bundle: {:?}", e);
+                    continue;
+                }
+            };
+
+            // Remove only the user operations rejected w/ `FailedOp`
+            for (entry, reason) in bundle.rejected.iter() {
+                let status = BundleStatus::Rejected(reason.clone());
+                remove_entries(mempool, chain_id, std::slice::from_ref(entry), &status)?;
+            }
+            if bundle.entries.is_empty() {
+                continue;
+            }
+
+            // Send the bundle
+            let tx_hash = match bundler.send_bundle(&provider, chain_id, &bundle).await {
+                Ok(tx_hash) => tx_hash,
+                Err(e) => {
+                    // Keep the user operations in the mempool to retry on the next bundle
+                    error!("Failed to send the bundle: {:?}", e);
+                    continue;
+                }
+            };
+            remove_entries(mempool, chain_id, &bundle.entries, &BundleStatus::Pending(tx_hash))?;
+
+            // Track the receipt of the bundle in the background
+            tokio::spawn({
+                let provider = provider.clone();
+                let mempool = mempool.clone();
+                let hashes: Vec<H256> = bundle.entries.iter().map(|entry| entry.hash).collect();
+                async move {
+                    let status = wait_for_bundle(&provider, tx_hash).await;
+                    info!("Bundle {:?} status: {:?}", tx_hash, status);
+
+                    for hash in hashes {
+                        let res = match &status {
+                            Ok(status) => mempool.set_status(hash, status, BUNDLE_STATUS_TTL),
+                            // The status is unknown if the receipt couldn't be tracked
+                            Err(_) => mempool.remove_status(hash),
+                        };
+                        if let Err(e) = res {
+                            error!("Failed to set the bundle status of {:?}: {:?}", hash, e);
+                        }
+                    }
+                }
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Validate a user operation w/ the tracer simulation and the validation rules.
+    /// The user operation is rejected on any failure, including the errors of the tracer.
+    pub async fn validate_user_operation(
+        &self,
+        chain_id: u64,
+        entry_point: Address,
+        user_operation: &UserOperation,
+    ) -> Result<()> {
+        // Simulate the user operation w/ the tracer
+        let success =
+            self.simulate_user_operation_with_tracer(chain_id, entry_point, user_operation).await?;
+        if !success {
+            return Err(eyre!("User operation failed the simulation"));
+        }
+
+        // Check the user operation against the validation rules
+        let violations =
+            self.validate_user_operation_with_tracer(chain_id, entry_point, user_operation).await?;
+        if !violations.is_empty() {
+            return Err(eyre!("User operation violates the validation rules: {:?}", violations));
+        }
+
+        Ok(())
+    }
+
+    /// Add a user operation validated w/ `validate_user_operation` to the mempool of the chain.
+    pub async fn add_user_operation(
+        &self,
+        chain_id: u64,
+        entry_point: Address,
+        user_operation: &UserOperation,
+    ) -> Result<H256> {
+        let mempool = self.mempool.as_ref().ok_or_else(|| eyre!("Bundler not enabled"))?;
+        let entry = MempoolEntry::new(
+            user_operation.op_hash(entry_point, chain_id)?,
+            entry_point,
+            user_operation.clone(),
+        )?;
+
+        // Add the user operation to the shared mempool, bundled by the replica holding the lock
+        let hash = mempool.add(chain_id, entry)?;
+        info!("Added user operation {:?} to the mempool of chain {}", hash, chain_id);
+
+        Ok(hash)
+    }
+
+    /// Get the bundle status of a user operation sent to the in-process bundler of any replica.
+    pub async fn get_user_operation_status(&self, hash: H256) -> Result<Option<BundleStatus>> {
+        match &self.mempool {
+            Some(mempool) => mempool.get_status(hash),
+            None => Ok(None),
+        }
     }
 
     pub async fn simulate_user_operation_with_backon(
@@ -82,10 +326,28 @@ impl Node {
         Ok(())
     }
 
+    /// Simulate a user operation on the node w/ `eth_call`
+    /// The simulation is picked from the entry point version.
+    pub async fn simulate_user_operation(
+        &self,
+        chain_id: u64,
+        entry_point: Address,
+        user_operation: &UserOperation,
+    ) -> Result<()> {
+        match EntryPointVersion::try_from(entry_point)? {
+            EntryPointVersion::V060 => {
+                self.simulate_user_operation_v060(chain_id, entry_point, user_operation).await
+            }
+            EntryPointVersion::V070 => {
+                self.simulate_user_operation_v070(chain_id, entry_point, user_operation).await
+            }
+        }
+    }
+
     /// Simulate a user operation on the node w/ `eth_call`
     /// Note that this function will always return an error because the call will revert on-chain
     /// Only for EntryPoint v0.6.0
-    pub async fn simulate_user_operation(
+    async fn simulate_user_operation_v060(
         &self,
         chain_id: u64,
         entry_point: Address,
@@ -113,6 +375,45 @@ impl Node {
         Ok(())
     }
 
+    /// Simulate a user operation on the node w/ `eth_call` of `handleOps`
+    /// The v0.7.0 entry point doesn't have `simulateHandleOp`, so the call succeeds if the
+    /// operation is valid, and reverts w/ `FailedOp` or `FailedOpWithRevert` otherwise
+    /// Only for EntryPoint v0.7.0
+    async fn simulate_user_operation_v070(
+        &self,
+        chain_id: u64,
+        entry_point: Address,
+        user_operation: &UserOperation,
+    ) -> Result<()> {
+        let entrypoint = get_entrypoint_v070(chain_id, entry_point).await?;
+
+        // Simulate the user operation w/ `eth_call`
+        let res = entrypoint
+            .handle_ops(
+                vec![PackedUserOperation::from(user_operation.clone()).into()],
+                Address::zero(),
+            )
+            .call_raw()
+            .await;
+        info!("res: {:?}", res);
+
+        // Decode the revert reason, if any
+        if let Err(err) = res {
+            let error_data = get_revert_bytes(err)?;
+            info!("error_data: {:?}", error_data);
+
+            if let Ok(failed_op) = FailedOpV070::decode(&error_data) {
+                return Err(eyre!(failed_op.reason));
+            }
+            if let Ok(failed_op) = FailedOpWithRevert::decode(&error_data) {
+                return Err(eyre!("{}: {:?}", failed_op.reason, failed_op.inner));
+            }
+            return Err(eyre!("handle_ops reverted: {:?}", error_data));
+        }
+
+        Ok(())
+    }
+
     pub async fn simulate_user_operation_with_tracer_with_backon(
         &self,
         chain_id: u64,
@@ -135,7 +436,7 @@ impl Node {
     // License: Apache-2.0
 
     /// Simulate a user operation on the node w/ `debug_traceCall`
-    /// Only for EntryPoint v0.6.0
+    /// Traces `simulateHandleOp` for EntryPoint v0.6.0, and `handleOps` for EntryPoint v0.7.0
     /// Parses the user operation event and the user operation revert event from the trace for next.
     pub async fn simulate_user_operation_with_tracer(
         &self,
@@ -143,23 +444,150 @@ impl Node {
         entry_point: Address,
         user_operation: &UserOperation,
     ) -> Result<bool> {
-        // Get the entrypoint
-        let entrypoint = get_entrypoint(chain_id, entry_point).await?;
-
         // From: https://github.com/silius-rs/silius/blob/f695b54cbbabf6b3f22f7af8918a2d6d83ca8960/crates/contracts/src/entry_point.rs#L139-L175
         // License: Apache-2.0
 
         // Debug trace call
-        let call = entrypoint.simulate_handle_op(
-            user_operation.clone().into(),
-            Address::zero(),
-            Bytes::default(),
-        );
-        let mut tx: TypedTransaction = call.tx;
+        let mut tx: TypedTransaction = match EntryPointVersion::try_from(entry_point)? {
+            EntryPointVersion::V060 => {
+                get_entrypoint(chain_id, entry_point)
+                    .await?
+                    .simulate_handle_op(
+                        user_operation.clone().into(),
+                        Address::zero(),
+                        Bytes::default(),
+                    )
+                    .tx
+            }
+            EntryPointVersion::V070 => {
+                get_entrypoint_v070(chain_id, entry_point)
+                    .await?
+                    .handle_ops(
+                        vec![PackedUserOperation::from(user_operation.clone()).into()],
+                        Address::zero(),
+                    )
+                    .tx
+            }
+        };
         tx.set_from(Address::zero());
         tx.set_gas_price(user_operation.clone().max_fee_per_gas);
         tx.set_gas(u64::MAX);
 
+        // Get the geth trace
+        let trace = self.debug_trace_call(chain_id, tx, EXECUTOR_TRACER).await?;
+
+        let tracer_result: ExecutorTracerResult =
+            ExecutorTracerResult::try_from(trace).map_err(|e| eyre!(e))?;
+        info!("tracer_result: {:?}", tracer_result);
+
+        let user_op_revert_event = tracer_result
+            .user_op_revert_event
+            .as_ref()
+            .and_then(|e| parse_user_op_event::<UserOperationRevertReasonFilter>(e).ok());
+        info!("user_op_revert_event: {:?}", user_op_revert_event);
+
+        let user_op_event = tracer_result
+            .user_op_event
+            .as_ref()
+            .ok_or(eyre!("Estimate trace simulate handle op user op event not found"))?;
+        let user_op_event = parse_user_op_event::<UserOperationEventFilter>(user_op_event)?;
+        info!("user_op_event: {:?}", user_op_event);
+
+        Ok(user_op_event.success)
+    }
+
+    /// Validate a user operation against the ERC-7562 validation rules w/ `debug_traceCall`
+    /// Traces `simulateValidation` for EntryPoint v0.6.0, and `handleOps` for EntryPoint v0.7.0
+    /// Returns the list of violations, which is empty if the user operation is valid.
+    pub async fn validate_user_operation_with_tracer(
+        &self,
+        chain_id: u64,
+        entry_point: Address,
+        user_operation: &UserOperation,
+    ) -> Result<Vec<ValidationViolation>> {
+        // Debug trace call
+        let mut tx: TypedTransaction = match EntryPointVersion::try_from(entry_point)? {
+            EntryPointVersion::V060 => {
+                get_entrypoint(chain_id, entry_point)
+                    .await?
+                    .simulate_validation(user_operation.clone().into())
+                    .tx
+            }
+            EntryPointVersion::V070 => {
+                get_entrypoint_v070(chain_id, entry_point)
+                    .await?
+                    .handle_ops(
+                        vec![PackedUserOperation::from(user_operation.clone()).into()],
+                        Address::zero(),
+                    )
+                    .tx
+            }
+        };
+        tx.set_from(Address::zero());
+        tx.set_gas_price(user_operation.clone().max_fee_per_gas);
+        tx.set_gas(u64::MAX);
+
+        // Get the geth trace
+        let trace = self.debug_trace_call(chain_id, tx, VALIDATION_TRACER).await?;
+
+        let tracer_result: ValidationTracerResult =
+            ValidationTracerResult::try_from(trace).map_err(|e| eyre!(e))?;
+        info!("tracer_result: {:?}", tracer_result);
+
+        // Get the stake info of the entities
+        let context = ValidationContext {
+            entry_point,
+            sender: self.get_stake_info(chain_id, entry_point, user_operation.sender).await?,
+            factory: match user_operation.factory() {
+                Some(factory) => Some(self.get_stake_info(chain_id, entry_point, factory).await?),
+                None => None,
+            },
+            paymaster: match user_operation.paymaster() {
+                Some(paymaster) => {
+                    Some(self.get_stake_info(chain_id, entry_point, paymaster).await?)
+                }
+                None => None,
+            },
+        };
+
+        let violations = check_validation_rules(&tracer_result, &context);
+        info!("violations: {:?}", violations);
+
+        Ok(violations)
+    }
+
+    /// Get the stake info of an entity from the entry point.
+    async fn get_stake_info(
+        &self,
+        chain_id: u64,
+        entry_point: Address,
+        address: Address,
+    ) -> Result<StakeInfo> {
+        let stake_info = match EntryPointVersion::try_from(entry_point)? {
+            EntryPointVersion::V060 => {
+                let info =
+                    get_entrypoint(chain_id, entry_point).await?.get_deposit_info(address).await?;
+                StakeInfo::new(address, info.staked, info.unstake_delay_sec)
+            }
+            EntryPointVersion::V070 => {
+                let info = get_entrypoint_v070(chain_id, entry_point)
+                    .await?
+                    .get_deposit_info(address)
+                    .await?;
+                StakeInfo::new(address, info.staked, info.unstake_delay_sec)
+            }
+        };
+
+        Ok(stake_info)
+    }
+
+    /// Trace the transaction w/ the js tracer, from the zero address w/ the max balance.
+    async fn debug_trace_call(
+        &self,
+        chain_id: u64,
+        tx: TypedTransaction,
+        tracer: &str,
+    ) -> Result<GethTrace> {
         // Get provider
         let provider = get_provider(chain_id).await?;
 
@@ -174,7 +602,7 @@ impl Node {
                         disable_stack: None,
                         enable_memory: None,
                         enable_return_data: None,
-                        tracer: Some(GethDebugTracerType::JsTracer(EXECUTOR_TRACER.into())),
+                        tracer: Some(GethDebugTracerType::JsTracer(tracer.into())),
                         tracer_config: None,
                         timeout: None,
                     },
@@ -190,24 +618,7 @@ impl Node {
             .await
             .map_err(|e| eyre!("Failed to debug trace call: {:?}", e))?;
 
-        let tracer_result: ExecutorTracerResult =
-            ExecutorTracerResult::try_from(trace).map_err(|e| eyre!(e))?;
-        info!("tracer_result: {:?}", tracer_result);
-
-        let user_op_revert_event = tracer_result
-            .user_op_revert_event
-            .as_ref()
-            .and_then(|e| parse_user_op_event::<UserOperationRevertReasonFilter>(e).ok());
-        info!("user_op_revert_event: {:?}", user_op_revert_event);
-
-        let user_op_event = tracer_result
-            .user_op_event
-            .as_ref()
-            .ok_or(eyre!("Estimate trace simulate handle op user op event not found"))?;
-        let user_op_event = parse_user_op_event::<UserOperationEventFilter>(user_op_event)?;
-        info!("user_op_event: {:?}", user_op_event);
-
-        Ok(user_op_event.success)
+        Ok(trace)
     }
 
     pub async fn send_user_operation_with_backon(
@@ -225,7 +636,9 @@ impl Node {
         res
     }
 
-    /// Send a user operation to the node
+    /// Send a user operation validated w/ `validate_user_operation` to the node
+    /// The user operation is added to the shared mempool if the bundler is enabled
+    /// Otherwise, the user operation is encoded in the RPC format of the entry point version
     /// From: https://github.com/qi-protocol/ethers-userop/blob/50cb1b18a551a681786f1a766d11215c80afa7cf/src/userop_middleware.rs#L128
     /// License: MIT
     pub async fn send_user_operation(
@@ -234,7 +647,19 @@ impl Node {
         entry_point: Address,
         user_operation: &UserOperation,
     ) -> Result<Response<H256>> {
-        let params = vec![json!(user_operation.clone()), json!(entry_point)];
+        if self.bundler.is_some() {
+            let hash = self.add_user_operation(chain_id, entry_point, user_operation).await?;
+
+            return Ok(Response { jsonrpc: "2.0".to_string(), id: 1, result: hash });
+        }
+
+        let user_operation_param = match EntryPointVersion::try_from(entry_point)? {
+            EntryPointVersion::V060 => json!(user_operation.clone()),
+            EntryPointVersion::V070 => json!(UserOperationRequestV070::from(
+                UserOperationRequest::from(user_operation.clone())
+            )),
+        };
+        let params = vec![user_operation_param, json!(entry_point)];
         info!("params: {:?}", params);
 
         let req_body = Request {
@@ -266,3 +691,19 @@ impl Node {
         Ok(res)
     }
 }
+
+/// Remove the entries from the shared mempool of the chain and set their status.
+fn remove_entries(
+    mempool: &SharedMempool,
+    chain_id: u64,
+    entries: &[MempoolEntry],
+    status: &BundleStatus,
+) -> Result<()> {
+    let hashes: Vec<H256> = entries.iter().map(|entry| entry.hash).collect();
+    mempool.remove(chain_id, &hashes)?;
+    for hash in hashes {
+        mempool.set_status(hash, status, BUNDLE_STATUS_TTL)?;
+    }
+
+    Ok(())
+}
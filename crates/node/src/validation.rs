@@ -0,0 +1,623 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// From: https://github.com/eth-infinitism/bundler/blob/a6c6f8b46bd4b6efd5e9e8ba4d7ab1feb4d8ff1c/packages/validation-manager/src/TracerResultParser.ts
+// License: GPL-3.0
+
+use ethers::{
+    abi::{decode, ParamType},
+    types::{Address, H256, U256},
+    utils::keccak256,
+};
+use lightdotso_contracts::tracer::{TopLevelCallInfo, ValidationTracerResult};
+use serde::Serialize;
+use std::{collections::HashSet, fmt};
+
+/// The minimum unstake delay of a staked entity in seconds.
+pub const MIN_UNSTAKE_DELAY: u32 = 86400;
+
+/// The max offset of a slot from the keccak of an associated address.
+const ASSOCIATED_SLOT_RANGE: u64 = 128;
+
+/// The opcodes banned during the validation of all entities. [OP-011]
+const BANNED_OPCODES: &[&str] = &[
+    "BALANCE",
+    "BASEFEE",
+    "BLOBBASEFEE",
+    "BLOBHASH",
+    "BLOCKHASH",
+    "COINBASE",
+    "CREATE",
+    "DIFFICULTY",
+    "GAS",
+    "GASLIMIT",
+    "GASPRICE",
+    "NUMBER",
+    "ORIGIN",
+    "PREVRANDAO",
+    "SELFBALANCE",
+    "SELFDESTRUCT",
+    "TIMESTAMP",
+];
+
+/// The selectors of the `createSender` call to the sender creator.
+const CREATE_SENDER_SELECTORS: &[[u8; 4]] = &[[0x57, 0x0e, 0x1a, 0x36]];
+
+/// The selectors of `validateUserOp` for EntryPoint v0.6.0 and v0.7.0.
+const VALIDATE_USER_OP_SELECTORS: &[[u8; 4]] =
+    &[[0x3a, 0x87, 0x1c, 0xdd], [0x19, 0x82, 0x2f, 0x7c]];
+
+/// The selectors of `validatePaymasterUserOp` for EntryPoint v0.6.0 and v0.7.0.
+const VALIDATE_PAYMASTER_USER_OP_SELECTORS: &[[u8; 4]] =
+    &[[0xf4, 0x65, 0xc7, 0x7e], [0x52, 0xb7, 0x51, 0x2c]];
+
+/// The selector of `depositTo`, the only entry point method callable during the validation.
+const DEPOSIT_TO_SELECTOR: &str = "0xb760faf9";
+
+/// The entity validated in a top level call of the entry point.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Entity {
+    Factory,
+    Account,
+    Paymaster,
+}
+
+impl fmt::Display for Entity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Entity::Factory => write!(f, "factory"),
+            Entity::Account => write!(f, "account"),
+            Entity::Paymaster => write!(f, "paymaster"),
+        }
+    }
+}
+
+/// The address and the stake status of an entity.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StakeInfo {
+    pub address: Address,
+    pub staked: bool,
+}
+
+impl StakeInfo {
+    /// Get the stake info from the `getDepositInfo` of the entry point.
+    pub fn new(address: Address, staked: bool, unstake_delay_sec: u32) -> Self {
+        Self { address, staked: staked && unstake_delay_sec >= MIN_UNSTAKE_DELAY }
+    }
+}
+
+/// The entities of the user operation validated against the rules.
+#[derive(Clone, Debug, Default)]
+pub struct ValidationContext {
+    pub entry_point: Address,
+    pub sender: StakeInfo,
+    pub factory: Option<StakeInfo>,
+    pub paymaster: Option<StakeInfo>,
+}
+
+impl ValidationContext {
+    /// Get the stake info of the entity, if the user operation has it.
+    pub fn entity(&self, entity: Entity) -> Option<StakeInfo> {
+        match entity {
+            Entity::Factory => self.factory,
+            Entity::Account => Some(self.sender),
+            Entity::Paymaster => self.paymaster,
+        }
+    }
+}
+
+/// A violation of the ERC-7562 validation rules.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum ValidationViolation {
+    /// The entity used a banned opcode [OP-011, OP-012, OP-031]
+    BannedOpcode { entity: Entity, opcode: String },
+    /// The validation of the entity ran out of gas [OP-020]
+    OutOfGas { entity: Entity },
+    /// The entity accessed an address without deployed code [OP-041]
+    MissingCode { entity: Entity, address: Address, opcode: String },
+    /// The entity called the entry point w/ a method other than `depositTo` [OP-052, OP-053]
+    EntryPointCall { entity: Entity, method: String },
+    /// The entity called a contract other than the entry point w/ value [OP-061]
+    CallWithValue { entity: Entity, target: Address },
+    /// The entity accessed a storage slot not allowed for any entity [STO-033]
+    StorageAccess { entity: Entity, contract: Address, slot: H256 },
+    /// The entity accessed a storage slot only allowed for staked entities
+    /// [STO-022, STO-031, STO-032, STO-033]
+    UnstakedStorageAccess { entity: Entity, contract: Address, slot: H256 },
+    /// The unstaked paymaster returned a context [EREP-050]
+    UnstakedPaymasterContext { paymaster: Address },
+}
+
+impl ValidationViolation {
+    /// Get the ERC-7562 rule id of the violation.
+    pub fn rule(&self) -> &'static str {
+        match self {
+            ValidationViolation::BannedOpcode { opcode, .. } if opcode == "GAS" => "OP-012",
+            ValidationViolation::BannedOpcode { opcode, .. } if opcode == "CREATE2" => "OP-031",
+            ValidationViolation::BannedOpcode { .. } => "OP-011",
+            ValidationViolation::OutOfGas { .. } => "OP-020",
+            ValidationViolation::MissingCode { .. } => "OP-041",
+            ValidationViolation::EntryPointCall { .. } => "OP-052",
+            ValidationViolation::CallWithValue { .. } => "OP-061",
+            ValidationViolation::StorageAccess { .. } => "STO-033",
+            ValidationViolation::UnstakedStorageAccess { .. } => "STO-031",
+            ValidationViolation::UnstakedPaymasterContext { .. } => "EREP-050",
+        }
+    }
+}
+
+impl fmt::Display for ValidationViolation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rule = self.rule();
+        match self {
+            ValidationViolation::BannedOpcode { entity, opcode } => {
+                write!(f, "[{rule}] {entity} uses banned opcode {opcode}")
+            }
+            ValidationViolation::OutOfGas { entity } => {
+                write!(f, "[{rule}] {entity} ran out of gas during the validation")
+            }
+            ValidationViolation::MissingCode { entity, address, opcode } => {
+                write!(f, "[{rule}] {entity} accesses {address:?} without code w/ {opcode}")
+            }
+            ValidationViolation::EntryPointCall { entity, method } => {
+                write!(f, "[{rule}] {entity} calls the entry point w/ {method}")
+            }
+            ValidationViolation::CallWithValue { entity, target } => {
+                write!(f, "[{rule}] {entity} calls {target:?} w/ value")
+            }
+            ValidationViolation::StorageAccess { entity, contract, slot } => {
+                write!(f, "[{rule}] {entity} accesses storage {contract:?} at slot {slot:?}")
+            }
+            ValidationViolation::UnstakedStorageAccess { entity, contract, slot } => {
+                write!(
+                    f,
+                    "[{rule}] unstaked {entity} accesses storage {contract:?} at slot {slot:?}"
+                )
+            }
+            ValidationViolation::UnstakedPaymasterContext { paymaster } => {
+                write!(f, "[{rule}] unstaked paymaster {paymaster:?} returns a context")
+            }
+        }
+    }
+}
+
+/// Check the result of the `VALIDATION_TRACER` against the ERC-7562 validation rules.
+/// Returns the list of violations, which is empty if the user operation is valid.
+pub fn check_validation_rules(
+    result: &ValidationTracerResult,
+    context: &ValidationContext,
+) -> Vec<ValidationViolation> {
+    let mut violations = vec![];
+
+    for call in result.calls_from_entry_point.iter() {
+        // Skip the top level calls other than the validation of the entities
+        let entity = match get_entity(call) {
+            Some(entity) => entity,
+            None => continue,
+        };
+        let stake_info = match context.entity(entity) {
+            Some(stake_info) => stake_info,
+            None => continue,
+        };
+
+        check_opcodes(call, entity, &mut violations);
+        check_calls(call, entity, context, &mut violations);
+        check_storage(call, entity, stake_info, &result.keccak, context, &mut violations);
+
+        // The paymaster context is only allowed for staked paymasters [EREP-050]
+        if entity == Entity::Paymaster && !stake_info.staked && has_paymaster_context(call) {
+            violations.push(ValidationViolation::UnstakedPaymasterContext {
+                paymaster: stake_info.address,
+            });
+        }
+    }
+
+    violations
+}
+
+/// Get the entity of the top level call from the method selector.
+fn get_entity(call: &TopLevelCallInfo) -> Option<Entity> {
+    let selector: [u8; 4] = call.top_level_method_sig.get(..4)?.try_into().ok()?;
+
+    if CREATE_SENDER_SELECTORS.contains(&selector) {
+        Some(Entity::Factory)
+    } else if VALIDATE_USER_OP_SELECTORS.contains(&selector) {
+        Some(Entity::Account)
+    } else if VALIDATE_PAYMASTER_USER_OP_SELECTORS.contains(&selector) {
+        Some(Entity::Paymaster)
+    } else {
+        None
+    }
+}
+
+/// Check the banned opcodes and the out of gas of the entity.
+fn check_opcodes(
+    call: &TopLevelCallInfo,
+    entity: Entity,
+    violations: &mut Vec<ValidationViolation>,
+) {
+    let mut opcodes: Vec<&String> =
+        call.opcodes.keys().filter(|opcode| BANNED_OPCODES.contains(&opcode.as_str())).collect();
+    opcodes.sort();
+    for opcode in opcodes {
+        violations.push(ValidationViolation::BannedOpcode { entity, opcode: opcode.clone() });
+    }
+
+    // The `CREATE2` opcode is only allowed once for the factory to deploy the sender [OP-031]
+    let create2_count = call.opcodes.get("CREATE2").copied().unwrap_or_default();
+    if create2_count > 0 && (entity != Entity::Factory || create2_count > 1) {
+        violations.push(ValidationViolation::BannedOpcode { entity, opcode: "CREATE2".into() });
+    }
+
+    if call.oog {
+        violations.push(ValidationViolation::OutOfGas { entity });
+    }
+}
+
+/// Check the calls and the accessed code of the entity.
+fn check_calls(
+    call: &TopLevelCallInfo,
+    entity: Entity,
+    context: &ValidationContext,
+    violations: &mut Vec<ValidationViolation>,
+) {
+    // The access to an address without code is forbidden, except the sender [OP-041, OP-042]
+    let mut addresses: Vec<_> = call.contract_size.iter().collect();
+    addresses.sort_by_key(|(address, _)| **address);
+    for (address, info) in addresses {
+        if *address != context.sender.address && info.contract_size == 0 {
+            violations.push(ValidationViolation::MissingCode {
+                entity,
+                address: *address,
+                opcode: info.opcode.clone(),
+            });
+        }
+    }
+
+    for entry in call.calls.iter() {
+        // Skip the top level call from the entry point itself
+        if entry.from == Some(context.entry_point) {
+            continue;
+        }
+
+        if entry.to == Some(context.entry_point) {
+            // Only `depositTo` and the fallback of the entry point are allowed [OP-052, OP-053]
+            let method = entry.method.clone().unwrap_or_default();
+            if method != "0x" && !method.is_empty() && method != DEPOSIT_TO_SELECTOR {
+                violations.push(ValidationViolation::EntryPointCall { entity, method });
+            }
+        } else if entry.value.unwrap_or_default() > U256::zero() {
+            // Calls w/ value are only allowed to the entry point [OP-061]
+            violations.push(ValidationViolation::CallWithValue {
+                entity,
+                target: entry.to.unwrap_or_default(),
+            });
+        }
+    }
+}
+
+/// Check the storage access of the entity.
+fn check_storage(
+    call: &TopLevelCallInfo,
+    entity: Entity,
+    stake_info: StakeInfo,
+    keccak: &[ethers::types::Bytes],
+    context: &ValidationContext,
+    violations: &mut Vec<ValidationViolation>,
+) {
+    let sender_slots = associated_slots(context.sender.address, keccak);
+    let entity_slots = associated_slots(stake_info.address, keccak);
+
+    let mut contracts: Vec<_> = call.access.iter().collect();
+    contracts.sort_by_key(|(contract, _)| **contract);
+    for (contract, access) in contracts {
+        // The storage of the entry point and the sender is always allowed [STO-010]
+        if *contract == context.entry_point || *contract == context.sender.address {
+            continue;
+        }
+
+        let mut slots: Vec<(H256, bool)> = access
+            .reads
+            .keys()
+            .map(|slot| (*slot, false))
+            .chain(access.writes.keys().map(|slot| (*slot, true)))
+            .collect();
+        slots.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+        slots.dedup_by_key(|(slot, _)| *slot);
+
+        for (slot, is_write) in slots {
+            let requires_stake = if is_associated(&slot, &sender_slots) {
+                // The associated storage of the sender is allowed if the sender exists, or if the
+                // factory is staked [STO-021, STO-022]
+                match context.factory {
+                    Some(factory) if !factory.staked => {
+                        violations.push(ValidationViolation::UnstakedStorageAccess {
+                            entity: Entity::Factory,
+                            contract: *contract,
+                            slot,
+                        });
+                    }
+                    _ => {}
+                }
+                continue;
+            } else if *contract == stake_info.address || is_associated(&slot, &entity_slots) {
+                // The own and the associated storage of the entity require stake [STO-031,
+                // STO-032]
+                true
+            } else if is_write {
+                // The write to the storage of non-entity contracts is forbidden [STO-033]
+                violations.push(ValidationViolation::StorageAccess {
+                    entity,
+                    contract: *contract,
+                    slot,
+                });
+                continue;
+            } else {
+                // The read-only access to non-entity contracts requires stake [STO-033]
+                true
+            };
+
+            if requires_stake && !stake_info.staked {
+                violations.push(ValidationViolation::UnstakedStorageAccess {
+                    entity,
+                    contract: *contract,
+                    slot,
+                });
+            }
+        }
+    }
+}
+
+/// Get the base slots of the mappings keyed by the address, e.g. `keccak256(address || x)`.
+fn associated_slots(address: Address, keccak: &[ethers::types::Bytes]) -> HashSet<U256> {
+    let padded = H256::from(address);
+
+    keccak
+        .iter()
+        .filter(|preimage| preimage.len() >= 32 && preimage[..32] == padded[..])
+        .map(|preimage| U256::from_big_endian(&keccak256(preimage)))
+        .collect()
+}
+
+/// Whether the slot is within the range of an associated base slot.
+fn is_associated(slot: &H256, base_slots: &HashSet<U256>) -> bool {
+    let slot = U256::from_big_endian(slot.as_bytes());
+
+    base_slots.iter().any(|base| slot >= *base && slot - *base < ASSOCIATED_SLOT_RANGE.into())
+}
+
+/// Whether the paymaster returned a non-empty context from `validatePaymasterUserOp`.
+fn has_paymaster_context(call: &TopLevelCallInfo) -> bool {
+    let output = match &call.output {
+        Some(output) => output,
+        None => return false,
+    };
+
+    decode(&[ParamType::Bytes, ParamType::Uint(256)], output)
+        .ok()
+        .and_then(|tokens| tokens.into_iter().next())
+        .and_then(|token| token.into_bytes())
+        .map(|context| !context.is_empty())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::{
+        abi::{encode, Token},
+        types::Bytes,
+    };
+    use lightdotso_contracts::tracer::{CallEntry, ContractSizeInfo, StorageAccessInfo};
+    use std::collections::HashMap;
+
+    fn context(factory_staked: bool, paymaster_staked: bool) -> ValidationContext {
+        ValidationContext {
+            entry_point: Address::repeat_byte(0xee),
+            sender: StakeInfo { address: Address::repeat_byte(0x01), staked: false },
+            factory: Some(StakeInfo {
+                address: Address::repeat_byte(0x02),
+                staked: factory_staked,
+            }),
+            paymaster: Some(StakeInfo {
+                address: Address::repeat_byte(0x03),
+                staked: paymaster_staked,
+            }),
+        }
+    }
+
+    fn call(selector: [u8; 4]) -> TopLevelCallInfo {
+        TopLevelCallInfo { top_level_method_sig: selector.to_vec().into(), ..Default::default() }
+    }
+
+    #[test]
+    fn test_stake_info() {
+        assert!(StakeInfo::new(Address::zero(), true, MIN_UNSTAKE_DELAY).staked);
+        assert!(!StakeInfo::new(Address::zero(), true, MIN_UNSTAKE_DELAY - 1).staked);
+        assert!(!StakeInfo::new(Address::zero(), false, MIN_UNSTAKE_DELAY).staked);
+    }
+
+    #[test]
+    fn test_banned_opcodes() {
+        let mut account = call(VALIDATE_USER_OP_SELECTORS[0]);
+        account.opcodes.insert("TIMESTAMP".to_string(), 1);
+        account.opcodes.insert("SLOAD".to_string(), 3);
+        account.opcodes.insert("CREATE2".to_string(), 1);
+        account.oog = true;
+
+        let mut factory = call(CREATE_SENDER_SELECTORS[0]);
+        factory.opcodes.insert("CREATE2".to_string(), 1);
+
+        // The top level calls other than the validation are skipped
+        let mut other = call([0xde, 0xad, 0xbe, 0xef]);
+        other.opcodes.insert("NUMBER".to_string(), 1);
+
+        let result = ValidationTracerResult {
+            calls_from_entry_point: vec![factory, account, other],
+            ..Default::default()
+        };
+        let violations = check_validation_rules(&result, &context(true, true));
+
+        assert_eq!(
+            violations,
+            vec![
+                ValidationViolation::BannedOpcode {
+                    entity: Entity::Account,
+                    opcode: "TIMESTAMP".to_string()
+                },
+                ValidationViolation::BannedOpcode {
+                    entity: Entity::Account,
+                    opcode: "CREATE2".to_string()
+                },
+                ValidationViolation::OutOfGas { entity: Entity::Account },
+            ]
+        );
+        assert_eq!(violations[0].rule(), "OP-011");
+        assert_eq!(violations[1].rule(), "OP-031");
+        assert_eq!(violations[2].rule(), "OP-020");
+    }
+
+    #[test]
+    fn test_calls() {
+        let ctx = context(true, true);
+
+        let mut account = call(VALIDATE_USER_OP_SELECTORS[1]);
+        account.contract_size.insert(
+            Address::repeat_byte(0x42),
+            ContractSizeInfo { contract_size: 0, opcode: "EXTCODEHASH".to_string() },
+        );
+        account.contract_size.insert(
+            ctx.sender.address,
+            ContractSizeInfo { contract_size: 0, opcode: "CALL".to_string() },
+        );
+        account.calls = vec![
+            // The prefund to the entry point is allowed
+            CallEntry {
+                typ: "CALL".to_string(),
+                from: Some(ctx.sender.address),
+                to: Some(ctx.entry_point),
+                method: Some("0x".to_string()),
+                value: Some(1.into()),
+            },
+            CallEntry {
+                typ: "CALL".to_string(),
+                from: Some(ctx.sender.address),
+                to: Some(ctx.entry_point),
+                method: Some("0x205c2878".to_string()),
+                value: None,
+            },
+            CallEntry {
+                typ: "CALL".to_string(),
+                from: Some(ctx.sender.address),
+                to: Some(Address::repeat_byte(0x43)),
+                method: Some("0x".to_string()),
+                value: Some(1.into()),
+            },
+        ];
+
+        let result =
+            ValidationTracerResult { calls_from_entry_point: vec![account], ..Default::default() };
+
+        assert_eq!(
+            check_validation_rules(&result, &ctx),
+            vec![
+                ValidationViolation::MissingCode {
+                    entity: Entity::Account,
+                    address: Address::repeat_byte(0x42),
+                    opcode: "EXTCODEHASH".to_string()
+                },
+                ValidationViolation::EntryPointCall {
+                    entity: Entity::Account,
+                    method: "0x205c2878".to_string()
+                },
+                ValidationViolation::CallWithValue {
+                    entity: Entity::Account,
+                    target: Address::repeat_byte(0x43)
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_storage() {
+        let ctx = context(false, false);
+        let token = Address::repeat_byte(0x44);
+
+        // The balance mapping of the sender in a token contract, e.g. `balances[sender]`
+        let preimage: Bytes =
+            [H256::from(ctx.sender.address).as_bytes(), H256::zero().as_bytes()].concat().into();
+        let sender_slot = H256::from(keccak256(&preimage));
+
+        let mut paymaster = call(VALIDATE_PAYMASTER_USER_OP_SELECTORS[0]);
+        paymaster.access.insert(
+            token,
+            StorageAccessInfo {
+                reads: HashMap::from([(sender_slot, "0x".to_string())]),
+                writes: HashMap::from([(H256::repeat_byte(0x01), 1)]),
+            },
+        );
+        paymaster.access.insert(
+            ctx.paymaster.unwrap().address,
+            StorageAccessInfo {
+                reads: HashMap::from([(H256::zero(), "0x".to_string())]),
+                writes: HashMap::new(),
+            },
+        );
+        paymaster.output =
+            Some(encode(&[Token::Bytes(vec![1, 2, 3]), Token::Uint(U256::zero())]).into());
+
+        let result = ValidationTracerResult {
+            calls_from_entry_point: vec![paymaster],
+            keccak: vec![preimage],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            check_validation_rules(&result, &ctx),
+            vec![
+                ValidationViolation::UnstakedStorageAccess {
+                    entity: Entity::Paymaster,
+                    contract: ctx.paymaster.unwrap().address,
+                    slot: H256::zero()
+                },
+                ValidationViolation::StorageAccess {
+                    entity: Entity::Paymaster,
+                    contract: token,
+                    slot: H256::repeat_byte(0x01)
+                },
+                ValidationViolation::UnstakedStorageAccess {
+                    entity: Entity::Factory,
+                    contract: token,
+                    slot: sender_slot
+                },
+                ValidationViolation::UnstakedPaymasterContext {
+                    paymaster: ctx.paymaster.unwrap().address
+                },
+            ]
+        );
+
+        // The staked factory and paymaster are allowed to access the storage
+        let violations = check_validation_rules(&result, &context(true, true));
+        assert_eq!(
+            violations,
+            vec![ValidationViolation::StorageAccess {
+                entity: Entity::Paymaster,
+                contract: token,
+                slot: H256::repeat_byte(0x01)
+            }]
+        );
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// The bundler against a local anvil fork w/ the canonical v0.6.0 entry point.
+// Run w/ `ANVIL_FORK_URL=<rpc url> cargo test -p lightdotso-node -- --ignored`, which requires the
+// `anvil` binary.
+
+use ethers::{
+    middleware::SignerMiddleware,
+    providers::{Http, Middleware, Provider},
+    signers::{LocalWallet, Signer},
+    types::{Address, Bytes, U256},
+    utils::{parse_ether, Anvil},
+};
+use eyre::Result;
+use lightdotso_contracts::{
+    constants::ENTRYPOINT_V060_ADDRESS, entrypoint::EntryPoint, types::UserOperation,
+};
+use lightdotso_node::{
+    bundler::{wait_for_bundle, BundleStatus, Bundler},
+    mempool::MempoolEntry,
+};
+use std::sync::Arc;
+
+/// The code of an account that validates and executes any call, returning a zero word.
+const ACCOUNT_CODE: &str = "0x60206000f3";
+
+async fn entry(provider: &Provider<Http>, sender: Address) -> Result<MempoolEntry> {
+    let chain_id = provider.get_chainid().await?.as_u64();
+    let base_fee = provider
+        .get_block(ethers::types::BlockNumber::Latest)
+        .await?
+        .and_then(|block| block.base_fee_per_gas)
+        .unwrap_or_default();
+
+    let user_operation = UserOperation {
+        sender,
+        nonce: U256::zero(),
+        init_code: Bytes::default(),
+        call_data: Bytes::default(),
+        call_gas_limit: 100_000.into(),
+        verification_gas_limit: 100_000.into(),
+        pre_verification_gas: 50_000.into(),
+        max_fee_per_gas: base_fee * 2 + 1_000_000_000,
+        max_priority_fee_per_gas: 1_000_000_000.into(),
+        paymaster_and_data: Bytes::default(),
+        signature: Bytes::default(),
+    };
+
+    MempoolEntry::new(
+        user_operation.op_hash(*ENTRYPOINT_V060_ADDRESS, chain_id)?,
+        *ENTRYPOINT_V060_ADDRESS,
+        user_operation,
+    )
+}
+
+#[ignore]
+#[tokio::test(flavor = "multi_thread")]
+async fn test_integration_bundler_fork() -> Result<()> {
+    let anvil = Anvil::new().fork(std::env::var("ANVIL_FORK_URL")?).spawn();
+    let provider = Provider::<Http>::try_from(anvil.endpoint())?;
+    let chain_id = provider.get_chainid().await?.as_u64();
+    let wallet = LocalWallet::from(anvil.keys()[0].clone()).with_chain_id(chain_id);
+
+    // Deploy the account, and deposit the prefund for it on the entry point
+    let sender = Address::repeat_byte(0x42);
+    provider.request::<_, ()>("anvil_setCode", (sender, ACCOUNT_CODE.parse::<Bytes>()?)).await?;
+    let client = Arc::new(SignerMiddleware::new(provider.clone(), wallet.clone()));
+    EntryPoint::new(*ENTRYPOINT_V060_ADDRESS, client)
+        .deposit_to(sender)
+        .value(parse_ether(1)?)
+        .send()
+        .await?
+        .await?;
+
+    let bundler = Bundler::new(wallet, None);
+
+    // The user operation of the undeployed account is rejected w/ `FailedOp`, w/o a bundle
+    let undeployed = entry(&provider, Address::repeat_byte(0x43)).await?;
+    let bundle =
+        bundler.build_bundle(&provider, *ENTRYPOINT_V060_ADDRESS, vec![undeployed]).await?;
+    assert!(bundle.tx.is_none());
+    assert_eq!(bundle.rejected.len(), 1);
+    assert!(bundle.rejected[0].1.starts_with("AA20"));
+
+    // The user operation of the deployed account is bundled and included
+    let deployed = entry(&provider, sender).await?;
+    let undeployed = entry(&provider, Address::repeat_byte(0x43)).await?;
+    let bundle = bundler
+        .build_bundle(&provider, *ENTRYPOINT_V060_ADDRESS, vec![undeployed, deployed.clone()])
+        .await?;
+    assert_eq!(bundle.rejected.len(), 1);
+    assert_eq!(bundle.entries.len(), 1);
+    assert_eq!(bundle.entries[0].hash, deployed.hash);
+
+    let tx_hash = bundler.send_bundle(&provider, chain_id, &bundle).await?;
+    let status = wait_for_bundle(&provider, tx_hash).await?;
+    assert!(matches!(status, BundleStatus::Included(hash, _) if hash == tx_hash));
+
+    Ok(())
+}
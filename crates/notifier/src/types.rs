@@ -61,6 +61,12 @@ pub enum WalletOnlyOperation {
     UserOperationExecuted,
     #[strum(serialize = "TRANSACTION_WITH_USER_OPERATION_EXECUTED")]
     TransactionWithUserOperationExecuted,
+    #[strum(serialize = "RECOVERY_OPERATION_CREATED")]
+    RecoveryOperationCreated,
+    #[strum(serialize = "RECOVERY_OPERATION_VETOED")]
+    RecoveryOperationVetoed,
+    #[strum(serialize = "RECOVERY_OPERATION_EXECUTED")]
+    RecoveryOperationExecuted,
 }
 
 // Utility function to match the activity entity/operation combination
@@ -83,6 +89,16 @@ pub fn match_wallet_only_notification_with_activity(
             }
         }
         (ActivityEntity::Transaction, ActivityOperation::Update) => None,
+        (ActivityEntity::RecoveryOperation, ActivityOperation::Create) => {
+            Some(WalletOnlyOperation::RecoveryOperationCreated)
+        }
+        (ActivityEntity::RecoveryOperation, ActivityOperation::Update) => {
+            match log["status"].as_str() {
+                Some("VETOED") => Some(WalletOnlyOperation::RecoveryOperationVetoed),
+                Some("EXECUTED") => Some(WalletOnlyOperation::RecoveryOperationExecuted),
+                _ => None,
+            }
+        }
         _ => None,
     }
 }
@@ -196,6 +212,9 @@ lazy_static! {
                     WalletOnlyOperation::UserOperationCreated => true,
                     WalletOnlyOperation::UserOperationExecuted => true,
                     WalletOnlyOperation::TransactionWithUserOperationExecuted => true,
+                    WalletOnlyOperation::RecoveryOperationCreated => true,
+                    WalletOnlyOperation::RecoveryOperationVetoed => true,
+                    WalletOnlyOperation::RecoveryOperationExecuted => true,
                 };
                 map.insert(notif.to_string(), is_default_enabled);
             }
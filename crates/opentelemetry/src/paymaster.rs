@@ -0,0 +1,90 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use lazy_static::lazy_static;
+use once_cell::sync::Lazy;
+use opentelemetry::{
+    global,
+    metrics::{Counter, Histogram},
+    KeyValue,
+};
+use std::{collections::HashMap, sync::RwLock};
+
+lazy_static! {
+    pub static ref PAYMASTER_SPONSORSHIP_COUNT: Lazy<Counter<u64>> =
+        Lazy::new(|| global::meter("").u64_counter("paymaster_sponsorship_count").init());
+    pub static ref PAYMASTER_SPONSORSHIP_LATENCY: Lazy<Histogram<f64>> =
+        Lazy::new(|| global::meter("").f64_histogram("paymaster_sponsorship_latency").init());
+}
+
+/// The latest deposit, burn rate per hour and runway in hours of the paymasters, by the chain id
+/// and the paymaster address.
+static PAYMASTER_DEPOSITS: Lazy<RwLock<HashMap<(u64, String), (f64, f64, f64)>>> =
+    Lazy::new(Default::default);
+
+/// The gauges of the paymaster deposits, observed from the latest deposits on each collection.
+static PAYMASTER_DEPOSIT_GAUGES: Lazy<()> = Lazy::new(|| {
+    let meter = global::meter("");
+    let deposit = meter.f64_observable_gauge("paymaster_deposit").init();
+    let burn_rate = meter.f64_observable_gauge("paymaster_deposit_burn_rate").init();
+    let runway = meter.f64_observable_gauge("paymaster_deposit_runway_hours").init();
+
+    let instruments = [deposit.as_any(), burn_rate.as_any(), runway.as_any()];
+    let _ = meter.register_callback(&instruments, move |observer| {
+        if let Ok(deposits) = PAYMASTER_DEPOSITS.read() {
+            for ((chain_id, paymaster), (d, b, r)) in deposits.iter() {
+                let labels = [
+                    KeyValue::new("chain_id", chain_id.to_string()),
+                    KeyValue::new("paymaster", paymaster.clone()),
+                ];
+
+                observer.observe_f64(&deposit, *d, &labels);
+                observer.observe_f64(&burn_rate, *b, &labels);
+                observer.observe_f64(&runway, *r, &labels);
+            }
+        }
+    });
+});
+
+pub struct PaymasterMetrics {}
+
+impl PaymasterMetrics {
+    pub fn set_sponsorship(chain_id: u64, provider: &str, success: bool, latency_ms: f64) {
+        let labels = [
+            KeyValue::new("chain_id", chain_id.to_string()),
+            KeyValue::new("provider", provider.to_string()),
+            KeyValue::new("status", if success { "success" } else { "error" }),
+        ];
+
+        PAYMASTER_SPONSORSHIP_COUNT.add(1, &labels);
+        PAYMASTER_SPONSORSHIP_LATENCY.record(latency_ms, &labels);
+    }
+
+    pub fn set_deposit(
+        chain_id: u64,
+        paymaster: &str,
+        deposit: f64,
+        burn_rate_per_hour: f64,
+        runway_hours: f64,
+    ) {
+        Lazy::force(&PAYMASTER_DEPOSIT_GAUGES);
+
+        if let Ok(mut deposits) = PAYMASTER_DEPOSITS.write() {
+            deposits.insert(
+                (chain_id, paymaster.to_string()),
+                (deposit, burn_rate_per_hour, runway_hours),
+            );
+        }
+    }
+}
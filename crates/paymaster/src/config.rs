@@ -13,30 +13,147 @@
 // limitations under the License.
 
 use clap::Parser;
+use ethers::types::Address;
 use eyre::{eyre, Result};
+use lightdotso_db::db::create_client;
 use lightdotso_jsonrpsee::rpc::{JsonRpcServer, JsonRpcServerType};
 use lightdotso_tracing::tracing::{error, info};
 use std::{
     future::pending,
     net::{IpAddr, Ipv6Addr},
+    sync::Arc,
 };
 
-use crate::{paymaster::PaymasterApi, paymaster_api::PaymasterApiServer};
+use crate::{
+    constants::{
+        LIGHT_PAYMASTER_VALID_AFTER_SECONDS, LIGHT_PAYMASTER_VALID_UNTIL_SECONDS,
+        PAYMASTER_MONITOR_ALERT_COOLDOWN_SECONDS, PAYMASTER_MONITOR_ALERT_MIN_DEPOSIT,
+        PAYMASTER_MONITOR_ALERT_RUNWAY_HOURS, PAYMASTER_MONITOR_INTERVAL_SECONDS,
+        PAYMASTER_MONITOR_WINDOW_HOURS, SPONSORSHIP_MAX_GAS_PER_OPERATION,
+        SPONSORSHIP_WALLET_DAILY_CAP_USD, SPONSORSHIP_WALLET_MONTHLY_CAP_USD,
+    },
+    monitor::DepositMonitor,
+    paymaster::PaymasterApi,
+    paymaster_api::PaymasterApiServer,
+    policy::engine::SponsorshipPolicyEngine,
+    sponsorship::registry::SponsorshipRegistry,
+    token_paymaster::TokenPaymaster,
+};
 
 #[derive(Debug, Clone, Parser)]
 pub struct PaymasterArgs {
     /// The alchemy API key
     #[clap(long, env = "ALCHEMY_API_KEY")]
-    pub alchemy_api_key: String,
+    pub alchemy_api_key: Option<String>,
     /// The pilmico API key
     #[clap(long, env = "PIMLICO_API_KEY")]
-    pub pimlico_api_key: String,
+    pub pimlico_api_key: Option<String>,
     /// The particle network project id
     #[clap(long, env = "PARTICLE_NETWORK_PROJECT_ID")]
-    pub particle_network_project_id: String,
+    pub particle_network_project_id: Option<String>,
     /// The particle network paymaster project key
     #[clap(long, env = "PARTICLE_NETWORK_PROJECT_KEY")]
-    pub particle_network_project_key: String,
+    pub particle_network_project_key: Option<String>,
+    /// The number of seconds the light paymaster sponsorship is valid for
+    #[clap(
+        long,
+        env = "LIGHT_PAYMASTER_VALID_UNTIL_SECONDS",
+        default_value_t = LIGHT_PAYMASTER_VALID_UNTIL_SECONDS
+    )]
+    pub light_paymaster_valid_until_seconds: u64,
+    /// The number of seconds the light paymaster sponsorship is backdated by
+    #[clap(
+        long,
+        env = "LIGHT_PAYMASTER_VALID_AFTER_SECONDS",
+        default_value_t = LIGHT_PAYMASTER_VALID_AFTER_SECONDS
+    )]
+    pub light_paymaster_valid_after_seconds: u64,
+    /// The chain ids allowed to be sponsored, all chains if empty
+    #[clap(long, env = "SPONSORSHIP_ALLOWED_CHAIN_IDS", value_delimiter = ',')]
+    pub sponsorship_allowed_chain_ids: Vec<u64>,
+    /// The call targets allowed to be sponsored, as `<address>` or `<address>:<selector>`, all
+    /// targets if empty
+    #[clap(long, env = "SPONSORSHIP_ALLOWED_TARGETS", value_delimiter = ',')]
+    pub sponsorship_allowed_targets: Vec<String>,
+    /// The max gas of a sponsored user operation
+    #[clap(
+        long,
+        env = "SPONSORSHIP_MAX_GAS_PER_OPERATION",
+        default_value_t = SPONSORSHIP_MAX_GAS_PER_OPERATION
+    )]
+    pub sponsorship_max_gas_per_operation: u64,
+    /// The cap of the USD sponsored per wallet in a day
+    #[clap(
+        long,
+        env = "SPONSORSHIP_WALLET_DAILY_CAP_USD",
+        default_value_t = SPONSORSHIP_WALLET_DAILY_CAP_USD
+    )]
+    pub sponsorship_wallet_daily_cap_usd: f64,
+    /// The cap of the USD sponsored per wallet in a month
+    #[clap(
+        long,
+        env = "SPONSORSHIP_WALLET_MONTHLY_CAP_USD",
+        default_value_t = SPONSORSHIP_WALLET_MONTHLY_CAP_USD
+    )]
+    pub sponsorship_wallet_monthly_cap_usd: f64,
+    /// Whether the user billing must have the balance to cover the cost of the sponsorship
+    #[clap(long, env = "SPONSORSHIP_REQUIRE_BILLING_BALANCE")]
+    pub sponsorship_require_billing_balance: bool,
+    /// The address of the token paymaster, the token paymaster is disabled if unset
+    #[clap(long, env = "LIGHT_TOKEN_PAYMASTER_ADDRESS")]
+    pub light_token_paymaster_address: Option<Address>,
+    /// The KMS key ids of the verifying signer of the token paymaster, separate from the signer of
+    /// the `LightPaymaster`
+    #[clap(long, env = "LIGHT_TOKEN_PAYMASTER_KMS_KEY_IDS", value_delimiter = ',')]
+    pub light_token_paymaster_kms_key_ids: Vec<String>,
+    /// The chain ids of the monitored paymaster deposits, all mainnets if empty
+    #[clap(long, env = "PAYMASTER_MONITOR_CHAIN_IDS", value_delimiter = ',')]
+    pub paymaster_monitor_chain_ids: Vec<u64>,
+    /// The number of seconds between the checks of the paymaster deposits
+    #[clap(
+        long,
+        env = "PAYMASTER_MONITOR_INTERVAL_SECONDS",
+        default_value_t = PAYMASTER_MONITOR_INTERVAL_SECONDS
+    )]
+    pub paymaster_monitor_interval_seconds: u64,
+    /// The number of hours of the paymaster operations the burn rate is projected from
+    #[clap(
+        long,
+        env = "PAYMASTER_MONITOR_WINDOW_HOURS",
+        default_value_t = PAYMASTER_MONITOR_WINDOW_HOURS
+    )]
+    pub paymaster_monitor_window_hours: u64,
+    /// The number of hours of the runway of the paymaster deposit below which an alert is sent
+    #[clap(
+        long,
+        env = "PAYMASTER_MONITOR_ALERT_RUNWAY_HOURS",
+        default_value_t = PAYMASTER_MONITOR_ALERT_RUNWAY_HOURS
+    )]
+    pub paymaster_monitor_alert_runway_hours: f64,
+    /// The deposit of the paymaster in the native token below which an alert is sent, regardless
+    /// of the runway
+    #[clap(
+        long,
+        env = "PAYMASTER_MONITOR_ALERT_MIN_DEPOSIT",
+        default_value_t = PAYMASTER_MONITOR_ALERT_MIN_DEPOSIT
+    )]
+    pub paymaster_monitor_alert_min_deposit: f64,
+    /// The number of seconds before the alert of the same paymaster deposit is sent again
+    #[clap(
+        long,
+        env = "PAYMASTER_MONITOR_ALERT_COOLDOWN_SECONDS",
+        default_value_t = PAYMASTER_MONITOR_ALERT_COOLDOWN_SECONDS
+    )]
+    pub paymaster_monitor_alert_cooldown_seconds: u64,
+    /// The discord webhook the paymaster deposit alerts are sent to
+    #[clap(long, env = "DISCORD_WEBHOOK")]
+    pub discord_webhook: Option<String>,
+    /// The telegram bot token the paymaster deposit alerts are sent w/
+    #[clap(long, env = "TELEGRAM_BOT_TOKEN")]
+    pub telegram_bot_token: Option<String>,
+    /// The telegram chat id the paymaster deposit alerts are sent to
+    #[clap(long, env = "TELEGRAM_CHAT_ID")]
+    pub telegram_chat_id: Option<i64>,
 }
 
 impl PaymasterArgs {
@@ -47,6 +164,41 @@ impl PaymasterArgs {
         // Print the config
         // info!("Config: {:?}", self);
 
+        // Create the db client
+        let db_client = Arc::new(create_client().await?);
+
+        // Create the sponsorship registry w/ the configured providers
+        let registry = Arc::new(SponsorshipRegistry::from_args(&self));
+
+        // Create the sponsorship policy engine w/ the configured policy
+        let policy = Arc::new(SponsorshipPolicyEngine::from_args(&self, db_client.clone())?);
+
+        // Create the token paymaster, if configured
+        let token_paymaster = self
+            .light_token_paymaster_address
+            .map(|address| {
+                // The token paymaster signs w/ its own keys, so that the signatures of the two
+                // paymasters are never interchangeable.
+                if self.light_token_paymaster_kms_key_ids.is_empty() {
+                    return Err(eyre!("The token paymaster requires its own KMS key ids"));
+                }
+
+                Ok(Arc::new(TokenPaymaster::new(
+                    address,
+                    self.light_token_paymaster_kms_key_ids.clone(),
+                    self.light_paymaster_valid_until_seconds,
+                    self.light_paymaster_valid_after_seconds,
+                    db_client.clone(),
+                )))
+            })
+            .transpose()?;
+
+        // Create the paymaster deposit monitor, and start it in the background
+        let monitor = DepositMonitor::from_args(&self, db_client);
+        tokio::spawn(async move {
+            monitor.run().await;
+        });
+
         tokio::spawn({
             async move {
                 // Create the server
@@ -60,7 +212,10 @@ impl PaymasterArgs {
                 );
 
                 // Add the paymaster server
-                server.add_methods(PaymasterApi {}.into_rpc(), JsonRpcServerType::Http)?;
+                server.add_methods(
+                    PaymasterApi { registry, policy, token_paymaster }.into_rpc(),
+                    JsonRpcServerType::Http,
+                )?;
 
                 // Start the server
                 let handle = server.start().await.map_err(|e| eyre!("Error in handle: {:?}", e));
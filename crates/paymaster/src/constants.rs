@@ -39,6 +39,34 @@ lazy_static! {
     };
 }
 
+// The default number of seconds the self-hosted paymaster sponsorship is valid for
+pub const LIGHT_PAYMASTER_VALID_UNTIL_SECONDS: u64 = 600;
+
+// The default number of seconds the self-hosted paymaster sponsorship is backdated by, to tolerate
+// the clock skew between the signer and the chain
+pub const LIGHT_PAYMASTER_VALID_AFTER_SECONDS: u64 = 60;
+
+// The placeholder signature of the self-hosted paymaster, recoverable so that the gas of the
+// paymaster validation is estimated w/o reverting
+pub const LIGHT_PAYMASTER_DUMMY_SIGNATURE: &str = "0xfffffffffffffffffffffffffffffff0000000000000000000000000000000007aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa1c";
+
+// The default max gas of a sponsored user operation
+pub const SPONSORSHIP_MAX_GAS_PER_OPERATION: u64 = 10_000_000;
+
+// The default cap of the USD sponsored per wallet in a day
+pub const SPONSORSHIP_WALLET_DAILY_CAP_USD: f64 = 25.0;
+
+// The default cap of the USD sponsored per wallet in a month
+pub const SPONSORSHIP_WALLET_MONTHLY_CAP_USD: f64 = 250.0;
+
+// The number of seconds the estimated cost of a sponsorship is reserved for, until the billing
+// operation of the sponsorship is written by the billing consumer
+pub const SPONSORSHIP_RESERVATION_TTL_SECONDS: i64 = 3600;
+
+// The markup on the token quote of the token paymaster, to cover the price movement until the user
+// operation is executed
+pub const TOKEN_PAYMASTER_PRICE_MARKUP: f64 = 1.1;
+
 // The pimlico sponsorship policy settings
 lazy_static! {
     #[derive(Debug)]
@@ -118,3 +146,19 @@ lazy_static! {
         m
     };
 }
+
+// The default number of seconds between the checks of the paymaster deposits
+pub const PAYMASTER_MONITOR_INTERVAL_SECONDS: u64 = 300;
+
+// The default number of hours of the paymaster operations the burn rate is projected from
+pub const PAYMASTER_MONITOR_WINDOW_HOURS: u64 = 24;
+
+// The default number of hours of the runway of the paymaster deposit below which an alert is sent
+pub const PAYMASTER_MONITOR_ALERT_RUNWAY_HOURS: f64 = 72.0;
+
+// The default deposit of the paymaster in the native token below which an alert is sent, regardless
+// of the runway
+pub const PAYMASTER_MONITOR_ALERT_MIN_DEPOSIT: f64 = 0.1;
+
+// The default number of seconds before the alert of the same paymaster deposit is sent again
+pub const PAYMASTER_MONITOR_ALERT_COOLDOWN_SECONDS: u64 = 21600;
@@ -16,6 +16,11 @@
 
 pub mod config;
 pub mod constants;
+pub mod light_paymaster;
+pub mod monitor;
 pub mod paymaster;
 pub mod paymaster_api;
+pub mod policy;
 pub mod server;
+pub mod sponsorship;
+pub mod token_paymaster;
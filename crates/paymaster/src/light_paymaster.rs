@@ -0,0 +1,173 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{constants::LIGHT_PAYMASTER_DUMMY_SIGNATURE, paymaster::estimate_user_operation_gas};
+use ethers::{
+    providers::Middleware,
+    signers::{AwsSigner, Signer},
+    types::Address,
+    utils::hex,
+};
+use eyre::{eyre, Result, WrapErr};
+use lightdotso_contracts::{
+    constants::LIGHT_PAYMASTER_ADDRESSES,
+    paymaster::{encode_paymaster_and_data, get_paymaster, get_paymaster_hash},
+    provider::get_provider,
+    types::{EntryPointVersion, GasAndPaymasterAndData, UserOperationRequest},
+};
+use lightdotso_signer::connect::connect_to_kms;
+use lightdotso_tracing::tracing::info;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::OnceCell;
+
+/// The KMS signer of the `LightPaymaster`, connected once and shared across requests.
+static LIGHT_PAYMASTER_SIGNER: OnceCell<AwsSigner> = OnceCell::const_new();
+
+/// Get the KMS signer of the `LightPaymaster`.
+pub(crate) async fn get_light_paymaster_signer() -> Result<&'static AwsSigner> {
+    LIGHT_PAYMASTER_SIGNER.get_or_try_init(connect_to_kms).await
+}
+
+/// Get the validity window of the sponsorship as (`valid_until`, `valid_after`) from the current
+/// timestamp.
+pub fn get_validity_window(
+    now: u64,
+    valid_until_seconds: u64,
+    valid_after_seconds: u64,
+) -> (u64, u64) {
+    (now + valid_until_seconds, now.saturating_sub(valid_after_seconds))
+}
+
+/// Whether the latest version of the `LightPaymaster` is deployed on the chain.
+pub async fn is_light_paymaster_deployed(chain_id: u64) -> Result<bool> {
+    let provider = get_provider(chain_id).await?;
+    let code = provider.get_code(LIGHT_PAYMASTER_ADDRESSES[2], None).await?;
+
+    Ok(!code.is_empty())
+}
+
+/// Sign the `paymasterAndData` of the user operation w/ our own `LightPaymaster`.
+/// W/ `estimate_gas`, the gas is re-estimated w/ the paymaster attached and signed over, otherwise
+/// the gas of the user operation is signed over as is.
+pub async fn get_light_paymaster_and_data(
+    user_operation: &UserOperationRequest,
+    entry_point: Address,
+    chain_id: u64,
+    estimate_gas: bool,
+    valid_until_seconds: u64,
+    valid_after_seconds: u64,
+) -> Result<GasAndPaymasterAndData> {
+    // The `LightPaymaster` only supports the v0.6.0 entry point.
+    if EntryPointVersion::try_from(entry_point)? != EntryPointVersion::V060 {
+        return Err(eyre!("The LightPaymaster does not support the entry point {:?}", entry_point));
+    }
+
+    // The fees are signed over as is, so they must be set by the caller.
+    let (Some(max_fee_per_gas), Some(max_priority_fee_per_gas)) =
+        (user_operation.max_fee_per_gas, user_operation.max_priority_fee_per_gas)
+    else {
+        return Err(eyre!("The user operation is missing the max fee per gas"));
+    };
+
+    // Get the latest version of the `LightPaymaster`.
+    let verifying_paymaster_address = LIGHT_PAYMASTER_ADDRESSES[2];
+    let paymaster = get_paymaster(chain_id, verifying_paymaster_address).await?;
+
+    // Get the signer, and check that it is the verifying signer of the paymaster.
+    let signer = get_light_paymaster_signer().await?;
+    let verifying_signer = paymaster.verifying_signer().call().await?;
+    if verifying_signer != signer.address() {
+        return Err(eyre!(
+            "The signer {:?} is not the verifying signer {:?} of the LightPaymaster",
+            signer.address(),
+            verifying_signer
+        ));
+    }
+
+    // Get the validity window of the sponsorship.
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let (valid_until, valid_after) =
+        get_validity_window(now, valid_until_seconds, valid_after_seconds);
+
+    let mut user_operation = UserOperationRequest {
+        max_fee_per_gas: Some(max_fee_per_gas),
+        max_priority_fee_per_gas: Some(max_priority_fee_per_gas),
+        paymaster_and_data: Some(
+            encode_paymaster_and_data(
+                verifying_paymaster_address,
+                valid_until,
+                valid_after,
+                &hex::decode(LIGHT_PAYMASTER_DUMMY_SIGNATURE)?,
+            )
+            .into(),
+        ),
+        ..user_operation.clone()
+    };
+    if estimate_gas {
+        // Re-estimate the gas w/ the paymaster and data attached, so that the verification gas
+        // covers the validation of the paymaster.
+        let estimation = estimate_user_operation_gas(chain_id, entry_point, &user_operation)
+            .await
+            .wrap_err("Failed to estimate the gas w/ the LightPaymaster")?
+            .result;
+        info!("estimation w/ the LightPaymaster: {:?}", estimation);
+        user_operation.call_gas_limit = Some(estimation.call_gas_limit);
+        user_operation.verification_gas_limit = Some(estimation.verification_gas_limit);
+        user_operation.pre_verification_gas = Some(estimation.pre_verification_gas);
+    }
+
+    // The caller only receives the paymaster and data w/o `estimate_gas`, so its gas must be set.
+    let (Some(call_gas_limit), Some(verification_gas_limit), Some(pre_verification_gas)) = (
+        user_operation.call_gas_limit,
+        user_operation.verification_gas_limit,
+        user_operation.pre_verification_gas,
+    ) else {
+        return Err(eyre!("The user operation is missing the gas to sign over"));
+    };
+
+    // Get the hash to sign over the gas, bound to the current sender nonce of the
+    // paymaster.
+    let sender_nonce = paymaster.sender_nonce(user_operation.sender).call().await?;
+    let hash = get_paymaster_hash(
+        &user_operation,
+        chain_id,
+        verifying_paymaster_address,
+        sender_nonce,
+        valid_until,
+        valid_after,
+    )?;
+
+    // Sign the hash w/ the `eth_sign` prefix, as verified by the paymaster.
+    let signature = signer
+        .sign_message(hash)
+        .await
+        .map_err(|e| eyre!("Failed to sign the paymaster hash: {}", e))?;
+    info!(
+        "Signed the LightPaymaster sponsorship for {:?} valid from {} until {}",
+        user_operation.sender, valid_after, valid_until
+    );
+
+    Ok(GasAndPaymasterAndData {
+        paymaster_and_data: encode_paymaster_and_data(
+            verifying_paymaster_address,
+            valid_until,
+            valid_after,
+            &signature.to_vec(),
+        )
+        .into(),
+        call_gas_limit,
+        verification_gas_limit,
+        pre_verification_gas,
+    })
+}
@@ -0,0 +1,378 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::config::PaymasterArgs;
+use ethers::{
+    abi::{decode, ParamType, Token},
+    contract::EthEvent,
+    providers::Middleware,
+    types::{Address, U256},
+    utils::{format_ether, to_checksum},
+};
+use eyre::{eyre, Result};
+use lightdotso_constants::chains::MAINNET_CHAIN_IDS;
+use lightdotso_contracts::{
+    constants::{ENTRYPOINT_V060_ADDRESS, LIGHT_PAYMASTER_ADDRESSES},
+    entrypoint::{get_entrypoint, UserOperationEventFilter},
+    paymaster::get_paymaster,
+    provider::get_provider,
+};
+use lightdotso_db::{
+    models::paymaster_operation::get_paymaster_operations_with_user_operations, types::Database,
+};
+use lightdotso_opentelemetry::paymaster::PaymasterMetrics;
+use lightdotso_prisma::user_operation;
+use lightdotso_tracing::tracing::{error, info, warn};
+use prisma_client_rust::chrono::{Duration, Utc};
+use std::{
+    collections::HashMap,
+    time::{Duration as StdDuration, Instant},
+};
+use tokio::{sync::Mutex, time::sleep};
+
+/// The deposit of the paymaster on the entrypoint, w/ the burn rate projected from the recent
+/// paymaster operations.
+#[derive(Debug, Clone)]
+pub struct PaymasterDeposit {
+    pub chain_id: u64,
+    pub paymaster: Address,
+    pub deposit: U256,
+    pub burn_rate_per_hour: U256,
+    pub runway_hours: Option<f64>,
+}
+
+/// The monitor of the deposits of the light paymasters, which exports the gauges and alerts
+/// before the deposits run dry.
+pub struct DepositMonitor {
+    db_client: Database,
+    chain_ids: Vec<u64>,
+    interval: StdDuration,
+    window_hours: u64,
+    alert_runway_hours: f64,
+    alert_min_deposit: f64,
+    alert_cooldown: StdDuration,
+    discord_webhook: Option<String>,
+    telegram: Option<(String, i64)>,
+    alerted_at: Mutex<HashMap<(u64, Address), Instant>>,
+}
+
+impl DepositMonitor {
+    /// Create the monitor from the configured chains, thresholds and alert channels. The mainnets
+    /// are monitored if no chain is configured.
+    pub fn from_args(args: &PaymasterArgs, db_client: Database) -> Self {
+        let mut chain_ids = if args.paymaster_monitor_chain_ids.is_empty() {
+            MAINNET_CHAIN_IDS.keys().copied().collect()
+        } else {
+            args.paymaster_monitor_chain_ids.clone()
+        };
+        chain_ids.sort_unstable();
+
+        Self {
+            db_client,
+            chain_ids,
+            interval: StdDuration::from_secs(args.paymaster_monitor_interval_seconds),
+            window_hours: args.paymaster_monitor_window_hours,
+            alert_runway_hours: args.paymaster_monitor_alert_runway_hours,
+            alert_min_deposit: args.paymaster_monitor_alert_min_deposit,
+            alert_cooldown: StdDuration::from_secs(args.paymaster_monitor_alert_cooldown_seconds),
+            discord_webhook: args.discord_webhook.clone(),
+            telegram: args.telegram_bot_token.clone().zip(args.telegram_chat_id),
+            alerted_at: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Check the deposits of the paymasters on every chain, on every interval.
+    pub async fn run(&self) {
+        loop {
+            for chain_id in self.chain_ids.iter() {
+                for paymaster in LIGHT_PAYMASTER_ADDRESSES.iter() {
+                    match self.check_deposit(*chain_id, *paymaster).await {
+                        Ok(Some(deposit)) => {
+                            info!("paymaster deposit: {:?}", deposit);
+                            self.alert(&deposit).await;
+                        }
+                        Ok(None) => {}
+                        Err(err) => {
+                            warn!(
+                                "Failed to check the deposit of {:?} on {}: {:?}",
+                                paymaster, chain_id, err
+                            );
+                        }
+                    }
+                }
+            }
+
+            sleep(self.interval).await;
+        }
+    }
+
+    /// Get the deposit of the paymaster and export the gauges, or `None` if the paymaster isn't
+    /// deployed on the chain.
+    pub async fn check_deposit(
+        &self,
+        chain_id: u64,
+        paymaster: Address,
+    ) -> Result<Option<PaymasterDeposit>> {
+        let provider = get_provider(chain_id).await?;
+        if provider.get_code(paymaster, None).await?.is_empty() {
+            return Ok(None);
+        }
+
+        // The deposit is read from the entrypoint and the paymaster, which should always match.
+        let entrypoint = get_entrypoint(chain_id, *ENTRYPOINT_V060_ADDRESS).await?;
+        let deposit = entrypoint.balance_of(paymaster).call().await?;
+        let paymaster_deposit =
+            get_paymaster(chain_id, paymaster).await?.get_deposit().call().await?;
+        if deposit != paymaster_deposit {
+            warn!(
+                "Deposit of {:?} on {} mismatch, entrypoint: {:?} paymaster: {:?}",
+                paymaster, chain_id, deposit, paymaster_deposit
+            );
+        }
+
+        let paymaster_operations = get_paymaster_operations_with_user_operations(
+            self.db_client.clone(),
+            chain_id as i64,
+            paymaster,
+            Utc::now() - Duration::hours(self.window_hours as i64),
+        )
+        .await?;
+        // The rows w/ the invalid gas are skipped, so that a single row doesn't stop the monitor.
+        let costs = paymaster_operations
+            .iter()
+            .filter_map(|op| op.user_operation.clone().flatten())
+            .filter_map(|op| match get_user_operation_cost(&op) {
+                Ok(cost) => Some(cost),
+                Err(err) => {
+                    warn!("Failed to get the cost of the user operation {}: {:?}", op.hash, err);
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let burn_rate_per_hour = get_burn_rate_per_hour(&costs, self.window_hours);
+        let runway_hours = get_runway_hours(deposit, burn_rate_per_hour)?;
+
+        PaymasterMetrics::set_deposit(
+            chain_id,
+            &to_checksum(&paymaster, None),
+            format_ether(deposit).parse::<f64>()?,
+            format_ether(burn_rate_per_hour).parse::<f64>()?,
+            runway_hours.unwrap_or(f64::INFINITY),
+        );
+
+        Ok(Some(PaymasterDeposit {
+            chain_id,
+            paymaster,
+            deposit,
+            burn_rate_per_hour,
+            runway_hours,
+        }))
+    }
+
+    /// Send the alert of the deposit to the configured channels if the deposit or the runway is
+    /// below the threshold, at most once per cooldown for each paymaster.
+    async fn alert(&self, deposit: &PaymasterDeposit) {
+        if !is_deposit_low(deposit, self.alert_min_deposit, self.alert_runway_hours) {
+            return;
+        }
+
+        {
+            let mut alerted_at = self.alerted_at.lock().await;
+            let key = (deposit.chain_id, deposit.paymaster);
+            if alerted_at.get(&key).is_some_and(|at| at.elapsed() < self.alert_cooldown) {
+                return;
+            }
+            alerted_at.insert(key, Instant::now());
+        }
+
+        let paymaster = to_checksum(&deposit.paymaster, None);
+        let chain_id = deposit.chain_id.to_string();
+        let amount = format_ether(deposit.deposit);
+        let runway_hours = deposit
+            .runway_hours
+            .map_or_else(|| "∞".to_string(), |runway_hours| format!("{:.1}", runway_hours));
+        warn!(
+            "Deposit of {} on {} is low: {} w/ {} hours of runway",
+            paymaster, chain_id, amount, runway_hours
+        );
+
+        if let Some(webhook) = &self.discord_webhook {
+            if let Err(err) = lightdotso_discord::notify_low_paymaster_deposit(
+                webhook,
+                &paymaster,
+                &chain_id,
+                &amount,
+                &runway_hours,
+            )
+            .await
+            {
+                error!("Failed to notify discord: {:?}", err);
+            }
+        }
+
+        if let Some((token, chat_id)) = &self.telegram {
+            if let Err(err) = lightdotso_telegram::notify_low_paymaster_deposit(
+                token,
+                *chat_id,
+                &paymaster,
+                &chain_id,
+                &amount,
+                &runway_hours,
+            )
+            .await
+            {
+                error!("Failed to notify telegram: {:?}", err);
+            }
+        }
+    }
+}
+
+/// Whether the deposit is below the min deposit, or its runway is below the threshold. The min
+/// deposit also covers the paymaster w/o any recent burn to project the runway from.
+pub fn is_deposit_low(deposit: &PaymasterDeposit, min_deposit: f64, runway_hours: f64) -> bool {
+    let is_below_min_deposit =
+        format_ether(deposit.deposit).parse::<f64>().is_ok_and(|amount| amount < min_deposit);
+
+    is_below_min_deposit || deposit.runway_hours.is_some_and(|hours| hours < runway_hours)
+}
+
+/// Get the cost of the user operation to the paymaster deposit, the actual cost from the
+/// `UserOperationEvent` or the max cost as the upper bound until the event is indexed.
+pub fn get_user_operation_cost(user_operation: &user_operation::Data) -> Result<U256> {
+    let topic = format!("{:?}-0", UserOperationEventFilter::signature());
+    let event = user_operation
+        .logs
+        .iter()
+        .flatten()
+        .find(|log| log.topics.iter().flatten().any(|log_topic| log_topic.id == topic));
+
+    match event {
+        Some(event) => get_user_operation_event_actual_gas_cost(&event.data),
+        None => get_user_operation_max_cost(user_operation),
+    }
+}
+
+/// Get the actual gas cost from the data of the `UserOperationEvent`, as
+/// `(nonce, success, actualGasCost, actualGasUsed)`.
+pub fn get_user_operation_event_actual_gas_cost(data: &[u8]) -> Result<U256> {
+    let tokens = decode(
+        &[ParamType::Uint(256), ParamType::Bool, ParamType::Uint(256), ParamType::Uint(256)],
+        data,
+    )?;
+
+    match tokens.as_slice() {
+        [_, _, Token::Uint(actual_gas_cost), _] => Ok(*actual_gas_cost),
+        _ => Err(eyre!("Invalid user operation event")),
+    }
+}
+
+/// Get the max cost of the user operation, the same as the prefund the entrypoint v0.6 locks from
+/// the paymaster deposit, which triples the verification gas for the `postOp` of the paymaster.
+pub fn get_user_operation_max_cost(user_operation: &user_operation::Data) -> Result<U256> {
+    let parse = |value: &str| U256::from_dec_str(value).map_err(|e| eyre!("Invalid gas: {}", e));
+
+    let call_gas_limit = parse(&user_operation.call_gas_limit)?;
+    let verification_gas_limit = parse(&user_operation.verification_gas_limit)?;
+    let pre_verification_gas = parse(&user_operation.pre_verification_gas)?;
+    let max_fee_per_gas = parse(&user_operation.max_fee_per_gas)?;
+
+    verification_gas_limit
+        .checked_mul(U256::from(3))
+        .and_then(|gas| gas.checked_add(call_gas_limit))
+        .and_then(|gas| gas.checked_add(pre_verification_gas))
+        .and_then(|gas| gas.checked_mul(max_fee_per_gas))
+        .ok_or(eyre!("Max cost overflow"))
+}
+
+/// Get the burn rate per hour of the costs sponsored over the window.
+pub fn get_burn_rate_per_hour(costs: &[U256], window_hours: u64) -> U256 {
+    if window_hours == 0 {
+        return U256::zero();
+    }
+
+    costs.iter().fold(U256::zero(), |acc, cost| acc.saturating_add(*cost)) / window_hours
+}
+
+/// Get the hours until the deposit runs dry at the burn rate, or `None` if nothing is burnt.
+pub fn get_runway_hours(deposit: U256, burn_rate_per_hour: U256) -> Result<Option<f64>> {
+    if burn_rate_per_hour.is_zero() {
+        return Ok(None);
+    }
+
+    // The amounts are formatted in ether first, so that they don't truncate above `u64::MAX` wei.
+    Ok(Some(
+        format_ether(deposit).parse::<f64>()? / format_ether(burn_rate_per_hour).parse::<f64>()?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_burn_rate_per_hour() {
+        let costs = [U256::from(10_u64.pow(18)), U256::from(2 * 10_u64.pow(18))];
+
+        assert_eq!(get_burn_rate_per_hour(&costs, 24), U256::from(125_000_000_000_000_000_u64));
+        assert_eq!(get_burn_rate_per_hour(&[], 24), U256::zero());
+        assert_eq!(get_burn_rate_per_hour(&costs, 0), U256::zero());
+    }
+
+    #[test]
+    fn test_is_deposit_low() {
+        let deposit = PaymasterDeposit {
+            chain_id: 1,
+            paymaster: Address::zero(),
+            deposit: U256::from(10_u64.pow(18)),
+            burn_rate_per_hour: U256::zero(),
+            runway_hours: None,
+        };
+
+        // The deposit w/o any burn is low only below the min deposit.
+        assert!(!is_deposit_low(&deposit, 0.5, 72.0));
+        assert!(is_deposit_low(&deposit, 2.0, 72.0));
+
+        // The deposit above the min deposit is low w/ the short runway.
+        let deposit = PaymasterDeposit { runway_hours: Some(10.0), ..deposit };
+        assert!(is_deposit_low(&deposit, 0.5, 72.0));
+        assert!(!is_deposit_low(&deposit, 0.5, 5.0));
+    }
+
+    #[test]
+    fn test_get_user_operation_event_actual_gas_cost() -> Result<()> {
+        let data = ethers::abi::encode(&[
+            Token::Uint(1.into()),
+            Token::Bool(true),
+            Token::Uint(123_456.into()),
+            Token::Uint(100.into()),
+        ]);
+
+        assert_eq!(get_user_operation_event_actual_gas_cost(&data)?, U256::from(123_456));
+        assert!(get_user_operation_event_actual_gas_cost(&data[..64]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_runway_hours() -> Result<()> {
+        let deposit = U256::from(10_u64.pow(18));
+
+        assert_eq!(get_runway_hours(deposit, U256::from(10_u64.pow(17)))?, Some(10.0));
+        assert_eq!(get_runway_hours(U256::zero(), U256::from(10_u64.pow(17)))?, Some(0.0));
+        assert_eq!(get_runway_hours(deposit, U256::zero())?, None);
+
+        Ok(())
+    }
+}
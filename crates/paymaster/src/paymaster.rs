@@ -19,8 +19,9 @@ use ethers::types::{Address, Bytes};
 use eyre::{eyre, Result};
 use jsonrpsee::core::RpcResult;
 use lightdotso_contracts::types::{
-    BiconomyGasAndPaymasterAndData, EstimateResult, GasAndPaymasterAndData, PaymasterAndData,
-    UserOperationConstruct, UserOperationRequest,
+    BiconomyGasAndPaymasterAndData, EntryPointVersion, EstimateResult, GasAndPaymasterAndData,
+    GasAndPaymasterAndDataV070, PaymasterAndData, TokenPaymasterAndData, UserOperationConstruct,
+    UserOperationRequest, UserOperationRequestV070,
 };
 use lightdotso_gas::types::GasEstimation;
 use lightdotso_jsonrpsee::{
@@ -32,19 +33,28 @@ use lightdotso_kafka::{
     get_producer, topics::paymaster_operation::produce_paymaster_operation_message,
     types::paymaster_operation::PaymasterOperationMessage,
 };
-use lightdotso_rpc::constants::{ALCHEMY_RPC_URLS, PARTICLE_RPC_URLS, PIMLICO_RPC_URLS};
 use lightdotso_tracing::tracing::{info, warn};
-use lightdotso_utils::is_testnet;
 use serde_json::{json, Value};
 use std::sync::Arc;
 
-use crate::constants::{
-    ALCHEMY_POLICY_IDS, BICONOMY_PAYMASTER_RPC_URLS, BICONOMY_POLICY_IDS,
-    PARTICLE_NETWORK_PAYMASTER_BASE_URL, PIMLICO_BASE_URL, PIMLICO_SPONSORSHIP_POLICIES,
+use crate::{
+    policy::{
+        denial::denials_to_rpc_error,
+        engine::{PolicyDecision, SponsorshipPolicyEngine},
+    },
+    sponsorship::registry::SponsorshipRegistry,
+    token_paymaster::TokenPaymaster,
 };
 
 /// The paymaster api implementation.
-pub(crate) struct PaymasterApi {}
+pub(crate) struct PaymasterApi {
+    /// The registry of the sponsorship providers.
+    pub(crate) registry: Arc<SponsorshipRegistry>,
+    /// The sponsorship policy engine.
+    pub(crate) policy: Arc<SponsorshipPolicyEngine>,
+    /// The token paymaster, if configured.
+    pub(crate) token_paymaster: Option<Arc<TokenPaymaster>>,
+}
 
 // Create the paymaster topic message.
 pub async fn create_billing_operation_msg(
@@ -71,143 +81,6 @@ pub async fn create_billing_operation_msg(
     Ok(())
 }
 
-// Retryable sponsorship fetch function.
-pub async fn fetch_gas_and_paymaster_and_data(
-    user_operation: UserOperationRequest,
-    entry_point: Address,
-    chain_id: u64,
-) -> Result<GasAndPaymasterAndData> {
-    // Get the environment variable, `PIMLICO_API_KEY`.
-    let pimlico_api_key =
-        std::env::var("PIMLICO_API_KEY").map_err(|_| eyre!("PIMLICO_API_KEY not set"))?;
-
-    // Check if the `chain_id` is one of the key of `PIMLICO_RPC_URLS`.
-    if (*PIMLICO_RPC_URLS).contains_key(&chain_id) {
-        // For each paymaster policy, attempt to fetch the user operation sponsorship.
-        for policy in PIMLICO_SPONSORSHIP_POLICIES.iter() {
-            info!("pimlico policy: {:?}", policy);
-
-            let sponsorship = get_gas_and_paymaster_and_data(
-                format!("{}/{}/rpc?apikey={}", *PIMLICO_BASE_URL, chain_id, pimlico_api_key),
-                entry_point,
-                &user_operation,
-                if !is_testnet(chain_id) {
-                    Some(json!({
-                        "sponsorshipPolicyId": policy
-                    }))
-                } else {
-                    None
-                },
-            )
-            .await
-            .map_err(JsonRpcError::from);
-
-            // If the sponsorship is successful, return the result.
-            if let Ok(sponsorship_data) = sponsorship {
-                return Ok(sponsorship_data.result);
-            } else {
-                warn!("Failed to fetch user operation sponsorship from pimlico");
-            }
-        }
-    }
-
-    // Get the environment variable, `PARTICLE_NETWORK_PROJECT_ID`.
-    let particle_network_project_id = std::env::var("PARTICLE_NETWORK_PROJECT_ID")
-        .map_err(|_| eyre!("PARTICLE_NETWORK_PROJECT_ID not set"))?;
-    let particle_network_paymaster_project_key = std::env::var("PARTICLE_NETWORK_PROJECT_KEY")
-        .map_err(|_| eyre!("PARTICLE_NETWORK_PROJECT_KEY not set"))?;
-
-    // Check if the `chain_id` is one of the key of `PARTICLE_RPC_URLS`.
-    if (*PARTICLE_RPC_URLS).contains_key(&chain_id) {
-        let sponsorship = get_gas_and_paymaster_and_data(
-            format!(
-                "{}?chainId={}&projectUuid={}&projectKey={}",
-                *PARTICLE_NETWORK_PAYMASTER_BASE_URL,
-                chain_id,
-                particle_network_project_id,
-                particle_network_paymaster_project_key
-            ),
-            entry_point,
-            &user_operation,
-            None,
-        )
-        .await
-        .map_err(JsonRpcError::from);
-
-        // If the sponsorship is successful, return the result.
-        if let Ok(sponsorship_data) = sponsorship {
-            return Ok(sponsorship_data.result);
-        } else {
-            warn!("Failed to fetch user operation sponsorship from particle network");
-        }
-    }
-
-    // Get the environment variable, `ALCHEMY_API_KEY`.
-    let alchemy_api_key =
-        std::env::var("ALCHEMY_API_KEY").map_err(|_| eyre!("ALCHEMY_API_KEY not set"))?;
-
-    // Check if the `chain_id` is one of the key of `ALCHEMY_POLICY_IDS`.
-    if (*ALCHEMY_POLICY_IDS).contains_key(&chain_id) {
-        // Get the alchemy rpc url from the `ALCHEMY_RPC_URLS`.
-        if let Some(alchemy_rpc_url) = (*ALCHEMY_RPC_URLS).get(&chain_id) {
-            let sponsorship = get_alchemy_paymaster_and_data(
-                format!("{}{}", alchemy_rpc_url, alchemy_api_key),
-                entry_point,
-                &user_operation,
-                (*ALCHEMY_POLICY_IDS).get(&chain_id).unwrap().to_string(),
-            )
-            .await
-            .map_err(JsonRpcError::from);
-
-            // If the sponsorship is successful, return the result.
-            if let Ok(sponsorship_data) = sponsorship {
-                return Ok(GasAndPaymasterAndData {
-                    paymaster_and_data: sponsorship_data.result.paymaster_and_data,
-                    call_gas_limit: user_operation.call_gas_limit.unwrap_or_default(),
-                    verification_gas_limit: user_operation
-                        .verification_gas_limit
-                        .unwrap_or_default(),
-                    pre_verification_gas: user_operation.pre_verification_gas.unwrap_or_default(),
-                });
-            } else {
-                warn!("Failed to fetch user operation sponsorship from alchemy");
-                // error!("{:?}", sponsorship.unwrap_err());
-            }
-        }
-    }
-
-    // Check if the `chain_id` is one of the key of `BICONOMY_POLICY_IDS`.
-    if (*BICONOMY_POLICY_IDS).contains_key(&chain_id) {
-        // Get the alchemy rpc url from the `BICONOMY_PAYMASTER_RPC_URLS`.
-        if let Some(biconomy_rpc_url) = (*BICONOMY_PAYMASTER_RPC_URLS).get(&chain_id) {
-            let sponsorship = get_biconomy_paymaster_and_data(
-                format!("{}{}", biconomy_rpc_url, BICONOMY_POLICY_IDS.get(&chain_id).unwrap()),
-                &user_operation,
-            )
-            .await
-            .map_err(JsonRpcError::from);
-
-            // If the sponsorship is successful, return the result.
-            if let Ok(sponsorship_data) = sponsorship {
-                return Ok(GasAndPaymasterAndData {
-                    paymaster_and_data: sponsorship_data.result.paymaster_and_data,
-                    call_gas_limit: user_operation.call_gas_limit.unwrap_or_default(),
-                    verification_gas_limit: user_operation
-                        .verification_gas_limit
-                        .unwrap_or_default(),
-                    pre_verification_gas: user_operation.pre_verification_gas.unwrap_or_default(),
-                });
-            } else {
-                warn!("Failed to fetch user operation sponsorship from alchemy");
-                // error!("{:?}", sponsorship.unwrap_err());
-            }
-        }
-    }
-
-    // If the sponsorship is not successful, return error.
-    Err(eyre!("Failed to fetch user operation sponsorship"))
-}
-
 impl PaymasterApi {
     pub(crate) async fn request_paymaster_and_data(
         &self,
@@ -215,16 +88,23 @@ impl PaymasterApi {
         entry_point: Address,
         chain_id: u64,
     ) -> RpcResult<PaymasterAndData> {
+        // Reject the unsupported entry points.
+        EntryPointVersion::try_from(entry_point).map_err(JsonRpcError::from)?;
+
+        // Only the paymaster and data is returned, so the gas is signed over as is and must be set.
+        if user_operation.call_gas_limit.is_none() ||
+            user_operation.verification_gas_limit.is_none() ||
+            user_operation.pre_verification_gas.is_none()
+        {
+            return Err(JsonRpcError::from(eyre!(
+                "The user operation is missing the gas, use `paymaster_requestGasAndPaymasterAndData` to estimate it"
+            ))
+            .into());
+        }
+
         // Get the paymaster operation sponsor.
         let gas_and_paymaster_and_data =
-            fetch_gas_and_paymaster_and_data(user_operation.clone(), entry_point, chain_id)
-                .await
-                .map_err(JsonRpcError::from)?;
-
-        // Write the paymaster operation to the database.
-        create_billing_operation_msg(chain_id, user_operation, gas_and_paymaster_and_data.clone())
-            .await
-            .map_err(JsonRpcError::from)?;
+            self.sponsor(&user_operation, entry_point, chain_id, false).await?;
 
         Ok(PaymasterAndData { paymaster_and_data: gas_and_paymaster_and_data.paymaster_and_data })
     }
@@ -235,6 +115,9 @@ impl PaymasterApi {
         entry_point: Address,
         chain_id: u64,
     ) -> RpcResult<GasAndPaymasterAndData> {
+        // Reject the unsupported entry points.
+        EntryPointVersion::try_from(entry_point).map_err(JsonRpcError::from)?;
+
         // Construct the user operation w/ rpc.
         let user_operation_construct =
             construct_user_operation(chain_id, user_operation, entry_point)
@@ -259,16 +142,115 @@ impl PaymasterApi {
 
         // Get the paymaster operation sponsor.
         let gas_and_paymaster_and_data =
-            fetch_gas_and_paymaster_and_data(user_operation.clone(), entry_point, chain_id)
+            self.sponsor(&user_operation, entry_point, chain_id, true).await?;
+
+        Ok(gas_and_paymaster_and_data)
+    }
+
+    /// Sponsor the user operation w/ the registry, once the sponsorship policy allows the signed
+    /// gas and its estimated cost is reserved for the wallet, and write the paymaster operation.
+    async fn sponsor(
+        &self,
+        user_operation: &UserOperationRequest,
+        entry_point: Address,
+        chain_id: u64,
+        estimate_gas: bool,
+    ) -> RpcResult<GasAndPaymasterAndData> {
+        let gas_and_paymaster_and_data = self
+            .registry
+            .sponsor(user_operation, entry_point, chain_id, estimate_gas)
+            .await
+            .map_err(JsonRpcError::from)?;
+
+        // Evaluate the sponsorship policy on the gas signed by the provider, before the paymaster
+        // and data is returned.
+        let reservation_id = match self
+            .policy
+            .reserve(user_operation, &gas_and_paymaster_and_data, chain_id)
+            .await
+            .map_err(JsonRpcError::from)?
+        {
+            PolicyDecision::Reserved(reservation_id) => reservation_id,
+            PolicyDecision::Denied(denials) => {
+                return Err(denials_to_rpc_error(&denials).into());
+            }
+        };
+
+        // Write the paymaster operation to the database.
+        if let Err(err) = create_billing_operation_msg(
+            chain_id,
+            user_operation.clone(),
+            gas_and_paymaster_and_data.clone(),
+        )
+        .await
+        {
+            // Release the reservation, as the sponsorship won't be billed.
+            if let Err(release_err) = self.policy.release(reservation_id).await {
+                warn!("Failed to release the sponsorship reservation: {:?}", release_err);
+            }
+            return Err(JsonRpcError::from(err).into());
+        }
+
+        Ok(gas_and_paymaster_and_data)
+    }
+
+    pub(crate) async fn request_token_paymaster_and_data(
+        &self,
+        user_operation: UserOperationRequest,
+        entry_point: Address,
+        chain_id: u64,
+        token: Address,
+    ) -> RpcResult<TokenPaymasterAndData> {
+        // Get the token paymaster.
+        let token_paymaster = self
+            .token_paymaster
+            .clone()
+            .ok_or(eyre!("Token paymaster not configured"))
+            .map_err(JsonRpcError::from)?;
+
+        // Construct the user operation w/ rpc.
+        let user_operation_construct =
+            construct_user_operation(chain_id, user_operation, entry_point)
                 .await
                 .map_err(JsonRpcError::from)?;
+        info!("construct: {:?}", user_operation_construct);
 
-        // Write the paymaster operation to the database.
-        create_billing_operation_msg(chain_id, user_operation, gas_and_paymaster_and_data.clone())
+        let user_operation = UserOperationRequest {
+            call_data: user_operation_construct.call_data.clone(),
+            init_code: user_operation_construct.init_code.clone(),
+            signature: user_operation_construct.signature.clone(),
+            nonce: user_operation_construct.nonce,
+            sender: user_operation_construct.sender,
+            pre_verification_gas: Some(user_operation_construct.pre_verification_gas),
+            verification_gas_limit: Some(user_operation_construct.verification_gas_limit),
+            call_gas_limit: Some(user_operation_construct.call_gas_limit),
+            max_fee_per_gas: Some(user_operation_construct.max_fee_per_gas),
+            max_priority_fee_per_gas: Some(user_operation_construct.max_priority_fee_per_gas),
+            paymaster_and_data: Some(Bytes::default()),
+        };
+
+        // Quote the gas in the token, and sign the paymaster and data that charges it.
+        // The gas is paid by the wallet in the token, so it isn't billed nor subject to the
+        // sponsorship policy.
+        let token_paymaster_and_data = token_paymaster
+            .get_token_paymaster_and_data(&user_operation, entry_point, chain_id, token)
             .await
             .map_err(JsonRpcError::from)?;
 
-        Ok(gas_and_paymaster_and_data)
+        Ok(token_paymaster_and_data)
+    }
+}
+
+/// Encode the user operation in the RPC format of the entry point version.
+pub fn user_operation_params(
+    entry_point: Address,
+    user_operation: &UserOperationRequest,
+) -> Result<Value> {
+    match EntryPointVersion::try_from(entry_point)? {
+        EntryPointVersion::V060 => Ok(json!(user_operation)),
+        EntryPointVersion::V070 => {
+            Ok(json!(UserOperationRequestV070::from(user_operation.clone())))
+        }
     }
 }
 
@@ -365,7 +347,7 @@ pub async fn estimate_user_operation_gas(
     entry_point: Address,
     user_operation: &UserOperationRequest,
 ) -> Result<Response<EstimateResult>> {
-    let params = vec![json!(user_operation), json!(entry_point)];
+    let params = vec![user_operation_params(entry_point, user_operation)?, json!(entry_point)];
     info!("params: {:?}", params);
 
     let req_body = Request {
@@ -392,10 +374,11 @@ pub async fn get_gas_and_paymaster_and_data(
     user_operation: &UserOperationRequest,
     sponsorship_policy: Option<Value>,
 ) -> Result<Response<GasAndPaymasterAndData>> {
+    let user_operation_param = user_operation_params(entry_point, user_operation)?;
     let params = if let Some(policy) = sponsorship_policy {
-        vec![json!(user_operation), json!(entry_point), policy]
+        vec![user_operation_param, json!(entry_point), policy]
     } else {
-        vec![json!(user_operation), json!(entry_point)]
+        vec![user_operation_param, json!(entry_point)]
     };
     info!("params: {:?}", params);
 
@@ -410,7 +393,14 @@ pub async fn get_gas_and_paymaster_and_data(
     let response = client.post(rpc_url).json(&req_body).send().await?;
 
     // Handle the response for the JSON-RPC API.
-    handle_response(response).await
+    // The v0.7.0 response has the paymaster fields split, so pack them back.
+    match EntryPointVersion::try_from(entry_point)? {
+        EntryPointVersion::V060 => handle_response(response).await,
+        EntryPointVersion::V070 => {
+            let res: Response<GasAndPaymasterAndDataV070> = handle_response(response).await?;
+            Ok(Response { jsonrpc: res.jsonrpc, id: res.id, result: res.result.into() })
+        }
+    }
 }
 
 pub async fn get_alchemy_paymaster_and_data(
@@ -419,9 +409,11 @@ pub async fn get_alchemy_paymaster_and_data(
     user_operation: &UserOperationRequest,
     policy_id: String,
 ) -> Result<Response<PaymasterAndData>> {
-    let params = vec![
-        json!({"policyId": policy_id, "entryPoint": entry_point, "userOperation": user_operation}),
-    ];
+    let params = vec![json!({
+        "policyId": policy_id,
+        "entryPoint": entry_point,
+        "userOperation": user_operation_params(entry_point, user_operation)?
+    })];
     info!("params: {:?}", params);
 
     let req_body = Request {
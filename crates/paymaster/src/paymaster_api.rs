@@ -14,7 +14,9 @@
 
 use ethers::types::Address;
 use jsonrpsee::{core::RpcResult, proc_macros::rpc};
-use lightdotso_contracts::types::{GasAndPaymasterAndData, PaymasterAndData, UserOperationRequest};
+use lightdotso_contracts::types::{
+    GasAndPaymasterAndData, PaymasterAndData, TokenPaymasterAndData, UserOperationRequest,
+};
 
 #[rpc(client, server, namespace = "paymaster")]
 #[cfg_attr(test, automock)]
@@ -34,4 +36,13 @@ pub trait PaymasterApi {
         entry_point: Address,
         chain_id: u64,
     ) -> RpcResult<GasAndPaymasterAndData>;
+
+    #[method(name = "requestTokenPaymasterAndData")]
+    async fn request_token_paymaster_and_data(
+        &self,
+        user_operation: UserOperationRequest,
+        entry_point: Address,
+        chain_id: u64,
+        token: Address,
+    ) -> RpcResult<TokenPaymasterAndData>;
 }
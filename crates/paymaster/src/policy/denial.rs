@@ -0,0 +1,92 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ethers::types::{Address, Bytes, U256};
+use jsonrpsee::types::ErrorObject;
+use lightdotso_jsonrpsee::error::JsonRpcError;
+use serde::Serialize;
+use std::fmt;
+
+/// The reason the sponsorship policy denied the user operation.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "reason", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum PolicyDenial {
+    /// The chain is not in the allowlist.
+    ChainNotAllowed { chain_id: u64 },
+    /// The gas of the user operation is above the max gas per operation.
+    GasLimitExceeded { gas: U256, max_gas: U256 },
+    /// The call data of the user operation could not be decoded into calls.
+    UnknownCallData,
+    /// The call target is not in the allowlist.
+    TargetNotAllowed { target: Address },
+    /// The call selector is not allowed for the target.
+    SelectorNotAllowed { target: Address, selector: Option<Bytes> },
+    /// The wallet would exceed the daily cap of the sponsored USD.
+    WalletDailyCapExceeded { spent_usd: f64, cost_usd: f64, cap_usd: f64 },
+    /// The wallet would exceed the monthly cap of the sponsored USD.
+    WalletMonthlyCapExceeded { spent_usd: f64, cost_usd: f64, cap_usd: f64 },
+    /// The billing balance of the wallet can't cover the estimated cost.
+    InsufficientBillingBalance { balance_usd: f64, cost_usd: f64 },
+}
+
+impl fmt::Display for PolicyDenial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PolicyDenial::ChainNotAllowed { chain_id } => {
+                write!(f, "chain {} is not allowed", chain_id)
+            }
+            PolicyDenial::GasLimitExceeded { gas, max_gas } => {
+                write!(f, "gas {} exceeds the max gas {}", gas, max_gas)
+            }
+            PolicyDenial::UnknownCallData => write!(f, "call data could not be decoded"),
+            PolicyDenial::TargetNotAllowed { target } => {
+                write!(f, "target {:?} is not allowed", target)
+            }
+            PolicyDenial::SelectorNotAllowed { target, selector } => {
+                write!(f, "selector {:?} is not allowed for target {:?}", selector, target)
+            }
+            PolicyDenial::WalletDailyCapExceeded { spent_usd, cost_usd, cap_usd } => write!(
+                f,
+                "daily spend {} + cost {} exceeds the cap {} USD",
+                spent_usd, cost_usd, cap_usd
+            ),
+            PolicyDenial::WalletMonthlyCapExceeded { spent_usd, cost_usd, cap_usd } => write!(
+                f,
+                "monthly spend {} + cost {} exceeds the cap {} USD",
+                spent_usd, cost_usd, cap_usd
+            ),
+            PolicyDenial::InsufficientBillingBalance { balance_usd, cost_usd } => write!(
+                f,
+                "billing balance {} USD can't cover the cost {} USD",
+                balance_usd, cost_usd
+            ),
+        }
+    }
+}
+
+/// The JSON-RPC error code of the user operation rejected by the paymaster.
+/// From: https://eips.ethereum.org/EIPS/eip-4337#rpc-methods-eth-namespace
+pub const PAYMASTER_REJECTED_CODE: i32 = -32501;
+
+/// Convert the denials into the JSON-RPC error, w/ the structured reasons in the data.
+pub fn denials_to_rpc_error(denials: &[PolicyDenial]) -> JsonRpcError {
+    JsonRpcError(ErrorObject::owned(
+        PAYMASTER_REJECTED_CODE,
+        format!(
+            "Sponsorship denied by policy: {}",
+            denials.iter().map(|d| d.to_string()).collect::<Vec<_>>().join(", ")
+        ),
+        Some(denials),
+    ))
+}
@@ -0,0 +1,200 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    config::PaymasterArgs,
+    constants::SPONSORSHIP_RESERVATION_TTL_SECONDS,
+    policy::{
+        denial::PolicyDenial,
+        rules::{get_policy_calls, parse_allowed_targets, PolicyContext, SponsorshipPolicy},
+    },
+};
+use ethers::{providers::Middleware, types::U256, utils::format_ether};
+use eyre::{eyre, Result};
+use lightdotso_client::crypto::get_native_token_price;
+use lightdotso_contracts::{
+    provider::get_provider,
+    types::{GasAndPaymasterAndData, UserOperationRequest},
+};
+use lightdotso_db::{
+    models::{
+        billing_operation::{get_wallet_billing, get_wallet_spent_billing_operations},
+        sponsorship_reservation::{
+            create_sponsorship_reservation, delete_sponsorship_reservation,
+            get_wallet_sponsorship_reservations, lock_wallet,
+        },
+    },
+    types::Database,
+};
+use lightdotso_tracing::tracing::{info, warn};
+use lightdotso_utils::{get_native_token_symbol, is_testnet};
+use prisma_client_rust::chrono::{Duration, Utc};
+
+/// The decision of the sponsorship policy for the user operation.
+#[derive(Clone, Debug)]
+pub enum PolicyDecision {
+    /// The sponsorship is allowed, w/ the id of the reservation of the estimated cost.
+    Reserved(String),
+    /// The sponsorship is denied, w/ all the reasons.
+    Denied(Vec<PolicyDenial>),
+}
+
+/// The engine that evaluates the sponsorship policy w/ the spend and the billing of the wallet.
+pub struct SponsorshipPolicyEngine {
+    policy: SponsorshipPolicy,
+    db_client: Database,
+}
+
+impl SponsorshipPolicyEngine {
+    pub fn new(policy: SponsorshipPolicy, db_client: Database) -> Self {
+        Self { policy, db_client }
+    }
+
+    /// Construct the engine w/ the policy configured in the arguments.
+    pub fn from_args(args: &PaymasterArgs, db_client: Database) -> Result<Self> {
+        let policy = SponsorshipPolicy {
+            allowed_chain_ids: args.sponsorship_allowed_chain_ids.iter().copied().collect(),
+            allowed_targets: parse_allowed_targets(&args.sponsorship_allowed_targets)?,
+            max_gas_per_operation: args.sponsorship_max_gas_per_operation.into(),
+            wallet_daily_cap_usd: args.sponsorship_wallet_daily_cap_usd,
+            wallet_monthly_cap_usd: args.sponsorship_wallet_monthly_cap_usd,
+            require_billing_balance: args.sponsorship_require_billing_balance,
+        };
+
+        Ok(Self::new(policy, db_client))
+    }
+
+    /// Evaluate the policy for the user operation w/ the gas signed by the provider, and reserve
+    /// the estimated cost if the sponsorship is allowed.
+    /// The wallet is locked while its spend is read and the cost is reserved, so that the
+    /// concurrent sponsorships of the wallet can't exceed the caps together before the billing
+    /// operations are written.
+    pub async fn reserve(
+        &self,
+        user_operation: &UserOperationRequest,
+        gas_and_paymaster_and_data: &GasAndPaymasterAndData,
+        chain_id: u64,
+    ) -> Result<PolicyDecision> {
+        // Get the total gas of the user operation.
+        let gas = gas_and_paymaster_and_data
+            .pre_verification_gas
+            .checked_add(gas_and_paymaster_and_data.verification_gas_limit)
+            .and_then(|gas| gas.checked_add(gas_and_paymaster_and_data.call_gas_limit))
+            .ok_or(eyre!("Gas limit overflow"))?;
+
+        // Get the estimated cost of the user operation.
+        let cost_usd = get_cost_usd(chain_id, gas, user_operation.max_fee_per_gas).await?;
+
+        let policy = self.policy.clone();
+        let sender = user_operation.sender;
+        let calls = get_policy_calls(&user_operation.call_data);
+
+        let decision: Result<PolicyDecision> = self
+            .db_client
+            ._transaction()
+            .run(|client| async move {
+                lock_wallet(&client, sender).await?;
+
+                // Get the spend of the wallet in the last month, and the last day out of it.
+                let now = Utc::now();
+                let billing_operations =
+                    get_wallet_spent_billing_operations(&client, sender, now - Duration::days(30))
+                        .await?;
+
+                // Get the costs reserved for the sponsorships w/o the billing operations yet,
+                // which expire well within the day.
+                let reserved_usd: f64 = get_wallet_sponsorship_reservations(&client, sender, now)
+                    .await?
+                    .iter()
+                    .map(|reservation| reservation.cost_usd)
+                    .sum();
+
+                let wallet_monthly_spent_usd =
+                    billing_operations.iter().map(|op| op.balance_usd).sum::<f64>() + reserved_usd;
+                let wallet_daily_spent_usd = billing_operations
+                    .iter()
+                    .filter(|op| op.created_at >= now - Duration::days(1))
+                    .map(|op| op.balance_usd)
+                    .sum::<f64>() +
+                    reserved_usd;
+
+                // Get the billing of the wallet.
+                let billing = get_wallet_billing(&client, sender)
+                    .await?
+                    .map(|billing| (billing.status, billing.balance_usd));
+
+                let ctx = PolicyContext {
+                    chain_id,
+                    sender,
+                    calls,
+                    gas,
+                    cost_usd,
+                    wallet_daily_spent_usd,
+                    wallet_monthly_spent_usd,
+                    billing,
+                };
+                info!("policy context: {:?}", ctx);
+
+                let denials = policy.evaluate(&ctx);
+                if !denials.is_empty() {
+                    return Ok(PolicyDecision::Denied(denials));
+                }
+
+                // Reserve the cost until the billing operation of the sponsorship is written.
+                let reservation = create_sponsorship_reservation(
+                    &client,
+                    sender,
+                    chain_id as i64,
+                    cost_usd,
+                    now + Duration::seconds(SPONSORSHIP_RESERVATION_TTL_SECONDS),
+                )
+                .await?;
+
+                Ok(PolicyDecision::Reserved(reservation.id))
+            })
+            .await;
+        let decision = decision?;
+
+        if let PolicyDecision::Denied(denials) = &decision {
+            warn!("Sponsorship of {:?} denied: {:?}", user_operation.sender, denials);
+        }
+
+        Ok(decision)
+    }
+
+    /// Release the reserved cost of the sponsorship that isn't billed.
+    pub async fn release(&self, reservation_id: String) -> Result<()> {
+        delete_sponsorship_reservation(self.db_client.clone(), reservation_id).await
+    }
+}
+
+/// Get the estimated cost of the gas in USD, w/ the max fee of the user operation or the gas price
+/// of the chain. Testnets are free, same as the billing.
+async fn get_cost_usd(chain_id: u64, gas: U256, max_fee_per_gas: Option<U256>) -> Result<f64> {
+    if is_testnet(chain_id) {
+        return Ok(0.0);
+    }
+
+    let gas_price = match max_fee_per_gas {
+        Some(max_fee_per_gas) if !max_fee_per_gas.is_zero() => max_fee_per_gas,
+        _ => get_provider(chain_id).await?.get_gas_price().await?,
+    };
+    let max_gas_consumed = gas_price.checked_mul(gas).ok_or(eyre!("Gas consumed overflow"))?;
+
+    let currency_price_usd =
+        get_native_token_price(get_native_token_symbol(chain_id).to_string()).await?;
+
+    // The amount is formatted in ether first, so that it doesn't truncate above `u64::MAX` wei.
+    Ok(format_ether(max_gas_consumed).parse::<f64>()? * currency_price_usd)
+}
@@ -0,0 +1,357 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::policy::denial::PolicyDenial;
+use ethers::{
+    abi::AbiDecode,
+    types::{Address, U256},
+};
+use eyre::{eyre, Result};
+use lightdotso_contracts::light_wallet::{ExecuteBatchCall, ExecuteCall};
+use lightdotso_prisma::BillingStatus;
+use std::collections::{HashMap, HashSet};
+
+/// The selector of a call, the first four bytes of the call data.
+pub type Selector = [u8; 4];
+
+/// A call of the user operation, w/ the target and the selector if the call has call data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PolicyCall {
+    pub target: Address,
+    pub selector: Option<Selector>,
+}
+
+/// The sponsorship policy, evaluated before the paymaster and data is returned.
+#[derive(Clone, Debug, Default)]
+pub struct SponsorshipPolicy {
+    /// The chains allowed to be sponsored, all chains if empty.
+    pub allowed_chain_ids: HashSet<u64>,
+    /// The call targets allowed to be sponsored w/ the allowed selectors, all targets if empty.
+    /// A target w/o any selectors allows any call to the target.
+    pub allowed_targets: HashMap<Address, HashSet<Selector>>,
+    /// The max gas of a sponsored user operation.
+    pub max_gas_per_operation: U256,
+    /// The cap of the USD sponsored per wallet in a day.
+    pub wallet_daily_cap_usd: f64,
+    /// The cap of the USD sponsored per wallet in a month.
+    pub wallet_monthly_cap_usd: f64,
+    /// Whether the user billing must have the balance to cover the cost.
+    pub require_billing_balance: bool,
+}
+
+/// The facts of the user operation the policy is evaluated against.
+#[derive(Clone, Debug)]
+pub struct PolicyContext {
+    pub chain_id: u64,
+    pub sender: Address,
+    /// The calls of the user operation, or `None` if the call data could not be decoded.
+    pub calls: Option<Vec<PolicyCall>>,
+    /// The total gas limit of the user operation.
+    pub gas: U256,
+    /// The estimated cost of the user operation in USD.
+    pub cost_usd: f64,
+    /// The USD sponsored or reserved for the wallet in the last day.
+    pub wallet_daily_spent_usd: f64,
+    /// The USD sponsored or reserved for the wallet in the last month.
+    pub wallet_monthly_spent_usd: f64,
+    /// The status and the balance in USD of the billing of the wallet.
+    pub billing: Option<(BillingStatus, f64)>,
+}
+
+impl SponsorshipPolicy {
+    /// Evaluate the policy, returning all the reasons the user operation is denied.
+    /// The user operation is allowed if no reasons are returned.
+    pub fn evaluate(&self, ctx: &PolicyContext) -> Vec<PolicyDenial> {
+        let mut denials = vec![];
+
+        if !self.allowed_chain_ids.is_empty() && !self.allowed_chain_ids.contains(&ctx.chain_id) {
+            denials.push(PolicyDenial::ChainNotAllowed { chain_id: ctx.chain_id });
+        }
+
+        if ctx.gas > self.max_gas_per_operation {
+            denials.push(PolicyDenial::GasLimitExceeded {
+                gas: ctx.gas,
+                max_gas: self.max_gas_per_operation,
+            });
+        }
+
+        if !self.allowed_targets.is_empty() {
+            match &ctx.calls {
+                Some(calls) => denials.extend(
+                    calls
+                        .iter()
+                        // Calls to the wallet itself, e.g. configuration updates, are always
+                        // allowed.
+                        .filter(|call| call.target != ctx.sender)
+                        .filter_map(|call| self.evaluate_call(call)),
+                ),
+                None => denials.push(PolicyDenial::UnknownCallData),
+            }
+        }
+
+        // The admin billing is exempt from the caps and the balance.
+        if matches!(ctx.billing, Some((BillingStatus::Admin, _))) {
+            return denials;
+        }
+
+        if ctx.wallet_daily_spent_usd + ctx.cost_usd > self.wallet_daily_cap_usd {
+            denials.push(PolicyDenial::WalletDailyCapExceeded {
+                spent_usd: ctx.wallet_daily_spent_usd,
+                cost_usd: ctx.cost_usd,
+                cap_usd: self.wallet_daily_cap_usd,
+            });
+        }
+
+        if ctx.wallet_monthly_spent_usd + ctx.cost_usd > self.wallet_monthly_cap_usd {
+            denials.push(PolicyDenial::WalletMonthlyCapExceeded {
+                spent_usd: ctx.wallet_monthly_spent_usd,
+                cost_usd: ctx.cost_usd,
+                cap_usd: self.wallet_monthly_cap_usd,
+            });
+        }
+
+        // The balance is only required if opted in, as the user billing is created lazily w/o any
+        // balance. Once opted in, the user billing (or a wallet w/o billing yet) is paid from the
+        // balance, and the sponsored billing is paid by us.
+        if !self.require_billing_balance {
+            return denials;
+        }
+        let balance_usd = match ctx.billing {
+            Some((BillingStatus::Sponsored, _)) => return denials,
+            Some((_, balance_usd)) => balance_usd,
+            None => 0.0,
+        };
+        if balance_usd < ctx.cost_usd {
+            denials.push(PolicyDenial::InsufficientBillingBalance {
+                balance_usd,
+                cost_usd: ctx.cost_usd,
+            });
+        }
+
+        denials
+    }
+
+    /// Evaluate the call against the allowed targets and selectors.
+    fn evaluate_call(&self, call: &PolicyCall) -> Option<PolicyDenial> {
+        let selectors = match self.allowed_targets.get(&call.target) {
+            Some(selectors) => selectors,
+            None => return Some(PolicyDenial::TargetNotAllowed { target: call.target }),
+        };
+
+        if selectors.is_empty() || call.selector.is_some_and(|s| selectors.contains(&s)) {
+            return None;
+        }
+
+        Some(PolicyDenial::SelectorNotAllowed {
+            target: call.target,
+            selector: call.selector.map(|s| s.to_vec().into()),
+        })
+    }
+}
+
+/// Get the calls of the `execute` or `executeBatch` call data of the light wallet.
+/// Returns `None` if the call data is neither.
+pub fn get_policy_calls(call_data: &[u8]) -> Option<Vec<PolicyCall>> {
+    fn selector(func: &[u8]) -> Option<Selector> {
+        func.get(..4).map(|s| [s[0], s[1], s[2], s[3]])
+    }
+
+    // An empty call data only deploys the wallet w/ the init code.
+    if call_data.is_empty() {
+        return Some(vec![]);
+    }
+
+    if let Ok(decoded) = ExecuteCall::decode(call_data) {
+        return Some(vec![PolicyCall { target: decoded.dest, selector: selector(&decoded.func) }]);
+    }
+
+    if let Ok(decoded) = ExecuteBatchCall::decode(call_data) {
+        return Some(
+            decoded
+                .dest
+                .into_iter()
+                .zip(decoded.func)
+                .map(|(target, func)| PolicyCall { target, selector: selector(&func) })
+                .collect(),
+        );
+    }
+
+    None
+}
+
+/// Parse the allowed targets, each either `<address>` for any call or `<address>:<selector>`.
+pub fn parse_allowed_targets(targets: &[String]) -> Result<HashMap<Address, HashSet<Selector>>> {
+    let mut allowed_targets: HashMap<Address, HashSet<Selector>> = HashMap::new();
+
+    for target in targets {
+        let (address, selector) = match target.split_once(':') {
+            Some((address, selector)) => (address, Some(selector)),
+            None => (target.as_str(), None),
+        };
+
+        let address: Address =
+            address.trim().parse().map_err(|_| eyre!("Invalid allowed target: {}", target))?;
+        let selectors = allowed_targets.entry(address).or_default();
+
+        if let Some(selector) = selector {
+            let selector = ethers::utils::hex::decode(selector.trim())
+                .ok()
+                .and_then(|s| Selector::try_from(s.as_slice()).ok())
+                .ok_or_else(|| eyre!("Invalid allowed selector: {}", target))?;
+            selectors.insert(selector);
+        }
+    }
+
+    Ok(allowed_targets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ethers::abi::AbiEncode;
+
+    const TRANSFER_SELECTOR: Selector = [0xa9, 0x05, 0x9c, 0xbb];
+
+    fn policy() -> SponsorshipPolicy {
+        SponsorshipPolicy {
+            allowed_chain_ids: HashSet::from([1]),
+            allowed_targets: parse_allowed_targets(&[
+                "0x0000000000000000000000000000000000000001:0xa9059cbb".to_string(),
+                "0x0000000000000000000000000000000000000002".to_string(),
+            ])
+            .unwrap(),
+            max_gas_per_operation: 1_000_000.into(),
+            wallet_daily_cap_usd: 10.0,
+            wallet_monthly_cap_usd: 100.0,
+            require_billing_balance: false,
+        }
+    }
+
+    fn context() -> PolicyContext {
+        PolicyContext {
+            chain_id: 1,
+            sender: Address::from_low_u64_be(100),
+            calls: Some(vec![PolicyCall {
+                target: Address::from_low_u64_be(1),
+                selector: Some(TRANSFER_SELECTOR),
+            }]),
+            gas: 500_000.into(),
+            cost_usd: 1.0,
+            wallet_daily_spent_usd: 0.0,
+            wallet_monthly_spent_usd: 0.0,
+            billing: Some((BillingStatus::Sponsored, 0.0)),
+        }
+    }
+
+    #[test]
+    fn test_evaluate_allowed() {
+        assert!(policy().evaluate(&context()).is_empty());
+    }
+
+    #[test]
+    fn test_evaluate_denials() {
+        let ctx = PolicyContext {
+            chain_id: 10,
+            calls: Some(vec![
+                PolicyCall { target: Address::from_low_u64_be(1), selector: None },
+                PolicyCall { target: Address::from_low_u64_be(3), selector: None },
+                // Calls to the wallet itself are allowed.
+                PolicyCall { target: Address::from_low_u64_be(100), selector: None },
+            ]),
+            gas: 2_000_000.into(),
+            wallet_daily_spent_usd: 9.5,
+            wallet_monthly_spent_usd: 99.5,
+            ..context()
+        };
+
+        assert_eq!(
+            policy().evaluate(&ctx),
+            vec![
+                PolicyDenial::ChainNotAllowed { chain_id: 10 },
+                PolicyDenial::GasLimitExceeded { gas: 2_000_000.into(), max_gas: 1_000_000.into() },
+                PolicyDenial::SelectorNotAllowed {
+                    target: Address::from_low_u64_be(1),
+                    selector: None
+                },
+                PolicyDenial::TargetNotAllowed { target: Address::from_low_u64_be(3) },
+                PolicyDenial::WalletDailyCapExceeded {
+                    spent_usd: 9.5,
+                    cost_usd: 1.0,
+                    cap_usd: 10.0
+                },
+                PolicyDenial::WalletMonthlyCapExceeded {
+                    spent_usd: 99.5,
+                    cost_usd: 1.0,
+                    cap_usd: 100.0
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_evaluate_billing() {
+        // The balance is not required w/o the opt in, e.g. for the lazily created user billing.
+        let ctx = PolicyContext { billing: Some((BillingStatus::User, 0.0)), ..context() };
+        assert!(policy().evaluate(&ctx).is_empty());
+        let ctx = PolicyContext { billing: None, ..context() };
+        assert!(policy().evaluate(&ctx).is_empty());
+
+        // The user billing must cover the cost once opted in.
+        let policy = SponsorshipPolicy { require_billing_balance: true, ..policy() };
+        let ctx = PolicyContext { billing: Some((BillingStatus::User, 0.5)), ..context() };
+        assert_eq!(
+            policy.evaluate(&ctx),
+            vec![PolicyDenial::InsufficientBillingBalance { balance_usd: 0.5, cost_usd: 1.0 }]
+        );
+        let ctx = PolicyContext { billing: None, ..context() };
+        assert_eq!(
+            policy.evaluate(&ctx),
+            vec![PolicyDenial::InsufficientBillingBalance { balance_usd: 0.0, cost_usd: 1.0 }]
+        );
+        let ctx = PolicyContext { billing: Some((BillingStatus::Sponsored, 0.0)), ..context() };
+        assert!(policy.evaluate(&ctx).is_empty());
+
+        // The admin billing is exempt from the caps and the balance.
+        let ctx = PolicyContext {
+            wallet_daily_spent_usd: 100.0,
+            billing: Some((BillingStatus::Admin, 0.0)),
+            ..context()
+        };
+        assert!(policy.evaluate(&ctx).is_empty());
+
+        // The unknown call data is denied w/ the allowed targets.
+        let ctx = PolicyContext { calls: None, ..context() };
+        assert_eq!(policy.evaluate(&ctx), vec![PolicyDenial::UnknownCallData]);
+    }
+
+    #[test]
+    fn test_get_policy_calls() {
+        let call_data = ExecuteCall {
+            dest: Address::from_low_u64_be(1),
+            value: 0.into(),
+            func: TRANSFER_SELECTOR.to_vec().into(),
+        }
+        .encode();
+
+        assert_eq!(
+            get_policy_calls(&call_data),
+            Some(vec![PolicyCall {
+                target: Address::from_low_u64_be(1),
+                selector: Some(TRANSFER_SELECTOR)
+            }])
+        );
+        assert_eq!(get_policy_calls(&[]), Some(vec![]));
+        assert_eq!(get_policy_calls(&[1, 2, 3, 4, 5]), None);
+    }
+}
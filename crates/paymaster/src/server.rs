@@ -16,7 +16,9 @@ use crate::{paymaster::PaymasterApi, paymaster_api::PaymasterApiServer};
 use async_trait::async_trait;
 use ethers::types::Address;
 use jsonrpsee::core::RpcResult;
-use lightdotso_contracts::types::{GasAndPaymasterAndData, PaymasterAndData, UserOperationRequest};
+use lightdotso_contracts::types::{
+    GasAndPaymasterAndData, PaymasterAndData, TokenPaymasterAndData, UserOperationRequest,
+};
 
 #[async_trait]
 impl PaymasterApiServer for PaymasterApi {
@@ -44,4 +46,21 @@ impl PaymasterApiServer for PaymasterApi {
         )
         .await?)
     }
+
+    async fn request_token_paymaster_and_data(
+        &self,
+        user_operation: UserOperationRequest,
+        entry_point: Address,
+        chain_id: u64,
+        token: Address,
+    ) -> RpcResult<TokenPaymasterAndData> {
+        Ok(PaymasterApi::request_token_paymaster_and_data(
+            self,
+            user_operation,
+            entry_point,
+            chain_id,
+            token,
+        )
+        .await?)
+    }
 }
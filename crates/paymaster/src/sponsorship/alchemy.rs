@@ -0,0 +1,76 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    constants::ALCHEMY_POLICY_IDS, paymaster::get_alchemy_paymaster_and_data,
+    sponsorship::provider::SponsorshipProvider,
+};
+use async_trait::async_trait;
+use ethers::types::Address;
+use eyre::{eyre, Result};
+use lightdotso_contracts::types::{GasAndPaymasterAndData, UserOperationRequest};
+use lightdotso_rpc::constants::ALCHEMY_RPC_URLS;
+
+/// The alchemy gas manager, w/ the policy of each chain.
+pub struct AlchemyProvider {
+    api_key: String,
+}
+
+impl AlchemyProvider {
+    pub fn new(api_key: String) -> Self {
+        Self { api_key }
+    }
+}
+
+#[async_trait]
+impl SponsorshipProvider for AlchemyProvider {
+    fn name(&self) -> &'static str {
+        "alchemy"
+    }
+
+    async fn supports_chain(&self, chain_id: u64) -> bool {
+        (*ALCHEMY_POLICY_IDS).contains_key(&chain_id) && (*ALCHEMY_RPC_URLS).contains_key(&chain_id)
+    }
+
+    async fn sponsor(
+        &self,
+        user_operation: &UserOperationRequest,
+        entry_point: Address,
+        chain_id: u64,
+        _estimate_gas: bool,
+    ) -> Result<GasAndPaymasterAndData> {
+        let alchemy_rpc_url = (*ALCHEMY_RPC_URLS)
+            .get(&chain_id)
+            .ok_or_else(|| eyre!("No alchemy rpc url for chain {}", chain_id))?;
+        let policy_id = (*ALCHEMY_POLICY_IDS)
+            .get(&chain_id)
+            .ok_or_else(|| eyre!("No alchemy policy for chain {}", chain_id))?;
+
+        let sponsorship = get_alchemy_paymaster_and_data(
+            format!("{}{}", alchemy_rpc_url, self.api_key),
+            entry_point,
+            user_operation,
+            policy_id.to_string(),
+        )
+        .await?;
+
+        // The alchemy paymaster only returns the paymaster and data, so keep the gas as is.
+        Ok(GasAndPaymasterAndData {
+            paymaster_and_data: sponsorship.result.paymaster_and_data,
+            call_gas_limit: user_operation.call_gas_limit.unwrap_or_default(),
+            verification_gas_limit: user_operation.verification_gas_limit.unwrap_or_default(),
+            pre_verification_gas: user_operation.pre_verification_gas.unwrap_or_default(),
+        })
+    }
+}
@@ -0,0 +1,67 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    constants::{BICONOMY_PAYMASTER_RPC_URLS, BICONOMY_POLICY_IDS},
+    paymaster::get_biconomy_paymaster_and_data,
+    sponsorship::provider::SponsorshipProvider,
+};
+use async_trait::async_trait;
+use ethers::types::Address;
+use eyre::{eyre, Result};
+use lightdotso_contracts::types::{GasAndPaymasterAndData, UserOperationRequest};
+
+/// The biconomy paymaster, w/ the policy of each chain.
+pub struct BiconomyProvider {}
+
+#[async_trait]
+impl SponsorshipProvider for BiconomyProvider {
+    fn name(&self) -> &'static str {
+        "biconomy"
+    }
+
+    async fn supports_chain(&self, chain_id: u64) -> bool {
+        (*BICONOMY_POLICY_IDS).contains_key(&chain_id) &&
+            (*BICONOMY_PAYMASTER_RPC_URLS).contains_key(&chain_id)
+    }
+
+    async fn sponsor(
+        &self,
+        user_operation: &UserOperationRequest,
+        _entry_point: Address,
+        chain_id: u64,
+        _estimate_gas: bool,
+    ) -> Result<GasAndPaymasterAndData> {
+        let biconomy_rpc_url = (*BICONOMY_PAYMASTER_RPC_URLS)
+            .get(&chain_id)
+            .ok_or_else(|| eyre!("No biconomy rpc url for chain {}", chain_id))?;
+        let policy_id = (*BICONOMY_POLICY_IDS)
+            .get(&chain_id)
+            .ok_or_else(|| eyre!("No biconomy policy for chain {}", chain_id))?;
+
+        let sponsorship = get_biconomy_paymaster_and_data(
+            format!("{}{}", biconomy_rpc_url, policy_id),
+            user_operation,
+        )
+        .await?;
+
+        // Keep the gas as is, as the biconomy paymaster is requested w/ the user operation gas.
+        Ok(GasAndPaymasterAndData {
+            paymaster_and_data: sponsorship.result.paymaster_and_data,
+            call_gas_limit: user_operation.call_gas_limit.unwrap_or_default(),
+            verification_gas_limit: user_operation.verification_gas_limit.unwrap_or_default(),
+            pre_verification_gas: user_operation.pre_verification_gas.unwrap_or_default(),
+        })
+    }
+}
@@ -0,0 +1,98 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ethers::providers::ProviderError;
+use std::{
+    cmp::Reverse,
+    time::{Duration, Instant},
+};
+
+/// The weight of the latest attempt in the moving averages of the health.
+const HEALTH_DECAY: f64 = 0.2;
+
+/// The number of buckets the success rate is rounded into, so that providers w/ a similar success
+/// rate are ordered by latency.
+const SUCCESS_RATE_BUCKETS: f64 = 20.0;
+
+/// The half-life of the failures of a provider, after which the success rate recovers halfway back
+/// to the default, so that a provider ordered last is tried again.
+const HEALTH_RECOVERY_HALF_LIFE: Duration = Duration::from_secs(300);
+
+/// The recent health of a provider on a chain.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ProviderHealth {
+    /// The exponential moving average of the success rate, from 0 to 1.
+    pub success_rate: f64,
+    /// The exponential moving average of the latency in milliseconds.
+    pub latency_ms: f64,
+    /// The number of recorded attempts.
+    pub attempts: u64,
+    /// The time of the latest recorded attempt.
+    pub updated_at: Option<Instant>,
+}
+
+impl Default for ProviderHealth {
+    /// Providers w/o any attempts are assumed healthy, so that they are tried in the registered
+    /// order.
+    fn default() -> Self {
+        Self { success_rate: 1.0, latency_ms: 0.0, attempts: 0, updated_at: None }
+    }
+}
+
+impl ProviderHealth {
+    /// Record the result of an attempt.
+    pub fn record(&mut self, success: bool, latency: Duration) {
+        let success = if success { 1.0 } else { 0.0 };
+        let latency_ms = latency.as_secs_f64() * 1000.0;
+
+        // The first attempt replaces the optimistic default latency.
+        if self.attempts == 0 {
+            self.latency_ms = latency_ms;
+        } else {
+            self.latency_ms += HEALTH_DECAY * (latency_ms - self.latency_ms);
+        }
+        self.success_rate += HEALTH_DECAY * (success - self.success_rate);
+        self.attempts += 1;
+        self.updated_at = Some(Instant::now());
+    }
+
+    /// The health at the time, w/ the success rate recovered toward the default since the latest
+    /// attempt.
+    pub fn recovered(&self, now: Instant) -> Self {
+        let Some(updated_at) = self.updated_at else {
+            return *self;
+        };
+
+        let half_lives = now.saturating_duration_since(updated_at).as_secs_f64() /
+            HEALTH_RECOVERY_HALF_LIFE.as_secs_f64();
+        Self { success_rate: 1.0 - (1.0 - self.success_rate) * 0.5_f64.powf(half_lives), ..*self }
+    }
+
+    /// The key to order the providers by, the higher success rate first, then the lower latency.
+    pub fn priority(&self) -> (Reverse<u64>, u64) {
+        (Reverse((self.success_rate * SUCCESS_RATE_BUCKETS).round() as u64), self.latency_ms as u64)
+    }
+}
+
+/// Whether the error of the sponsorship is a transport or an availability error of the provider,
+/// as opposed to the provider rejecting the user operation.
+/// Only the former count toward the health, so that rejected user operations don't demote a
+/// healthy provider.
+pub fn is_availability_error(err: &eyre::Report) -> bool {
+    err.chain().any(|cause| {
+        cause.is::<reqwest::Error>() ||
+            cause.is::<ProviderError>() ||
+            cause.is::<serde_json::Error>()
+    })
+}
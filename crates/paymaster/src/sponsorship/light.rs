@@ -0,0 +1,88 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    light_paymaster::{get_light_paymaster_and_data, is_light_paymaster_deployed},
+    sponsorship::provider::SponsorshipProvider,
+};
+use async_trait::async_trait;
+use ethers::types::Address;
+use eyre::Result;
+use lightdotso_contracts::types::{GasAndPaymasterAndData, UserOperationRequest};
+use lightdotso_tracing::tracing::warn;
+use std::collections::HashSet;
+use tokio::sync::RwLock;
+
+/// Our own `LightPaymaster`, signed w/ the KMS signer.
+pub struct LightPaymasterProvider {
+    valid_until_seconds: u64,
+    valid_after_seconds: u64,
+    /// The chains the `LightPaymaster` is deployed on, cached once found as it can't be
+    /// undeployed.
+    deployments: RwLock<HashSet<u64>>,
+}
+
+impl LightPaymasterProvider {
+    pub fn new(valid_until_seconds: u64, valid_after_seconds: u64) -> Self {
+        Self { valid_until_seconds, valid_after_seconds, deployments: RwLock::default() }
+    }
+}
+
+#[async_trait]
+impl SponsorshipProvider for LightPaymasterProvider {
+    fn name(&self) -> &'static str {
+        "light"
+    }
+
+    /// The `LightPaymaster` is only supported on the chains it is deployed on, and the verifying
+    /// signer is checked on each sponsorship.
+    async fn supports_chain(&self, chain_id: u64) -> bool {
+        if self.deployments.read().await.contains(&chain_id) {
+            return true;
+        }
+
+        // The chain is checked again on the next sponsorship if it isn't deployed yet or the check
+        // fails, as the paymaster can be deployed while the process runs.
+        match is_light_paymaster_deployed(chain_id).await {
+            Ok(deployed) => {
+                if deployed {
+                    self.deployments.write().await.insert(chain_id);
+                }
+                deployed
+            }
+            Err(e) => {
+                warn!("Failed to check the LightPaymaster deployment on {}: {:?}", chain_id, e);
+                false
+            }
+        }
+    }
+
+    async fn sponsor(
+        &self,
+        user_operation: &UserOperationRequest,
+        entry_point: Address,
+        chain_id: u64,
+        estimate_gas: bool,
+    ) -> Result<GasAndPaymasterAndData> {
+        get_light_paymaster_and_data(
+            user_operation,
+            entry_point,
+            chain_id,
+            estimate_gas,
+            self.valid_until_seconds,
+            self.valid_after_seconds,
+        )
+        .await
+    }
+}
@@ -0,0 +1,67 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    constants::PARTICLE_NETWORK_PAYMASTER_BASE_URL, paymaster::get_gas_and_paymaster_and_data,
+    sponsorship::provider::SponsorshipProvider,
+};
+use async_trait::async_trait;
+use ethers::types::Address;
+use eyre::Result;
+use lightdotso_contracts::types::{GasAndPaymasterAndData, UserOperationRequest};
+use lightdotso_rpc::constants::PARTICLE_RPC_URLS;
+
+/// The particle network paymaster.
+pub struct ParticleProvider {
+    project_id: String,
+    project_key: String,
+}
+
+impl ParticleProvider {
+    pub fn new(project_id: String, project_key: String) -> Self {
+        Self { project_id, project_key }
+    }
+}
+
+#[async_trait]
+impl SponsorshipProvider for ParticleProvider {
+    fn name(&self) -> &'static str {
+        "particle"
+    }
+
+    async fn supports_chain(&self, chain_id: u64) -> bool {
+        (*PARTICLE_RPC_URLS).contains_key(&chain_id)
+    }
+
+    async fn sponsor(
+        &self,
+        user_operation: &UserOperationRequest,
+        entry_point: Address,
+        chain_id: u64,
+        _estimate_gas: bool,
+    ) -> Result<GasAndPaymasterAndData> {
+        let sponsorship = get_gas_and_paymaster_and_data(
+            format!(
+                "{}?chainId={}&projectUuid={}&projectKey={}",
+                *PARTICLE_NETWORK_PAYMASTER_BASE_URL, chain_id, self.project_id, self.project_key
+            ),
+            entry_point,
+            user_operation,
+            None,
+        )
+        .await?;
+
+        Ok(sponsorship.result)
+    }
+}
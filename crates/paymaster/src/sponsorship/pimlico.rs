@@ -0,0 +1,90 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    constants::{PIMLICO_BASE_URL, PIMLICO_SPONSORSHIP_POLICIES},
+    paymaster::get_gas_and_paymaster_and_data,
+    sponsorship::provider::SponsorshipProvider,
+};
+use async_trait::async_trait;
+use ethers::types::Address;
+use eyre::{eyre, Result};
+use lightdotso_contracts::types::{GasAndPaymasterAndData, UserOperationRequest};
+use lightdotso_rpc::constants::PIMLICO_RPC_URLS;
+use lightdotso_tracing::tracing::{info, warn};
+use lightdotso_utils::is_testnet;
+use serde_json::json;
+
+/// The pimlico paymaster, w/ the sponsorship policies on mainnets.
+pub struct PimlicoProvider {
+    api_key: String,
+}
+
+impl PimlicoProvider {
+    pub fn new(api_key: String) -> Self {
+        Self { api_key }
+    }
+}
+
+#[async_trait]
+impl SponsorshipProvider for PimlicoProvider {
+    fn name(&self) -> &'static str {
+        "pimlico"
+    }
+
+    async fn supports_chain(&self, chain_id: u64) -> bool {
+        (*PIMLICO_RPC_URLS).contains_key(&chain_id)
+    }
+
+    async fn sponsor(
+        &self,
+        user_operation: &UserOperationRequest,
+        entry_point: Address,
+        chain_id: u64,
+        _estimate_gas: bool,
+    ) -> Result<GasAndPaymasterAndData> {
+        // For each paymaster policy, attempt to fetch the user operation sponsorship.
+        let mut last_error = None;
+        for policy in PIMLICO_SPONSORSHIP_POLICIES.iter() {
+            info!("pimlico policy: {:?}", policy);
+
+            let sponsorship = get_gas_and_paymaster_and_data(
+                format!("{}/{}/rpc?apikey={}", *PIMLICO_BASE_URL, chain_id, self.api_key),
+                entry_point,
+                user_operation,
+                if !is_testnet(chain_id) {
+                    Some(json!({
+                        "sponsorshipPolicyId": policy
+                    }))
+                } else {
+                    None
+                },
+            )
+            .await;
+
+            // If the sponsorship is successful, return the result.
+            match sponsorship {
+                Ok(sponsorship_data) => return Ok(sponsorship_data.result),
+                Err(e) => {
+                    warn!("Failed to fetch pimlico sponsorship w/ {}: {:?}", policy, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        // Keep the cause of the last failure, so that the transport errors count toward the health.
+        let msg = "No pimlico sponsorship policy sponsored the user operation";
+        Err(last_error.map_or_else(|| eyre!(msg), |e| e.wrap_err(msg)))
+    }
+}
@@ -0,0 +1,39 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use async_trait::async_trait;
+use ethers::types::Address;
+use eyre::Result;
+use lightdotso_contracts::types::{GasAndPaymasterAndData, UserOperationRequest};
+
+/// A paymaster that sponsors user operations, registered in the `SponsorshipRegistry`.
+#[async_trait]
+pub trait SponsorshipProvider: Send + Sync {
+    /// The name of the provider, used to track the health and the metrics.
+    fn name(&self) -> &'static str;
+
+    /// Whether the provider sponsors user operations on the chain.
+    async fn supports_chain(&self, chain_id: u64) -> bool;
+
+    /// Sponsor the user operation, returning the gas and the paymaster and data.
+    /// W/o `estimate_gas`, only the paymaster and data is returned to the caller, so the gas of
+    /// the user operation must be kept as is.
+    async fn sponsor(
+        &self,
+        user_operation: &UserOperationRequest,
+        entry_point: Address,
+        chain_id: u64,
+        estimate_gas: bool,
+    ) -> Result<GasAndPaymasterAndData>;
+}
@@ -0,0 +1,373 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    config::PaymasterArgs,
+    sponsorship::{
+        alchemy::AlchemyProvider,
+        biconomy::BiconomyProvider,
+        health::{is_availability_error, ProviderHealth},
+        light::LightPaymasterProvider,
+        particle::ParticleProvider,
+        pimlico::PimlicoProvider,
+        provider::SponsorshipProvider,
+    },
+};
+use ethers::types::Address;
+use eyre::{eyre, Result};
+use lightdotso_contracts::types::{GasAndPaymasterAndData, UserOperationRequest};
+use lightdotso_opentelemetry::paymaster::PaymasterMetrics;
+use lightdotso_tracing::tracing::{info, warn};
+use std::{collections::HashMap, sync::Arc, time::Instant};
+use tokio::sync::RwLock;
+
+/// The registry of the sponsorship providers, tried in the order of their recent health on each
+/// chain.
+#[derive(Default)]
+pub struct SponsorshipRegistry {
+    providers: Vec<Arc<dyn SponsorshipProvider>>,
+    health: RwLock<HashMap<(u64, &'static str), ProviderHealth>>,
+}
+
+impl SponsorshipRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Construct the registry w/ the providers configured in the arguments.
+    /// Providers w/o the required keys are not registered.
+    pub fn from_args(args: &PaymasterArgs) -> Self {
+        let mut registry = Self::new().with_provider(Arc::new(LightPaymasterProvider::new(
+            args.light_paymaster_valid_until_seconds,
+            args.light_paymaster_valid_after_seconds,
+        )));
+
+        if let Some(api_key) = &args.pimlico_api_key {
+            registry = registry.with_provider(Arc::new(PimlicoProvider::new(api_key.clone())));
+        }
+        if let (Some(project_id), Some(project_key)) =
+            (&args.particle_network_project_id, &args.particle_network_project_key)
+        {
+            registry = registry.with_provider(Arc::new(ParticleProvider::new(
+                project_id.clone(),
+                project_key.clone(),
+            )));
+        }
+        if let Some(api_key) = &args.alchemy_api_key {
+            registry = registry.with_provider(Arc::new(AlchemyProvider::new(api_key.clone())));
+        }
+
+        registry.with_provider(Arc::new(BiconomyProvider {}))
+    }
+
+    /// Register the provider, after the already registered providers.
+    pub fn with_provider(mut self, provider: Arc<dyn SponsorshipProvider>) -> Self {
+        self.providers.push(provider);
+        self
+    }
+
+    /// Get the providers of the chain, ordered by their recent health.
+    pub async fn providers(&self, chain_id: u64) -> Vec<Arc<dyn SponsorshipProvider>> {
+        let mut providers = vec![];
+        for provider in self.providers.iter() {
+            if provider.supports_chain(chain_id).await {
+                providers.push(provider.clone());
+            }
+        }
+
+        // The sort is stable, so providers w/ the same priority keep the registered order.
+        let health = self.health.read().await;
+        let now = Instant::now();
+        providers.sort_by_key(|provider| {
+            health
+                .get(&(chain_id, provider.name()))
+                .copied()
+                .unwrap_or_default()
+                .recovered(now)
+                .priority()
+        });
+
+        providers
+    }
+
+    /// Get the recent health of the provider on the chain.
+    pub async fn health(&self, chain_id: u64, name: &'static str) -> ProviderHealth {
+        self.health.read().await.get(&(chain_id, name)).copied().unwrap_or_default()
+    }
+
+    /// Sponsor the user operation w/ the first provider that succeeds.
+    /// W/o `estimate_gas`, the sponsorships that change the gas of the user operation are rejected.
+    pub async fn sponsor(
+        &self,
+        user_operation: &UserOperationRequest,
+        entry_point: Address,
+        chain_id: u64,
+        estimate_gas: bool,
+    ) -> Result<GasAndPaymasterAndData> {
+        for provider in self.providers(chain_id).await {
+            info!("sponsorship provider: {:?}", provider.name());
+
+            let start = Instant::now();
+            let sponsorship = provider
+                .sponsor(user_operation, entry_point, chain_id, estimate_gas)
+                .await
+                .and_then(|sponsorship| {
+                    if !estimate_gas && !keeps_gas(user_operation, &sponsorship) {
+                        return Err(eyre!("The sponsorship changed the gas of the user operation"));
+                    }
+                    Ok(sponsorship)
+                });
+            let latency = start.elapsed();
+
+            // Record the result to the health and the metrics.
+            // The rejections of the user operation are not failures of the provider, so only the
+            // transport and availability errors count toward the health.
+            let is_available = match &sponsorship {
+                Ok(_) => true,
+                Err(e) => !is_availability_error(e),
+            };
+            self.health
+                .write()
+                .await
+                .entry((chain_id, provider.name()))
+                .or_default()
+                .record(is_available, latency);
+            PaymasterMetrics::set_sponsorship(
+                chain_id,
+                provider.name(),
+                sponsorship.is_ok(),
+                latency.as_secs_f64() * 1000.0,
+            );
+
+            // If the sponsorship is successful, return the result.
+            match sponsorship {
+                Ok(sponsorship_data) => return Ok(sponsorship_data),
+                Err(e) => {
+                    warn!(
+                        "Failed to fetch user operation sponsorship from {}: {:?}",
+                        provider.name(),
+                        e
+                    )
+                }
+            }
+        }
+
+        // If the sponsorship is not successful, return error.
+        Err(eyre!("Failed to fetch user operation sponsorship"))
+    }
+}
+
+/// Whether the sponsorship signs over the gas of the user operation as is.
+fn keeps_gas(user_operation: &UserOperationRequest, sponsorship: &GasAndPaymasterAndData) -> bool {
+    user_operation.call_gas_limit == Some(sponsorship.call_gas_limit) &&
+        user_operation.verification_gas_limit == Some(sponsorship.verification_gas_limit) &&
+        user_operation.pre_verification_gas == Some(sponsorship.pre_verification_gas)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use ethers::types::U256;
+
+    #[derive(Clone, Copy)]
+    enum MockResult {
+        Success,
+        Reestimated,
+        Unavailable,
+        Rejected,
+    }
+
+    struct MockProvider {
+        name: &'static str,
+        chain_id: u64,
+        result: MockResult,
+    }
+
+    #[async_trait]
+    impl SponsorshipProvider for MockProvider {
+        fn name(&self) -> &'static str {
+            self.name
+        }
+
+        async fn supports_chain(&self, chain_id: u64) -> bool {
+            self.chain_id == chain_id
+        }
+
+        async fn sponsor(
+            &self,
+            user_operation: &UserOperationRequest,
+            _entry_point: Address,
+            _chain_id: u64,
+            _estimate_gas: bool,
+        ) -> Result<GasAndPaymasterAndData> {
+            let mut call_gas_limit = user_operation.call_gas_limit.unwrap_or_default();
+            match self.result {
+                MockResult::Success => {}
+                MockResult::Reestimated => call_gas_limit += U256::one(),
+                MockResult::Unavailable => {
+                    let err = serde_json::from_str::<serde_json::Value>("<html>").unwrap_err();
+                    return Err(eyre::Report::new(err).wrap_err(format!("{} failed", self.name)));
+                }
+                MockResult::Rejected => return Err(eyre!("{} rejected", self.name)),
+            }
+            Ok(GasAndPaymasterAndData {
+                call_gas_limit,
+                verification_gas_limit: user_operation.verification_gas_limit.unwrap_or_default(),
+                pre_verification_gas: user_operation.pre_verification_gas.unwrap_or_default(),
+                paymaster_and_data: self.name.as_bytes().to_vec().into(),
+            })
+        }
+    }
+
+    fn user_operation() -> UserOperationRequest {
+        UserOperationRequest {
+            sender: Address::zero(),
+            nonce: 0.into(),
+            init_code: Default::default(),
+            call_data: Default::default(),
+            call_gas_limit: None,
+            verification_gas_limit: None,
+            pre_verification_gas: None,
+            max_fee_per_gas: None,
+            max_priority_fee_per_gas: None,
+            paymaster_and_data: None,
+            signature: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_registry_fallback_and_ordering() -> Result<()> {
+        let registry = SponsorshipRegistry::new()
+            .with_provider(Arc::new(MockProvider {
+                name: "first",
+                chain_id: 1,
+                result: MockResult::Unavailable,
+            }))
+            .with_provider(Arc::new(MockProvider {
+                name: "second",
+                chain_id: 1,
+                result: MockResult::Success,
+            }))
+            .with_provider(Arc::new(MockProvider {
+                name: "other",
+                chain_id: 10,
+                result: MockResult::Success,
+            }));
+
+        // Only the providers of the chain are tried, in the registered order.
+        let names: Vec<_> = registry.providers(1).await.iter().map(|p| p.name()).collect();
+        assert_eq!(names, vec!["first", "second"]);
+
+        // The failing provider falls back to the next one.
+        let sponsorship = registry.sponsor(&user_operation(), Address::zero(), 1, true).await?;
+        assert_eq!(sponsorship.paymaster_and_data.to_vec(), b"second".to_vec());
+
+        // The failing provider is now ordered after the healthy one.
+        let names: Vec<_> = registry.providers(1).await.iter().map(|p| p.name()).collect();
+        assert_eq!(names, vec!["second", "first"]);
+        assert_eq!(registry.health(1, "first").await.attempts, 1);
+
+        // The health is tracked per chain.
+        assert_eq!(registry.health(10, "first").await, ProviderHealth::default());
+
+        // No provider on the chain.
+        assert!(registry.sponsor(&user_operation(), Address::zero(), 2, true).await.is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_registry_rejection_health() -> Result<()> {
+        let registry = SponsorshipRegistry::new()
+            .with_provider(Arc::new(MockProvider {
+                name: "first",
+                chain_id: 1,
+                result: MockResult::Rejected,
+            }))
+            .with_provider(Arc::new(MockProvider {
+                name: "second",
+                chain_id: 1,
+                result: MockResult::Success,
+            }));
+
+        // The rejection of the user operation falls back, but doesn't count toward the health.
+        let sponsorship = registry.sponsor(&user_operation(), Address::zero(), 1, true).await?;
+        assert_eq!(sponsorship.paymaster_and_data.to_vec(), b"second".to_vec());
+        assert_eq!(registry.health(1, "first").await.success_rate, 1.0);
+
+        let names: Vec<_> = registry.providers(1).await.iter().map(|p| p.name()).collect();
+        assert_eq!(names, vec!["first", "second"]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_registry_keeps_gas() -> Result<()> {
+        let registry = SponsorshipRegistry::new()
+            .with_provider(Arc::new(MockProvider {
+                name: "first",
+                chain_id: 1,
+                result: MockResult::Reestimated,
+            }))
+            .with_provider(Arc::new(MockProvider {
+                name: "second",
+                chain_id: 1,
+                result: MockResult::Success,
+            }));
+        let user_operation = UserOperationRequest {
+            call_gas_limit: Some(1.into()),
+            verification_gas_limit: Some(2.into()),
+            pre_verification_gas: Some(3.into()),
+            ..user_operation()
+        };
+
+        // The re-estimated gas is returned w/ `estimate_gas`.
+        let sponsorship = registry.sponsor(&user_operation, Address::zero(), 1, true).await?;
+        assert_eq!(sponsorship.paymaster_and_data.to_vec(), b"first".to_vec());
+        assert_eq!(sponsorship.call_gas_limit, 2.into());
+
+        // Otherwise, the sponsorship that changes the gas falls back to the next one.
+        let sponsorship = registry.sponsor(&user_operation, Address::zero(), 1, false).await?;
+        assert_eq!(sponsorship.paymaster_and_data.to_vec(), b"second".to_vec());
+        assert_eq!(sponsorship.call_gas_limit, 1.into());
+
+        // The gas must be set to be kept.
+        assert!(registry.sponsor(&user_operation(), Address::zero(), 1, false).await.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_provider_health_priority() {
+        let mut fast = ProviderHealth::default();
+        fast.record(true, std::time::Duration::from_millis(100));
+        let mut slow = ProviderHealth::default();
+        slow.record(true, std::time::Duration::from_millis(900));
+        let mut failing = ProviderHealth::default();
+        failing.record(false, std::time::Duration::from_millis(10));
+
+        // The success rate comes first, then the latency.
+        assert!(fast.priority() < slow.priority());
+        assert!(slow.priority() < failing.priority());
+
+        // The failures recover toward the default over time.
+        let updated_at = failing.updated_at.unwrap();
+        assert_eq!(failing.recovered(updated_at).success_rate, failing.success_rate);
+        let recovered = failing.recovered(updated_at + std::time::Duration::from_secs(300));
+        assert!((recovered.success_rate - 0.9).abs() < 1e-9);
+        let recovered = failing.recovered(updated_at + std::time::Duration::from_secs(3600));
+        assert!(recovered.priority() < slow.priority());
+    }
+}
@@ -0,0 +1,297 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    constants::{LIGHT_PAYMASTER_DUMMY_SIGNATURE, TOKEN_PAYMASTER_PRICE_MARKUP},
+    light_paymaster::get_validity_window,
+    paymaster::estimate_user_operation_gas,
+};
+use ethers::{
+    signers::{AwsSigner, Signer},
+    types::{Address, U256},
+    utils::hex,
+};
+use eyre::{eyre, Result, WrapErr};
+use lightdotso_client::crypto::get_native_token_price;
+use lightdotso_contracts::{
+    paymaster::encode_token_paymaster_and_data,
+    token_paymaster::{get_token_paymaster, get_token_paymaster_hash},
+    types::{EntryPointVersion, TokenPaymasterAndData, UserOperationRequest},
+};
+use lightdotso_db::{models::token::get_token_with_latest_price, types::Database};
+use lightdotso_signer::connect::connect_to_kms_with_key_ids;
+use lightdotso_simulator::{
+    simulator::simulate_erc20_balance_and_allowance, types::SimulationUserOperationRequest,
+};
+use lightdotso_tracing::tracing::info;
+use lightdotso_utils::get_native_token_symbol;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::OnceCell;
+
+/// The token paymaster, which charges the gas in a stablecoin at the quote signed w/ its own KMS
+/// signer.
+pub struct TokenPaymaster {
+    address: Address,
+    kms_key_ids: Vec<String>,
+    signer: OnceCell<AwsSigner>,
+    valid_until_seconds: u64,
+    valid_after_seconds: u64,
+    db_client: Database,
+}
+
+impl TokenPaymaster {
+    pub fn new(
+        address: Address,
+        kms_key_ids: Vec<String>,
+        valid_until_seconds: u64,
+        valid_after_seconds: u64,
+        db_client: Database,
+    ) -> Self {
+        Self {
+            address,
+            kms_key_ids,
+            signer: OnceCell::new(),
+            valid_until_seconds,
+            valid_after_seconds,
+            db_client,
+        }
+    }
+
+    /// Get the KMS signer of the token paymaster, connected once and shared across requests.
+    async fn get_signer(&self) -> Result<&AwsSigner> {
+        self.signer.get_or_try_init(|| connect_to_kms_with_key_ids(self.kms_key_ids.clone())).await
+    }
+
+    /// Quote the gas of the user operation in the token, verify that the wallet can pay for it,
+    /// and sign the paymaster and data that charges the token.
+    pub async fn get_token_paymaster_and_data(
+        &self,
+        user_operation: &UserOperationRequest,
+        entry_point: Address,
+        chain_id: u64,
+        token: Address,
+    ) -> Result<TokenPaymasterAndData> {
+        // The token paymaster only supports the v0.6.0 entry point.
+        if EntryPointVersion::try_from(entry_point)? != EntryPointVersion::V060 {
+            return Err(eyre!(
+                "The token paymaster does not support the entry point {:?}",
+                entry_point
+            ));
+        }
+
+        // The fees are signed over as is, so they must be set by the caller.
+        let (Some(max_fee_per_gas), Some(max_priority_fee_per_gas)) =
+            (user_operation.max_fee_per_gas, user_operation.max_priority_fee_per_gas)
+        else {
+            return Err(eyre!("The user operation is missing the max fee per gas"));
+        };
+
+        // Check that the token is supported by the token paymaster on the chain.
+        let token_paymaster = get_token_paymaster(chain_id, self.address).await?;
+        if !token_paymaster.supported_tokens(token).call().await? {
+            return Err(eyre!("The token {:?} is not supported on chain {}", token, chain_id));
+        }
+
+        // Get the signer, and check that it is the verifying signer of the token paymaster.
+        let signer = self.get_signer().await?;
+        let verifying_signer = token_paymaster.verifying_signer().call().await?;
+        if verifying_signer != signer.address() {
+            return Err(eyre!(
+                "The signer {:?} is not the verifying signer {:?} of the token paymaster",
+                signer.address(),
+                verifying_signer
+            ));
+        }
+
+        // Get the exchange rate from the token price and the native token price.
+        let token_data =
+            get_token_with_latest_price(self.db_client.clone(), chain_id as i64, token)
+                .await?
+                .ok_or(eyre!("Token {:?} not found", token))?;
+        let token_price_usd = token_data
+            .prices
+            .and_then(|prices| prices.first().map(|price| price.price))
+            .ok_or(eyre!("Token price of {:?} not found", token))?;
+        let decimals =
+            token_data.decimals.ok_or(eyre!("Token decimals of {:?} not found", token))?;
+        let native_price_usd =
+            get_native_token_price(get_native_token_symbol(chain_id).to_string()).await?;
+        let exchange_rate = get_exchange_rate(
+            native_price_usd,
+            token_price_usd,
+            decimals as u32,
+            TOKEN_PAYMASTER_PRICE_MARKUP,
+        )?;
+
+        // Get the validity window of the quote.
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let (valid_until, valid_after) =
+            get_validity_window(now, self.valid_until_seconds, self.valid_after_seconds);
+
+        // Re-estimate the gas w/ the paymaster and data attached, so that the verification gas
+        // covers the validation of the token paymaster.
+        let mut user_operation = UserOperationRequest {
+            max_fee_per_gas: Some(max_fee_per_gas),
+            max_priority_fee_per_gas: Some(max_priority_fee_per_gas),
+            paymaster_and_data: Some(
+                encode_token_paymaster_and_data(
+                    self.address,
+                    valid_until,
+                    valid_after,
+                    token,
+                    exchange_rate,
+                    &hex::decode(LIGHT_PAYMASTER_DUMMY_SIGNATURE)?,
+                )
+                .into(),
+            ),
+            ..user_operation.clone()
+        };
+        let estimation = estimate_user_operation_gas(chain_id, entry_point, &user_operation)
+            .await
+            .wrap_err("Failed to estimate the gas w/ the token paymaster")?
+            .result;
+        info!("estimation w/ the token paymaster: {:?}", estimation);
+        user_operation.call_gas_limit = Some(estimation.call_gas_limit);
+        user_operation.verification_gas_limit = Some(estimation.verification_gas_limit);
+        user_operation.pre_verification_gas = Some(estimation.pre_verification_gas);
+
+        // Get the max gas cost of the user operation in wei as the entry point prefunds it, w/ the
+        // verification gas limit for the postOp of the paymaster, and the max token cost of it.
+        let max_gas_cost = estimation
+            .verification_gas_limit
+            .checked_mul(3.into())
+            .and_then(|gas| gas.checked_add(estimation.pre_verification_gas))
+            .and_then(|gas| gas.checked_add(estimation.call_gas_limit))
+            .and_then(|gas| gas.checked_mul(max_fee_per_gas))
+            .ok_or(eyre!("Gas cost overflow"))?;
+        let max_token_cost = get_max_token_cost(max_gas_cost, exchange_rate)?;
+        info!("max_token_cost: {} w/ exchange_rate: {}", max_token_cost, exchange_rate);
+
+        // Verify the token balance and the allowance to the paymaster after the user operation.
+        let (balance, allowance) = simulate_erc20_balance_and_allowance(
+            SimulationUserOperationRequest {
+                chain_id,
+                sender: user_operation.sender,
+                nonce: user_operation.nonce,
+                init_code: Some(user_operation.init_code.to_vec().into()),
+                call_data: Some(user_operation.call_data.to_vec().into()),
+                state_overrides: None,
+            },
+            token,
+            self.address,
+        )
+        .await?;
+        if balance < max_token_cost {
+            return Err(eyre!("Insufficient token balance: {} < {}", balance, max_token_cost));
+        }
+        if allowance < max_token_cost {
+            return Err(eyre!("Insufficient token allowance: {} < {}", allowance, max_token_cost));
+        }
+
+        // Get the hash to sign over the estimated gas from the token paymaster, and sign it w/ the
+        // `eth_sign` prefix.
+        let hash = get_token_paymaster_hash(
+            &token_paymaster,
+            &user_operation,
+            valid_until,
+            valid_after,
+            token,
+            exchange_rate,
+        )
+        .await?;
+        let signature = signer
+            .sign_message(hash)
+            .await
+            .map_err(|e| eyre!("Failed to sign the token paymaster hash: {}", e))?;
+
+        Ok(TokenPaymasterAndData {
+            call_gas_limit: estimation.call_gas_limit,
+            verification_gas_limit: estimation.verification_gas_limit,
+            pre_verification_gas: estimation.pre_verification_gas,
+            paymaster_and_data: encode_token_paymaster_and_data(
+                self.address,
+                valid_until,
+                valid_after,
+                token,
+                exchange_rate,
+                &signature.to_vec(),
+            )
+            .into(),
+            token,
+            exchange_rate,
+            max_token_cost,
+        })
+    }
+}
+
+/// Get the exchange rate in the token units per 1 ether of the native gas, w/ the markup.
+pub fn get_exchange_rate(
+    native_price_usd: f64,
+    token_price_usd: f64,
+    decimals: u32,
+    markup: f64,
+) -> Result<U256> {
+    if token_price_usd <= 0.0 {
+        return Err(eyre!("Invalid token price: {}", token_price_usd));
+    }
+
+    let exchange_rate = native_price_usd / token_price_usd * 10_f64.powi(decimals as i32) * markup;
+    if !exchange_rate.is_finite() || exchange_rate <= 0.0 || exchange_rate >= u128::MAX as f64 {
+        return Err(eyre!("Invalid exchange rate: {}", exchange_rate));
+    }
+
+    Ok(U256::from(exchange_rate.round() as u128))
+}
+
+/// Get the max token units charged for the max gas cost in wei, rounded up.
+pub fn get_max_token_cost(max_gas_cost: U256, exchange_rate: U256) -> Result<U256> {
+    let wei_per_ether = U256::exp10(18);
+
+    max_gas_cost
+        .checked_mul(exchange_rate)
+        .map(|cost| (cost + wei_per_ether - 1) / wei_per_ether)
+        .ok_or(eyre!("Token cost overflow"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_exchange_rate() -> Result<()> {
+        // 1 ETH at 3000 USD is 3000 USDC w/ 6 decimals.
+        assert_eq!(get_exchange_rate(3000.0, 1.0, 6, 1.0)?, U256::from(3_000_000_000_u64));
+
+        // The markup is applied on top.
+        assert_eq!(get_exchange_rate(3000.0, 1.0, 6, 1.1)?, U256::from(3_300_000_000_u64));
+
+        // The token w/o a price can't be quoted.
+        assert!(get_exchange_rate(3000.0, 0.0, 6, 1.0).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_max_token_cost() -> Result<()> {
+        let exchange_rate = U256::from(3_000_000_000_u64);
+
+        // 0.001 ETH of gas is 3 USDC.
+        assert_eq!(get_max_token_cost(U256::exp10(15), exchange_rate)?, U256::from(3_000_000));
+
+        // The cost is rounded up.
+        assert_eq!(get_max_token_cost(U256::one(), exchange_rate)?, U256::one());
+
+        Ok(())
+    }
+}
@@ -29,6 +29,7 @@ use ethers::{
     utils::to_checksum,
 };
 use eyre::{eyre, Result};
+use lightdotso_common::traits::HexToBytes;
 use lightdotso_contracts::{
     provider::get_provider,
     types::{UserOperationReceipt, UserOperationWithTransactionAndReceiptLogs},
@@ -71,7 +72,7 @@ use lightdotso_kafka::{
 use lightdotso_opentelemetry::polling::PollingMetrics;
 use lightdotso_prisma::{user_operation, ActivityEntity, ActivityOperation, PrismaClient};
 use lightdotso_redis::{get_redis_client, query::wallet::add_to_wallets, redis::Client};
-use lightdotso_sequence::init::get_image_hash_salt_from_init_code;
+use lightdotso_sequence::init::get_factory_image_hash_salt_from_init_code;
 use lightdotso_tracing::tracing::{error, info, trace, warn};
 use lightdotso_utils::get_chain_block_seconds;
 use serde_json::json;
@@ -743,8 +744,8 @@ impl Polling {
 
         if let Some(init_code) = &user_operation.init_code {
             if init_code.0.len() > 2 {
-                let (_, salt) =
-                    get_image_hash_salt_from_init_code(init_code.clone().0.into_bytes()).unwrap();
+                let (_, _, salt) =
+                    get_factory_image_hash_salt_from_init_code(&init_code.0.hex_to_bytes()?)?;
 
                 return Ok({
                     || {
@@ -29,6 +29,11 @@ lazy_static! {
     pub static ref QUEUE_PORTFOLIO: String = "queue:portfolio".to_string();
 }
 
+// The reconciliation queue namespace
+lazy_static! {
+    pub static ref QUEUE_RECONCILIATION: String = "queue:reconciliation".to_string();
+}
+
 // The token queue namespace
 lazy_static! {
     pub static ref QUEUE_TOKEN: String = "queue:token".to_string();
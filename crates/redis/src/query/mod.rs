@@ -14,6 +14,7 @@
 
 pub mod node;
 pub mod portfolio;
+pub mod reconciliation;
 pub mod token;
 pub mod transaction;
 pub mod user_operation;
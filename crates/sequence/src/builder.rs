@@ -32,7 +32,15 @@
 // Truly thank you for providing this out there as free and open source work.
 // We are forever grateful
 
-use crate::types::SignerNode;
+use crate::{
+    config::WalletConfig,
+    types::{
+        AddressSignatureLeaf, DynamicSignatureLeaf, DynamicSignatureType, ECDSASignatureLeaf,
+        ECDSASignatureType, NestedLeaf, NodeLeaf, SignatureLeaf, Signer, SignerNode, SubdigestLeaf,
+        WalletSignatureType, ECDSA_SIGNATURE_LENGTH,
+    },
+};
+use ethers::types::{Address, H256};
 use eyre::{eyre, Result};
 
 // From: https://github.com/0xsequence/sequence.js/blob/e5659ab1a304ae48b28c843b0d99fb3b3f6bc0b1/packages/core/src/v2/config.ts#L317
@@ -54,15 +62,411 @@ pub fn rooted_node_builder(members: Vec<SignerNode>) -> Result<SignerNode> {
     Ok(acc)
 }
 
+/// The member of a wallet config, which is either an owner, a nested config w/ its own members, or
+/// a pre-approved subdigest.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConfigMember {
+    /// The owner w/ the weight
+    Owner { address: Address, weight: u8 },
+    /// The nested config w/ the external weight, and the internal threshold of its members
+    Nested { weight: u8, threshold: u16, members: Vec<ConfigMember> },
+    /// The hardcoded subdigest that is approved w/o any signature
+    Subdigest { hash: H256 },
+}
+
+impl ConfigMember {
+    /// Get the signer node of the member, where the nested config has the rooted tree of its
+    /// members as the children.
+    pub fn to_signer_node(&self) -> Result<SignerNode> {
+        match self {
+            ConfigMember::Owner { address, weight } => Ok(SignerNode {
+                signer: Some(Signer {
+                    weight: Some(*weight),
+                    leaf: SignatureLeaf::AddressSignature(AddressSignatureLeaf {
+                        address: *address,
+                    }),
+                }),
+                left: None,
+                right: None,
+            }),
+            ConfigMember::Nested { weight, threshold, members } => {
+                let internal = config_tree_builder(members)?;
+                let internal_root = internal.calculate_image_hash_from_node([0; 32])?;
+
+                // The internal tree of a single member is the member itself
+                let (left, right) = if internal.signer.is_some() {
+                    (Some(Box::new(internal)), None)
+                } else {
+                    (internal.left, internal.right)
+                };
+
+                Ok(SignerNode {
+                    signer: Some(Signer {
+                        weight: Some(*weight),
+                        leaf: SignatureLeaf::NestedSignature(NestedLeaf {
+                            internal_threshold: *threshold,
+                            external_weight: *weight,
+                            internal_root: internal_root.into(),
+                            // The size is set from the signed members on encoding
+                            size: 0,
+                        }),
+                    }),
+                    left,
+                    right,
+                })
+            }
+            ConfigMember::Subdigest { hash } => Ok(SignerNode {
+                signer: Some(Signer {
+                    weight: None,
+                    leaf: SignatureLeaf::SubdigestSignature(SubdigestLeaf { hash: *hash }),
+                }),
+                left: None,
+                right: None,
+            }),
+        }
+    }
+}
+
+/// Roots the config members sequentially in order, w/ the nested configs as the subtrees.
+pub fn config_tree_builder(members: &[ConfigMember]) -> Result<SignerNode> {
+    rooted_node_builder(
+        members.iter().map(ConfigMember::to_signer_node).collect::<Result<Vec<_>>>()?,
+    )
+}
+
+/// Parse the ECDSA signature leaf of the owner from the 65 bytes signature w/ the trailing
+/// signature type byte, which is either `1` for EIP-712 or `2` for eth_sign.
+pub fn ecdsa_signature_leaf(address: Address, bytes: &[u8]) -> Result<SignatureLeaf> {
+    if bytes.len() != ECDSA_SIGNATURE_LENGTH + 1 {
+        return Err(eyre!("Invalid ECDSA signature length: {}", bytes.len()));
+    }
+
+    let mut signature = [0; ECDSA_SIGNATURE_LENGTH];
+    signature.copy_from_slice(&bytes[..ECDSA_SIGNATURE_LENGTH]);
+    let signature_type = match bytes[ECDSA_SIGNATURE_LENGTH] {
+        0x1 => ECDSASignatureType::ECDSASignatureTypeEIP712,
+        0x2 => ECDSASignatureType::ECDSASignatureTypeEthSign,
+        signature_type => {
+            return Err(eyre!("Invalid ECDSA signature type: {}", signature_type));
+        }
+    };
+
+    Ok(SignatureLeaf::ECDSASignature(ECDSASignatureLeaf {
+        address,
+        signature_type,
+        signature: signature.into(),
+    }))
+}
+
+/// Create the dynamic signature leaf of the contract owner from the EIP-1271 signature.
+pub fn erc_1271_signature_leaf(address: Address, signature: &[u8]) -> SignatureLeaf {
+    SignatureLeaf::DynamicSignature(DynamicSignatureLeaf {
+        address,
+        signature_type: DynamicSignatureType::DynamicSignatureTypeEIP1271,
+        signature: signature.to_vec().into(),
+        // The size includes the trailing signature type
+        size: signature.len() as u32 + 1,
+    })
+}
+
+/// Assembles the encoded signature of a wallet config from the collected owner signatures.
+/// The owner signatures can be of any signing leaf type (ECDSA or dynamic), and the signed prior
+/// configs are chained from the newest to the oldest for the checkpoint upgrades.
+#[derive(Clone, Debug)]
+pub struct SignatureBuilder {
+    /// The wallet config w/ the owner tree to sign
+    config: WalletConfig,
+    /// The collected owner signatures
+    signatures: Vec<SignatureLeaf>,
+    /// The signature type of the wallet config, except for the chained type
+    signature_type: WalletSignatureType,
+    /// The prior configs to chain the signature from
+    prior_configs: Vec<SignatureBuilder>,
+}
+
+impl SignatureBuilder {
+    /// Create the builder for the wallet config, defaults to the dynamic signature type.
+    pub fn new(config: WalletConfig) -> Self {
+        Self {
+            config,
+            signatures: vec![],
+            signature_type: WalletSignatureType::Dynamic,
+            prior_configs: vec![],
+        }
+    }
+
+    /// Add a collected owner signature.
+    pub fn signature(mut self, signature: SignatureLeaf) -> Self {
+        self.signatures.push(signature);
+        self
+    }
+
+    /// Add the collected owner signatures.
+    pub fn signatures(mut self, signatures: impl IntoIterator<Item = SignatureLeaf>) -> Self {
+        self.signatures.extend(signatures);
+        self
+    }
+
+    /// Set the signature type of the wallet config.
+    pub fn signature_type(mut self, signature_type: WalletSignatureType) -> Self {
+        self.signature_type = signature_type;
+        self
+    }
+
+    /// Chain the signature from the signed prior config, which has to be older than the
+    /// previously chained ones.
+    pub fn chain(mut self, prior_config: SignatureBuilder) -> Self {
+        self.prior_configs.push(prior_config);
+        self
+    }
+
+    /// Build the wallet config w/ the owner signatures filled in the tree, and the subtrees w/o
+    /// signatures collapsed into the node leaves.
+    pub fn build_config(self) -> Result<WalletConfig> {
+        if self.signature_type == WalletSignatureType::Chained {
+            return Err(eyre!("Chained signature type is set from the prior configs"));
+        }
+        if self.signature_type == WalletSignatureType::Legacy && !self.prior_configs.is_empty() {
+            return Err(eyre!("Legacy signatures can't be chained"));
+        }
+
+        // Fill in the owner signatures, keeping the weights of the tree.
+        let mut tree = self.config.tree.clone();
+        for signature in self.signatures.iter() {
+            let address = signing_address(signature)?;
+            if !fill_signature(&mut tree, address, signature) {
+                return Err(eyre!("Signer {:?} is not an owner of the wallet config", address));
+            }
+        }
+        let weight = signed_weight(&tree);
+
+        // The prior configs are always signed in the chain agnostic mode.
+        let prior_configs = self
+            .prior_configs
+            .into_iter()
+            .map(|prior_config| {
+                prior_config.signature_type(WalletSignatureType::NoChainId).build_config()
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(WalletConfig {
+            weight,
+            tree: collapse_unsigned(&tree)?,
+            signature_type: self.signature_type as u8,
+            internal_recovered_configs: if prior_configs.is_empty() {
+                None
+            } else {
+                Some(prior_configs)
+            },
+            ..self.config
+        })
+    }
+
+    /// Build the minimal encoded signature of the wallet config.
+    pub fn build(self) -> Result<Vec<u8>> {
+        let mut config = self.build_config()?;
+
+        // Encode the nested configs in front of their members.
+        config.tree = encoding_tree(&config.tree)?;
+        if let Some(prior_configs) = config.internal_recovered_configs.as_mut() {
+            for prior_config in prior_configs.iter_mut() {
+                prior_config.tree = encoding_tree(&prior_config.tree)?;
+            }
+        }
+
+        if config.internal_recovered_configs.is_some() {
+            config.encode_chained_wallet()
+        } else {
+            config.encode()
+        }
+    }
+}
+
+/// Chain the upgrade signatures, ordered from the newest to the oldest configuration, where each
+/// configuration signs the image hash of the one before it in the chain. The upgrades are signed in
+/// the chain agnostic mode, so that the signature is valid on every chain of the wallet.
+pub fn chain_upgrade_signatures(
+    builders: impl IntoIterator<Item = SignatureBuilder>,
+) -> Result<Vec<u8>> {
+    let mut builders = builders.into_iter();
+    let builder = builders.next().ok_or(eyre!("No upgrade signatures"))?;
+
+    builders
+        .fold(builder.signature_type(WalletSignatureType::NoChainId), |builder, prior_config| {
+            builder.chain(prior_config)
+        })
+        .build()
+}
+
+/// Get the address of the signing leaf.
+fn signing_address(leaf: &SignatureLeaf) -> Result<Address> {
+    match leaf {
+        SignatureLeaf::ECDSASignature(leaf) => Ok(leaf.address),
+        SignatureLeaf::DynamicSignature(leaf) => Ok(leaf.address),
+        _ => Err(eyre!("Unsupported signature leaf: {:?}", leaf)),
+    }
+}
+
+/// Replace the address leaves of the owner w/ the signature, returns whether the owner was found.
+fn fill_signature(node: &mut SignerNode, address: Address, signature: &SignatureLeaf) -> bool {
+    let mut found = false;
+
+    if let Some(signer) = node.signer.as_mut() {
+        if matches!(&signer.leaf, SignatureLeaf::AddressSignature(leaf) if leaf.address == address)
+        {
+            signer.leaf = signature.clone();
+            found = true;
+        }
+    }
+
+    // Traverse the children, which include the members of the nested configs
+    if let Some(left) = node.left.as_mut() {
+        found |= fill_signature(left, address, signature);
+    }
+    if let Some(right) = node.right.as_mut() {
+        found |= fill_signature(right, address, signature);
+    }
+
+    found
+}
+
+/// Whether the tree has a leaf that has to be encoded as is.
+fn has_signature(node: &SignerNode) -> bool {
+    let is_signed = node.signer.as_ref().map_or(false, |signer| {
+        matches!(
+            signer.leaf,
+            SignatureLeaf::ECDSASignature(_) |
+                SignatureLeaf::DynamicSignature(_) |
+                SignatureLeaf::SubdigestSignature(_)
+        )
+    });
+
+    is_signed ||
+        node.left.as_ref().map_or(false, |left| has_signature(left)) ||
+        node.right.as_ref().map_or(false, |right| has_signature(right))
+}
+
+/// Get the total weight of the signed leaves in the tree, where the nested configs only count w/
+/// their external weight once the internal threshold is met.
+fn signed_weight(node: &SignerNode) -> u32 {
+    let children_weight = node.left.as_ref().map_or(0, |left| signed_weight(left)) +
+        node.right.as_ref().map_or(0, |right| signed_weight(right));
+
+    match node.signer.as_ref() {
+        Some(signer) => match &signer.leaf {
+            SignatureLeaf::ECDSASignature(_) | SignatureLeaf::DynamicSignature(_) => {
+                signer.weight.unwrap_or(0) as u32
+            }
+            SignatureLeaf::NestedSignature(leaf) => {
+                if children_weight >= leaf.internal_threshold as u32 {
+                    leaf.external_weight as u32
+                } else {
+                    0
+                }
+            }
+            _ => children_weight,
+        },
+        None => children_weight,
+    }
+}
+
+/// Whether the node is a nested config.
+fn is_nested(node: &SignerNode) -> bool {
+    node.signer
+        .as_ref()
+        .map_or(false, |signer| matches!(signer.leaf, SignatureLeaf::NestedSignature(_)))
+}
+
+/// Collapse the branches w/o signatures into the node leaves of their hashes, which are encoded
+/// in a single leaf instead of every unsigned owner.
+fn collapse_unsigned(node: &SignerNode) -> Result<SignerNode> {
+    // Keep the leaves as is
+    if node.signer.is_some() && !is_nested(node) {
+        return Ok(node.clone());
+    }
+
+    if (is_nested(node) || (node.left.is_some() && node.right.is_some())) && !has_signature(node) {
+        return Ok(SignerNode {
+            signer: Some(Signer {
+                weight: None,
+                leaf: SignatureLeaf::NodeSignature(NodeLeaf {
+                    hash: node.calculate_image_hash_from_node([0; 32])?.into(),
+                }),
+            }),
+            left: None,
+            right: None,
+        });
+    }
+
+    Ok(SignerNode {
+        signer: node.signer.clone(),
+        left: node.left.as_ref().map(|left| collapse_unsigned(left).map(Box::new)).transpose()?,
+        right: node
+            .right
+            .as_ref()
+            .map(|right| collapse_unsigned(right).map(Box::new))
+            .transpose()?,
+    })
+}
+
+/// Get the tree in the encoding order, where the nested config is encoded in front of its members
+/// w/ the size of their encoding.
+fn encoding_tree(node: &SignerNode) -> Result<SignerNode> {
+    let left = node.left.as_ref().map(|left| encoding_tree(left).map(Box::new)).transpose()?;
+    let right = node.right.as_ref().map(|right| encoding_tree(right).map(Box::new)).transpose()?;
+
+    if let Some(Signer { weight, leaf: SignatureLeaf::NestedSignature(leaf) }) = &node.signer {
+        let internal = SignerNode { signer: None, left, right };
+        let size = internal.encode_hash_from_signers()?.len() as u32;
+
+        return Ok(SignerNode {
+            signer: Some(Signer {
+                weight: *weight,
+                leaf: SignatureLeaf::NestedSignature(NestedLeaf { size, ..leaf.clone() }),
+            }),
+            left: None,
+            right: Some(Box::new(internal)),
+        });
+    }
+
+    Ok(SignerNode { signer: node.signer.clone(), left, right })
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
+        node::{leaf_for_address_and_weight, leaf_for_nested},
         types::{AddressSignatureLeaf, NestedLeaf, SignatureLeaf, Signer, SubdigestLeaf},
-        utils::parse_hex_to_bytes32,
+        utils::{hash_keccak_256, parse_hex_to_bytes32},
     };
+    use ethers::types::H256;
 
     use super::*;
 
+    /// The empty 65 bytes signature w/ the trailing eth_sign signature type.
+    const ECDSA_SIGNATURE: [u8; ECDSA_SIGNATURE_LENGTH + 1] = {
+        let mut signature = [0u8; ECDSA_SIGNATURE_LENGTH + 1];
+        signature[ECDSA_SIGNATURE_LENGTH] = 2;
+        signature
+    };
+
+    #[test]
+    fn test_ecdsa_signature_leaf_signature_type() -> Result<()> {
+        let mut signature = ECDSA_SIGNATURE;
+        assert!(ecdsa_signature_leaf(Address::zero(), &signature).is_ok());
+
+        signature[ECDSA_SIGNATURE_LENGTH] = 1;
+        assert!(ecdsa_signature_leaf(Address::zero(), &signature).is_ok());
+
+        // Only the EIP-712 and eth_sign signature types are accepted
+        for signature_type in [0, 3, 0xff] {
+            signature[ECDSA_SIGNATURE_LENGTH] = signature_type;
+            assert!(ecdsa_signature_leaf(Address::zero(), &signature).is_err());
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn test_config_1() -> Result<()> {
         let members = vec![
@@ -343,4 +747,224 @@ mod tests {
 
         Ok(())
     }
+
+    fn owner_config(owners: &[Address], threshold: u16) -> Result<WalletConfig> {
+        let owner_nodes = owners
+            .iter()
+            .map(|owner| SignerNode {
+                signer: Some(Signer {
+                    weight: Some(1),
+                    leaf: SignatureLeaf::AddressSignature(AddressSignatureLeaf { address: *owner }),
+                }),
+                left: None,
+                right: None,
+            })
+            .collect();
+
+        Ok(WalletConfig {
+            checkpoint: 1,
+            threshold,
+            weight: 0,
+            image_hash: H256::zero(),
+            tree: rooted_node_builder(owner_nodes)?,
+            signature_type: 1,
+            internal_root: None,
+            internal_recovered_configs: None,
+        })
+    }
+
+    #[test]
+    fn test_signature_builder_collapses_unsigned() -> Result<()> {
+        let owners: Vec<Address> = (1..=4).map(Address::from_low_u64_be).collect();
+        let config = owner_config(&owners, 1)?;
+
+        let signed_config = SignatureBuilder::new(config.clone())
+            .signature(ecdsa_signature_leaf(owners[2], &ECDSA_SIGNATURE)?)
+            .build_config()?;
+        assert_eq!(signed_config.weight, 1);
+
+        // The unsigned owners in front are collapsed into a node, and the rest are kept.
+        let signers = signed_config.tree.get_signers();
+        assert_eq!(signers.len(), 3);
+        assert!(matches!(signers[0].leaf, SignatureLeaf::NodeSignature(_)));
+        assert!(matches!(signers[1].leaf, SignatureLeaf::ECDSASignature(_)));
+        assert!(matches!(signers[2].leaf, SignatureLeaf::AddressSignature(_)));
+
+        // The image hash of the tree is unchanged.
+        assert_eq!(
+            signed_config.tree.calculate_image_hash_from_node([0; 32])?,
+            config.tree.calculate_image_hash_from_node([0; 32])?
+        );
+
+        // The dynamic signature type is prepended.
+        let signature = SignatureBuilder::new(config.clone())
+            .signature(ecdsa_signature_leaf(owners[2], &ECDSA_SIGNATURE)?)
+            .build()?;
+        assert_eq!(signature[0], WalletSignatureType::Dynamic as u8);
+
+        // The legacy signature starts w/ the threshold.
+        let signature = SignatureBuilder::new(config)
+            .signature(ecdsa_signature_leaf(owners[2], &ECDSA_SIGNATURE)?)
+            .signature_type(WalletSignatureType::Legacy)
+            .build()?;
+        assert_eq!(signature[..2], [0, 1]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_signature_builder_chained() -> Result<()> {
+        let owners: Vec<Address> = (1..=2).map(Address::from_low_u64_be).collect();
+        let config = owner_config(&owners, 1)?;
+
+        let signed_config = SignatureBuilder::new(config.clone())
+            .signature(ecdsa_signature_leaf(owners[0], &ECDSA_SIGNATURE)?)
+            .chain(
+                SignatureBuilder::new(config.clone())
+                    .signature(ecdsa_signature_leaf(owners[1], &ECDSA_SIGNATURE)?),
+            )
+            .build_config()?;
+
+        // The prior configs are always in the chain agnostic mode.
+        let prior_configs =
+            signed_config.internal_recovered_configs.clone().ok_or(eyre!("No prior configs"))?;
+        assert_eq!(prior_configs.len(), 1);
+        assert_eq!(prior_configs[0].signature_type, WalletSignatureType::NoChainId as u8);
+
+        let signature = signed_config.encode_chained_wallet()?;
+        assert_eq!(signature[0], WalletSignatureType::Chained as u8);
+
+        // Legacy signatures can't be chained.
+        assert!(SignatureBuilder::new(config.clone())
+            .signature_type(WalletSignatureType::Legacy)
+            .chain(SignatureBuilder::new(config))
+            .build()
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_signature_builder_unknown_owner() -> Result<()> {
+        let config = owner_config(&[Address::from_low_u64_be(1)], 1)?;
+
+        let res = SignatureBuilder::new(config)
+            .signature(ecdsa_signature_leaf(Address::from_low_u64_be(2), &ECDSA_SIGNATURE)?)
+            .build();
+        assert!(res.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_signature_builder_erc_1271() -> Result<()> {
+        let owners: Vec<Address> = (1..=2).map(Address::from_low_u64_be).collect();
+        let config = owner_config(&owners, 2)?;
+
+        let signed_config = SignatureBuilder::new(config)
+            .signature(ecdsa_signature_leaf(owners[0], &ECDSA_SIGNATURE)?)
+            .signature(erc_1271_signature_leaf(owners[1], &[7u8; 100]))
+            .build_config()?;
+        assert_eq!(signed_config.weight, 2);
+
+        // The contract signature is encoded as a dynamic leaf w/ the size and the trailing type.
+        let encoded = signed_config.tree.encode_hash_from_signers()?;
+        let dynamic = &encoded[1 + 1 + 66..];
+        assert_eq!(dynamic[0], 0x2);
+        assert_eq!(dynamic[1], 1);
+        assert_eq!(&dynamic[2..22], owners[1].as_bytes());
+        assert_eq!(dynamic[22..25], [0, 0, 101]);
+        assert_eq!(dynamic[25..125], [7u8; 100]);
+        assert_eq!(dynamic[125], DynamicSignatureType::DynamicSignatureTypeEIP1271 as u8);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_tree_builder_nested() -> Result<()> {
+        let owners: Vec<Address> = (1..=3).map(Address::from_low_u64_be).collect();
+        let leaf = |owner: Address| leaf_for_address_and_weight(owner, 1);
+
+        let tree = config_tree_builder(&[
+            ConfigMember::Owner { address: owners[0], weight: 1 },
+            ConfigMember::Nested {
+                weight: 2,
+                threshold: 1,
+                members: vec![
+                    ConfigMember::Owner { address: owners[1], weight: 1 },
+                    ConfigMember::Owner { address: owners[2], weight: 1 },
+                ],
+            },
+        ])?;
+        let nested = leaf_for_nested(hash_keccak_256(leaf(owners[1])?, leaf(owners[2])?), 1, 2)?;
+        assert_eq!(
+            tree.calculate_image_hash_from_node([0; 32])?,
+            hash_keccak_256(leaf(owners[0])?, nested)
+        );
+
+        // The internal root of a single member is the member itself.
+        let tree = config_tree_builder(&[ConfigMember::Nested {
+            weight: 1,
+            threshold: 1,
+            members: vec![ConfigMember::Owner { address: owners[1], weight: 1 }],
+        }])?;
+        assert_eq!(
+            tree.calculate_image_hash_from_node([0; 32])?,
+            leaf_for_nested(leaf(owners[1])?, 1, 1)?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_signature_builder_nested() -> Result<()> {
+        let owners: Vec<Address> = (1..=3).map(Address::from_low_u64_be).collect();
+        let tree = config_tree_builder(&[
+            ConfigMember::Owner { address: owners[0], weight: 1 },
+            ConfigMember::Nested {
+                weight: 1,
+                threshold: 1,
+                members: vec![
+                    ConfigMember::Owner { address: owners[1], weight: 1 },
+                    ConfigMember::Owner { address: owners[2], weight: 1 },
+                ],
+            },
+        ])?;
+        let config = WalletConfig { tree, ..owner_config(&owners, 2)? };
+
+        // The nested config counts w/ its external weight once the internal threshold is met.
+        let signed_config = SignatureBuilder::new(config.clone())
+            .signature(ecdsa_signature_leaf(owners[0], &ECDSA_SIGNATURE)?)
+            .signature(ecdsa_signature_leaf(owners[2], &ECDSA_SIGNATURE)?)
+            .build_config()?;
+        assert_eq!(signed_config.weight, 2);
+
+        // The unsigned nested config is collapsed into a node.
+        let signed_config = SignatureBuilder::new(config.clone())
+            .signature(ecdsa_signature_leaf(owners[0], &ECDSA_SIGNATURE)?)
+            .build_config()?;
+        assert_eq!(signed_config.weight, 1);
+        assert_eq!(
+            signed_config.tree.calculate_image_hash_from_node([0; 32])?,
+            config.tree.calculate_image_hash_from_node([0; 32])?
+        );
+        assert!(matches!(
+            signed_config.tree.get_signers()[1].leaf,
+            SignatureLeaf::NodeSignature(_)
+        ));
+
+        // The nested config is encoded in front of its members w/ the size of their encoding.
+        let signature = SignatureBuilder::new(config)
+            .signature(ecdsa_signature_leaf(owners[0], &ECDSA_SIGNATURE)?)
+            .signature(ecdsa_signature_leaf(owners[2], &ECDSA_SIGNATURE)?)
+            .build()?;
+        let nested = &signature[1 + 2 + 4 + 68..];
+        assert_eq!(nested[..7], [0x6, 1, 0, 1, 0, 0, 22 + 68]);
+        assert_eq!(nested[7..9], [0x1, 1]);
+        assert_eq!(&nested[9..29], owners[1].as_bytes());
+        assert_eq!(nested[29..31], [0x0, 1]);
+        assert_eq!(nested.len(), 7 + 22 + 68);
+
+        Ok(())
+    }
 }
@@ -28,10 +28,10 @@
 
 #![allow(clippy::unwrap_used)]
 
-use crate::types::SignerNode;
+use crate::types::{SignatureLeaf, SignerNode};
 use ethers::{
     abi::{encode, Token},
-    types::{H256, U256},
+    types::{Address, H256, U256},
     utils::keccak256,
 };
 use eyre::{eyre, Result};
@@ -104,9 +104,7 @@ impl WalletConfig {
         }
 
         // Check if the total weight of the signers is greater than or equal to the threshold
-        let total_weight: u8 =
-            self.tree.get_signers().iter().map(|signer| signer.weight.unwrap_or(0)).sum();
-        total_weight >= self.threshold as u8
+        reachable_weight(&self.tree, &|_| true) >= self.threshold as u64
     }
 
     /// Encode the wallet config into bytes
@@ -202,6 +200,41 @@ impl WalletConfig {
     }
 }
 
+/// Get the total weight of the signers in the tree, where the nested configs only count w/ their
+/// external weight if the internal threshold is reachable.
+/// Only the owners accepted by `is_signer` count towards the weight.
+pub(crate) fn reachable_weight(node: &SignerNode, is_signer: &dyn Fn(&Address) -> bool) -> u64 {
+    let children_weight = node.left.as_ref().map_or(0, |left| reachable_weight(left, is_signer)) +
+        node.right.as_ref().map_or(0, |right| reachable_weight(right, is_signer));
+
+    match node.signer.as_ref() {
+        Some(signer) => match &signer.leaf {
+            SignatureLeaf::NestedSignature(leaf) => {
+                if children_weight >= leaf.internal_threshold as u64 {
+                    leaf.external_weight as u64
+                } else {
+                    0
+                }
+            }
+            leaf => match leaf_address(leaf) {
+                Some(address) if !is_signer(&address) => children_weight,
+                _ => children_weight + signer.weight.unwrap_or(0) as u64,
+            },
+        },
+        None => children_weight,
+    }
+}
+
+/// Get the address of the owner of the leaf, if the leaf is signed by an owner.
+pub(crate) fn leaf_address(leaf: &SignatureLeaf) -> Option<Address> {
+    match leaf {
+        SignatureLeaf::ECDSASignature(leaf) => Some(leaf.address),
+        SignatureLeaf::AddressSignature(leaf) => Some(leaf.address),
+        SignatureLeaf::DynamicSignature(leaf) => Some(leaf.address),
+        _ => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,156 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// Signatures of the counterfactual contracts w/ ERC-6492.
+// From: https://eips.ethereum.org/EIPS/eip-6492
+
+use crate::types::ERC1271_MAGICVALUE_BYTES32;
+use ethers::{
+    abi::{decode, encode, AbiEncode, ParamType, Token},
+    providers::Middleware,
+    types::{Address, H256},
+};
+use eyre::{eyre, Result};
+use lightdotso_contracts::{
+    erc1271::get_erc_1271_wallet, light_wallet_factory::CreateAccountCall, provider::get_provider,
+};
+use lightdotso_simulator::simulator::simulate_erc_6492_signature;
+use lightdotso_tracing::tracing::info;
+
+/// The suffix of the ERC-6492 wrapped signatures.
+pub const ERC6492_DETECTION_SUFFIX: [u8; 32] = [
+    0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92,
+    0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92, 0x64, 0x92,
+];
+
+/// Whether the signature is wrapped w/ ERC-6492.
+pub fn is_erc_6492_signature(signature: &[u8]) -> bool {
+    signature.len() > ERC6492_DETECTION_SUFFIX.len() &&
+        signature.ends_with(&ERC6492_DETECTION_SUFFIX)
+}
+
+/// Wrap the signature w/ the factory and the calldata that deploys the signer.
+pub fn encode_erc_6492_signature(
+    factory: Address,
+    factory_calldata: &[u8],
+    signature: &[u8],
+) -> Vec<u8> {
+    [
+        encode(&[
+            Token::Address(factory),
+            Token::Bytes(factory_calldata.to_vec()),
+            Token::Bytes(signature.to_vec()),
+        ]),
+        ERC6492_DETECTION_SUFFIX.to_vec(),
+    ]
+    .concat()
+}
+
+/// Unwrap the signature into the factory, the calldata that deploys the signer, and the original
+/// signature.
+pub fn decode_erc_6492_signature(signature: &[u8]) -> Result<(Address, Vec<u8>, Vec<u8>)> {
+    if !is_erc_6492_signature(signature) {
+        return Err(eyre!("Signature is not wrapped w/ ERC-6492"));
+    }
+
+    let tokens = decode(
+        &[ParamType::Address, ParamType::Bytes, ParamType::Bytes],
+        &signature[..signature.len() - ERC6492_DETECTION_SUFFIX.len()],
+    )?;
+
+    match tokens.as_slice() {
+        [Token::Address(factory), Token::Bytes(factory_calldata), Token::Bytes(signature)] => {
+            Ok((*factory, factory_calldata.clone(), signature.clone()))
+        }
+        _ => Err(eyre!("Invalid ERC-6492 signature")),
+    }
+}
+
+/// Get the calldata of the Light wallet factory that deploys the wallet w/ the initial image hash
+/// and the salt.
+pub fn light_wallet_factory_calldata(image_hash: H256, salt: H256) -> Vec<u8> {
+    CreateAccountCall { hash: image_hash.0, salt: salt.0 }.encode()
+}
+
+/// Wrap the signature of the undeployed Light wallet w/ the deployment from the factory, so that
+/// it can be verified w/ ERC-6492 before the wallet is deployed.
+pub fn wrap_light_wallet_signature(
+    factory: Address,
+    image_hash: H256,
+    salt: H256,
+    signature: &[u8],
+) -> Vec<u8> {
+    encode_erc_6492_signature(factory, &light_wallet_factory_calldata(image_hash, salt), signature)
+}
+
+/// Verify the ERC-6492 wrapped signature of the contract signer over the hash.
+/// The deployed signer is verified w/ EIP-1271 directly, and the undeployed signer is verified on
+/// top of the simulated deployment from the factory.
+pub async fn verify_erc_6492_signature(
+    chain_id: u64,
+    address: Address,
+    hash: &[u8; 32],
+    signature: &[u8],
+) -> Result<bool> {
+    let (factory, factory_calldata, signature) = decode_erc_6492_signature(signature)?;
+
+    // Get the code of the signer
+    let provider = get_provider(chain_id).await?;
+    let code = provider.get_code(address, None).await?;
+
+    // If the signer is already deployed, the original signature is verified w/ EIP-1271
+    if !code.is_empty() {
+        let wallet = get_erc_1271_wallet(chain_id, address).await?;
+        let res = wallet.is_valid_signature(*hash, signature.into()).await?;
+        return Ok(res == ERC1271_MAGICVALUE_BYTES32);
+    }
+
+    info!("Simulating the deployment of {:?} w/ the factory {:?}", address, factory);
+    simulate_erc_6492_signature(
+        chain_id,
+        ethers_main::types::Address::from(address.0),
+        ethers_main::types::H256::from(*hash),
+        ethers_main::types::Address::from(factory.0),
+        factory_calldata.into(),
+        signature.into(),
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_erc_6492_signature() -> Result<()> {
+        let factory = Address::from_low_u64_be(1);
+        let image_hash = H256::repeat_byte(2);
+        let salt = H256::repeat_byte(3);
+        let signature = vec![4u8; 70];
+
+        let wrapped = wrap_light_wallet_signature(factory, image_hash, salt, &signature);
+        assert!(is_erc_6492_signature(&wrapped));
+        assert!(!is_erc_6492_signature(&signature));
+
+        let (decoded_factory, factory_calldata, decoded_signature) =
+            decode_erc_6492_signature(&wrapped)?;
+        assert_eq!(decoded_factory, factory);
+        assert_eq!(factory_calldata, light_wallet_factory_calldata(image_hash, salt));
+        // The selector of `createAccount(bytes32,bytes32)`
+        assert_eq!(factory_calldata[..4], [0x18, 0x38, 0x15, 0xc8]);
+        assert_eq!(decoded_signature, signature);
+
+        Ok(())
+    }
+}
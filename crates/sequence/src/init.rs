@@ -12,17 +12,53 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use eyre::Result;
+use crate::{
+    hash::{get_address_with_factory, get_address_with_factory_from_chain},
+    version::WalletVersion,
+};
+use ethers::types::Address;
+use eyre::{eyre, Result};
+
+/// Get the factory, the image hash and the salt from the init code of the user operation, which
+/// is the factory followed by the calldata of `createAccount(bytes32,bytes32)`.
+pub fn get_factory_image_hash_salt_from_init_code(
+    init_code: &[u8],
+) -> Result<(Address, [u8; 32], [u8; 32])> {
+    if init_code.len() < 88 {
+        return Err(eyre!("Invalid init code length: {}", init_code.len()));
+    }
+
+    let factory = Address::from_slice(&init_code[0..20]);
 
-pub fn get_image_hash_salt_from_init_code(init_code: Vec<u8>) -> Result<([u8; 32], [u8; 32])> {
     let mut image_hash = [0; 32];
     image_hash.copy_from_slice(&init_code[24..56]);
 
     let mut salt = [0; 32];
     salt.copy_from_slice(&init_code[56..88]);
+    Ok((factory, image_hash, salt))
+}
+
+pub fn get_image_hash_salt_from_init_code(init_code: Vec<u8>) -> Result<([u8; 32], [u8; 32])> {
+    let (_, image_hash, salt) = get_factory_image_hash_salt_from_init_code(&init_code)?;
     Ok((image_hash, salt))
 }
 
+/// Get the counterfactual address and the version of the wallet from the init code.
+pub fn get_address_from_init_code(init_code: &[u8]) -> Result<(Address, WalletVersion)> {
+    let (factory, image_hash, salt) = get_factory_image_hash_salt_from_init_code(init_code)?;
+    get_address_with_factory(factory, image_hash.into(), salt.into())
+}
+
+/// Get the counterfactual address and the version of the wallet from the init code, asking the
+/// factory on-chain if the implementation of the version is not tracked.
+pub async fn get_address_from_init_code_from_chain(
+    chain_id: u64,
+    init_code: &[u8],
+) -> Result<(Address, WalletVersion)> {
+    let (factory, image_hash, salt) = get_factory_image_hash_salt_from_init_code(init_code)?;
+    get_address_with_factory_from_chain(chain_id, factory, image_hash.into(), salt.into()).await
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -44,4 +80,19 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_get_address_from_init_code() -> Result<()> {
+        let init_code = "0x0000000000756d3e6464f5efe7e413a0af1c7474183815c8b7f285c774a1c925209bebaab24662b22e7cf32e2f7a412bfcb1bf52294b9ed60000000000000000000000000000000000000000000000000000000000000001".hex_to_bytes()?;
+
+        let (address, version) = get_address_from_init_code(&init_code)?;
+
+        let expected: Address = "0x10DbbE70128929723c1b982e53c51653232e4Ff2".parse()?;
+        assert_eq!(address, expected);
+        assert_eq!(version, WalletVersion::V010);
+
+        assert!(get_address_from_init_code(&init_code[..87]).is_err());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,221 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::{
+    module::SigModule,
+    recover::set_image_hash,
+    types::{Signature, SignatureLeafType},
+    utils::read_uint24,
+};
+use async_recursion::async_recursion;
+use ethers::types::{Address, H256};
+use eyre::{eyre, Result};
+use serde::{Deserialize, Serialize};
+
+/// The report of a decoded signature leaf.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SignatureLeafReport {
+    /// The depth of the leaf, incremented inside the branches and the nested configs
+    pub depth: u32,
+    /// The type of the leaf
+    pub leaf_type: SignatureLeafType,
+    /// The address of the signer, recovered for the signed leaves
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signer: Option<Address>,
+    /// The weight of the signer, or the external weight of the nested config
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub weight: Option<u8>,
+    /// The internal threshold of the nested config
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<u16>,
+    /// The hash of the node, the subdigest, or the internal root of the nested config
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hash: Option<H256>,
+    /// The weight the leaf contributes to the signature
+    pub contributed_weight: u64,
+    /// The cumulative weight of the signature after the leaf, within its branch or nested config
+    pub cumulative_weight: u64,
+}
+
+impl SignatureLeafReport {
+    /// Initializes an empty report of the leaf type
+    pub fn new(leaf_type: SignatureLeafType) -> Self {
+        Self {
+            depth: 0,
+            leaf_type,
+            signer: None,
+            weight: None,
+            threshold: None,
+            hash: None,
+            contributed_weight: 0,
+            cumulative_weight: 0,
+        }
+    }
+}
+
+/// The report of a decoded signature, w/ the chained signatures of the older configs.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SignatureReport {
+    /// The type of the signature
+    pub signature_type: u8,
+    /// The subdigest signed by the signature
+    pub subdigest: H256,
+    /// The threshold of the config
+    pub threshold: u16,
+    /// The checkpoint of the config
+    pub checkpoint: u32,
+    /// The total weight of the signature
+    pub weight: u64,
+    /// Whether the weight of the signature reaches the threshold
+    pub is_threshold_met: bool,
+    /// The computed image hash of the config
+    pub image_hash: H256,
+    /// The decoded leaves in order
+    pub leaves: Vec<SignatureLeafReport>,
+    /// The reports of the chained signatures, each signing the image hash of the previous config
+    pub chained: Vec<SignatureReport>,
+    /// The problems found while decoding the signature
+    pub warnings: Vec<String>,
+}
+
+impl SignatureReport {
+    /// Flag the mismatch of the computed image hash against the stored config of the checkpoint.
+    pub fn check_stored_image_hash(&mut self, stored_image_hash: Option<H256>) {
+        match stored_image_hash {
+            Some(stored_image_hash) if stored_image_hash != self.image_hash => {
+                self.warnings.push(format!(
+                    "Image hash {:?} does not match the stored image hash {:?} of checkpoint {}",
+                    self.image_hash, stored_image_hash, self.checkpoint
+                ))
+            }
+            Some(_) => {}
+            None => self
+                .warnings
+                .push(format!("No stored configuration for checkpoint {}", self.checkpoint)),
+        }
+    }
+}
+
+/// Decode the signature of the wallet over the digest into the report, instead of failing on the
+/// invalid weights and checkpoints like `recover_signature`.
+#[async_recursion]
+pub async fn inspect_signature(
+    address: Address,
+    chain_id: u64,
+    digest: [u8; 32],
+    sig: Signature,
+) -> Result<SignatureReport> {
+    if sig.is_empty() {
+        return Err(eyre!("Invalid signature length"));
+    }
+
+    let signature_type = sig.as_slice()[0];
+
+    // Chained signature
+    if signature_type == 0x03 {
+        return inspect_chained(address, chain_id, digest, sig).await;
+    }
+
+    // Legacy signature includes the first byte in the threshold
+    let sig = match signature_type {
+        0x00 => sig,
+        0x01 | 0x02 => sig.as_slice()[1..].to_vec().into(),
+        _ => return Err(eyre!("Invalid signature type")),
+    };
+
+    let mut base_sig_module = SigModule::new(address, chain_id, digest, None);
+    base_sig_module.set_subdigest()?;
+    base_sig_module.set_signature(sig);
+    let config = base_sig_module.recover(signature_type).await?;
+
+    // The weight of the config is the cumulative weight after the last top-level leaf
+    let weight = base_sig_module
+        .leaves
+        .iter()
+        .rev()
+        .find(|leaf| leaf.depth == 0)
+        .map_or(0, |leaf| leaf.cumulative_weight);
+    let is_threshold_met = weight >= config.threshold as u64;
+
+    let mut warnings = vec![];
+    if !is_threshold_met {
+        warnings.push(format!("Weight {} is less than threshold {}", weight, config.threshold));
+    }
+
+    Ok(SignatureReport {
+        signature_type,
+        subdigest: base_sig_module.subdigest.into(),
+        threshold: config.threshold,
+        checkpoint: config.checkpoint,
+        weight,
+        is_threshold_met,
+        image_hash: config.image_hash,
+        leaves: base_sig_module.leaves,
+        chained: vec![],
+        warnings,
+    })
+}
+
+async fn inspect_chained(
+    address: Address,
+    chain_id: u64,
+    digest: [u8; 32],
+    sig: Signature,
+) -> Result<SignatureReport> {
+    let (sig_size, rindex) = read_uint24(sig.as_slice(), 1)?;
+    let nrindex = rindex + (sig_size as usize);
+
+    let mut report = inspect_signature(
+        address,
+        chain_id,
+        digest,
+        sig.as_slice()[rindex..nrindex].to_vec().into(),
+    )
+    .await?;
+
+    let mut rindex = nrindex;
+    let mut image_hash = report.image_hash;
+    let mut checkpoint = report.checkpoint;
+
+    while rindex < sig.len() {
+        let (sig_size, sig_rindex) = read_uint24(sig.as_slice(), rindex)?;
+        let nrindex = sig_rindex + (sig_size as usize);
+
+        // Each of the chained signatures signs the image hash of the previous config
+        let mut chained = inspect_signature(
+            address,
+            chain_id,
+            set_image_hash(image_hash.as_bytes().to_vec())?,
+            sig.as_slice()[sig_rindex..nrindex].to_vec().into(),
+        )
+        .await?;
+
+        if chained.checkpoint >= checkpoint {
+            chained.warnings.push(format!(
+                "Checkpoint {} is not less than the previous checkpoint {}",
+                chained.checkpoint, checkpoint
+            ));
+        }
+
+        image_hash = chained.image_hash;
+        checkpoint = chained.checkpoint;
+        rindex = nrindex;
+
+        report.chained.push(chained);
+    }
+
+    report.signature_type = 0x03;
+
+    Ok(report)
+}
@@ -15,13 +15,18 @@
 pub mod builder;
 pub mod config;
 pub mod create;
+pub mod erc6492;
 pub mod hash;
 pub mod init;
+pub mod inspect;
 pub mod io;
 pub mod merkle;
 pub mod module;
 pub mod node;
 pub mod recover;
+pub mod recovery;
 pub mod signature;
+pub mod transition;
 pub mod types;
 pub mod utils;
+pub mod version;
@@ -12,9 +12,110 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use ethers::abi::{encode, encode_packed, Token};
-use eyre::Result;
+// Merkle tree of the user operation hashes across the chains, signed once w/ the merkle root.
 
+use ethers::{
+    abi::{encode, encode_packed, Token},
+    utils::keccak256,
+};
+use eyre::{eyre, Result};
+use rs_merkle::{Hasher as MerkleHasher, MerkleTree};
+
+#[derive(Clone)]
+pub struct KeccakAlgorithm {}
+
+// Custom implementation of the MerkleHasher trait for the KeccakAlgorithm
+// Code from: https://github.com/arslanpixpel/dex-bridge/blob/a2bf3dc2a1ad2c5baea1db1876e8da56d32aafcd/relayer/src/merkle.rs#L87-L118
+// License: MIT
+
+impl MerkleHasher for KeccakAlgorithm {
+    type Hash = [u8; 32];
+
+    fn hash(data: &[u8]) -> [u8; 32] {
+        keccak256(data)
+    }
+
+    // The OpenZeppelin contract computes the hash of inner nodes by ordering them
+    // lexicographically first. So we must override the default implementation.
+    fn concat_and_hash(left: &Self::Hash, right: Option<&Self::Hash>) -> Self::Hash {
+        match right {
+            Some(right_node) => hash_sorted_pair(left, right_node),
+            None => *left,
+        }
+    }
+
+    fn hash_size() -> usize {
+        std::mem::size_of::<Self::Hash>()
+    }
+}
+
+/// Hash the pair of the nodes in the lexicographical order, as the OpenZeppelin `MerkleProof`.
+fn hash_sorted_pair(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    if a <= b {
+        keccak256([a.as_slice(), b.as_slice()].concat())
+    } else {
+        keccak256([b.as_slice(), a.as_slice()].concat())
+    }
+}
+
+/// The merkle tree of the user operation hashes.
+/// The leaves are the sorted hashes, and the lone node of an odd level is promoted w/o hashing, so
+/// that the root is independent of the order of the user operations.
+#[derive(Clone)]
+pub struct UserOperationMerkleTree {
+    /// The sorted leaves of the tree
+    leaves: Vec<[u8; 32]>,
+    /// The internal tree of the leaves
+    tree: MerkleTree<KeccakAlgorithm>,
+}
+
+impl UserOperationMerkleTree {
+    /// Build the tree from the user operation hashes in any order.
+    pub fn new(hashes: &[[u8; 32]]) -> Result<Self> {
+        if hashes.is_empty() {
+            return Err(eyre!("No user operation hashes to build the merkle tree"));
+        }
+
+        let mut leaves = hashes.to_vec();
+        leaves.sort();
+
+        Ok(Self { tree: MerkleTree::<KeccakAlgorithm>::from_leaves(&leaves), leaves })
+    }
+
+    /// Get the sorted leaves of the tree.
+    pub fn leaves(&self) -> &[[u8; 32]] {
+        &self.leaves
+    }
+
+    /// Get the merkle root of the tree.
+    pub fn root(&self) -> Result<[u8; 32]> {
+        self.tree.root().ok_or(eyre!("Merkle tree has no root"))
+    }
+
+    /// Get the merkle proof of the user operation hash, from the leaf to the root.
+    pub fn proof(&self, hash: &[u8; 32]) -> Result<Vec<[u8; 32]>> {
+        let index = self
+            .leaves
+            .iter()
+            .position(|leaf| leaf == hash)
+            .ok_or(eyre!("User operation hash is not in the merkle tree"))?;
+
+        Ok(self.tree.proof(&[index]).proof_hashes().to_vec())
+    }
+
+    /// Render the signature of the user operation from the signature over the merkle root.
+    pub fn render_signature(&self, hash: &[u8; 32], signature: Vec<u8>) -> Result<Vec<u8>> {
+        render_merkle(self.root()?, self.proof(hash)?, signature)
+    }
+}
+
+/// Verify the merkle proof of the leaf against the root, as the OpenZeppelin `MerkleProof.verify`.
+pub fn verify_merkle_proof(root: [u8; 32], leaf: [u8; 32], proof: &[[u8; 32]]) -> bool {
+    proof.iter().fold(leaf, |node, sibling| hash_sorted_pair(&node, sibling)) == root
+}
+
+/// Render the signature w/ the merkle indicator, the merkle root, the proof of the user operation
+/// and the signature over the merkle root.
 pub fn render_merkle(
     merkle_root: [u8; 32],
     merkle_proofs: Vec<[u8; 32]>,
@@ -63,4 +164,86 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_user_operation_merkle_tree() -> Result<()> {
+        let hashes = [[3u8; 32], [1u8; 32], [2u8; 32], [5u8; 32], [4u8; 32]];
+
+        let tree = UserOperationMerkleTree::new(&hashes)?;
+        let root = tree.root()?;
+
+        // The root is independent of the order of the hashes.
+        let mut reversed = hashes;
+        reversed.reverse();
+        assert_eq!(UserOperationMerkleTree::new(&reversed)?.root()?, root);
+
+        // The proofs of all of the leaves are verified against the root.
+        for hash in hashes.iter() {
+            assert!(verify_merkle_proof(root, *hash, &tree.proof(hash)?));
+        }
+
+        // The proofs are not verified for the other leaves.
+        assert!(!verify_merkle_proof(root, [6u8; 32], &tree.proof(&hashes[0])?));
+        assert!(tree.proof(&[6u8; 32]).is_err());
+        assert!(UserOperationMerkleTree::new(&[]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_user_operation_merkle_tree_simple() -> Result<()> {
+        let hashes = [
+            parse_hex_to_bytes32(
+                "0x0000000000000000000000000000000000000000000000000000000000000001",
+            )?,
+            parse_hex_to_bytes32(
+                "0x0000000000000000000000000000000000000000000000000000000000000002",
+            )?,
+        ];
+
+        let tree = UserOperationMerkleTree::new(&hashes)?;
+        assert_eq!(
+            tree.root()?,
+            parse_hex_to_bytes32(
+                "0xe90b7bceb6e7df5418fb78d8ee546e97c83a08bbccc01a0644d599ccd2a7c2e0"
+            )?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_user_operation_merkle_tree_solidity() -> Result<()> {
+        // From: contracts/test/unit/signature/Signature.t.sol `test_merkle_verify` and
+        // `test_merkle_verify_odd_leaf`
+        let hashes = [
+            parse_hex_to_bytes32(
+                "0x0000000000000000000000000000000000000000000000000000000000000003",
+            )?,
+            parse_hex_to_bytes32(
+                "0x0000000000000000000000000000000000000000000000000000000000000001",
+            )?,
+            parse_hex_to_bytes32(
+                "0x0000000000000000000000000000000000000000000000000000000000000002",
+            )?,
+        ];
+
+        let tree = UserOperationMerkleTree::new(&hashes)?;
+        assert_eq!(
+            tree.root()?,
+            parse_hex_to_bytes32(
+                "0x9b0225f2c6f59eeaf8302811ea290e95258763189b82dc033158e99a6ef45a87"
+            )?
+        );
+        assert_eq!(tree.proof(&hashes[1])?, vec![hashes[2], hashes[0]]);
+        assert!(verify_merkle_proof(tree.root()?, hashes[1], &tree.proof(&hashes[1])?));
+        assert_eq!(
+            tree.proof(&hashes[0])?,
+            vec![parse_hex_to_bytes32(
+                "0xe90b7bceb6e7df5418fb78d8ee546e97c83a08bbccc01a0644d599ccd2a7c2e0"
+            )?]
+        );
+
+        Ok(())
+    }
 }
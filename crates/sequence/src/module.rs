@@ -28,11 +28,12 @@
 
 use crate::{
     config::WalletConfig,
+    inspect::SignatureLeafReport,
     node::{leaf_for_address_and_weight, leaf_for_hardcoded_subdigest, leaf_for_nested},
     signature::{recover_dynamic_signature, recover_ecdsa_signature},
     types::{
-        AddressSignatureLeaf, BranchLeaf, NestedLeaf, NodeLeaf, Signature, SignatureLeaf, Signer,
-        SignerNode, SubdigestLeaf,
+        AddressSignatureLeaf, BranchLeaf, NestedLeaf, NodeLeaf, Signature, SignatureLeaf,
+        SignatureLeafType, Signer, SignerNode, SubdigestLeaf,
     },
     utils::{
         hash_keccak_256, left_pad_u16_to_bytes32, left_pad_u32_to_bytes32, read_bytes32,
@@ -66,6 +67,8 @@ pub struct SigModule {
     weight: u64,
     /// The internal tree of the module
     pub tree: SignerNode,
+    /// The reports of the decoded leaves in order
+    pub leaves: Vec<SignatureLeafReport>,
 }
 
 impl SigModule {
@@ -85,6 +88,7 @@ impl SigModule {
             weight: 0,
             chain_id,
             tree: tree.unwrap_or_else(|| SignerNode { signer: None, left: None, right: None }),
+            leaves: vec![],
         }
     }
 
@@ -149,6 +153,18 @@ impl SigModule {
         self.root = if !self.root.is_zero() { hash_keccak_256(self.root, node) } else { node };
     }
 
+    /// Records the report of the decoded leaf w/ the cumulative weight so far
+    fn push_leaf_report(&mut self, report: SignatureLeafReport) {
+        self.leaves.push(SignatureLeafReport { cumulative_weight: self.weight, ..report });
+    }
+
+    /// Records the reports of the leaves decoded by the inner module one level deeper
+    fn extend_leaf_reports(&mut self, leaves: Vec<SignatureLeafReport>) {
+        self.leaves.extend(
+            leaves.into_iter().map(|leaf| SignatureLeafReport { depth: leaf.depth + 1, ..leaf }),
+        );
+    }
+
     /// Injects a signer node into the tree
     fn inject_signer_node(
         &mut self,
@@ -213,6 +229,13 @@ impl SigModule {
         let node = leaf_for_address_and_weight(signature_leaf.address, addr_weight)?;
         self.return_valid_root(node);
 
+        self.push_leaf_report(SignatureLeafReport {
+            signer: Some(signature_leaf.address),
+            weight: Some(addr_weight),
+            contributed_weight: addr_weight as u64,
+            ..SignatureLeafReport::new(SignatureLeafType::ECDSASignature)
+        });
+
         Ok(())
     }
 
@@ -231,6 +254,12 @@ impl SigModule {
         let node = leaf_for_address_and_weight(addr, addr_weight)?;
         self.return_valid_root(node);
 
+        self.push_leaf_report(SignatureLeafReport {
+            signer: Some(addr),
+            weight: Some(addr_weight),
+            ..SignatureLeafReport::new(SignatureLeafType::Address)
+        });
+
         Ok(())
     }
 
@@ -262,6 +291,13 @@ impl SigModule {
         let node = leaf_for_address_and_weight(addr, addr_weight)?;
         self.return_valid_root(node);
 
+        self.push_leaf_report(SignatureLeafReport {
+            signer: Some(addr),
+            weight: Some(addr_weight),
+            contributed_weight: addr_weight as u64,
+            ..SignatureLeafReport::new(SignatureLeafType::DynamicSignature)
+        });
+
         Ok(())
     }
 
@@ -271,6 +307,11 @@ impl SigModule {
         self.return_valid_root(node);
         self.rindex = rindex;
 
+        self.push_leaf_report(SignatureLeafReport {
+            hash: Some(node.into()),
+            ..SignatureLeafReport::new(SignatureLeafType::Node)
+        });
+
         let signer = Signer {
             weight: None,
             leaf: SignatureLeaf::NodeSignature(NodeLeaf { hash: node.into() }),
@@ -296,6 +337,13 @@ impl SigModule {
         self.root = hash_keccak_256(self.root, node);
         self.rindex = nrindex;
 
+        self.push_leaf_report(SignatureLeafReport {
+            hash: Some(node.into()),
+            contributed_weight: nweight as u64,
+            ..SignatureLeafReport::new(SignatureLeafType::Branch)
+        });
+        self.extend_leaf_reports(std::mem::take(&mut base_sig_module.leaves));
+
         let signer_node = base_sig_module.tree;
 
         let branch_signer = Signer {
@@ -312,10 +360,17 @@ impl SigModule {
     fn decode_digest_signature(&mut self) -> Result<()> {
         let (hardcoded, rindex) = read_bytes32(self.sig.as_slice(), self.rindex)?;
         self.rindex = rindex;
-        if hardcoded == self.subdigest {
+        let is_subdigest = hardcoded == self.subdigest;
+        if is_subdigest {
             self.weight = u64::MAX;
         }
 
+        self.push_leaf_report(SignatureLeafReport {
+            hash: Some(hardcoded.into()),
+            contributed_weight: if is_subdigest { u64::MAX } else { 0 },
+            ..SignatureLeafReport::new(SignatureLeafType::Subdigest)
+        });
+
         let node = leaf_for_hardcoded_subdigest(hardcoded)?;
         self.return_valid_root(node);
 
@@ -344,13 +399,23 @@ impl SigModule {
         let (internal_weight, internal_root) = base_sig_module.recover_branch().await?;
         self.rindex = nrindex;
 
-        if (internal_weight as u16) >= internal_threshold {
+        let is_threshold_met = (internal_weight as u16) >= internal_threshold;
+        if is_threshold_met {
             self.weight += external_weight as u64;
         }
 
         let node = leaf_for_nested(internal_root, internal_threshold, external_weight)?;
         self.return_valid_root(node);
 
+        self.push_leaf_report(SignatureLeafReport {
+            weight: Some(external_weight),
+            threshold: Some(internal_threshold),
+            hash: Some(internal_root.into()),
+            contributed_weight: if is_threshold_met { external_weight as u64 } else { 0 },
+            ..SignatureLeafReport::new(SignatureLeafType::Nested)
+        });
+        self.extend_leaf_reports(std::mem::take(&mut base_sig_module.leaves));
+
         let signer_node = base_sig_module.tree;
         let nested_signer = Signer {
             weight: Some(external_weight),
@@ -102,7 +102,11 @@ impl SignerNode {
                     leaf_for_hardcoded_subdigest(leaf.hash.into())?
                 }
                 SignatureLeaf::NestedSignature(ref leaf) => {
-                    let node_hash = self.get_node_hash(subdigest)?;
+                    // The internal root of a single member is the member itself
+                    let node_hash = match (&self.left, &self.right) {
+                        (Some(left), None) => left.calculate_image_hash_from_node(subdigest)?,
+                        _ => self.get_node_hash(subdigest)?,
+                    };
                     leaf_for_nested(node_hash, leaf.internal_threshold, leaf.external_weight)?
                 }
                 SignatureLeaf::BranchSignature(_) => [0; 32],
@@ -206,7 +206,7 @@ async fn recover_chained(
     }
 }
 
-fn set_image_hash(sig_hash: Vec<u8>) -> Result<[u8; 32]> {
+pub(crate) fn set_image_hash(sig_hash: Vec<u8>) -> Result<[u8; 32]> {
     Ok(keccak256(encode_packed(&[
         Token::FixedBytes(keccak256("SetImageHash(bytes32 imageHash)").to_vec()),
         Token::FixedBytes(sig_hash),
@@ -0,0 +1,146 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// The social recovery of the wallet by the guardians.
+// The guardians are a nested config in the wallet config, w/ the external weight of the wallet
+// threshold so that the quorum of the guardians can sign the update of the image hash on their
+// own, for the owners who lost their keys. The timelock and the veto of the owners are the
+// safeguard of the recovery, by holding the signatures of the guardians until the delay expires.
+
+use crate::{
+    builder::{ConfigMember, SignatureBuilder},
+    config::{leaf_address, WalletConfig},
+    types::{SignatureLeaf, WalletSignatureType},
+    utils::{hash_image_bytes32, render_subdigest},
+};
+use ethers::types::{Address, H256};
+use eyre::{eyre, Result};
+
+/// Get the nested config member of the guardians, each w/ the weight of 1 and the internal
+/// threshold of the guardians required to recover the wallet.
+/// The external weight is the threshold of the wallet config, so that the quorum of the guardians
+/// reaches it on its own.
+pub fn guardian_member(
+    guardians: &[Address],
+    threshold: u16,
+    wallet_threshold: u16,
+) -> Result<ConfigMember> {
+    let weight = u8::try_from(wallet_threshold)
+        .ok()
+        .filter(|weight| *weight > 0)
+        .ok_or_else(|| eyre!("Invalid wallet threshold for the guardians: {}", wallet_threshold))?;
+    if guardians.is_empty() {
+        return Err(eyre!("Empty guardians"));
+    }
+    if threshold == 0 || threshold as usize > guardians.len() {
+        return Err(eyre!(
+            "Invalid guardian threshold: {} for {} guardians",
+            threshold,
+            guardians.len()
+        ));
+    }
+
+    Ok(ConfigMember::Nested {
+        weight,
+        threshold,
+        members: guardians
+            .iter()
+            .map(|address| ConfigMember::Owner { address: *address, weight: 1 })
+            .collect(),
+    })
+}
+
+/// Get the subdigest the guardians sign to recover the wallet to the image hash, which is the
+/// chain agnostic update of the image hash.
+pub fn recovery_subdigest(address: Address, image_hash: H256) -> Result<[u8; 32]> {
+    render_subdigest(0, address, hash_image_bytes32(&image_hash.0)?)
+}
+
+/// Build the recovery signature of the current wallet config, w/ the quorum of the guardians
+/// signing the update of the image hash, optionally w/ the owners co-signing.
+/// Fails if the quorum of the guardians didn't sign, or if the signers don't reach the threshold.
+pub fn recovery_signature_builder(
+    config: WalletConfig,
+    guardians: &[Address],
+    signatures: Vec<SignatureLeaf>,
+) -> Result<SignatureBuilder> {
+    let mut guardian_signatures = vec![];
+    for signature in signatures.iter() {
+        let address = leaf_address(signature)
+            .ok_or_else(|| eyre!("Unsupported signature leaf: {:?}", signature))?;
+        if guardians.contains(&address) {
+            guardian_signatures.push(signature.clone());
+        }
+    }
+
+    // The quorum of the guardians has to approve the recovery, otherwise it is an update of the
+    // owners which doesn't need the recovery.
+    if guardian_signatures.is_empty() {
+        return Err(eyre!("The guardians didn't sign the recovery"));
+    }
+    let guardian_weight = SignatureBuilder::new(config.clone())
+        .signatures(guardian_signatures)
+        .build_config()?
+        .weight;
+    if guardian_weight == 0 {
+        return Err(eyre!("The guardians didn't reach their threshold to sign the recovery"));
+    }
+
+    let builder = SignatureBuilder::new(config.clone())
+        .signatures(signatures)
+        .signature_type(WalletSignatureType::NoChainId);
+
+    let weight = builder.clone().build_config()?.weight;
+    if weight < config.threshold.into() {
+        return Err(eyre!(
+            "Less than threshold: the signers of the recovery signed the weight {} of the \
+             threshold {}",
+            weight,
+            config.threshold
+        ));
+    }
+
+    Ok(builder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guardian_member() -> Result<()> {
+        let guardians = [Address::from_low_u64_be(1), Address::from_low_u64_be(2)];
+
+        assert_eq!(
+            guardian_member(&guardians, 2, 2)?,
+            ConfigMember::Nested {
+                weight: 2,
+                threshold: 2,
+                members: vec![
+                    ConfigMember::Owner { address: guardians[0], weight: 1 },
+                    ConfigMember::Owner { address: guardians[1], weight: 1 },
+                ],
+            }
+        );
+        assert!(guardian_member(&guardians, 0, 2).is_err());
+        assert!(guardian_member(&guardians, 3, 2).is_err());
+        assert!(guardian_member(&[], 1, 2).is_err());
+
+        // The weight of the guardians is the wallet threshold.
+        assert!(guardian_member(&guardians, 2, 0).is_err());
+        assert!(guardian_member(&guardians, 2, 256).is_err());
+
+        Ok(())
+    }
+}
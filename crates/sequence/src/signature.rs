@@ -28,9 +28,12 @@
 
 #![allow(clippy::unnecessary_fallible_conversions)]
 
-use crate::types::{
-    DynamicSignatureLeaf, DynamicSignatureType, ECDSASignatureLeaf, ECDSASignatureType, Signature,
-    ECDSA_SIGNATURE_LENGTH, ERC1271_MAGICVALUE_BYTES32,
+use crate::{
+    erc6492::{is_erc_6492_signature, verify_erc_6492_signature},
+    types::{
+        DynamicSignatureLeaf, DynamicSignatureType, ECDSASignatureLeaf, ECDSASignatureType,
+        Signature, ECDSA_SIGNATURE_LENGTH, ERC1271_MAGICVALUE_BYTES32,
+    },
 };
 use ethers::{
     types::{Address, RecoveryMessage, Signature as EthersSignature, H256},
@@ -115,17 +118,10 @@ pub async fn recover_dynamic_signature(
             signature_leaf.address
         }
         DynamicSignatureType::DynamicSignatureTypeEIP1271 => {
-            // The length is the remaining length of the slice
-            let signature = Signature(slice[..slice.len() - 1].to_vec());
-            // Call the contract on-chain to verify the signature
-            let wallet = get_erc_1271_wallet(chain_id, address).await?;
-            let res = wallet
-                .is_valid_signature(
-                    subdigest.to_vec().into(),
-                    signature.clone().as_slice().to_vec().into(),
-                )
-                .await?;
-            if res == ERC1271_MAGICVALUE_BYTES32 {
+            // Call the contract on-chain to verify the signature w/o the signature type
+            if verify_erc_1271_signature(chain_id, address, subdigest, &slice[..slice.len() - 1])
+                .await?
+            {
                 address
             } else {
                 Address::zero()
@@ -150,6 +146,43 @@ pub async fn recover_dynamic_signature(
     })
 }
 
+/// Verify the EIP-1271 signature of the contract signer over the subdigest on-chain.
+/// The ERC-6492 wrapped signature of the undeployed signer is verified w/ the simulated deployment.
+pub async fn verify_erc_1271_signature(
+    chain_id: u64,
+    address: Address,
+    subdigest: &[u8; 32],
+    signature: &[u8],
+) -> Result<bool> {
+    if is_erc_6492_signature(signature) {
+        return verify_erc_6492_signature(chain_id, address, subdigest, signature).await;
+    }
+
+    let wallet = get_erc_1271_wallet(chain_id, address).await?;
+    let res = wallet.is_valid_signature(*subdigest, signature.to_vec().into()).await?;
+
+    Ok(res == ERC1271_MAGICVALUE_BYTES32)
+}
+
+/// Verify the signature of the owner over the subdigest, w/ EIP-1271 for the contract owners and
+/// w/ ECDSA recovery for the rest.
+pub async fn verify_owner_signature(
+    chain_id: u64,
+    address: Address,
+    is_contract: bool,
+    subdigest: &[u8; 32],
+    signature: &[u8],
+) -> Result<bool> {
+    if is_contract {
+        return verify_erc_1271_signature(chain_id, address, subdigest, signature).await;
+    }
+
+    let recovered_sig = recover_ecdsa_signature(signature, subdigest, 0)?;
+    info!(?recovered_sig);
+
+    Ok(recovered_sig.address == address)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
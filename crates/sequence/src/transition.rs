@@ -0,0 +1,332 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+// The transition of the wallet config to the next one, w/ the diff of the owners and the lockout
+// check of the next config.
+
+use crate::{
+    config::{leaf_address, reachable_weight, WalletConfig},
+    types::{SignatureLeaf, SignerNode},
+};
+use ethers::{
+    abi::AbiEncode,
+    types::{Address, H256},
+};
+use eyre::{eyre, Result};
+use lightdotso_contracts::light_wallet::UpdateImageHashCall;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+/// The owner of the wallet config w/ the weight.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnerWeight {
+    /// The address of the owner
+    pub address: Address,
+    /// The weight of the owner
+    pub weight: u8,
+}
+
+/// The owner of both of the wallet configs w/ the changed weight.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OwnerWeightChange {
+    /// The address of the owner
+    pub address: Address,
+    /// The weight of the owner in the current config
+    pub previous_weight: u8,
+    /// The weight of the owner in the next config
+    pub weight: u8,
+}
+
+/// The transition from the current wallet config to the next one.
+/// The owners in the nested configs are compared w/ their internal weights.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ConfigTransition {
+    /// The owners only in the next config
+    pub added_owners: Vec<OwnerWeight>,
+    /// The owners only in the current config
+    pub removed_owners: Vec<OwnerWeight>,
+    /// The owners in both of the configs w/ the changed weights
+    pub changed_owners: Vec<OwnerWeightChange>,
+    /// The pre-approved subdigests only in the next config
+    pub added_subdigests: Vec<H256>,
+    /// The pre-approved subdigests only in the current config
+    pub removed_subdigests: Vec<H256>,
+    /// The threshold of the current config
+    pub previous_threshold: u16,
+    /// The threshold of the next config
+    pub threshold: u16,
+    /// The checkpoint of the current config
+    pub previous_checkpoint: u32,
+    /// The checkpoint of the next config
+    pub checkpoint: u32,
+    /// The image hash of the next config
+    pub image_hash: H256,
+    /// The weight of the next config reachable by the owners of the current config
+    pub current_signers_weight: u64,
+    /// Whether the owners of the current config can still reach the threshold of the next config
+    pub is_reachable_by_current_signers: bool,
+}
+
+/// Plan the transition from the current wallet config to the next one.
+/// Fails if the next config does not follow the current checkpoint, or if it is locked out.
+pub fn plan_config_transition(
+    current: &WalletConfig,
+    next: &WalletConfig,
+) -> Result<ConfigTransition> {
+    if next.checkpoint <= current.checkpoint {
+        return Err(eyre!(
+            "Invalid checkpoint: {} is not after the current checkpoint {}",
+            next.checkpoint,
+            current.checkpoint
+        ));
+    }
+
+    check_config_lockout(next)?;
+
+    let current_owners = owner_weights(&current.tree);
+    let next_owners = owner_weights(&next.tree);
+
+    let added_owners = next_owners
+        .iter()
+        .filter(|(address, _)| !current_owners.contains_key(address))
+        .map(|(address, weight)| OwnerWeight { address: *address, weight: *weight })
+        .collect();
+    let removed_owners = current_owners
+        .iter()
+        .filter(|(address, _)| !next_owners.contains_key(address))
+        .map(|(address, weight)| OwnerWeight { address: *address, weight: *weight })
+        .collect();
+    let changed_owners = next_owners
+        .iter()
+        .filter_map(|(address, weight)| {
+            current_owners.get(address).filter(|previous_weight| *previous_weight != weight).map(
+                |previous_weight| OwnerWeightChange {
+                    address: *address,
+                    previous_weight: *previous_weight,
+                    weight: *weight,
+                },
+            )
+        })
+        .collect();
+
+    let current_subdigests = subdigests(&current.tree);
+    let next_subdigests = subdigests(&next.tree);
+
+    // The weight of the next config, signed only by the owners of the current config.
+    let current_signers_weight =
+        reachable_weight(&next.tree, &|address| current_owners.contains_key(address));
+
+    Ok(ConfigTransition {
+        added_owners,
+        removed_owners,
+        changed_owners,
+        added_subdigests: next_subdigests.difference(&current_subdigests).copied().collect(),
+        removed_subdigests: current_subdigests.difference(&next_subdigests).copied().collect(),
+        previous_threshold: current.threshold,
+        threshold: next.threshold,
+        previous_checkpoint: current.checkpoint,
+        checkpoint: next.checkpoint,
+        image_hash: next.image_hash_of_wallet_config()?.into(),
+        current_signers_weight,
+        is_reachable_by_current_signers: current_signers_weight >= next.threshold as u64,
+    })
+}
+
+/// Check that the wallet config can ever be signed, w/ the threshold reachable by the owners
+/// other than the zero address.
+pub fn check_config_lockout(config: &WalletConfig) -> Result<()> {
+    if config.threshold == 0 {
+        return Err(eyre!("Invalid threshold: 0"));
+    }
+
+    let weight = reachable_weight(&config.tree, &|address| !address.is_zero());
+    if weight < config.threshold as u64 {
+        return Err(eyre!(
+            "Locked out: the reachable weight {} is less than the threshold {}",
+            weight,
+            config.threshold
+        ));
+    }
+
+    Ok(())
+}
+
+/// Get the calldata of `updateImageHash(bytes32)`, which the wallet calls on itself to upgrade to
+/// the image hash.
+pub fn update_image_hash_calldata(image_hash: H256) -> Vec<u8> {
+    UpdateImageHashCall { image_hash: image_hash.0 }.encode()
+}
+
+/// Get the owners of the tree w/ the weights, summing the weights of the duplicated owners.
+fn owner_weights(node: &SignerNode) -> BTreeMap<Address, u8> {
+    let mut owners = BTreeMap::new();
+    visit_leaves(node, &mut |weight, leaf| {
+        if let Some(address) = leaf_address(leaf) {
+            let entry = owners.entry(address).or_insert(0u8);
+            *entry = entry.saturating_add(weight.unwrap_or(0));
+        }
+    });
+    owners
+}
+
+/// Get the pre-approved subdigests of the tree.
+fn subdigests(node: &SignerNode) -> BTreeSet<H256> {
+    let mut subdigests = BTreeSet::new();
+    visit_leaves(node, &mut |_, leaf| {
+        if let SignatureLeaf::SubdigestSignature(leaf) = leaf {
+            subdigests.insert(leaf.hash);
+        }
+    });
+    subdigests
+}
+
+/// Visit the leaves of the tree, including the ones in the nested configs.
+fn visit_leaves(node: &SignerNode, visit: &mut dyn FnMut(Option<u8>, &SignatureLeaf)) {
+    if let Some(signer) = &node.signer {
+        visit(signer.weight, &signer.leaf);
+    }
+    if let Some(left) = &node.left {
+        visit_leaves(left, visit);
+    }
+    if let Some(right) = &node.right {
+        visit_leaves(right, visit);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::builder::{config_tree_builder, ConfigMember};
+
+    fn config(checkpoint: u32, threshold: u16, members: &[ConfigMember]) -> Result<WalletConfig> {
+        Ok(WalletConfig {
+            signature_type: 0,
+            checkpoint,
+            threshold,
+            weight: 1,
+            image_hash: [0; 32].into(),
+            tree: config_tree_builder(members)?,
+            internal_root: None,
+            internal_recovered_configs: None,
+        })
+    }
+
+    #[test]
+    fn test_plan_config_transition() -> Result<()> {
+        let a = Address::from_low_u64_be(1);
+        let b = Address::from_low_u64_be(2);
+        let c = Address::from_low_u64_be(3);
+        let subdigest = H256::repeat_byte(4);
+
+        let current = config(
+            0,
+            2,
+            &[
+                ConfigMember::Owner { address: a, weight: 1 },
+                ConfigMember::Owner { address: b, weight: 1 },
+            ],
+        )?;
+        let next = config(
+            1,
+            3,
+            &[
+                ConfigMember::Owner { address: a, weight: 2 },
+                ConfigMember::Nested {
+                    weight: 1,
+                    threshold: 1,
+                    members: vec![ConfigMember::Owner { address: c, weight: 1 }],
+                },
+                ConfigMember::Subdigest { hash: subdigest },
+            ],
+        )?;
+
+        let transition = plan_config_transition(&current, &next)?;
+        assert_eq!(transition.added_owners, vec![OwnerWeight { address: c, weight: 1 }]);
+        assert_eq!(transition.removed_owners, vec![OwnerWeight { address: b, weight: 1 }]);
+        assert_eq!(
+            transition.changed_owners,
+            vec![OwnerWeightChange { address: a, previous_weight: 1, weight: 2 }]
+        );
+        assert_eq!(transition.added_subdigests, vec![subdigest]);
+        assert!(transition.removed_subdigests.is_empty());
+        assert_eq!((transition.previous_threshold, transition.threshold), (2, 3));
+        assert_eq!(transition.image_hash, H256::from(next.image_hash_of_wallet_config()?));
+
+        // Only `a` of the current owners remains, w/ the weight of 2 out of 3.
+        assert_eq!(transition.current_signers_weight, 2);
+        assert!(!transition.is_reachable_by_current_signers);
+
+        // The checkpoint has to increase.
+        assert!(plan_config_transition(&next, &current).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_config_lockout() -> Result<()> {
+        let a = Address::from_low_u64_be(1);
+
+        assert!(check_config_lockout(&config(
+            1,
+            1,
+            &[ConfigMember::Owner { address: a, weight: 1 }]
+        )?)
+        .is_ok());
+        assert!(check_config_lockout(&config(
+            1,
+            0,
+            &[ConfigMember::Owner { address: a, weight: 1 }]
+        )?)
+        .is_err());
+        assert!(check_config_lockout(&config(
+            1,
+            2,
+            &[ConfigMember::Owner { address: a, weight: 1 }]
+        )?)
+        .is_err());
+
+        // The zero address can never sign.
+        assert!(check_config_lockout(&config(
+            1,
+            2,
+            &[
+                ConfigMember::Owner { address: a, weight: 1 },
+                ConfigMember::Owner { address: Address::zero(), weight: 1 },
+            ]
+        )?)
+        .is_err());
+
+        // The nested config only counts if its internal threshold is reachable.
+        assert!(check_config_lockout(&config(
+            1,
+            1,
+            &[ConfigMember::Nested {
+                weight: 1,
+                threshold: 2,
+                members: vec![ConfigMember::Owner { address: a, weight: 1 }],
+            }]
+        )?)
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_image_hash_calldata() {
+        let calldata = update_image_hash_calldata(H256::repeat_byte(1));
+        assert_eq!(calldata.len(), 36);
+        // The selector of `updateImageHash(bytes32)`
+        assert_eq!(calldata[..4], [0x29, 0x56, 0x14, 0x26]);
+        assert_eq!(calldata[4..], [1u8; 32]);
+    }
+}
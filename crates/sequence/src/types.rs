@@ -89,6 +89,18 @@ pub enum SignatureLeafType {
     Nested = 6,
 }
 
+/// The enum representation of a wallet signature type, prepended to the encoded signature
+/// Derived from: https://github.com/0xsequence/wallet-contracts/blob/46838284e90baf27cf93b944b056c0b4a64c9733/contracts/modules/commons/ModuleAuth.sol#L56
+/// License: Apache-2.0
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[repr(u8)]
+pub enum WalletSignatureType {
+    Legacy = 0,
+    Dynamic = 1,
+    NoChainId = 2,
+    Chained = 3,
+}
+
 /// The struct representation of an ECDSA signature leaf type
 /// Derived from: https://github.com/0xsequence/wallet-contracts/blob/e0c5382636a88b4db4bcf0a70623355d7cd30fb4/contracts/utils/SignatureValidator.sol#L83
 /// License: Apache-2.0
@@ -0,0 +1,194 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+// The versions of the Light wallet, w/ the factory and the implementation deployed for each.
+
+use ethers::{
+    providers::Middleware,
+    types::{Address, H256},
+};
+use eyre::{eyre, Result};
+use lightdotso_contracts::{
+    constants::{
+        LIGHT_WALLET_FACTORY_ADDRESS, LIGHT_WALLET_FACTORY_ADDRESSES,
+        LIGHT_WALLET_FACTORY_IMPLEMENTATION_ADDRESS,
+        LIGHT_WALLET_FACTORY_IMPLEMENTATION_V010_ADDRESS, LIGHT_WALLET_FACTORY_V010_ADDRESS,
+    },
+    provider::get_provider,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// The storage slot of the implementation of the ERC-1967 proxy.
+/// From: https://eips.ethereum.org/EIPS/eip-1967
+pub const ERC1967_IMPLEMENTATION_SLOT: [u8; 32] = [
+    0x36, 0x08, 0x94, 0xa1, 0x3b, 0xa1, 0xa3, 0x21, 0x06, 0x67, 0xc8, 0x28, 0x49, 0x2d, 0xb9, 0x8d,
+    0xca, 0x3e, 0x20, 0x76, 0xcc, 0x37, 0x35, 0xa9, 0x20, 0xa3, 0xca, 0x50, 0x5d, 0x38, 0x2b, 0xbc,
+];
+
+/// The version of the Light wallet, named after the factory which deployed it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WalletVersion {
+    /// The factory of the local devnet
+    #[serde(rename = "local")]
+    Local,
+    #[serde(rename = "v0.0.0")]
+    V000,
+    #[serde(rename = "v0.0.1")]
+    V001,
+    #[serde(rename = "v0.1.0")]
+    V010,
+    #[serde(rename = "v0.2.0")]
+    V020,
+}
+
+impl WalletVersion {
+    /// All of the versions, from the oldest to the latest.
+    pub const ALL: [WalletVersion; 5] = [
+        WalletVersion::Local,
+        WalletVersion::V000,
+        WalletVersion::V001,
+        WalletVersion::V010,
+        WalletVersion::V020,
+    ];
+
+    /// The version of the wallets created from now on.
+    pub const LATEST: WalletVersion = WalletVersion::V020;
+
+    /// Get the address of the factory of the version.
+    pub fn factory_address(&self) -> Address {
+        match self {
+            WalletVersion::Local => LIGHT_WALLET_FACTORY_ADDRESSES[0],
+            WalletVersion::V000 => LIGHT_WALLET_FACTORY_ADDRESSES[1],
+            WalletVersion::V001 => LIGHT_WALLET_FACTORY_ADDRESSES[2],
+            WalletVersion::V010 => *LIGHT_WALLET_FACTORY_V010_ADDRESS,
+            WalletVersion::V020 => *LIGHT_WALLET_FACTORY_ADDRESS,
+        }
+    }
+
+    /// Get the address of the implementation the factory of the version deploys the proxies with.
+    /// The implementations of the versions before v0.1.0 are not tracked, and have to be read from
+    /// the factory on-chain.
+    pub fn implementation_address(&self) -> Option<Address> {
+        match self {
+            WalletVersion::V010 => Some(*LIGHT_WALLET_FACTORY_IMPLEMENTATION_V010_ADDRESS),
+            WalletVersion::V020 => Some(*LIGHT_WALLET_FACTORY_IMPLEMENTATION_ADDRESS),
+            _ => None,
+        }
+    }
+
+    /// Get the version from the address of the factory.
+    pub fn from_factory_address(factory: Address) -> Option<WalletVersion> {
+        WalletVersion::ALL.into_iter().find(|version| version.factory_address() == factory)
+    }
+
+    /// Get the version from the address of the implementation.
+    pub fn from_implementation_address(implementation: Address) -> Option<WalletVersion> {
+        WalletVersion::ALL
+            .into_iter()
+            .find(|version| version.implementation_address() == Some(implementation))
+    }
+}
+
+impl fmt::Display for WalletVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WalletVersion::Local => write!(f, "local"),
+            WalletVersion::V000 => write!(f, "v0.0.0"),
+            WalletVersion::V001 => write!(f, "v0.0.1"),
+            WalletVersion::V010 => write!(f, "v0.1.0"),
+            WalletVersion::V020 => write!(f, "v0.2.0"),
+        }
+    }
+}
+
+/// The detected version of the wallet on the chain.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalletVersionDetection {
+    /// The version of the wallet, if the factory or the implementation is known
+    pub version: Option<WalletVersion>,
+    /// The implementation of the deployed wallet
+    pub implementation: Option<Address>,
+    /// Whether the wallet is deployed on the chain
+    pub is_deployed: bool,
+}
+
+/// Detect the version of the wallet on the chain.
+/// The deployed wallet is detected from the implementation of the proxy, which also reflects the
+/// upgrades of the wallet, and the undeployed wallet is detected from the factory it is going to
+/// be deployed w/.
+pub async fn detect_wallet_version(
+    chain_id: u64,
+    address: Address,
+    factory: Option<Address>,
+) -> Result<WalletVersionDetection> {
+    // Get the provider.
+    let provider = get_provider(chain_id).await?;
+
+    // Get the implementation of the proxy.
+    let slot = provider.get_storage_at(address, H256(ERC1967_IMPLEMENTATION_SLOT), None).await?;
+    let implementation = Address::from(slot);
+
+    // If the implementation is not set, the wallet is not deployed.
+    if implementation.is_zero() {
+        return Ok(WalletVersionDetection {
+            version: factory.and_then(WalletVersion::from_factory_address),
+            implementation: None,
+            is_deployed: false,
+        });
+    }
+
+    Ok(WalletVersionDetection {
+        version: WalletVersion::from_implementation_address(implementation)
+            .or(factory.and_then(WalletVersion::from_factory_address)),
+        implementation: Some(implementation),
+        is_deployed: true,
+    })
+}
+
+/// Get the version of the factory, or an error if the factory is not a Light wallet factory.
+pub fn get_wallet_version_from_factory(factory: Address) -> Result<WalletVersion> {
+    WalletVersion::from_factory_address(factory)
+        .ok_or(eyre!("Unknown wallet factory: {:?}", factory))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wallet_version() -> Result<()> {
+        for version in WalletVersion::ALL {
+            assert_eq!(
+                WalletVersion::from_factory_address(version.factory_address()),
+                Some(version)
+            );
+            assert_eq!(
+                serde_json::to_value(version)?,
+                serde_json::Value::String(version.to_string())
+            );
+        }
+
+        assert_eq!(WalletVersion::LATEST.factory_address(), *LIGHT_WALLET_FACTORY_ADDRESS);
+        assert_eq!(
+            WalletVersion::from_implementation_address(
+                *LIGHT_WALLET_FACTORY_IMPLEMENTATION_V010_ADDRESS
+            ),
+            Some(WalletVersion::V010)
+        );
+        assert!(get_wallet_version_from_factory(Address::zero()).is_err());
+
+        Ok(())
+    }
+}
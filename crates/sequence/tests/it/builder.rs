@@ -12,12 +12,20 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-use eyre::Result;
+use ethers::{
+    signers::{LocalWallet, Signer as _},
+    types::{Address, H256},
+};
+use eyre::{eyre, Result};
 use lightdotso_sequence::{
-    builder::rooted_node_builder,
+    builder::{
+        chain_upgrade_signatures, config_tree_builder, ecdsa_signature_leaf, rooted_node_builder,
+        ConfigMember, SignatureBuilder,
+    },
+    config::WalletConfig,
     recover::recover_signature,
-    types::SignerNode,
-    utils::{from_hex_string, parse_hex_to_bytes32},
+    types::{AddressSignatureLeaf, SignatureLeaf, Signer, SignerNode, WalletSignatureType},
+    utils::{from_hex_string, hash_image_bytes32, parse_hex_to_bytes32, render_subdigest},
 };
 
 // https://sepolia.etherscan.io/tx/0x4dcceb715de1825bee83424e2385a7ed2cc00af70d883ff25aaa29f2c6efbd68
@@ -65,3 +73,199 @@ async fn test_integration_rooted_builder() -> Result<()> {
 
     Ok(())
 }
+
+fn wallet_config(owners: &[&LocalWallet], checkpoint: u32, threshold: u16) -> Result<WalletConfig> {
+    let owner_nodes = owners
+        .iter()
+        .map(|owner| SignerNode {
+            signer: Some(Signer {
+                weight: Some(1),
+                leaf: SignatureLeaf::AddressSignature(AddressSignatureLeaf {
+                    address: owner.address(),
+                }),
+            }),
+            left: None,
+            right: None,
+        })
+        .collect();
+    let tree = rooted_node_builder(owner_nodes)?;
+
+    let mut config = WalletConfig {
+        checkpoint,
+        threshold,
+        weight: 0,
+        image_hash: H256::zero(),
+        internal_root: Some(tree.calculate_image_hash_from_node([0; 32])?.into()),
+        tree,
+        signature_type: 1,
+        internal_recovered_configs: None,
+    };
+    config.image_hash = config.image_hash_of_wallet_config()?.into();
+
+    Ok(config)
+}
+
+fn sign(owner: &LocalWallet, subdigest: [u8; 32]) -> Result<SignatureLeaf> {
+    let mut signature = owner.sign_hash(subdigest.into())?.to_vec();
+    // Set the `ECDSASignatureType` to `ECDSASignatureTypeEIP712`
+    signature.push(1);
+
+    ecdsa_signature_leaf(owner.address(), &signature)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_integration_signature_builder() -> Result<()> {
+    let owners: Vec<LocalWallet> =
+        (0..4).map(|_| LocalWallet::new(&mut rand::thread_rng())).collect();
+    let config = wallet_config(&owners.iter().collect::<Vec<_>>(), 1, 2)?;
+
+    let address: Address = "0xFbd80Fe5cE1ECe895845Fd131bd621e2B6A1345F".parse()?;
+    let digest = [1u8; 32];
+    let subdigest = render_subdigest(11155111, address, digest)?;
+
+    // Sign w/ the last two owners, so that the first two are collapsed.
+    let signature = SignatureBuilder::new(config.clone())
+        .signature(sign(&owners[2], subdigest)?)
+        .signature(sign(&owners[3], subdigest)?)
+        .build()?;
+
+    let recovered = recover_signature(address, 11155111, digest, signature.into()).await?;
+    assert_eq!(recovered.image_hash, config.image_hash);
+    assert_eq!(recovered.weight, 2);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_integration_signature_builder_chained() -> Result<()> {
+    let owners: Vec<LocalWallet> =
+        (0..2).map(|_| LocalWallet::new(&mut rand::thread_rng())).collect();
+    let prior_config = wallet_config(&[&owners[0]], 1, 1)?;
+    let config = wallet_config(&[&owners[1]], 2, 1)?;
+
+    let address: Address = "0xFbd80Fe5cE1ECe895845Fd131bd621e2B6A1345F".parse()?;
+    let digest = [1u8; 32];
+
+    // The prior config signs the update to the image hash of the current config.
+    let subdigest = render_subdigest(0, address, digest)?;
+    let prior_subdigest = render_subdigest(0, address, hash_image_bytes32(&config.image_hash.0)?)?;
+
+    let signature = SignatureBuilder::new(config.clone())
+        .signature(sign(&owners[1], subdigest)?)
+        .signature_type(WalletSignatureType::NoChainId)
+        .chain(
+            SignatureBuilder::new(prior_config.clone())
+                .signature(sign(&owners[0], prior_subdigest)?),
+        )
+        .build()?;
+    assert_eq!(signature[0], WalletSignatureType::Chained as u8);
+
+    let recovered = recover_signature(address, 0, digest, signature.into()).await?;
+    assert_eq!(recovered.image_hash, config.image_hash);
+    let prior_configs = recovered.internal_recovered_configs.ok_or(eyre!("No prior configs"))?;
+    assert_eq!(prior_configs.last().map(|config| config.image_hash), Some(prior_config.image_hash));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_integration_chain_upgrade_signatures() -> Result<()> {
+    let owners: Vec<LocalWallet> =
+        (0..3).map(|_| LocalWallet::new(&mut rand::thread_rng())).collect();
+    let configs = owners
+        .iter()
+        .enumerate()
+        .map(|(i, owner)| wallet_config(&[owner], i as u32 + 1, 1))
+        .collect::<Result<Vec<_>>>()?;
+
+    let address: Address = "0xFbd80Fe5cE1ECe895845Fd131bd621e2B6A1345F".parse()?;
+
+    // The configurations before the latest one sign the image hash of the next one, from the
+    // newest to the on-chain one.
+    let builders = configs[..2]
+        .iter()
+        .zip(owners.iter())
+        .zip(configs[1..].iter())
+        .rev()
+        .map(|((config, owner), next_config)| -> Result<SignatureBuilder> {
+            let subdigest =
+                render_subdigest(0, address, hash_image_bytes32(&next_config.image_hash.0)?)?;
+            Ok(SignatureBuilder::new(config.clone()).signature(sign(owner, subdigest)?))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let signature = chain_upgrade_signatures(builders)?;
+    assert_eq!(signature[0], WalletSignatureType::Chained as u8);
+
+    // The upgrades recover to the on-chain configuration on any chain, from the latest image hash.
+    let recovered = recover_signature(
+        address,
+        11155111,
+        hash_image_bytes32(&configs[2].image_hash.0)?,
+        signature.into(),
+    )
+    .await?;
+    assert_eq!(recovered.image_hash, configs[1].image_hash);
+    let prior_configs = recovered.internal_recovered_configs.ok_or(eyre!("No prior configs"))?;
+    assert_eq!(prior_configs.last().map(|config| config.image_hash), Some(configs[0].image_hash));
+
+    // A single upgrade is not chained.
+    let subdigest = render_subdigest(0, address, hash_image_bytes32(&configs[1].image_hash.0)?)?;
+    let signature = chain_upgrade_signatures([
+        SignatureBuilder::new(configs[0].clone()).signature(sign(&owners[0], subdigest)?)
+    ])?;
+    assert_eq!(signature[0], WalletSignatureType::NoChainId as u8);
+
+    let recovered = recover_signature(
+        address,
+        11155111,
+        hash_image_bytes32(&configs[1].image_hash.0)?,
+        signature.into(),
+    )
+    .await?;
+    assert_eq!(recovered.image_hash, configs[0].image_hash);
+
+    // No upgrades can't be chained.
+    assert!(chain_upgrade_signatures(Vec::<SignatureBuilder>::new()).is_err());
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_integration_signature_builder_nested() -> Result<()> {
+    let owners: Vec<LocalWallet> =
+        (0..4).map(|_| LocalWallet::new(&mut rand::thread_rng())).collect();
+    let member = |owner: &LocalWallet| ConfigMember::Owner { address: owner.address(), weight: 1 };
+
+    // 2 of {owner, 1 of {owner, owner}, 1 of {owner}}
+    let tree = config_tree_builder(&[
+        member(&owners[0]),
+        ConfigMember::Nested {
+            weight: 1,
+            threshold: 1,
+            members: vec![member(&owners[1]), member(&owners[2])],
+        },
+        ConfigMember::Nested { weight: 1, threshold: 1, members: vec![member(&owners[3])] },
+    ])?;
+    let mut config = WalletConfig {
+        internal_root: Some(tree.calculate_image_hash_from_node([0; 32])?.into()),
+        tree,
+        ..wallet_config(&[&owners[0]], 1, 2)?
+    };
+    config.image_hash = config.image_hash_of_wallet_config()?.into();
+
+    let address: Address = "0xFbd80Fe5cE1ECe895845Fd131bd621e2B6A1345F".parse()?;
+    let digest = [1u8; 32];
+    let subdigest = render_subdigest(11155111, address, digest)?;
+
+    let signature = SignatureBuilder::new(config.clone())
+        .signature(sign(&owners[2], subdigest)?)
+        .signature(sign(&owners[3], subdigest)?)
+        .build()?;
+
+    let recovered = recover_signature(address, 11155111, digest, signature.into()).await?;
+    assert_eq!(recovered.image_hash, config.image_hash);
+    assert_eq!(recovered.weight, 2);
+
+    Ok(())
+}
@@ -16,9 +16,10 @@ use ethers::types::{Address, H256};
 use eyre::Result;
 use lightdotso_sequence::{
     config::WalletConfig,
-    hash::get_address,
+    hash::get_address_with_factory,
     types::{NodeLeaf, SignatureLeaf, Signer, SignerNode},
     utils::parse_hex_to_bytes32,
+    version::WalletVersion,
 };
 
 #[ignore]
@@ -57,8 +58,12 @@ async fn test_integration_hash_first() -> Result<()> {
     let salt_bytes: H256 =
         "0x0000000000000000000000000000000000000000000000000000000000000001".parse()?;
 
-    // Calculate the new wallet address.
-    let new_wallet_address = get_address(image_hash_bytes, salt_bytes)?;
+    // Calculate the new wallet address from the v0.1.0 factory.
+    let (new_wallet_address, _) = get_address_with_factory(
+        WalletVersion::V010.factory_address(),
+        image_hash_bytes,
+        salt_bytes,
+    )?;
 
     // Check the new wallet address.
     let expected: Address = "0x10DbbE70128929723c1b982e53c51653232e4Ff2".parse()?;
@@ -0,0 +1,57 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use eyre::Result;
+use lightdotso_sequence::{
+    inspect::inspect_signature,
+    recover::recover_signature,
+    types::SignatureLeafType,
+    utils::{from_hex_string, parse_hex_to_bytes32},
+};
+
+// https://sepolia.etherscan.io/tx/0x4dcceb715de1825bee83424e2385a7ed2cc00af70d883ff25aaa29f2c6efbd68
+const SIGNATURE: &str = "0x0100010000000001014fd9d0ee6d6564e80a9ee00c0163fc952d0a45ed00012395bc3e577accfb42eaa452519853a168ca8bd8267063b73957a684c2583a0066a37fe568c1d5e918e98d2a99d6d6e0f0b8448ff704283c7757a82fd37b9dfa1b02";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_integration_inspect_signature() -> Result<()> {
+    let address = "0xFbd80Fe5cE1ECe895845Fd131bd621e2B6A1345F".parse()?;
+    let user_op_hash =
+        parse_hex_to_bytes32("0x4fc471aea4f6850725688fbdba63383a7678b9dcba1b4ae9a837bf3d01a1833e")?;
+
+    let report =
+        inspect_signature(address, 11155111, user_op_hash, from_hex_string(SIGNATURE)?.into())
+            .await?;
+    let config =
+        recover_signature(address, 11155111, user_op_hash, from_hex_string(SIGNATURE)?.into())
+            .await?;
+
+    assert_eq!(report.signature_type, 1);
+    assert_eq!(report.threshold, 1);
+    assert_eq!(report.checkpoint, 0);
+    assert_eq!(report.image_hash, config.image_hash);
+    assert!(report.chained.is_empty());
+    assert!(report.warnings.is_empty());
+
+    // The address leaf is listed w/o weight, and the ECDSA leaf signs w/ its weight.
+    assert_eq!(report.leaves.len(), 2);
+    assert_eq!(report.leaves[0].leaf_type, SignatureLeafType::Address);
+    assert_eq!(report.leaves[0].contributed_weight, 0);
+    assert_eq!(report.leaves[1].leaf_type, SignatureLeafType::ECDSASignature);
+    assert_eq!(report.leaves[1].contributed_weight, 1);
+    assert_eq!(report.leaves[1].cumulative_weight, 1);
+    assert_eq!(report.weight, 1);
+    assert!(report.is_threshold_met);
+
+    Ok(())
+}
@@ -19,6 +19,8 @@
 mod builder;
 mod config;
 mod hash;
+mod inspect;
 mod node;
 mod recover;
+mod recovery;
 mod signatures;
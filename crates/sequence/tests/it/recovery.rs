@@ -0,0 +1,109 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ethers::{
+    signers::{LocalWallet, Signer as _},
+    types::{Address, H256},
+};
+use eyre::Result;
+use lightdotso_sequence::{
+    builder::{config_tree_builder, ecdsa_signature_leaf, ConfigMember},
+    config::WalletConfig,
+    recover::recover_signature,
+    recovery::{guardian_member, recovery_signature_builder, recovery_subdigest},
+    types::SignatureLeaf,
+    utils::hash_image_bytes32,
+};
+
+fn sign(signer: &LocalWallet, subdigest: [u8; 32]) -> Result<SignatureLeaf> {
+    let mut signature = signer.sign_hash(subdigest.into())?.to_vec();
+    // Set the `ECDSASignatureType` to `ECDSASignatureTypeEIP712`
+    signature.push(1);
+
+    ecdsa_signature_leaf(signer.address(), &signature)
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_integration_recovery_signature_builder() -> Result<()> {
+    let owners: Vec<LocalWallet> =
+        (0..2).map(|_| LocalWallet::new(&mut rand::thread_rng())).collect();
+    let guardians: Vec<LocalWallet> =
+        (0..3).map(|_| LocalWallet::new(&mut rand::thread_rng())).collect();
+    let guardian_addresses = guardians.iter().map(|g| g.address()).collect::<Vec<_>>();
+
+    // 2 of {owner, owner, 2 * 2 of {guardian, guardian, guardian}}
+    let tree = config_tree_builder(&[
+        ConfigMember::Owner { address: owners[0].address(), weight: 1 },
+        ConfigMember::Owner { address: owners[1].address(), weight: 1 },
+        guardian_member(&guardian_addresses, 2, 2)?,
+    ])?;
+    let mut config = WalletConfig {
+        signature_type: 1,
+        checkpoint: 1,
+        threshold: 2,
+        weight: 0,
+        image_hash: H256::zero(),
+        internal_root: Some(tree.calculate_image_hash_from_node([0; 32])?.into()),
+        tree,
+        internal_recovered_configs: None,
+    };
+    config.image_hash = config.image_hash_of_wallet_config()?.into();
+
+    let address: Address = "0xFbd80Fe5cE1ECe895845Fd131bd621e2B6A1345F".parse()?;
+    let image_hash = H256::repeat_byte(1);
+    let subdigest = recovery_subdigest(address, image_hash)?;
+
+    // The quorum of the guardians recovers the wallet on its own.
+    let signature = recovery_signature_builder(
+        config.clone(),
+        &guardian_addresses,
+        vec![sign(&guardians[0], subdigest)?, sign(&guardians[2], subdigest)?],
+    )?
+    .build()?;
+
+    let recovered =
+        recover_signature(address, 0, hash_image_bytes32(&image_hash.0)?, signature.into()).await?;
+    assert_eq!(recovered.image_hash, config.image_hash);
+    assert!(recovered.weight >= config.threshold as u32);
+
+    // The owners can co-sign the recovery w/ the quorum of the guardians.
+    assert!(recovery_signature_builder(
+        config.clone(),
+        &guardian_addresses,
+        vec![
+            sign(&guardians[0], subdigest)?,
+            sign(&guardians[1], subdigest)?,
+            sign(&owners[1], subdigest)?,
+        ],
+    )
+    .is_ok());
+
+    // A single guardian is below the guardian threshold, even w/ an owner.
+    assert!(recovery_signature_builder(
+        config.clone(),
+        &guardian_addresses,
+        vec![sign(&guardians[0], subdigest)?, sign(&owners[1], subdigest)?],
+    )
+    .is_err());
+
+    // The owners can't sign the recovery w/o the guardians.
+    assert!(recovery_signature_builder(
+        config,
+        &guardian_addresses,
+        vec![sign(&owners[0], subdigest)?, sign(&owners[1], subdigest)?],
+    )
+    .is_err());
+
+    Ok(())
+}
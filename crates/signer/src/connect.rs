@@ -20,18 +20,21 @@ use std::time::Duration;
 use tokio::time::timeout;
 
 pub async fn connect_to_kms() -> Result<AwsSigner, eyre::Report> {
+    connect_to_kms_with_key_ids(
+        std::env::var("AWS_KMS_KEY_IDS")
+            .wrap_err("Failed to get AWS_KMS_KEY_IDS from environment")?
+            .split(',')
+            .map(|s| s.to_string())
+            .collect(),
+    )
+    .await
+}
+
+/// Connect to the KMS signer w/ the key ids, for the signers w/ their own keys.
+pub async fn connect_to_kms_with_key_ids(key_ids: Vec<String>) -> Result<AwsSigner, eyre::Report> {
     let signer = timeout(
         Duration::from_millis(30000 / 10),
-        KmsSigner::connect(
-            1,
-            Region::UsEast1,
-            std::env::var("AWS_KMS_KEY_IDS")
-                .wrap_err("Failed to get AWS_KMS_KEY_IDS from environment")?
-                .split(',')
-                .map(|s| s.to_string())
-                .collect(),
-            3000,
-        ),
+        KmsSigner::connect(1, Region::UsEast1, key_ids, 3000),
     )
     .await
     .map_err(|e| eyre!("Timeout Error: {}", e))?;
@@ -15,20 +15,29 @@
 // From: https://github.com/EnsoFinance/transaction-simulator/blob/64fe96afd52e5ff138ea0c22ad23aa4287346e7c/src/evm.rs
 // License: MIT
 
-use crate::types::CallRawResult;
+use crate::{
+    snapshot::StateSnapshot,
+    types::{CallRawResult, StateOverride},
+};
 use ethers_main::{
     abi::{Address, Uint},
-    types::Bytes,
+    types::{Bytes, H256},
+    utils::keccak256,
 };
 use eyre::{eyre, Result};
 use foundry_evm::{
     executor::{fork::CreateFork, opts::EvmOpts, Backend, Executor, ExecutorBuilder},
     trace::{identifier::SignaturesIdentifier, CallTraceDecoderBuilder},
 };
-use revm::primitives::Env;
+use revm::{
+    primitives::{Bytecode, Env, State},
+    DatabaseRef,
+};
 
 pub struct Evm {
     executor: Executor,
+    /// The state touched by the calls, recorded w/ the values before the first access
+    recording: Option<StateSnapshot>,
 }
 
 impl Evm {
@@ -71,7 +80,10 @@ impl Evm {
             builder = builder.with_config(fork_opts.env.clone());
         }
 
-        let executor = builder.build(db.await);
+        let mut executor = builder.build(db.await);
+
+        // Impersonate the callers, so that contract accounts (e.g. wallets) can send transactions
+        executor.env_mut().cfg.disable_eip3607 = true;
 
         let mut decoder = CallTraceDecoderBuilder::new().with_verbosity(5).build();
 
@@ -81,7 +93,89 @@ impl Evm {
             decoder.add_signature_identifier(identifier);
         }
 
-        Evm { executor }
+        Evm { executor, recording: None }
+    }
+
+    /// Construct the EVM from the pinned state of the snapshot w/o a live RPC fork.
+    pub async fn new_from_snapshot(
+        env: Option<Env>,
+        snapshot: &StateSnapshot,
+        gas_limit: u64,
+        tracing: bool,
+    ) -> Result<Self> {
+        let db = Backend::spawn(None);
+
+        let mut env = env.unwrap_or_default();
+        env.cfg.chain_id = Uint::from(snapshot.chain_id).into();
+        env.cfg.disable_eip3607 = true;
+        env.block.number = Uint::from(snapshot.block_number).into();
+        env.block.timestamp = Uint::from(snapshot.block_timestamp).into();
+
+        let executor = ExecutorBuilder::default()
+            .with_gas_limit(gas_limit.into())
+            .set_tracing(tracing)
+            .with_config(env)
+            .build(db.await);
+
+        let mut evm = Evm { executor, recording: None };
+
+        // Insert the accounts of the snapshot
+        evm.apply_state_overrides(&StateOverride::from(snapshot)).await?;
+
+        Ok(evm)
+    }
+
+    /// Start recording the state touched by the following calls.
+    pub fn start_recording(&mut self) {
+        self.recording = Some(StateSnapshot {
+            chain_id: self.get_chain_id().low_u64(),
+            block_number: self.get_block().low_u64(),
+            block_timestamp: self.get_block_timestamp().low_u64(),
+            accounts: Default::default(),
+        });
+    }
+
+    /// Stop recording and get the snapshot of the touched state, to replay w/
+    /// `new_from_snapshot`.
+    pub fn take_recording(&mut self) -> Option<StateSnapshot> {
+        self.recording.take()
+    }
+
+    /// Record the accounts and the slots of the state changeset of a call.
+    /// Must be called before the changeset is committed, to record the state before the call.
+    fn record(&mut self, state_changeset: Option<&State>) -> Result<()> {
+        let Evm { executor, recording } = self;
+        let (recording, state_changeset) = match (recording.as_mut(), state_changeset) {
+            (Some(recording), Some(state_changeset)) => (recording, state_changeset),
+            _ => return Ok(()),
+        };
+
+        for (address, account) in state_changeset.iter() {
+            let ethers_address = Address::from(address.0);
+
+            // Record the account info before the first access
+            if !recording.accounts.contains_key(&ethers_address) {
+                let info = executor.backend.basic(*address).map_err(|err| eyre!(err))?;
+                let snapshot = recording.account_mut(ethers_address);
+                if let Some(info) = info {
+                    snapshot.balance = info.balance.into();
+                    snapshot.nonce = info.nonce;
+                    snapshot.code =
+                        info.code.map(|code| code.original_bytes().into()).unwrap_or_default();
+                }
+            }
+
+            // Record the slots before the first access
+            let snapshot = recording.account_mut(ethers_address);
+            for (slot, value) in account.storage.iter() {
+                snapshot
+                    .storage
+                    .entry(H256::from(slot.to_be_bytes::<32>()))
+                    .or_insert(H256::from(value.original_value.to_be_bytes::<32>()));
+            }
+        }
+
+        Ok(())
     }
 
     pub async fn call_raw(
@@ -99,6 +193,9 @@ impl Evm {
                 eyre!(err)
             })?;
 
+        // Record the touched state, which isn't committed
+        self.record(res.state_changeset.as_ref())?;
+
         Ok(CallRawResult {
             gas_used: res.gas_used,
             block_number: res.env.block.number.to(),
@@ -119,6 +216,16 @@ impl Evm {
         gas_limit: u64,
     ) -> Result<CallRawResult> {
         self.executor.set_gas_limit(gas_limit.into());
+
+        // Record the touched state w/ a call before committing
+        if self.recording.is_some() {
+            let res = self
+                .executor
+                .call_raw(from, to, data.clone().unwrap_or_default().0, value.unwrap_or_default())
+                .map_err(|err| eyre!(err))?;
+            self.record(res.state_changeset.as_ref())?;
+        }
+
         let res = self
             .executor
             .call_raw_committing(from, to, data.unwrap_or_default().0, value.unwrap_or_default())
@@ -138,6 +245,47 @@ impl Evm {
         })
     }
 
+    /// Apply the state overrides to the forked backend, before `call_raw` or
+    /// `call_raw_committing`.
+    pub async fn apply_state_overrides(&mut self, overrides: &StateOverride) -> Result<()> {
+        for (address, account) in overrides.iter() {
+            if let Some(balance) = account.balance {
+                self.executor.set_balance(*address, balance).map_err(|err| eyre!(err))?;
+            }
+
+            if let Some(nonce) = account.nonce {
+                self.executor.set_nonce(*address, nonce).map_err(|err| eyre!(err))?;
+            }
+
+            if let Some(code) = &account.code {
+                let mut info = self
+                    .executor
+                    .backend
+                    .basic((*address).into())
+                    .map_err(|err| eyre!(err))?
+                    .unwrap_or_default();
+                info.code_hash = keccak256(code).into();
+                info.code = Some(Bytecode::new_raw(code.0.clone()).to_checked());
+                self.executor.backend.insert_account_info(*address, info);
+            }
+
+            if let Some(storage) = &account.storage {
+                for (slot, value) in storage.iter() {
+                    self.executor
+                        .backend
+                        .insert_account_storage(
+                            *address,
+                            Uint::from_big_endian(slot.as_bytes()),
+                            Uint::from_big_endian(value.as_bytes()),
+                        )
+                        .map_err(|err| eyre!(err))?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     pub async fn get_balance(&self, address: Address) -> Result<Uint> {
         let balance = self.executor.get_balance(address).map_err(|err| {
             dbg!(&err);
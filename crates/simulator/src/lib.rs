@@ -14,4 +14,5 @@
 
 pub mod evm;
 pub mod simulator;
+pub mod snapshot;
 pub mod types;
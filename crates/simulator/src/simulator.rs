@@ -17,20 +17,61 @@
 
 use crate::{
     evm::Evm,
-    types::{SimulationRequest, SimulationResponse},
+    snapshot::StateSnapshot,
+    types::{
+        ExecutionResult, FailedOp, SimulateHandleOpCall, SimulationRequest, SimulationResponse,
+        SimulationUserOperationRequest, UserOperation, UserOperationEventFilter,
+        UserOperationRequest, UserOperationSimulationResponse,
+    },
 };
-use ethers::providers::Middleware;
-use ethers_main::abi::Uint;
-use eyre::{eyre, Result};
-use lightdotso_contracts::provider::get_provider;
+use ethers::{
+    abi::{AbiDecode, AbiEncode, RawLog},
+    contract::EthEvent,
+    providers::Middleware,
+};
+use ethers_main::{
+    abi::{decode, encode, Address, ParamType, Token},
+    types::{Bytes, H256, U256},
+};
+use eyre::{eyre, Result, WrapErr};
+use foundry_evm::trace::{CallTraceArena, RawOrDecodedCall, RawOrDecodedReturnData};
+use lightdotso_contracts::{
+    constants::{ENTRYPOINT_V060_ADDRESS, ENTRYPOINT_V070_ADDRESS},
+    provider::get_provider,
+};
+
+/// The selectors of the validation calls of the entry point v0.6.0: `createSender`,
+/// `validateUserOp` and `validatePaymasterUserOp`.
+const VALIDATION_SELECTORS: [[u8; 4]; 3] =
+    [[0x57, 0x0e, 0x1a, 0x36], [0x3a, 0x87, 0x1c, 0xdd], [0xf4, 0x65, 0xc7, 0x7e]];
+
+/// The selector of `validatePaymasterUserOp` of the entry point v0.6.0.
+const VALIDATE_PAYMASTER_USER_OP_SELECTOR: [u8; 4] = [0xf4, 0x65, 0xc7, 0x7e];
+
+/// The selector of `postOp` of the paymaster of the entry point v0.6.0.
+const POST_OP_SELECTOR: [u8; 4] = [0xa9, 0xa2, 0x34, 0x09];
+
+/// The selector of `isValidSignature(bytes32,bytes)` of EIP-1271, which is also the magic value.
+const IS_VALID_SIGNATURE_SELECTOR: [u8; 4] = [0x16, 0x26, 0xba, 0x7e];
+
+/// The selector of `balanceOf(address)` of ERC-20.
+const BALANCE_OF_SELECTOR: [u8; 4] = [0x70, 0xa0, 0x82, 0x31];
+
+/// The selector of `allowance(address,address)` of ERC-20.
+const ALLOWANCE_SELECTOR: [u8; 4] = [0xdd, 0x62, 0xed, 0x3e];
 
 async fn run(
     evm: &mut Evm,
     request: SimulationRequest,
     commit: bool,
 ) -> Result<SimulationResponse> {
+    // Apply the state overrides
+    if let Some(state_overrides) = &request.state_overrides {
+        evm.apply_state_overrides(state_overrides).await?;
+    }
+
     // Get the value
-    let value = request.value.map(Uint::from);
+    let value = request.value;
 
     // Run the transaction and get the result
     let result = if commit {
@@ -45,6 +86,7 @@ async fn run(
 
     // Return the result
     Ok(SimulationResponse {
+        chain_id: request.chain_id,
         gas_used: result.gas_used,
         block_number: request.block_number.unwrap_or(0),
         success: result.success,
@@ -77,15 +119,94 @@ pub async fn simulate(transaction: SimulationRequest) -> Result<SimulationRespon
     Ok(response)
 }
 
+/// Simulate the transaction on the pinned state of the snapshot, w/o a live RPC fork.
+pub async fn simulate_with_snapshot(
+    transaction: SimulationRequest,
+    snapshot: &StateSnapshot,
+) -> Result<SimulationResponse> {
+    // Construct the EVM
+    let mut evm = Evm::new_from_snapshot(None, snapshot, transaction.gas_limit, true).await?;
+
+    // Run the transaction
+    let mut response = run(&mut evm, transaction, false).await?;
+    response.block_number = snapshot.block_number;
+
+    // Return the response
+    Ok(response)
+}
+
+/// Simulate the transaction on the fork, and record the touched state into a snapshot to replay
+/// w/ `simulate_with_snapshot`.
+pub async fn simulate_and_record(
+    transaction: SimulationRequest,
+) -> Result<(SimulationResponse, StateSnapshot)> {
+    // Get the provider
+    let provider = get_provider(transaction.chain_id).await?;
+
+    // Get the fork url
+    let fork_url = provider.url().to_string();
+
+    // If block number is not provided, use the latest block number
+    let latest_block_number = provider.get_block_number().await?;
+
+    // Get the block number
+    let block_number = transaction.block_number.unwrap_or(latest_block_number.low_u64());
+
+    // Construct the EVM, and record the touched state
+    let mut evm = Evm::new(None, fork_url, Some(block_number), transaction.gas_limit, true).await;
+    evm.start_recording();
+
+    // Run the transaction
+    let response = run(&mut evm, transaction, false).await?;
+
+    // Get the recorded snapshot
+    let snapshot = evm.take_recording().ok_or(eyre!("Failed to record the state"))?;
+
+    // Return the response
+    Ok((response, snapshot))
+}
+
+/// Simulate the bundle of transactions, which may span multiple chains.
+/// The transactions are grouped per chain, and each group runs in order on its own fork.
+/// The responses are returned in the order of the transactions.
 pub async fn simulate_bundle(
     transactions: Vec<SimulationRequest>,
 ) -> Result<Vec<SimulationResponse>> {
-    // Get the first chain id and block number
-    let first_chain_id = transactions[0].chain_id;
-    let first_block_number = transactions[0].block_number;
+    // Group the transactions per chain, keeping the order of the transactions
+    let mut groups: Vec<(u64, Vec<(usize, SimulationRequest)>)> = Vec::new();
+    for (idx, transaction) in transactions.into_iter().enumerate() {
+        match groups.iter_mut().find(|(chain_id, _)| *chain_id == transaction.chain_id) {
+            Some((_, group)) => group.push((idx, transaction)),
+            None => groups.push((transaction.chain_id, vec![(idx, transaction)])),
+        }
+    }
+
+    // Run each group on its own fork
+    let mut responses: Vec<Option<SimulationResponse>> =
+        vec![None; groups.iter().map(|(_, group)| group.len()).sum()];
+    for (chain_id, group) in groups {
+        let (indices, transactions): (Vec<usize>, Vec<SimulationRequest>) =
+            group.into_iter().unzip();
+        let chain_responses = simulate_chain_bundle(chain_id, transactions).await?;
+        for (idx, res) in indices.into_iter().zip(chain_responses) {
+            responses[idx] = Some(res);
+        }
+    }
+
+    // Return the response
+    responses.into_iter().map(|res| res.ok_or(eyre!("Missing simulation response"))).collect()
+}
+
+/// Simulate the transactions of a single chain in order on the same fork.
+async fn simulate_chain_bundle(
+    chain_id: u64,
+    transactions: Vec<SimulationRequest>,
+) -> Result<Vec<SimulationResponse>> {
+    // Get the first block number
+    let first_block_number = transactions.first().and_then(|transaction| transaction.block_number);
 
     // Get the provider
-    let provider = get_provider(first_chain_id).await?;
+    let provider = get_provider(chain_id).await?;
 
     // Get the fork url
     let fork_url = provider.url().to_string();
@@ -97,19 +218,308 @@ pub async fn simulate_bundle(
     let block_number = first_block_number.unwrap_or(latest_block_number.low_u64());
 
     // Construct the EVM
-    let mut evm =
-        Evm::new(None, fork_url, Some(block_number), transactions[0].gas_limit, true).await;
+    let gas_limit =
+        transactions.first().map(|transaction| transaction.gas_limit).unwrap_or(u64::MAX);
+    let mut evm = Evm::new(None, fork_url, Some(block_number), gas_limit, true).await;
 
     // Run the transactions
     let mut response = Vec::with_capacity(transactions.len());
     for transaction in transactions {
-        if transaction.chain_id != first_chain_id {
-            return Err(eyre!("Multiple chain ids"));
-        }
         if transaction.block_number != first_block_number {
-            return Err(eyre!("Multiple block numbers"));
+            return Err(eyre!("Multiple block numbers on chain {}", chain_id));
+        }
+        let mut res = run(&mut evm, transaction, true).await?;
+        res.block_number = block_number;
+        response.push(res);
+    }
+
+    // Return the response
+    Ok(response)
+}
+
+/// Simulate a user operation through `simulateHandleOp` of the entry point on the fork.
+/// The entry point deploys the sender w/ the init code, runs the validation and the execution, and
+/// always reverts w/ `ExecutionResult`, so the signature doesn't have to be valid.
+/// Only the entry point v0.6.0 is supported; the entry point v0.7.0 returns an error as its
+/// `simulateHandleOp` lives in the off-chain `EntryPointSimulations` and takes a packed user
+/// operation.
+pub async fn simulate_user_operation(
+    request: UserOperationRequest,
+) -> Result<UserOperationSimulationResponse> {
+    // Get the entry point
+    let entry_point_v060 = Address::from_slice(ENTRYPOINT_V060_ADDRESS.as_bytes());
+    let entry_point = request.entrypoint.unwrap_or(entry_point_v060);
+    if entry_point == Address::from_slice(ENTRYPOINT_V070_ADDRESS.as_bytes()) {
+        return Err(eyre!("The entry point v0.7.0 is not supported for the simulation"));
+    }
+    if entry_point != entry_point_v060 {
+        return Err(eyre!("Only the entry point v0.6.0 is supported: {:?}", entry_point));
+    }
+
+    // Get the provider
+    let provider = get_provider(request.chain_id).await?;
+
+    // Get the fork url
+    let fork_url = provider.url().to_string();
+
+    // If block number is not provided, use the latest block number
+    let latest_block_number = provider.get_block_number().await?;
+
+    // Get the block number
+    let block_number = request.block_number.unwrap_or(latest_block_number.low_u64());
+
+    // Construct the EVM
+    let mut evm = Evm::new(None, fork_url, Some(block_number), u64::MAX, true).await;
+
+    // Apply the state overrides
+    if let Some(state_overrides) = &request.state_overrides {
+        evm.apply_state_overrides(state_overrides).await?;
+    }
+
+    // Encode the `simulateHandleOp` call w/o the target
+    let user_operation = UserOperation::from(request.clone());
+    let data = SimulateHandleOpCall {
+        op: user_operation.clone(),
+        target: Address::zero(),
+        target_call_data: Bytes::default(),
+    }
+    .encode();
+
+    // Run the user operation through the entry point
+    let result = evm
+        .call_raw(Address::zero(), entry_point, None, Some(data.into()))
+        .await
+        .wrap_err("Failed to call simulateHandleOp")?;
+
+    // Decode the revert of `simulateHandleOp`
+    let (execution_result, revert_reason) =
+        if let Ok(execution_result) = ExecutionResult::decode(&result.return_data) {
+            (Some(execution_result), None)
+        } else if let Ok(failed_op) = FailedOp::decode(&result.return_data) {
+            (None, Some(failed_op.reason))
+        } else {
+            (None, Some(format!("Unknown revert: {:?}", result.return_data)))
+        };
+
+    // Get the result of the execution from the `UserOperationEvent`
+    let user_operation_event = result.logs.iter().find_map(|log| {
+        if log.address != entry_point ||
+            log.topics.first() != Some(&UserOperationEventFilter::signature())
+        {
+            return None;
+        }
+        <UserOperationEventFilter as EthEvent>::decode_log(&RawLog {
+            topics: log.topics.clone(),
+            data: log.data.to_vec(),
+        })
+        .ok()
+    });
+
+    // Get the gas of each phase from the calls of the entry point
+    let arena = result.trace.clone().unwrap_or_default();
+    let validation_gas =
+        phase_gas(&arena, entry_point, |selector| VALIDATION_SELECTORS.contains(&selector));
+    let post_op_gas = phase_gas(&arena, entry_point, |selector| selector == POST_OP_SELECTOR);
+    let execution_gas = arena
+        .arena
+        .iter()
+        .filter(|node| {
+            node.trace.caller == entry_point &&
+                node.trace.address == request.sender &&
+                !call_selector(&node.trace.data)
+                    .is_some_and(|s| VALIDATION_SELECTORS.contains(&s))
+        })
+        .map(|node| node.trace.gas_cost)
+        .sum();
+
+    // Get the context returned by the paymaster
+    let paymaster_context = arena
+        .arena
+        .iter()
+        .find(|node| {
+            node.trace.caller == entry_point &&
+                call_selector(&node.trace.data) == Some(VALIDATE_PAYMASTER_USER_OP_SELECTOR)
+        })
+        .and_then(|node| match &node.trace.output {
+            RawOrDecodedReturnData::Raw(output) => {
+                decode(&[ParamType::Bytes, ParamType::Uint(256)], output).ok()
+            }
+            _ => None,
+        })
+        .and_then(|tokens| tokens.into_iter().next())
+        .and_then(|token| token.into_bytes())
+        .map(Bytes::from);
+
+    Ok(UserOperationSimulationResponse {
+        block_number,
+        success: user_operation_event.as_ref().is_some_and(|event| event.success),
+        revert_reason,
+        deployed: !user_operation.init_code.is_empty() && execution_result.is_some(),
+        pre_op_gas: execution_result.map(|res| res.pre_op_gas).unwrap_or_default(),
+        validation_gas,
+        execution_gas,
+        post_op_gas,
+        actual_gas_used: user_operation_event
+            .as_ref()
+            .map(|event| event.actual_gas_used)
+            .unwrap_or_default(),
+        actual_gas_cost: user_operation_event
+            .as_ref()
+            .map(|event| event.actual_gas_cost)
+            .unwrap_or_default(),
+        paymaster_context,
+        arena: result.trace,
+        logs: result.logs,
+        exit_reason: result.exit_reason,
+    })
+}
+
+/// Verify the EIP-1271 signature of the undeployed contract from the ERC-6492 wrapped signature.
+/// The contract is deployed w/ the factory calldata on the fork, and `isValidSignature` is called
+/// on top of the deployment.
+/// From: https://eips.ethereum.org/EIPS/eip-6492
+pub async fn simulate_erc_6492_signature(
+    chain_id: u64,
+    signer: Address,
+    hash: H256,
+    factory: Address,
+    factory_calldata: Bytes,
+    signature: Bytes,
+) -> Result<bool> {
+    // Get the provider
+    let provider = get_provider(chain_id).await?;
+
+    // Get the fork url
+    let fork_url = provider.url().to_string();
+
+    // Get the latest block number
+    let block_number = provider.get_block_number().await?;
+
+    // Construct the EVM
+    let mut evm = Evm::new(None, fork_url, Some(block_number.low_u64()), u64::MAX, false).await;
+
+    // Deploy the contract w/ the factory, which reverts if the contract is already deployed
+    let deployment = evm
+        .call_raw_committing(Address::zero(), factory, None, Some(factory_calldata), u64::MAX)
+        .await?;
+    if !deployment.success {
+        return Err(eyre!("Failed to deploy the signer {:?} w/ the factory", signer));
+    }
+
+    // Call `isValidSignature` on the deployed contract
+    let data = [
+        IS_VALID_SIGNATURE_SELECTOR.to_vec(),
+        encode(&[Token::FixedBytes(hash.0.to_vec()), Token::Bytes(signature.to_vec())]),
+    ]
+    .concat();
+    let result = evm.call_raw(Address::zero(), signer, None, Some(data.into())).await?;
+
+    Ok(result.success && result.return_data.get(..4) == Some(&IS_VALID_SIGNATURE_SELECTOR[..]))
+}
+
+/// Get the ERC-20 balance of the sender and the allowance to the spender after the calls of the
+/// user operation on the fork, so that the deployment and an approval in the same user operation
+/// are accounted for.
+pub async fn simulate_erc20_balance_and_allowance(
+    request: SimulationUserOperationRequest,
+    token: Address,
+    spender: Address,
+) -> Result<(U256, U256)> {
+    let chain_id = request.chain_id;
+    let owner = request.sender;
+
+    // Get the calls of the user operation, failing if they can't be decoded so that the balance and
+    // the allowance are never checked w/o the calls
+    let transactions: Vec<SimulationRequest> = request.try_into()?;
+
+    // Get the provider
+    let provider = get_provider(chain_id).await?;
+
+    // Get the fork url
+    let fork_url = provider.url().to_string();
+
+    // Get the latest block number
+    let block_number = provider.get_block_number().await?;
+
+    // Construct the EVM
+    let mut evm = Evm::new(None, fork_url, Some(block_number.low_u64()), u64::MAX, false).await;
+
+    // Run the calls of the user operation
+    for transaction in transactions {
+        run(&mut evm, transaction, true).await?;
+    }
+
+    // Call `balanceOf` and `allowance` of the token
+    let balance = call_uint(
+        &mut evm,
+        token,
+        [BALANCE_OF_SELECTOR.to_vec(), encode(&[Token::Address(owner)])].concat(),
+    )
+    .await?;
+    let allowance = call_uint(
+        &mut evm,
+        token,
+        [ALLOWANCE_SELECTOR.to_vec(), encode(&[Token::Address(owner), Token::Address(spender)])]
+            .concat(),
+    )
+    .await?;
+
+    Ok((balance, allowance))
+}
+
+/// Call the contract and decode the returned uint.
+async fn call_uint(evm: &mut Evm, to: Address, data: Vec<u8>) -> Result<U256> {
+    let result = evm.call_raw(Address::zero(), to, None, Some(data.into())).await?;
+    if !result.success {
+        return Err(eyre!("Failed to call {:?}", to));
+    }
+
+    decode(&[ParamType::Uint(256)], &result.return_data)?
+        .into_iter()
+        .next()
+        .and_then(|token| token.into_uint())
+        .ok_or_else(|| eyre!("Invalid return data of {:?}", to))
+}
+
+/// Get the selector of the raw call data of the trace.
+fn call_selector(data: &RawOrDecodedCall) -> Option<[u8; 4]> {
+    match data {
+        RawOrDecodedCall::Raw(data) => data.get(..4)?.try_into().ok(),
+        _ => None,
+    }
+}
+
+/// Get the sum of the gas of the calls from the entry point w/ the matching selector.
+fn phase_gas(arena: &CallTraceArena, entry_point: Address, f: impl Fn([u8; 4]) -> bool) -> u64 {
+    arena
+        .arena
+        .iter()
+        .filter(|node| {
+            node.trace.caller == entry_point && call_selector(&node.trace.data).is_some_and(&f)
+        })
+        .map(|node| node.trace.gas_cost)
+        .sum()
+}
+
+/// Simulate the bundle of transactions on the pinned state of the snapshot, w/o a live RPC fork.
+pub async fn simulate_bundle_with_snapshot(
+    transactions: Vec<SimulationRequest>,
+    snapshot: &StateSnapshot,
+) -> Result<Vec<SimulationResponse>> {
+    // Construct the EVM
+    let gas_limit =
+        transactions.first().map(|transaction| transaction.gas_limit).unwrap_or(u64::MAX);
+    let mut evm = Evm::new_from_snapshot(None, snapshot, gas_limit, true).await?;
+
+    // Run the transactions
+    let mut response = Vec::with_capacity(transactions.len());
+    for transaction in transactions {
+        if transaction.chain_id != snapshot.chain_id {
+            return Err(eyre!("Chain id does not match the snapshot"));
         }
-        response.push(run(&mut evm, transaction, true).await?);
+        let mut res = run(&mut evm, transaction, true).await?;
+        res.block_number = snapshot.block_number;
+        response.push(res);
     }
 
     // Return the response
@@ -0,0 +1,105 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::types::{AccountOverride, StateOverride};
+use ethers_main::{
+    abi::Address,
+    types::{Bytes, H256, U256},
+};
+use eyre::Result;
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, path::Path};
+
+/// The state of an account in the snapshot.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountSnapshot {
+    /// Balance of the account
+    #[serde(default)]
+    pub balance: U256,
+    /// Nonce of the account
+    #[serde(default)]
+    pub nonce: u64,
+    /// Code of the account
+    #[serde(default)]
+    pub code: Bytes,
+    /// Storage slots of the account
+    #[serde(default)]
+    pub storage: BTreeMap<H256, H256>,
+}
+
+/// The pinned state of the chain to run the EVM from w/o a live RPC fork.
+/// The accounts not in the snapshot are empty.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct StateSnapshot {
+    /// Chain ID of the network
+    pub chain_id: u64,
+    /// Block number of the snapshot
+    pub block_number: u64,
+    /// Block timestamp of the snapshot
+    #[serde(default)]
+    pub block_timestamp: u64,
+    /// Accounts of the snapshot
+    #[serde(default)]
+    pub accounts: BTreeMap<Address, AccountSnapshot>,
+}
+
+impl StateSnapshot {
+    /// Parse the snapshot from the JSON dump.
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Serialize the snapshot to the JSON dump.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Load the snapshot from the JSON dump on disk.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Self::from_json(&std::fs::read_to_string(path)?)
+    }
+
+    /// Save the snapshot to the JSON dump on disk.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        std::fs::write(path, self.to_json()?)?;
+        Ok(())
+    }
+
+    /// Get the account of the snapshot, inserting an empty one if missing.
+    pub fn account_mut(&mut self, address: Address) -> &mut AccountSnapshot {
+        self.accounts.entry(address).or_default()
+    }
+}
+
+impl From<&StateSnapshot> for StateOverride {
+    fn from(snapshot: &StateSnapshot) -> Self {
+        snapshot
+            .accounts
+            .iter()
+            .map(|(address, account)| {
+                (
+                    *address,
+                    AccountOverride {
+                        balance: Some(account.balance),
+                        nonce: Some(account.nonce),
+                        code: Some(account.code.clone()),
+                        storage: Some(account.storage.clone().into_iter().collect()),
+                    },
+                )
+            })
+            .collect()
+    }
+}
@@ -16,12 +16,13 @@ use ethers::abi::{AbiDecode, AbiError};
 use ethers_main::{
     abi::Address,
     contract::abigen,
-    types::{Bytes, Log, U256},
+    types::{Bytes, Log, H256, U256},
 };
 use eyre::{eyre, Result};
 use foundry_evm::trace::CallTraceArena;
 use revm::interpreter::InstructionResult;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 abigen!(
     LightWalletFactory,
@@ -30,6 +31,17 @@ abigen!(
     ]"#,
 );
 
+abigen!(
+    EntryPoint,
+    r#"[
+        struct UserOperation { address sender; uint256 nonce; bytes initCode; bytes callData; uint256 callGasLimit; uint256 verificationGasLimit; uint256 preVerificationGas; uint256 maxFeePerGas; uint256 maxPriorityFeePerGas; bytes paymasterAndData; bytes signature; }
+        function simulateHandleOp(UserOperation calldata op, address target, bytes calldata targetCallData) external
+        event UserOperationEvent(bytes32 indexed userOpHash, address indexed sender, address indexed paymaster, uint256 nonce, bool success, uint256 actualGasCost, uint256 actualGasUsed)
+        error ExecutionResult(uint256 preOpGas, uint256 paid, uint48 validAfter, uint48 validUntil, bool targetSuccess, bytes targetResult)
+        error FailedOp(uint256 opIndex, string reason)
+    ]"#,
+);
+
 abigen!(
     LightWallet,
     r#"[
@@ -38,6 +50,27 @@ abigen!(
     ]"#,
 );
 
+/// The override of an account, in the format of the `eth_call` state overrides.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountOverride {
+    /// Balance to set for the account
+    pub balance: Option<U256>,
+    /// Nonce to set for the account
+    pub nonce: Option<u64>,
+    /// Code to set for the account
+    pub code: Option<Bytes>,
+    /// Storage slots to set for the account
+    #[serde(alias = "stateDiff")]
+    pub storage: Option<HashMap<H256, H256>>,
+}
+
+/// The state overrides by the account address, applied to the forked state before the call.
+pub type StateOverride = HashMap<Address, AccountOverride>;
+
+/// The default gas limits of a simulated user operation w/o the gas limits.
+pub const DEFAULT_USER_OPERATION_GAS_LIMIT: u64 = 10_000_000;
+
 // Entire file is derived from https://github.com/EnsoFinance/transaction-simulator/blob/42bc679fb171de760838457820d5c6622e53ab15/src/simulation.rs
 // License: MIT
 
@@ -54,9 +87,12 @@ pub struct SimulationRequest {
     /// Gas limit of the transaction
     pub gas_limit: u64,
     /// Value to send
-    pub value: Option<u64>,
+    pub value: Option<U256>,
     /// Block number of the request
     pub block_number: Option<u64>,
+    /// State overrides applied before the transaction
+    #[serde(default)]
+    pub state_overrides: Option<StateOverride>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,16 +101,21 @@ pub struct SimulationUserOperationRequest {
     pub chain_id: u64,
     /// From address of the transaction
     pub sender: Address,
-    /// Nonce of the transaction
-    pub nonce: u64,
+    /// Nonce of the transaction, w/ the key of the 2D nonce in the upper 192 bits
+    pub nonce: U256,
     /// Init code of the transaction
     pub init_code: Option<Bytes>,
     /// Calldata of the transaction
     pub call_data: Option<Bytes>,
+    /// State overrides applied before the first transaction
+    #[serde(default)]
+    pub state_overrides: Option<StateOverride>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SimulationResponse {
+    /// Chain ID of the simulation
+    pub chain_id: u64,
     /// Gas used by the transaction
     pub gas_used: u64,
     /// Block number of the simulation
@@ -110,6 +151,7 @@ pub struct UserOperationRequest {
     /// From address of the transaction
     pub sender: Address,
     /// Entrypoint address of the transaction
+    /// Only the entry point v0.6.0 is supported by `simulate_user_operation`, defaults to it
     pub entrypoint: Option<Address>,
     /// Nonce of the transaction
     pub nonce: Option<U256>,
@@ -131,6 +173,41 @@ pub struct UserOperationRequest {
     pub paymaster_and_data: Option<Bytes>,
     /// Signature of the transaction
     pub signature: Option<Bytes>,
+    /// State overrides applied before the user operation
+    #[serde(default)]
+    pub state_overrides: Option<StateOverride>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct UserOperationSimulationResponse {
+    /// Block number of the simulation
+    pub block_number: u64,
+    /// Whether the execution of the user operation was successful
+    pub success: bool,
+    /// The reason of the entry point if the user operation failed the validation
+    pub revert_reason: Option<String>,
+    /// Whether the sender was deployed w/ the init code
+    pub deployed: bool,
+    /// Gas used before the execution, including the pre verification gas
+    pub pre_op_gas: U256,
+    /// Gas used by the validation of the factory, the account and the paymaster
+    pub validation_gas: u64,
+    /// Gas used by the execution of the call data
+    pub execution_gas: u64,
+    /// Gas used by the post op of the paymaster
+    pub post_op_gas: u64,
+    /// Actual gas used by the user operation, from the `UserOperationEvent`
+    pub actual_gas_used: U256,
+    /// Actual gas cost paid by the user operation, from the `UserOperationEvent`
+    pub actual_gas_cost: U256,
+    /// Context returned by `validatePaymasterUserOp` of the paymaster
+    pub paymaster_context: Option<Bytes>,
+    /// Trace of the user operation in the form of a CallTraceArena
+    pub arena: Option<CallTraceArena>,
+    /// Logs of the user operation
+    pub logs: Vec<Log>,
+    /// Exit reason of the user operation
+    pub exit_reason: InstructionResult,
 }
 
 // -----------------------------------------------------------------------------
@@ -161,6 +238,7 @@ impl TryFrom<SimulationUserOperationRequest> for Vec<SimulationRequest> {
                         gas_limit: u64::MAX,
                         value: None,
                         block_number: None,
+                        state_overrides: None,
                     });
                 }
             }
@@ -177,8 +255,9 @@ impl TryFrom<SimulationUserOperationRequest> for Vec<SimulationRequest> {
                     to: decoded.dest,
                     data: Some(decoded.func.0.into()),
                     gas_limit: u64::MAX,
-                    value: Some(decoded.value.low_u64()),
+                    value: Some(decoded.value),
                     block_number: None,
+                    state_overrides: None,
                 });
             }
         }
@@ -197,13 +276,20 @@ impl TryFrom<SimulationUserOperationRequest> for Vec<SimulationRequest> {
                         to: dest,
                         data: Some(func.0.into()),
                         gas_limit: u64::MAX,
-                        value: Some(value.low_u64()),
+                        value: Some(value),
                         block_number: None,
+                        state_overrides: None,
                     });
                 }
             }
         }
 
+        // Apply the state overrides before the first transaction, which are kept in the
+        // committed state of the following transactions
+        if let Some(request) = requests.first_mut() {
+            request.state_overrides = params.state_overrides;
+        }
+
         if requests.is_empty() {
             Err(eyre!("Invalid transaction"))
         } else {
@@ -216,6 +302,26 @@ impl TryFrom<SimulationUserOperationRequest> for Vec<SimulationRequest> {
 // From
 // -----------------------------------------------------------------------------
 
+impl From<UserOperationRequest> for UserOperation {
+    fn from(uo: UserOperationRequest) -> Self {
+        UserOperation {
+            sender: uo.sender,
+            nonce: uo.nonce.unwrap_or_default(),
+            init_code: uo.init_code.unwrap_or_default(),
+            call_data: uo.call_data.unwrap_or_default(),
+            call_gas_limit: uo.call_gas_limit.unwrap_or(DEFAULT_USER_OPERATION_GAS_LIMIT.into()),
+            verification_gas_limit: uo
+                .verification_gas_limit
+                .unwrap_or(DEFAULT_USER_OPERATION_GAS_LIMIT.into()),
+            pre_verification_gas: uo.pre_verification_gas.unwrap_or_default(),
+            max_fee_per_gas: uo.max_fee_per_gas.unwrap_or_default(),
+            max_priority_fee_per_gas: uo.max_priority_fee_per_gas.unwrap_or_default(),
+            paymaster_and_data: uo.paymaster_and_data.unwrap_or_default(),
+            signature: uo.signature.unwrap_or_default(),
+        }
+    }
+}
+
 impl From<UserOperationRequest> for SimulationRequest {
     fn from(uo: UserOperationRequest) -> Self {
         SimulationRequest {
@@ -226,6 +332,7 @@ impl From<UserOperationRequest> for SimulationRequest {
             gas_limit: uo.call_gas_limit.unwrap_or_default().low_u64(),
             value: None,
             block_number: uo.block_number,
+            state_overrides: None,
         }
     }
 }
@@ -0,0 +1,47 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use eyre::Result;
+use lightdotso_simulator::{simulator::simulate_bundle, types::SimulationRequest};
+
+fn eth_transfer(chain_id: u64) -> Result<SimulationRequest> {
+    Ok(SimulationRequest {
+        chain_id,
+        // kaki.eth
+        from: "0x4fd9D0eE6D6564E80A9Ee00c0163fC952d0A45Ed".parse()?,
+        // fiveoutofnine.eth
+        to: "0xA85572Cd96f1643458f17340b6f0D6549Af482F5".parse()?,
+        data: None,
+        value: Some(1.into()),
+        gas_limit: u64::MAX,
+        block_number: None,
+        state_overrides: None,
+    })
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_integration_multi_chain_bundle() -> Result<()> {
+    let requests = vec![eth_transfer(1)?, eth_transfer(137)?, eth_transfer(1)?];
+
+    let res = simulate_bundle(requests).await?;
+
+    println!("res: {:?}", res);
+
+    // The responses are in the order of the requests, each simulated on its own chain.
+    assert_eq!(res.len(), 3);
+    assert_eq!(res.iter().map(|res| res.chain_id).collect::<Vec<_>>(), vec![1, 137, 1]);
+    assert_eq!(res[0].block_number, res[2].block_number);
+
+    Ok(())
+}
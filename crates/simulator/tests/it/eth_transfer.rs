@@ -24,9 +24,10 @@ async fn test_integration_eth_transfer() -> Result<()> {
         // fiveoutofnine.eth
         to: "0xA85572Cd96f1643458f17340b6f0D6549Af482F5".parse()?,
         data: None,
-        value: Some(1),
+        value: Some(1.into()),
         gas_limit: u64::MAX,
         block_number: None,
+        state_overrides: None,
     };
 
     let res = simulate(request).await?;
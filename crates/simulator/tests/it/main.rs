@@ -12,4 +12,8 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+mod bundle;
 mod eth_transfer;
+mod snapshot;
+mod state_override;
+mod user_operation;
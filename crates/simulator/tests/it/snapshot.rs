@@ -0,0 +1,83 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use eyre::Result;
+use lightdotso_simulator::{
+    simulator::{simulate_and_record, simulate_with_snapshot},
+    snapshot::StateSnapshot,
+    types::SimulationRequest,
+};
+
+const SNAPSHOT: &str = r#"{
+    "chainId": 1,
+    "blockNumber": 18000000,
+    "blockTimestamp": 1693066895,
+    "accounts": {
+        "0x4fd9d0ee6d6564e80a9ee00c0163fc952d0a45ed": {
+            "balance": "0xde0b6b3a7640000",
+            "nonce": 1
+        }
+    }
+}"#;
+
+fn eth_transfer_request(value: u64, block_number: Option<u64>) -> Result<SimulationRequest> {
+    Ok(SimulationRequest {
+        chain_id: 1,
+        // kaki.eth
+        from: "0x4fd9D0eE6D6564E80A9Ee00c0163fC952d0A45Ed".parse()?,
+        // fiveoutofnine.eth
+        to: "0xA85572Cd96f1643458f17340b6f0D6549Af482F5".parse()?,
+        data: None,
+        value: Some(value.into()),
+        gas_limit: u64::MAX,
+        block_number,
+        state_overrides: None,
+    })
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_integration_snapshot_offline() -> Result<()> {
+    let snapshot = StateSnapshot::from_json(SNAPSHOT)?;
+
+    // The transfer within the balance of the snapshot succeeds
+    let res = simulate_with_snapshot(eth_transfer_request(1, None)?, &snapshot).await?;
+    assert!(res.success);
+    assert_eq!(res.block_number, 18000000);
+
+    // The transfer over the balance of the snapshot fails
+    let res =
+        simulate_with_snapshot(eth_transfer_request(2_000_000_000_000_000_000, None)?, &snapshot)
+            .await;
+    assert!(res.is_err() || !res?.success);
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_integration_snapshot_record_and_replay() -> Result<()> {
+    let request = eth_transfer_request(1, Some(18000000))?;
+
+    // Record the state touched by the forked run
+    let (res, snapshot) = simulate_and_record(request.clone()).await?;
+    assert!(snapshot.accounts.contains_key(&request.from));
+
+    // Replay the run from the snapshot, which doesn't hit the network
+    let snapshot = StateSnapshot::from_json(&snapshot.to_json()?)?;
+    let replayed = simulate_with_snapshot(request, &snapshot).await?;
+
+    assert_eq!(res.success, replayed.success);
+    assert_eq!(res.gas_used, replayed.gas_used);
+
+    Ok(())
+}
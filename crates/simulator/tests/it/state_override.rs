@@ -0,0 +1,51 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use ethers_main::types::U256;
+use eyre::Result;
+use lightdotso_simulator::{
+    simulator::simulate,
+    types::{AccountOverride, SimulationRequest},
+};
+use std::collections::HashMap;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_integration_state_override_balance() -> Result<()> {
+    // An address w/o any balance
+    let from = "0x00000000000000000000000000000000DeaDBeef".parse()?;
+
+    let request = SimulationRequest {
+        chain_id: 1,
+        from,
+        // fiveoutofnine.eth
+        to: "0xA85572Cd96f1643458f17340b6f0D6549Af482F5".parse()?,
+        data: None,
+        // 10 ETH
+        value: Some(U256::exp10(19)),
+        gas_limit: u64::MAX,
+        block_number: None,
+        state_overrides: Some(HashMap::from([(
+            from,
+            AccountOverride { balance: Some(U256::exp10(21)), ..Default::default() },
+        )])),
+    };
+
+    let res = simulate(request).await?;
+
+    println!("res: {:?}", res);
+
+    assert!(res.success);
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+// Copyright 2023-2024 LightDotSo.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//   http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use eyre::Result;
+use lightdotso_simulator::{simulator::simulate_user_operation, types::UserOperationRequest};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_integration_user_operation_not_deployed() -> Result<()> {
+    let request = UserOperationRequest {
+        chain_id: 1,
+        block_number: None,
+        // An address w/o code, and w/o the init code
+        sender: "0x00000000000000000000000000000000DeaDBeef".parse()?,
+        entrypoint: None,
+        nonce: None,
+        init_code: None,
+        call_data: None,
+        call_gas_limit: None,
+        verification_gas_limit: None,
+        pre_verification_gas: None,
+        max_fee_per_gas: None,
+        max_priority_fee_per_gas: None,
+        paymaster_and_data: None,
+        signature: None,
+        state_overrides: None,
+    };
+
+    let res = simulate_user_operation(request).await?;
+
+    println!("res: {:?}", res);
+
+    assert!(!res.success);
+    assert!(!res.deployed);
+    assert_eq!(res.revert_reason, Some("AA20 account not deployed".to_string()));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_integration_user_operation_entry_point_v070() -> Result<()> {
+    let request = UserOperationRequest {
+        chain_id: 1,
+        block_number: None,
+        sender: "0x00000000000000000000000000000000DeaDBeef".parse()?,
+        entrypoint: Some("0x0000000071727De22E5E9d8BAf0edAc6f37da032".parse()?),
+        nonce: None,
+        init_code: None,
+        call_data: None,
+        call_gas_limit: None,
+        verification_gas_limit: None,
+        pre_verification_gas: None,
+        max_fee_per_gas: None,
+        max_priority_fee_per_gas: None,
+        paymaster_and_data: None,
+        signature: None,
+        state_overrides: None,
+    };
+
+    // The entry point v0.7.0 is rejected before forking the chain
+    assert!(simulate_user_operation(request).await.is_err());
+
+    Ok(())
+}
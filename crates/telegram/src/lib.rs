@@ -12,6 +12,35 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use eyre::Result;
+use teloxide::{prelude::Requester, types::ChatId, Bot};
+
 pub fn rust_greeting(to: String) -> String {
     format!("Hello World, {}!", to)
 }
+
+pub async fn notify(token: &str, chat_id: i64, text: &str) -> Result<()> {
+    let bot = Bot::new(token);
+
+    bot.send_message(ChatId(chat_id), text).await?;
+
+    Ok(())
+}
+
+pub async fn notify_low_paymaster_deposit(
+    token: &str,
+    chat_id: i64,
+    paymaster: &str,
+    chain_id: &str,
+    deposit: &str,
+    runway_hours: &str,
+) -> Result<()> {
+    let text = format!(
+        "Paymaster Deposit Low\nPaymaster: {}\nChainid: {}\nDeposit: {}\nRunway: {} hours\n",
+        paymaster, chain_id, deposit, runway_hours
+    );
+
+    notify(token, chat_id, &text).await?;
+
+    Ok(())
+}
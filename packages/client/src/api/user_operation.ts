@@ -196,16 +196,16 @@ export const createUserOperation = async (
       user_operation: {
         chain_id: number;
         call_data: string;
-        call_gas_limit: number;
+        call_gas_limit: string;
         hash: string;
         init_code: string;
-        max_fee_per_gas: number;
-        max_priority_fee_per_gas: number;
+        max_fee_per_gas: string;
+        max_priority_fee_per_gas: string;
         nonce: number;
         paymaster_and_data: string;
-        pre_verification_gas: number;
+        pre_verification_gas: string;
         sender: string;
-        verification_gas_limit: number;
+        verification_gas_limit: string;
       };
     };
   },
@@ -250,16 +250,16 @@ export const createBatchUserOperation = async (
       user_operations: {
         chain_id: number;
         call_data: string;
-        call_gas_limit: number;
+        call_gas_limit: string;
         hash: string;
         init_code: string;
-        max_fee_per_gas: number;
-        max_priority_fee_per_gas: number;
+        max_fee_per_gas: string;
+        max_priority_fee_per_gas: string;
         nonce: number;
         paymaster_and_data: string;
-        pre_verification_gas: number;
+        pre_verification_gas: string;
         sender: string;
-        verification_gas_limit: number;
+        verification_gas_limit: string;
       }[];
     };
   },
@@ -3405,11 +3405,8 @@ export interface components {
     UserOperation: {
       /** @description The call data of the user operation. */
       call_data: string;
-      /**
-       * Format: int64
-       * @description The call gas of the user operation.
-       */
-      call_gas_limit: number;
+      /** @description The call gas of the user operation. */
+      call_gas_limit: string;
       /**
        * Format: int64
        * @description The chain id of the user operation.
@@ -3422,16 +3419,10 @@ export interface components {
       /** @description The init code of the user operation. */
       init_code: string;
       interpretation?: components["schemas"]["Interpretation"] | null;
-      /**
-       * Format: int64
-       * @description The maximum fee per gas of the user operation.
-       */
-      max_fee_per_gas: number;
-      /**
-       * Format: int64
-       * @description The maximum priority fee per gas of the user operation.
-       */
-      max_priority_fee_per_gas: number;
+      /** @description The maximum fee per gas of the user operation. */
+      max_fee_per_gas: string;
+      /** @description The maximum priority fee per gas of the user operation. */
+      max_priority_fee_per_gas: string;
       /**
        * Format: int64
        * @description The nonce of the user operation.
@@ -3441,11 +3432,8 @@ export interface components {
       /** @description The paymaster and data of the user operation. */
       paymaster_and_data: string;
       paymaster_operation?: components["schemas"]["PaymasterOperation"] | null;
-      /**
-       * Format: int64
-       * @description The pre verification gas of the user operation.
-       */
-      pre_verification_gas: number;
+      /** @description The pre verification gas of the user operation. */
+      pre_verification_gas: string;
       /** @description The sender of the user operation. */
       sender: string;
       /** @description The signatures of the user operation. */
@@ -3455,11 +3443,8 @@ export interface components {
       transaction?: components["schemas"]["Transaction"] | null;
       /** @description The timestamp updated of the user operation. */
       updated_at: string;
-      /**
-       * Format: int64
-       * @description The verification gas of the user operation.
-       */
-      verification_gas_limit: number;
+      /** @description The verification gas of the user operation. */
+      verification_gas_limit: string;
     };
     UserOperationCreateBatchRequestParams: {
       merkle_root: string;
@@ -3469,24 +3454,19 @@ export interface components {
     /** @description Item to create. */
     UserOperationCreateParams: {
       call_data: string;
-      /** Format: int64 */
-      call_gas_limit: number;
+      call_gas_limit: string;
       /** Format: int64 */
       chain_id: number;
       hash: string;
       init_code: string;
-      /** Format: int64 */
-      max_fee_per_gas: number;
-      /** Format: int64 */
-      max_priority_fee_per_gas: number;
+      max_fee_per_gas: string;
+      max_priority_fee_per_gas: string;
       /** Format: int64 */
       nonce: number;
       paymaster_and_data: string;
-      /** Format: int64 */
-      pre_verification_gas: number;
+      pre_verification_gas: string;
       sender: string;
-      /** Format: int64 */
-      verification_gas_limit: number;
+      verification_gas_limit: string;
     };
     UserOperationCreateRequestParams: {
       signature: components["schemas"]["SignatureCreateParams"];
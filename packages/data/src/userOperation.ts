@@ -21,15 +21,15 @@ import type { PaymasterOperationData } from "./paymasterOperation";
 
 export type UserOperationData = {
   call_data: string;
-  call_gas_limit: number;
+  call_gas_limit: string;
   chain_id: number;
   hash: string;
   init_code: string;
-  max_fee_per_gas: number;
-  max_priority_fee_per_gas: number;
+  max_fee_per_gas: string;
+  max_priority_fee_per_gas: string;
   nonce: number;
   paymaster_and_data: string;
-  pre_verification_gas: number;
+  pre_verification_gas: string;
   sender: string;
   signatures: {
     owner_id: string;
@@ -44,7 +44,7 @@ export type UserOperationData = {
       }
     | null
     | undefined;
-  verification_gas_limit: number;
+  verification_gas_limit: string;
   created_at: string;
   updated_at: string;
   paymaster_operation?: PaymasterOperationData | null | undefined;
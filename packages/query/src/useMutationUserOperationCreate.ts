@@ -101,15 +101,15 @@ export const useMutationUserOperationCreate = (
               init_code: body.userOperation.initCode,
               sender: body.userOperation.sender,
               call_data: body.userOperation.callData,
-              call_gas_limit: Number(body.userOperation.callGasLimit),
-              verification_gas_limit: Number(
+              call_gas_limit: String(body.userOperation.callGasLimit),
+              verification_gas_limit: String(
                 body.userOperation.verificationGasLimit,
               ),
-              pre_verification_gas: Number(
+              pre_verification_gas: String(
                 body.userOperation.preVerificationGas,
               ),
-              max_fee_per_gas: Number(body.userOperation.maxFeePerGas),
-              max_priority_fee_per_gas: Number(
+              max_fee_per_gas: String(body.userOperation.maxFeePerGas),
+              max_priority_fee_per_gas: String(
                 body.userOperation.maxPriorityFeePerGas,
               ),
               paymaster_and_data: body.userOperation.paymasterAndData,
@@ -114,13 +114,13 @@ export const useMutationUserOperationCreateBatch = (
                 sender: userOperation.sender!,
                 // biome-ignore lint/style/noNonNullAssertion: <explanation>
                 call_data: userOperation.callData!,
-                call_gas_limit: Number(userOperation.callGasLimit),
-                verification_gas_limit: Number(
+                call_gas_limit: String(userOperation.callGasLimit),
+                verification_gas_limit: String(
                   userOperation.verificationGasLimit,
                 ),
-                pre_verification_gas: Number(userOperation.preVerificationGas),
-                max_fee_per_gas: Number(userOperation.maxFeePerGas),
-                max_priority_fee_per_gas: Number(
+                pre_verification_gas: String(userOperation.preVerificationGas),
+                max_fee_per_gas: String(userOperation.maxFeePerGas),
+                max_priority_fee_per_gas: String(
                   userOperation.maxPriorityFeePerGas,
                 ),
                 // biome-ignore lint/style/noNonNullAssertion: <explanation>
@@ -76,11 +76,15 @@ export const useMutationUserOperationSend = (
           initCode: userOperation.init_code,
           callData: userOperation.call_data,
           paymasterAndData: userOperation.paymaster_and_data,
-          callGasLimit: toHex(userOperation.call_gas_limit),
-          verificationGasLimit: toHex(userOperation.verification_gas_limit),
-          preVerificationGas: toHex(userOperation.pre_verification_gas),
-          maxFeePerGas: toHex(userOperation.max_fee_per_gas),
-          maxPriorityFeePerGas: toHex(userOperation.max_priority_fee_per_gas),
+          callGasLimit: toHex(BigInt(userOperation.call_gas_limit)),
+          verificationGasLimit: toHex(
+            BigInt(userOperation.verification_gas_limit),
+          ),
+          preVerificationGas: toHex(BigInt(userOperation.pre_verification_gas)),
+          maxFeePerGas: toHex(BigInt(userOperation.max_fee_per_gas)),
+          maxPriorityFeePerGas: toHex(
+            BigInt(userOperation.max_priority_fee_per_gas),
+          ),
           signature: userOperationSignature,
         },
         // Hardcoded to use the latest version of the wallet factory